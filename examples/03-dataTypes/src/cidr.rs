@@ -0,0 +1,712 @@
+//! CIDR blocks, IP ranges, IP sets, and longest-prefix-match tables.
+//!
+//! Addresses of both families are widened to `u128` internally so one set of
+//! range algorithms serves IPv4 and IPv6 alike; the [`IpFamily`] tag keeps
+//! them apart.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Address family of a block, range, or set entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IpFamily {
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    /// Address width in bits (32 or 128).
+    #[must_use]
+    pub const fn bits(self) -> u8 {
+        match self {
+            Self::V4 => 32,
+            Self::V6 => 128,
+        }
+    }
+
+    const fn of(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => Self::V4,
+            IpAddr::V6(_) => Self::V6,
+        }
+    }
+}
+
+/// Errors from parsing or building CIDR blocks and ranges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CidrError {
+    /// The address part did not parse.
+    InvalidAddress(String),
+    /// The prefix part was not a number.
+    InvalidPrefix(String),
+    /// Prefix longer than the family allows (`/33` for IPv4).
+    PrefixTooLong { prefix: u8, max: u8 },
+    /// Range endpoints belong to different families.
+    MixedFamilies,
+    /// Range start is greater than its end.
+    ReversedRange,
+}
+
+impl fmt::Display for CidrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddress(text) => write!(f, "invalid IP address: {text:?}"),
+            Self::InvalidPrefix(text) => write!(f, "invalid prefix length: {text:?}"),
+            Self::PrefixTooLong { prefix, max } => {
+                write!(f, "prefix /{prefix} exceeds maximum /{max}")
+            }
+            Self::MixedFamilies => write!(f, "range mixes IPv4 and IPv6 endpoints"),
+            Self::ReversedRange => write!(f, "range start is after range end"),
+        }
+    }
+}
+
+impl std::error::Error for CidrError {}
+
+fn to_bits(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(v4) => u128::from(u32::from(v4)),
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+fn from_bits(family: IpFamily, bits: u128) -> IpAddr {
+    match family {
+        IpFamily::V4 => IpAddr::V4(Ipv4Addr::from(u32::try_from(bits).unwrap_or(u32::MAX))),
+        IpFamily::V6 => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+/// Mask covering the low `host_bits` bits.
+const fn host_mask(host_bits: u8) -> u128 {
+    if host_bits >= 128 {
+        u128::MAX
+    } else {
+        (1_u128 << host_bits) - 1
+    }
+}
+
+/// A network block such as `10.0.0.0/8` or `2001:db8::/32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cidr {
+    family: IpFamily,
+    network: u128,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Build a block, clearing any host bits set in `addr`.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, CidrError> {
+        let family = IpFamily::of(addr);
+        if prefix > family.bits() {
+            return Err(CidrError::PrefixTooLong {
+                prefix,
+                max: family.bits(),
+            });
+        }
+        Ok(Self::from_parts(family, to_bits(addr), prefix))
+    }
+
+    /// IPv4 block from octets, usable in `const` tables.
+    ///
+    /// Host bits are cleared; a prefix above 32 is clamped to 32.
+    #[must_use]
+    pub const fn v4(octets: [u8; 4], prefix: u8) -> Self {
+        let prefix = if prefix > 32 { 32 } else { prefix };
+        let addr = u32::from_be_bytes(octets) as u128;
+        Self::from_parts(IpFamily::V4, addr, prefix)
+    }
+
+    const fn from_parts(family: IpFamily, addr: u128, prefix: u8) -> Self {
+        let host = host_mask(family.bits() - prefix);
+        Self {
+            family,
+            network: addr & !host,
+            prefix,
+        }
+    }
+
+    /// Address family.
+    #[must_use]
+    pub const fn family(&self) -> IpFamily {
+        self.family
+    }
+
+    /// Prefix length in bits.
+    #[must_use]
+    pub const fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// First address (the network address).
+    #[must_use]
+    pub fn network(&self) -> IpAddr {
+        from_bits(self.family, self.network)
+    }
+
+    /// Last address (the broadcast address for IPv4).
+    #[must_use]
+    pub fn last(&self) -> IpAddr {
+        from_bits(self.family, self.last_bits())
+    }
+
+    const fn last_bits(&self) -> u128 {
+        self.network | host_mask(self.family.bits() - self.prefix)
+    }
+
+    /// Whether `addr` falls inside this block.
+    #[must_use]
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        IpFamily::of(addr) == self.family && {
+            let bits = to_bits(addr);
+            bits >= self.network && bits <= self.last_bits()
+        }
+    }
+
+    /// Whether `other` is entirely inside this block.
+    #[must_use]
+    pub const fn contains_cidr(&self, other: &Self) -> bool {
+        matches!(
+            (self.family, other.family),
+            (IpFamily::V4, IpFamily::V4) | (IpFamily::V6, IpFamily::V6)
+        ) && other.prefix >= self.prefix
+            && other.network >= self.network
+            && other.last_bits() <= self.last_bits()
+    }
+
+    /// The block as an inclusive [`IpRange`].
+    #[must_use]
+    pub const fn to_range(&self) -> IpRange {
+        IpRange {
+            family: self.family,
+            start: self.network,
+            end: self.last_bits(),
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = CidrError;
+
+    /// Parse `addr/prefix`; a bare address is a single-host block.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (addr_text, prefix_text) = s.split_once('/').map_or((s, None), |(a, p)| (a, Some(p)));
+        let addr: IpAddr = addr_text
+            .parse()
+            .map_err(|_| CidrError::InvalidAddress(addr_text.to_owned()))?;
+        let prefix = match prefix_text {
+            Some(text) => text
+                .parse()
+                .map_err(|_| CidrError::InvalidPrefix(text.to_owned()))?,
+            None => IpFamily::of(addr).bits(),
+        };
+        Self::new(addr, prefix)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network(), self.prefix)
+    }
+}
+
+/// Inclusive range of addresses within one family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpRange {
+    family: IpFamily,
+    start: u128,
+    end: u128,
+}
+
+impl IpRange {
+    /// Build `start..=end`; both ends must share a family.
+    pub fn new(start: IpAddr, end: IpAddr) -> Result<Self, CidrError> {
+        let family = IpFamily::of(start);
+        if family != IpFamily::of(end) {
+            return Err(CidrError::MixedFamilies);
+        }
+        let (start, end) = (to_bits(start), to_bits(end));
+        if start > end {
+            return Err(CidrError::ReversedRange);
+        }
+        Ok(Self { family, start, end })
+    }
+
+    /// Address family.
+    #[must_use]
+    pub const fn family(&self) -> IpFamily {
+        self.family
+    }
+
+    /// First address.
+    #[must_use]
+    pub fn start(&self) -> IpAddr {
+        from_bits(self.family, self.start)
+    }
+
+    /// Last address.
+    #[must_use]
+    pub fn end(&self) -> IpAddr {
+        from_bits(self.family, self.end)
+    }
+
+    /// Whether `addr` falls inside the range.
+    #[must_use]
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        IpFamily::of(addr) == self.family && (self.start..=self.end).contains(&to_bits(addr))
+    }
+
+    /// Smallest list of CIDR blocks that exactly covers the range.
+    #[must_use]
+    pub fn to_cidrs(&self) -> Vec<Cidr> {
+        let bits = self.family.bits();
+        let mut blocks = Vec::new();
+        let mut start = self.start;
+        loop {
+            let aligned = u8::try_from(start.trailing_zeros()).unwrap_or(bits);
+            let mut host = aligned.min(bits);
+            while host > 0 && start | host_mask(host) > self.end {
+                host -= 1;
+            }
+            blocks.push(Cidr::from_parts(self.family, start, bits - host));
+            let last = start | host_mask(host);
+            if last >= self.end {
+                break;
+            }
+            start = last + 1;
+        }
+        blocks
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = CidrError;
+
+    /// Parse `start-end` or a CIDR block.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let parse = |text: &str| {
+                    text.trim()
+                        .parse::<IpAddr>()
+                        .map_err(|_| CidrError::InvalidAddress(text.trim().to_owned()))
+                };
+                Self::new(parse(start)?, parse(end)?)
+            }
+            None => s.parse::<Cidr>().map(|cidr| cidr.to_range()),
+        }
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start(), self.end())
+    }
+}
+
+/// Set of addresses stored as sorted, merged ranges per family.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpSet {
+    v4: Vec<(u128, u128)>,
+    v6: Vec<(u128, u128)>,
+}
+
+impl IpSet {
+    /// Empty set.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            v4: Vec::new(),
+            v6: Vec::new(),
+        }
+    }
+
+    /// Add every address in `cidr`.
+    pub fn insert(&mut self, cidr: Cidr) {
+        self.insert_range(cidr.to_range());
+    }
+
+    /// Add every address in `range`.
+    pub fn insert_range(&mut self, range: IpRange) {
+        let list = self.list_mut(range.family);
+        list.push((range.start, range.end));
+        normalize(list);
+    }
+
+    /// Whether `addr` is in the set.
+    #[must_use]
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let bits = to_bits(addr);
+        let list = self.list(IpFamily::of(addr));
+        let idx = list.partition_point(|&(start, _)| start <= bits);
+        idx > 0 && list[idx - 1].1 >= bits
+    }
+
+    /// Whether the set holds no addresses.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    /// Addresses in either set.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let merge = |a: &[(u128, u128)], b: &[(u128, u128)]| {
+            let mut list = [a, b].concat();
+            normalize(&mut list);
+            list
+        };
+        Self {
+            v4: merge(&self.v4, &other.v4),
+            v6: merge(&self.v6, &other.v6),
+        }
+    }
+
+    /// Addresses in both sets.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            v4: intersect(&self.v4, &other.v4),
+            v6: intersect(&self.v6, &other.v6),
+        }
+    }
+
+    /// Addresses in `self` but not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            v4: subtract(&self.v4, &other.v4),
+            v6: subtract(&self.v6, &other.v6),
+        }
+    }
+
+    /// Merged ranges, IPv4 first.
+    #[must_use]
+    pub fn ranges(&self) -> Vec<IpRange> {
+        let tag =
+            |family: IpFamily| move |&(start, end): &(u128, u128)| IpRange { family, start, end };
+        self.v4
+            .iter()
+            .map(tag(IpFamily::V4))
+            .chain(self.v6.iter().map(tag(IpFamily::V6)))
+            .collect()
+    }
+
+    /// Minimal CIDR list covering exactly the set (aggregation).
+    #[must_use]
+    pub fn to_cidrs(&self) -> Vec<Cidr> {
+        self.ranges().iter().flat_map(IpRange::to_cidrs).collect()
+    }
+
+    const fn list(&self, family: IpFamily) -> &Vec<(u128, u128)> {
+        match family {
+            IpFamily::V4 => &self.v4,
+            IpFamily::V6 => &self.v6,
+        }
+    }
+
+    const fn list_mut(&mut self, family: IpFamily) -> &mut Vec<(u128, u128)> {
+        match family {
+            IpFamily::V4 => &mut self.v4,
+            IpFamily::V6 => &mut self.v6,
+        }
+    }
+}
+
+impl FromIterator<Cidr> for IpSet {
+    fn from_iter<I: IntoIterator<Item = Cidr>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for cidr in iter {
+            let list = set.list_mut(cidr.family);
+            list.push((cidr.network, cidr.last_bits()));
+        }
+        normalize(&mut set.v4);
+        normalize(&mut set.v6);
+        set
+    }
+}
+
+/// Sort ranges and merge any that overlap or touch.
+fn normalize(list: &mut Vec<(u128, u128)>) {
+    list.sort_unstable();
+    let mut merged: Vec<(u128, u128)> = Vec::with_capacity(list.len());
+    for &(start, end) in list.iter() {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *list = merged;
+}
+
+fn intersect(a: &[(u128, u128)], b: &[(u128, u128)]) -> Vec<(u128, u128)> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        let start = a[i].0.max(b[j].0);
+        let end = a[i].1.min(b[j].1);
+        if start <= end {
+            out.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+fn subtract(a: &[(u128, u128)], b: &[(u128, u128)]) -> Vec<(u128, u128)> {
+    let mut out = Vec::new();
+    for &(start, end) in a {
+        let mut cursor = start;
+        let mut exhausted = false;
+        for &(cut_start, cut_end) in b.iter().filter(|r| r.1 >= start && r.0 <= end) {
+            if cut_start > cursor {
+                out.push((cursor, cut_start - 1));
+            }
+            if cut_end >= end {
+                exhausted = true;
+                break;
+            }
+            cursor = cursor.max(cut_end + 1);
+        }
+        if !exhausted && cursor <= end {
+            out.push((cursor, end));
+        }
+    }
+    out
+}
+
+/// Longest-prefix-match table mapping networks to values (labels, regions, …).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixTable<T> {
+    entries: BTreeMap<Cidr, T>,
+}
+
+impl<T> Default for PrefixTable<T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> PrefixTable<T> {
+    /// Empty table.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Map `cidr` to `value`, returning any value it replaced.
+    pub fn insert(&mut self, cidr: Cidr, value: T) -> Option<T> {
+        self.entries.insert(cidr, value)
+    }
+
+    /// Most specific entry containing `addr`.
+    #[must_use]
+    pub fn lookup(&self, addr: IpAddr) -> Option<(Cidr, &T)> {
+        let family = IpFamily::of(addr);
+        let bits = to_bits(addr);
+        (0..=family.bits()).rev().find_map(|prefix| {
+            let candidate = Cidr::from_parts(family, bits, prefix);
+            self.entries.get(&candidate).map(|value| (candidate, value))
+        })
+    }
+
+    /// Number of entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the table is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// RFC 1918 private IPv4 blocks.
+pub const RFC1918: [Cidr; 3] = [
+    Cidr::v4([10, 0, 0, 0], 8),
+    Cidr::v4([172, 16, 0, 0], 12),
+    Cidr::v4([192, 168, 0, 0], 16),
+];
+
+/// Private and non-routable space for both families: RFC 1918, loopback,
+/// link-local, CGNAT (RFC 6598), and IPv6 unique-local/link-local/loopback.
+#[must_use]
+pub fn private_ranges() -> IpSet {
+    let mut set: IpSet = RFC1918.into_iter().collect();
+    for cidr in [
+        Cidr::v4([127, 0, 0, 0], 8),
+        Cidr::v4([169, 254, 0, 0], 16),
+        Cidr::v4([100, 64, 0, 0], 10),
+    ] {
+        set.insert(cidr);
+    }
+    for (addr, prefix) in [
+        (Ipv6Addr::LOCALHOST, 128),
+        (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7),
+        (Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0), 10),
+    ] {
+        if let Ok(cidr) = Cidr::new(IpAddr::V6(addr), prefix) {
+            set.insert(cidr);
+        }
+    }
+    set
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn cidr(text: &str) -> Cidr {
+        text.parse().expect("valid CIDR")
+    }
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().expect("valid IP")
+    }
+
+    #[test]
+    fn parse_masks_host_bits_and_displays() {
+        assert_eq!(cidr("10.1.2.3/8").to_string(), "10.0.0.0/8");
+        assert_eq!(cidr("2001:db8::1/32").to_string(), "2001:db8::/32");
+        assert_eq!(cidr("192.0.2.7").prefix(), 32);
+        assert_eq!(cidr("10.0.0.0/8").last(), ip("10.255.255.255"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "10.0.0.0/33".parse::<Cidr>(),
+            Err(CidrError::PrefixTooLong {
+                prefix: 33,
+                max: 32
+            })
+        );
+        assert!(matches!(
+            "10.0.0/8".parse::<Cidr>(),
+            Err(CidrError::InvalidAddress(_))
+        ));
+        assert!(matches!(
+            "10.0.0.0/x".parse::<Cidr>(),
+            Err(CidrError::InvalidPrefix(_))
+        ));
+        assert_eq!(
+            "10.0.0.1-::1".parse::<IpRange>(),
+            Err(CidrError::MixedFamilies)
+        );
+        assert_eq!(
+            "10.0.0.9-10.0.0.1".parse::<IpRange>(),
+            Err(CidrError::ReversedRange)
+        );
+    }
+
+    #[test]
+    fn containment() {
+        let net = cidr("172.16.0.0/12");
+        assert!(net.contains(ip("172.31.255.255")));
+        assert!(!net.contains(ip("172.32.0.0")));
+        assert!(!net.contains(ip("::ffff:172.16.0.1")));
+        assert!(net.contains_cidr(&cidr("172.20.0.0/16")));
+        assert!(!cidr("172.20.0.0/16").contains_cidr(&net));
+    }
+
+    #[test]
+    fn range_to_cidrs_is_minimal() {
+        let range: IpRange = "10.0.0.1-10.0.0.10".parse().expect("range");
+        let blocks: Vec<String> = range.to_cidrs().iter().map(Cidr::to_string).collect();
+        assert_eq!(
+            blocks,
+            [
+                "10.0.0.1/32",
+                "10.0.0.2/31",
+                "10.0.0.4/30",
+                "10.0.0.8/31",
+                "10.0.0.10/32"
+            ]
+        );
+        let everything: IpRange = "::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+            .parse()
+            .expect("range");
+        assert_eq!(everything.to_cidrs(), vec![cidr("::/0")]);
+    }
+
+    #[test]
+    fn set_aggregates_adjacent_blocks() {
+        let set: IpSet = ["10.0.0.0/25", "10.0.0.128/25", "10.0.1.0/24"]
+            .iter()
+            .map(|text| cidr(text))
+            .collect();
+        assert_eq!(set.to_cidrs(), vec![cidr("10.0.0.0/23")]);
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: IpSet = [cidr("10.0.0.0/24"), cidr("2001:db8::/64")]
+            .into_iter()
+            .collect();
+        let b: IpSet = [cidr("10.0.0.128/25"), cidr("10.0.1.0/24")]
+            .into_iter()
+            .collect();
+
+        let union = a.union(&b);
+        assert!(union.contains(ip("10.0.1.7")));
+        assert!(union.contains(ip("2001:db8::42")));
+
+        let both = a.intersection(&b);
+        assert_eq!(both.to_cidrs(), vec![cidr("10.0.0.128/25")]);
+
+        let only_a = a.difference(&b);
+        assert_eq!(
+            only_a.to_cidrs(),
+            vec![cidr("10.0.0.0/25"), cidr("2001:db8::/64")]
+        );
+        assert!(a.difference(&a).is_empty());
+    }
+
+    #[test]
+    fn longest_prefix_match_prefers_specific_routes() {
+        let mut table = PrefixTable::new();
+        table.insert(cidr("0.0.0.0/0"), "internet");
+        table.insert(cidr("10.0.0.0/8"), "corp");
+        table.insert(cidr("10.20.0.0/16"), "corp-dmz");
+        table.insert(cidr("2001:db8::/32"), "documentation");
+
+        assert_eq!(
+            table.lookup(ip("10.20.1.1")).map(|(_, v)| *v),
+            Some("corp-dmz")
+        );
+        assert_eq!(table.lookup(ip("10.9.1.1")).map(|(_, v)| *v), Some("corp"));
+        assert_eq!(
+            table.lookup(ip("8.8.8.8")).map(|(c, _)| c),
+            Some(cidr("0.0.0.0/0"))
+        );
+        assert_eq!(
+            table.lookup(ip("2001:db8::5")).map(|(_, v)| *v),
+            Some("documentation")
+        );
+        assert!(table.lookup(ip("::1")).is_none());
+    }
+
+    #[test]
+    fn private_ranges_cover_both_families() {
+        let private = private_ranges();
+        assert!(private.contains(ip("192.168.1.10")));
+        assert!(private.contains(ip("100.64.0.1")));
+        assert!(private.contains(ip("fd12:3456::1")));
+        assert!(!private.contains(ip("8.8.8.8")));
+        assert!(!private.contains(ip("2606:4700::1111")));
+    }
+}
@@ -1,7 +1,12 @@
 //! Shared helpers for the data-types example.
 //!
 //! Parses a simplified IPv4-like header from a fixed byte array and exposes
-//! typed fields for demos, unit tests, and integration tests.
+//! typed fields for demos, unit tests, and integration tests. The [`cidr`]
+//! module adds network blocks, IP sets, and prefix tables on top.
+
+pub mod cidr;
+
+pub use cidr::{Cidr, CidrError, IpFamily, IpRange, IpSet, PrefixTable, RFC1918, private_ranges};
 
 /// Fixed size of our educational packet header (bytes).
 pub const HEADER_LEN: usize = 20;
@@ -128,15 +133,10 @@ pub const fn protocol_label(protocol: u8) -> char {
     }
 }
 
-/// Human-readable protocol name (for demos).
+/// IANA protocol keyword from the compiled-in registry, or `"UNKNOWN"`.
 #[must_use]
-pub const fn protocol_name(protocol: u8) -> &'static str {
-    match protocol {
-        PROTO_ICMP => "ICMP",
-        PROTO_TCP => "TCP",
-        PROTO_UDP => "UDP",
-        _ => "UNKNOWN",
-    }
+pub fn protocol_name(protocol: u8) -> &'static str {
+    datatypes_exercises::registry::protocol_name(protocol).unwrap_or("UNKNOWN")
 }
 
 /// Format an IPv4 address array as dotted decimal.
@@ -162,13 +162,18 @@ pub const fn ttl_budget_ratio(ttl: u8, initial_ttl: u8) -> f64 {
     (ttl as f64) / (initial_ttl as f64)
 }
 
-/// Whether the packet looks like internal LAN traffic (simple heuristic).
+/// Whether the packet comes from an RFC 1918 private block.
 #[must_use]
 pub const fn is_private_src(src: [u8; 4]) -> bool {
-    // 10.0.0.0/8, 172.16.0.0/12, 192.168.0.0/16
-    src[0] == 10
-        || (src[0] == 172 && src[1] >= 16 && src[1] <= 31)
-        || (src[0] == 192 && src[1] == 168)
+    let host = Cidr::v4(src, 32);
+    let mut i = 0;
+    while i < RFC1918.len() {
+        if RFC1918[i].contains_cidr(&host) {
+            return true;
+        }
+        i += 1;
+    }
+    false
 }
 
 #[cfg(test)]
@@ -208,6 +213,8 @@ mod tests {
     fn protocol_label_and_name() {
         assert_eq!(protocol_label(PROTO_TCP), 'T');
         assert_eq!(protocol_name(PROTO_UDP), "UDP");
+        assert_eq!(protocol_name(47), "GRE");
+        assert_eq!(protocol_name(255), "UNKNOWN");
         assert_eq!(protocol_label(99), '?');
     }
 
@@ -15,8 +15,8 @@
 //! - Numeric literal forms: decimal, hex, binary, underscores
 
 use example_datatypes::{
-    HEADER_LEN, endpoint_summary, format_ipv4, is_private_src, parse_header, protocol_label,
-    protocol_name, sample_header_bytes, ttl_budget_ratio,
+    Cidr, HEADER_LEN, PrefixTable, endpoint_summary, format_ipv4, is_private_src, parse_header,
+    protocol_label, protocol_name, sample_header_bytes, ttl_budget_ratio,
 };
 
 fn main() {
@@ -103,5 +103,23 @@ fn main() {
     println!("  decimal port = {well_known_https}");
     println!("  underscored  = {epoch_demo}");
 
+    // === Networks as values: CIDR blocks and prefix tables ===
+    let mut zones: PrefixTable<&str> = PrefixTable::new();
+    for (block, zone) in [("0.0.0.0/0", "internet"), ("192.168.0.0/16", "office-lan")] {
+        if let Ok(cidr) = block.parse::<Cidr>() {
+            zones.insert(cidr, zone);
+        }
+    }
+    println!("\nZone lookup (longest prefix match):");
+    for addr in [header.src, header.dst] {
+        if let Some((cidr, zone)) = zones.lookup(addr.into()) {
+            println!(
+                "  {:<15} in {:<16} -> {zone}",
+                format_ipv4(addr),
+                cidr.to_string()
+            );
+        }
+    }
+
     println!("\nDone. See README.md for exercises and Rust Book links.");
 }
@@ -5,8 +5,9 @@
 #![allow(clippy::expect_used)]
 
 use example_datatypes::{
-    HEADER_LEN, PROTO_TCP, PROTO_UDP, endpoint_summary, format_ipv4, is_private_src, parse_header,
-    protocol_label, protocol_name, read_u16_be, sample_header_bytes, ttl_budget_ratio,
+    Cidr, HEADER_LEN, IpRange, IpSet, PROTO_TCP, PROTO_UDP, PrefixTable, endpoint_summary,
+    format_ipv4, is_private_src, parse_header, private_ranges, protocol_label, protocol_name,
+    read_u16_be, sample_header_bytes, ttl_budget_ratio,
 };
 
 #[test]
@@ -54,3 +55,46 @@ fn ttl_ratio_bounds() {
 fn read_u16_be_integration() {
     assert_eq!(read_u16_be(0xAB, 0xCD), 0xABCD);
 }
+
+#[test]
+fn private_heuristic_agrees_with_rfc1918_set() {
+    let private = private_ranges();
+    for octets in [
+        [10, 1, 2, 3],
+        [172, 31, 0, 1],
+        [172, 32, 0, 1],
+        [192, 169, 0, 1],
+    ] {
+        assert_eq!(is_private_src(octets), private.contains(octets.into()));
+    }
+}
+
+#[test]
+fn watchlist_set_from_mixed_entries() {
+    let entries = ["203.0.113.0/24", "198.51.100.7", "2001:db8:bad::/48"];
+    let watchlist: IpSet = entries
+        .iter()
+        .map(|text| text.parse::<Cidr>().expect("valid entry"))
+        .collect();
+    assert!(watchlist.contains("203.0.113.99".parse().expect("ip")));
+    assert!(watchlist.contains("2001:db8:bad::1".parse().expect("ip")));
+    assert!(!watchlist.contains("198.51.100.8".parse().expect("ip")));
+
+    let mut allowlisted = IpSet::new();
+    allowlisted.insert(Cidr::v4([203, 0, 113, 0], 25));
+    let effective = watchlist.difference(&allowlisted);
+    assert!(!effective.contains("203.0.113.1".parse().expect("ip")));
+    assert!(effective.contains("203.0.113.200".parse().expect("ip")));
+}
+
+#[test]
+fn range_conversion_and_prefix_labels() {
+    let range: IpRange = "192.168.0.0-192.168.3.255".parse().expect("range");
+    assert_eq!(range.to_cidrs(), vec![Cidr::v4([192, 168, 0, 0], 22)]);
+
+    let mut geo = PrefixTable::new();
+    geo.insert(Cidr::v4([81, 0, 0, 0], 8), "EU");
+    geo.insert(Cidr::v4([81, 2, 0, 0], 16), "EU-ES");
+    let hit = geo.lookup([81, 2, 3, 4].into());
+    assert_eq!(hit.map(|(_, label)| *label), Some("EU-ES"));
+}
@@ -0,0 +1,70 @@
+//! Injectable time source for expiry and lockout logic.
+//!
+//! Production code uses [`SystemClock`]; tests hand a [`ManualClock`] to the
+//! component and keep a clone to move time forward deterministically.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of "now" as milliseconds since the Unix epoch.
+pub trait Clock {
+    fn now_ms(&self) -> u64;
+}
+
+/// Wall-clock time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+/// Hand-driven clock; clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    #[must_use]
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            now: Arc::new(AtomicU64::new(start_ms)),
+        }
+    }
+
+    pub fn set(&self, now_ms: u64) {
+        self.now.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, by_ms: u64) {
+        self.now.fetch_add(by_ms, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clock_clones_share_time() {
+        let clock = ManualClock::new(10);
+        let handle = clock.clone();
+        handle.advance(5);
+        assert_eq!(clock.now_ms(), 15);
+        handle.set(1);
+        assert_eq!(clock.now_ms(), 1);
+        assert!(SystemClock.now_ms() > 0);
+    }
+}
@@ -0,0 +1,755 @@
+//! Stateful escalation workflow built on [`Severity`] and [`AccessDecision`].
+//!
+//! Each case moves through a fixed set of states:
+//!
+//! ```text
+//! New ──▶ Triage ──▶ Decided(Allow | Challenge | Deny) ──▶ Acknowledged ──▶ Resolved
+//!                          └──────────── (Allow only) ─────────────────────▲
+//! ```
+//!
+//! Every case gets an acknowledgement deadline from its [`SlaPolicy`]. If the
+//! deadline passes before an analyst acknowledges the case,
+//! [`EscalationQueue::check_slas`] raises the severity by one level and starts
+//! a new deadline. Each change is appended to an audit trail that can be read
+//! but not edited. Time comes from a [`Clock`], so tests can drive it with a
+//! [`ManualClock`](crate::clock::ManualClock).
+
+use std::fmt;
+
+use crate::clock::{Clock, SystemClock};
+use crate::{
+    AccessDecision, REVIEW_PIN, Severity, access_decision, apply_analyst_pin, decision_label,
+    severity_label,
+};
+
+const MINUTE_MS: u64 = 60 * 1000;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+
+/// How long each severity may wait for acknowledgement (`None` = no SLA).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlaPolicy {
+    pub info_ms: Option<u64>,
+    pub low_ms: Option<u64>,
+    pub medium_ms: Option<u64>,
+    pub high_ms: Option<u64>,
+    pub critical_ms: Option<u64>,
+}
+
+impl SlaPolicy {
+    #[must_use]
+    pub const fn ack_within(&self, level: Severity) -> Option<u64> {
+        match level {
+            Severity::Info => self.info_ms,
+            Severity::Low => self.low_ms,
+            Severity::Medium => self.medium_ms,
+            Severity::High => self.high_ms,
+            Severity::Critical => self.critical_ms,
+        }
+    }
+}
+
+impl Default for SlaPolicy {
+    /// Info: none, Low: 24 h, Medium: 4 h, High: 1 h, Critical: 15 min.
+    fn default() -> Self {
+        Self {
+            info_ms: None,
+            low_ms: Some(24 * HOUR_MS),
+            medium_ms: Some(4 * HOUR_MS),
+            high_ms: Some(HOUR_MS),
+            critical_ms: Some(15 * MINUTE_MS),
+        }
+    }
+}
+
+/// Identifier handed out by [`EscalationQueue::open`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CaseId(pub u64);
+
+impl fmt::Display for CaseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ESC-{:04}", self.0)
+    }
+}
+
+/// Where a case is in the workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseState {
+    New,
+    Triage,
+    Decided(AccessDecision),
+    Acknowledged,
+    Resolved,
+}
+
+impl fmt::Display for CaseState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::New => f.write_str("NEW"),
+            Self::Triage => f.write_str("TRIAGE"),
+            Self::Decided(decision) => write!(f, "DECIDED({})", decision_label(*decision)),
+            Self::Acknowledged => f.write_str("ACKNOWLEDGED"),
+            Self::Resolved => f.write_str("RESOLVED"),
+        }
+    }
+}
+
+/// Who caused an audit entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Actor {
+    System,
+    Analyst(String),
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::System => f.write_str("system"),
+            Self::Analyst(name) => f.write_str(name),
+        }
+    }
+}
+
+/// What happened in an audit entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditAction {
+    Opened {
+        severity: Severity,
+    },
+    TriageStarted,
+    Decided {
+        decision: AccessDecision,
+        pinned: bool,
+    },
+    Assigned {
+        analyst: String,
+    },
+    Acknowledged,
+    Resolved {
+        note: String,
+    },
+    AutoEscalated {
+        from: Severity,
+        to: Severity,
+    },
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Opened { severity } => write!(f, "opened at {}", severity_label(*severity)),
+            Self::TriageStarted => f.write_str("triage started"),
+            Self::Decided { decision, pinned } => {
+                write!(f, "decided {}", decision_label(*decision))?;
+                if *pinned {
+                    f.write_str(" (analyst pin)")?;
+                }
+                Ok(())
+            }
+            Self::Assigned { analyst } => write!(f, "assigned to {analyst}"),
+            Self::Acknowledged => f.write_str("acknowledged"),
+            Self::Resolved { note } => write!(f, "resolved: {note}"),
+            Self::AutoEscalated { from, to } => write!(
+                f,
+                "SLA lapsed, escalated {} -> {}",
+                severity_label(*from),
+                severity_label(*to)
+            ),
+        }
+    }
+}
+
+/// One append-only audit record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at_ms: u64,
+    pub case: CaseId,
+    pub actor: Actor,
+    pub action: AuditAction,
+    /// State before the action (`None` when the case was opened).
+    pub from: Option<CaseState>,
+    pub to: CaseState,
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} t={} {} [{}] {}",
+            self.seq, self.at_ms, self.case, self.actor, self.action
+        )?;
+        match self.from {
+            Some(from) if from != self.to => write!(f, " ({from} -> {})", self.to),
+            Some(_) => Ok(()),
+            None => write!(f, " (-> {})", self.to),
+        }
+    }
+}
+
+/// A single escalation case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub id: CaseId,
+    pub summary: String,
+    pub severity: Severity,
+    pub state: CaseState,
+    pub assignee: Option<String>,
+    pub opened_at_ms: u64,
+    /// When acknowledgement is due; `None` once acknowledged or without an SLA.
+    pub ack_deadline_ms: Option<u64>,
+    /// How many times the SLA lapsed and the severity was raised.
+    pub escalations: u32,
+}
+
+/// Why a workflow operation was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscalationError {
+    UnknownCase(CaseId),
+    InvalidTransition {
+        case: CaseId,
+        from: CaseState,
+        attempted: &'static str,
+    },
+    Unassigned(CaseId),
+    NotAssignee {
+        case: CaseId,
+        assignee: String,
+    },
+}
+
+impl fmt::Display for EscalationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownCase(case) => write!(f, "unknown case {case}"),
+            Self::InvalidTransition {
+                case,
+                from,
+                attempted,
+            } => write!(f, "{case}: cannot {attempted} from {from}"),
+            Self::Unassigned(case) => write!(f, "{case} has no assigned analyst"),
+            Self::NotAssignee { case, assignee } => {
+                write!(f, "{case} is assigned to {assignee}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EscalationError {}
+
+/// Open cases, their SLA timers and the audit trail.
+#[derive(Debug)]
+pub struct EscalationQueue<C: Clock = SystemClock> {
+    clock: C,
+    policy: SlaPolicy,
+    cases: Vec<Case>,
+    audit: Vec<AuditEntry>,
+}
+
+impl EscalationQueue<SystemClock> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock, SlaPolicy::default())
+    }
+}
+
+impl Default for EscalationQueue<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> EscalationQueue<C> {
+    pub const fn with_clock(clock: C, policy: SlaPolicy) -> Self {
+        Self {
+            clock,
+            policy,
+            cases: Vec::new(),
+            audit: Vec::new(),
+        }
+    }
+
+    pub const fn policy(&self) -> &SlaPolicy {
+        &self.policy
+    }
+
+    pub fn cases(&self) -> &[Case] {
+        &self.cases
+    }
+
+    pub fn case(&self, id: CaseId) -> Option<&Case> {
+        self.cases.iter().find(|case| case.id == id)
+    }
+
+    /// Every transition so far, oldest first.
+    pub fn audit(&self) -> &[AuditEntry] {
+        &self.audit
+    }
+
+    /// Audit entries for one case.
+    pub fn history(&self, id: CaseId) -> impl Iterator<Item = &AuditEntry> {
+        self.audit.iter().filter(move |entry| entry.case == id)
+    }
+
+    /// Cases that are not yet resolved.
+    pub fn open_cases(&self) -> impl Iterator<Item = &Case> {
+        self.cases
+            .iter()
+            .filter(|case| case.state != CaseState::Resolved)
+    }
+
+    /// Record a new event and start its SLA timer.
+    pub fn open(&mut self, severity: Severity, summary: impl Into<String>) -> CaseId {
+        let now = self.clock.now_ms();
+        let id = CaseId(self.cases.len() as u64 + 1);
+        self.cases.push(Case {
+            id,
+            summary: summary.into(),
+            severity,
+            state: CaseState::New,
+            assignee: None,
+            opened_at_ms: now,
+            ack_deadline_ms: self.deadline_from(now, severity),
+            escalations: 0,
+        });
+        self.record(
+            id,
+            Actor::System,
+            AuditAction::Opened { severity },
+            None,
+            CaseState::New,
+        );
+        id
+    }
+
+    /// `New -> Triage`.
+    ///
+    /// # Errors
+    ///
+    /// Unknown case, or the case is not `New`.
+    pub fn start_triage(&mut self, id: CaseId, analyst: &str) -> Result<(), EscalationError> {
+        let case = self.case_mut(id)?;
+        if case.state != CaseState::New {
+            return Err(invalid(case, "start triage"));
+        }
+        case.state = CaseState::Triage;
+        self.record(
+            id,
+            Actor::Analyst(analyst.to_string()),
+            AuditAction::TriageStarted,
+            Some(CaseState::New),
+            CaseState::Triage,
+        );
+        Ok(())
+    }
+
+    /// `Triage -> Decided`, using [`access_decision`] and the optional analyst pin.
+    ///
+    /// # Errors
+    ///
+    /// Unknown case, or the case is not in `Triage`.
+    pub fn decide(
+        &mut self,
+        id: CaseId,
+        analyst: &str,
+        pin: Option<&str>,
+    ) -> Result<AccessDecision, EscalationError> {
+        let case = self.case_mut(id)?;
+        if case.state != CaseState::Triage {
+            return Err(invalid(case, "decide"));
+        }
+        let base = access_decision(case.severity);
+        let decision = apply_analyst_pin(base, pin);
+        // A pin on a case that was already `Challenge` still counts as a pin.
+        let pinned = pin == Some(REVIEW_PIN);
+        case.state = CaseState::Decided(decision);
+        self.record(
+            id,
+            Actor::Analyst(analyst.to_string()),
+            AuditAction::Decided { decision, pinned },
+            Some(CaseState::Triage),
+            CaseState::Decided(decision),
+        );
+        Ok(decision)
+    }
+
+    /// Assign (or reassign) an analyst. Does not change the state.
+    ///
+    /// # Errors
+    ///
+    /// Unknown case, or the case is already resolved.
+    pub fn assign(&mut self, id: CaseId, analyst: &str) -> Result<(), EscalationError> {
+        let case = self.case_mut(id)?;
+        if case.state == CaseState::Resolved {
+            return Err(invalid(case, "assign"));
+        }
+        case.assignee = Some(analyst.to_string());
+        let state = case.state;
+        self.record(
+            id,
+            Actor::System,
+            AuditAction::Assigned {
+                analyst: analyst.to_string(),
+            },
+            Some(state),
+            state,
+        );
+        Ok(())
+    }
+
+    /// `Decided -> Acknowledged` by the assigned analyst; stops the SLA timer.
+    ///
+    /// # Errors
+    ///
+    /// Unknown case, wrong state, no assignee, or a different assignee.
+    pub fn acknowledge(&mut self, id: CaseId, analyst: &str) -> Result<(), EscalationError> {
+        let case = self.case_mut(id)?;
+        let from = case.state;
+        if !matches!(from, CaseState::Decided(_)) {
+            return Err(invalid(case, "acknowledge"));
+        }
+        check_assignee(case, analyst)?;
+        case.state = CaseState::Acknowledged;
+        case.ack_deadline_ms = None;
+        self.record(
+            id,
+            Actor::Analyst(analyst.to_string()),
+            AuditAction::Acknowledged,
+            Some(from),
+            CaseState::Acknowledged,
+        );
+        Ok(())
+    }
+
+    /// `Acknowledged -> Resolved`, or straight from `Decided(Allow)`.
+    ///
+    /// # Errors
+    ///
+    /// Unknown case, wrong state, or (after acknowledgement) a different assignee.
+    pub fn resolve(
+        &mut self,
+        id: CaseId,
+        analyst: &str,
+        note: &str,
+    ) -> Result<(), EscalationError> {
+        let case = self.case_mut(id)?;
+        let from = case.state;
+        match from {
+            CaseState::Acknowledged => check_assignee(case, analyst)?,
+            CaseState::Decided(AccessDecision::Allow) => {}
+            _ => return Err(invalid(case, "resolve")),
+        }
+        case.state = CaseState::Resolved;
+        case.ack_deadline_ms = None;
+        self.record(
+            id,
+            Actor::Analyst(analyst.to_string()),
+            AuditAction::Resolved {
+                note: note.to_string(),
+            },
+            Some(from),
+            CaseState::Resolved,
+        );
+        Ok(())
+    }
+
+    /// Escalate every case whose acknowledgement deadline has passed.
+    ///
+    /// Severity goes up one level (Critical stays Critical), a decided case is
+    /// tightened to the stricter of its decision and the new severity's, and
+    /// a fresh deadline starts. Returns the escalated case ids.
+    pub fn check_slas(&mut self) -> Vec<CaseId> {
+        let now = self.clock.now_ms();
+        let mut escalated = Vec::new();
+        let mut entries = Vec::new();
+
+        for case in &mut self.cases {
+            let Some(deadline) = case.ack_deadline_ms else {
+                continue;
+            };
+            if now < deadline {
+                continue;
+            }
+            let from_severity = case.severity;
+            let from_state = case.state;
+            case.severity = raise(from_severity);
+            case.escalations += 1;
+            if let CaseState::Decided(decision) = case.state {
+                case.state = CaseState::Decided(stricter(decision, access_decision(case.severity)));
+            }
+            case.ack_deadline_ms = self
+                .policy
+                .ack_within(case.severity)
+                .map(|window| now.saturating_add(window));
+            escalated.push(case.id);
+            entries.push((
+                case.id,
+                AuditAction::AutoEscalated {
+                    from: from_severity,
+                    to: case.severity,
+                },
+                from_state,
+                case.state,
+            ));
+        }
+
+        for (id, action, from, to) in entries {
+            self.record(id, Actor::System, action, Some(from), to);
+        }
+        escalated
+    }
+
+    fn deadline_from(&self, now: u64, severity: Severity) -> Option<u64> {
+        self.policy
+            .ack_within(severity)
+            .map(|window| now.saturating_add(window))
+    }
+
+    fn case_mut(&mut self, id: CaseId) -> Result<&mut Case, EscalationError> {
+        self.cases
+            .iter_mut()
+            .find(|case| case.id == id)
+            .ok_or(EscalationError::UnknownCase(id))
+    }
+
+    fn record(
+        &mut self,
+        case: CaseId,
+        actor: Actor,
+        action: AuditAction,
+        from: Option<CaseState>,
+        to: CaseState,
+    ) {
+        self.audit.push(AuditEntry {
+            seq: self.audit.len() as u64 + 1,
+            at_ms: self.clock.now_ms(),
+            case,
+            actor,
+            action,
+            from,
+            to,
+        });
+    }
+}
+
+const fn invalid(case: &Case, attempted: &'static str) -> EscalationError {
+    EscalationError::InvalidTransition {
+        case: case.id,
+        from: case.state,
+        attempted,
+    }
+}
+
+fn check_assignee(case: &Case, analyst: &str) -> Result<(), EscalationError> {
+    match &case.assignee {
+        None => Err(EscalationError::Unassigned(case.id)),
+        Some(assignee) if assignee != analyst => Err(EscalationError::NotAssignee {
+            case: case.id,
+            assignee: assignee.clone(),
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+const fn raise(level: Severity) -> Severity {
+    match level {
+        Severity::Info => Severity::Low,
+        Severity::Low => Severity::Medium,
+        Severity::Medium => Severity::High,
+        Severity::High | Severity::Critical => Severity::Critical,
+    }
+}
+
+const fn strictness(decision: AccessDecision) -> u8 {
+    match decision {
+        AccessDecision::Allow => 0,
+        AccessDecision::Challenge => 1,
+        AccessDecision::Deny => 2,
+    }
+}
+
+const fn stricter(a: AccessDecision, b: AccessDecision) -> AccessDecision {
+    if strictness(b) > strictness(a) { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::REVIEW_PIN;
+    use crate::clock::ManualClock;
+
+    fn queue() -> (ManualClock, EscalationQueue<ManualClock>) {
+        let clock = ManualClock::new(1_000);
+        let queue = EscalationQueue::with_clock(clock.clone(), SlaPolicy::default());
+        (clock, queue)
+    }
+
+    #[test]
+    fn happy_path_records_every_transition() {
+        let (clock, mut q) = queue();
+        let id = q.open(Severity::High, "ssh brute force");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(q.decide(id, "alice", None), Ok(AccessDecision::Challenge));
+        assert!(q.assign(id, "bob").is_ok());
+        clock.advance(MINUTE_MS);
+        assert!(q.acknowledge(id, "bob").is_ok());
+        assert!(q.resolve(id, "bob", "password reset").is_ok());
+
+        let states: Vec<CaseState> = q.history(id).map(|e| e.to).collect();
+        assert_eq!(
+            states,
+            vec![
+                CaseState::New,
+                CaseState::Triage,
+                CaseState::Decided(AccessDecision::Challenge),
+                CaseState::Decided(AccessDecision::Challenge),
+                CaseState::Acknowledged,
+                CaseState::Resolved,
+            ]
+        );
+        assert_eq!(q.audit()[4].at_ms, 1_000 + MINUTE_MS);
+        assert_eq!(q.case(id).map(|c| c.ack_deadline_ms), Some(None));
+        assert_eq!(q.open_cases().count(), 0);
+    }
+
+    #[test]
+    fn pin_is_audited() {
+        let (_, mut q) = queue();
+        let id = q.open(Severity::Low, "odd login hour");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(
+            q.decide(id, "alice", Some(REVIEW_PIN)),
+            Ok(AccessDecision::Challenge)
+        );
+        assert!(matches!(
+            q.audit().last().map(|e| &e.action),
+            Some(AuditAction::Decided { pinned: true, .. })
+        ));
+    }
+
+    #[test]
+    fn pin_is_audited_when_it_matches_the_base_decision() {
+        let (_, mut q) = queue();
+        let id = q.open(Severity::High, "impossible travel");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(
+            q.decide(id, "alice", Some(REVIEW_PIN)),
+            Ok(AccessDecision::Challenge)
+        );
+        assert!(matches!(
+            q.audit().last().map(|e| &e.action),
+            Some(AuditAction::Decided { pinned: true, .. })
+        ));
+
+        let id = q.open(Severity::High, "impossible travel");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert!(q.decide(id, "alice", Some("wrong")).is_ok());
+        assert!(matches!(
+            q.audit().last().map(|e| &e.action),
+            Some(AuditAction::Decided { pinned: false, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_out_of_order_and_wrong_analyst() {
+        let (_, mut q) = queue();
+        let id = q.open(Severity::Critical, "ransomware note");
+        assert!(matches!(
+            q.decide(id, "alice", None),
+            Err(EscalationError::InvalidTransition {
+                from: CaseState::New,
+                ..
+            })
+        ));
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(q.decide(id, "alice", None), Ok(AccessDecision::Deny));
+        assert_eq!(
+            q.acknowledge(id, "alice"),
+            Err(EscalationError::Unassigned(id))
+        );
+        assert!(q.assign(id, "bob").is_ok());
+        assert!(matches!(
+            q.acknowledge(id, "alice"),
+            Err(EscalationError::NotAssignee { .. })
+        ));
+        assert!(q.resolve(id, "bob", "too early").is_err());
+        assert_eq!(
+            q.start_triage(CaseId(99), "alice"),
+            Err(EscalationError::UnknownCase(CaseId(99)))
+        );
+    }
+
+    #[test]
+    fn allow_can_close_without_acknowledgement() {
+        let (_, mut q) = queue();
+        let id = q.open(Severity::Info, "heartbeat");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(q.decide(id, "alice", None), Ok(AccessDecision::Allow));
+        assert!(q.resolve(id, "alice", "benign").is_ok());
+    }
+
+    #[test]
+    fn lapsed_sla_escalates_and_restarts_timer() {
+        let (clock, mut q) = queue();
+        let id = q.open(Severity::Medium, "repeated 403s");
+        assert!(q.start_triage(id, "alice").is_ok());
+        assert_eq!(q.decide(id, "alice", None), Ok(AccessDecision::Allow));
+
+        clock.advance(4 * HOUR_MS - 1);
+        assert!(q.check_slas().is_empty());
+        clock.advance(1);
+        assert_eq!(q.check_slas(), vec![id]);
+
+        let case = q.case(id);
+        assert_eq!(case.map(|c| c.severity), Some(Severity::High));
+        assert_eq!(
+            case.map(|c| c.state),
+            Some(CaseState::Decided(AccessDecision::Challenge))
+        );
+        assert_eq!(
+            case.and_then(|c| c.ack_deadline_ms),
+            Some(1_000 + 5 * HOUR_MS)
+        );
+
+        clock.advance(HOUR_MS);
+        assert_eq!(q.check_slas(), vec![id]);
+        assert_eq!(
+            q.case(id).map(|c| (c.severity, c.escalations)),
+            Some((Severity::Critical, 2))
+        );
+        assert!(
+            q.audit()
+                .iter()
+                .filter(|e| e.actor == Actor::System)
+                .count()
+                >= 3
+        );
+    }
+
+    #[test]
+    fn acknowledged_and_slaless_cases_do_not_escalate() {
+        let (clock, mut q) = queue();
+        let quiet = q.open(Severity::Info, "no sla");
+        let acked = q.open(Severity::Critical, "handled");
+        assert!(q.start_triage(acked, "alice").is_ok());
+        assert!(q.decide(acked, "alice", None).is_ok());
+        assert!(q.assign(acked, "alice").is_ok());
+        assert!(q.acknowledge(acked, "alice").is_ok());
+
+        clock.advance(48 * HOUR_MS);
+        assert!(q.check_slas().is_empty());
+        assert_eq!(q.case(quiet).map(|c| c.severity), Some(Severity::Info));
+    }
+
+    #[test]
+    fn display_formats() {
+        assert_eq!(CaseId(7).to_string(), "ESC-0007");
+        let (_, mut q) = queue();
+        let id = q.open(Severity::High, "x");
+        assert!(q.start_triage(id, "alice").is_ok());
+        let line = q.audit()[1].to_string();
+        assert_eq!(
+            line,
+            "#2 t=1000 ESC-0001 [alice] triage started (NEW -> TRIAGE)"
+        );
+    }
+}
@@ -2,6 +2,23 @@
 //!
 //! A tiny threat-score / log classifier: severity enums, scoring with `if`
 //! expressions, `match`, loops with `break`/`continue`, and `if let`.
+//! [`escalation`] turns the one-shot decisions into a stateful workflow, and
+//! [`topk`] ranks an unbounded stream instead of stopping at a budget.
+//! [`clock`] is the injectable time source they share with later exercises.
+
+pub mod clock;
+pub mod escalation;
+pub mod topk;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use escalation::{
+    Actor, AuditAction, AuditEntry, Case, CaseId, CaseState, EscalationError, EscalationQueue,
+    SlaPolicy,
+};
+pub use topk::{DropReason, DropReport, DroppedEvent, RankedEvent, TopKClassifier, template_of};
+
+/// Analyst pin that forces a [`AccessDecision::Challenge`].
+pub const REVIEW_PIN: &str = "REVIEW";
 
 /// Severity levels for a classified security event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -170,7 +187,7 @@ pub fn accumulate_until_budget(scores: &[u8], budget: u32) -> u32 {
 pub fn apply_analyst_pin(decision: AccessDecision, pin: Option<&str>) -> AccessDecision {
     // `if let` with a guard keeps the Option demo while staying clippy-clean.
     if let Some(code) = pin
-        && code == "REVIEW"
+        && code == REVIEW_PIN
     {
         return AccessDecision::Challenge;
     }
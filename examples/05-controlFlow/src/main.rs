@@ -13,9 +13,11 @@
 //! - `break` / `continue` (including break with a value)
 //! - `match` on integers and enums
 //! - `if let` for concise `Option` handling
+//! - `match` on state enums to drive an escalation workflow
 
 use example_controlflow::{
-    AccessDecision, accumulate_until_budget, apply_analyst_pin, classify_batch, decision_label,
+    AccessDecision, EscalationQueue, ManualClock, REVIEW_PIN, Severity, SlaPolicy, TopKClassifier,
+    accumulate_until_budget, apply_analyst_pin, classify_batch, decision_label,
     first_critical_index, score_log_line, severity_from_score, severity_label, severity_weight,
 };
 
@@ -76,6 +78,25 @@ fn main() {
     let used = accumulate_until_budget(&scores, 30);
     println!("Budget 30 consumed: {used}");
 
+    // === unbounded stream, bounded memory ===
+    // The budgeted helpers above never look past their limit; top-K does.
+    let mut topk = TopKClassifier::new(3).with_min_score(5);
+    topk.extend((0..5_000).map(|i| format!("info: heartbeat seq={i}")));
+    topk.extend((1..=40).map(|i| format!("warning: failed login for user{i} from 10.0.0.{i}")));
+    topk.extend(logs);
+    topk.push("CRITICAL exploit beacon to 203.0.113.66:4444");
+    println!("\nTop-3 of {} streamed lines:", topk.report().seen);
+    for event in topk.ranked() {
+        println!(
+            "  score={:3} {:>8} x{:<5} {}",
+            event.score,
+            severity_label(event.severity),
+            event.count,
+            event.template
+        );
+    }
+    println!("  {}", topk.report());
+
     // === match + if let ===
     // `if let` unwraps Option without a full match.
     if let Some(idx) = first_critical_index(&scores) {
@@ -88,7 +109,7 @@ fn main() {
     }
 
     // match on the decision enum for a final policy note
-    let final_decision = apply_analyst_pin(AccessDecision::Allow, Some("REVIEW"));
+    let final_decision = apply_analyst_pin(AccessDecision::Allow, Some(REVIEW_PIN));
     let note = match final_decision {
         AccessDecision::Allow => "open the gate",
         AccessDecision::Challenge => "step-up authentication required",
@@ -99,14 +120,49 @@ fn main() {
         decision_label(final_decision)
     );
 
+    escalation_demo();
+
     println!("\nDone. See README.md for exercises and Rust Book links.");
 }
 
-fn access_with_optional_pin(level: example_controlflow::Severity, score: u8) -> AccessDecision {
+fn escalation_demo() {
+    println!("\nEscalation workflow (manual clock):");
+    let clock = ManualClock::new(0);
+    let mut queue = EscalationQueue::with_clock(clock.clone(), SlaPolicy::default());
+
+    let brute = queue.open(Severity::High, "ssh brute force on bastion");
+    let noisy = queue.open(Severity::Medium, "burst of 403s from CDN");
+    let steps = [
+        queue.start_triage(brute, "alice"),
+        queue.decide(brute, "alice", None).map(|_| ()),
+        queue.assign(brute, "bob"),
+        queue.acknowledge(brute, "bob"),
+        queue.start_triage(noisy, "alice"),
+        queue.decide(noisy, "alice", None).map(|_| ()),
+    ];
+    for err in steps.iter().filter_map(|step| step.as_ref().err()) {
+        println!("  refused: {err}");
+    }
+
+    // Nobody acknowledges the 403 burst: after 4h the SLA lapses.
+    clock.advance(4 * 60 * 60 * 1000);
+    for id in queue.check_slas() {
+        println!("  SLA lapsed for {id}");
+    }
+    if let Err(err) = queue.resolve(brute, "bob", "source blocked at edge") {
+        println!("  refused: {err}");
+    }
+
+    for entry in queue.audit() {
+        println!("  {entry}");
+    }
+}
+
+fn access_with_optional_pin(level: Severity, score: u8) -> AccessDecision {
     use example_controlflow::access_decision;
 
     let base = access_decision(level);
     // Demonstrate if-expression selecting an optional pin.
-    let pin = if score >= 70 { Some("REVIEW") } else { None };
+    let pin = if score >= 70 { Some(REVIEW_PIN) } else { None };
     apply_analyst_pin(base, pin)
 }
@@ -0,0 +1,409 @@
+//! Top-K classification over an unbounded stream.
+//!
+//! [`classify_batch`](crate::classify_batch) and
+//! [`accumulate_until_budget`](crate::accumulate_until_budget) stop in input
+//! order, so a critical line after the budget is never seen. [`TopKClassifier`]
+//! reads every line but keeps only the `K` highest-scoring *templates* (a
+//! min-heap of size `K`). A template is the line with IPv4 addresses replaced
+//! by `<IP>` and other numbers by `<N>`. Repeats of a template are counted
+//! instead of stored again. A [`DropReport`] records everything that was not
+//! kept and why.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+use crate::{Severity, score_log_line, severity_from_score, severity_label};
+
+/// Mask IPv4 addresses as `<IP>` and remaining digit runs as `<N>`.
+#[must_use]
+pub fn template_of(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line.trim();
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let run_len = tail
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(tail.len());
+        let run = tail[..run_len].trim_end_matches('.');
+        if is_ipv4(run) {
+            out.push_str("<IP>");
+        } else {
+            mask_numbers(run, &mut out);
+        }
+        rest = &tail[run.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_ipv4(run: &str) -> bool {
+    let mut parts = 0;
+    for part in run.split('.') {
+        parts += 1;
+        if part.is_empty() || part.len() > 3 || part.parse::<u8>().is_err() {
+            return false;
+        }
+    }
+    parts == 4
+}
+
+fn mask_numbers(run: &str, out: &mut String) {
+    let mut in_digits = false;
+    for c in run.chars() {
+        if c.is_ascii_digit() {
+            if !in_digits {
+                out.push_str("<N>");
+            }
+            in_digits = true;
+        } else {
+            out.push(c);
+            in_digits = false;
+        }
+    }
+}
+
+/// One kept template with its occurrence count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedEvent {
+    pub template: String,
+    /// First line seen for this template.
+    pub sample: String,
+    pub score: u8,
+    pub severity: Severity,
+    pub count: u64,
+    /// Zero-based stream positions of the first and latest occurrence.
+    pub first_index: u64,
+    pub last_index: u64,
+}
+
+/// Why a line did not end up in the top-K.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Empty or whitespace-only line.
+    Blank,
+    /// Score under the configured minimum.
+    BelowMinimum,
+    /// Queue was full and every kept template outranked this one.
+    Rejected,
+    /// Was kept, then pushed out by a higher-ranked template.
+    Evicted,
+}
+
+/// Worst line that was dropped, kept so a report can say what was lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedEvent {
+    pub template: String,
+    pub score: u8,
+    pub severity: Severity,
+    pub reason: DropReason,
+}
+
+/// Counts of lines seen, merged and dropped (by reason).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DropReport {
+    pub seen: u64,
+    /// Repeats folded into an already-kept template.
+    pub merged: u64,
+    pub blank: u64,
+    pub below_minimum: u64,
+    pub rejected: u64,
+    /// Occurrences (not templates) lost when a kept template was evicted.
+    pub evicted: u64,
+    pub worst_dropped: Option<DroppedEvent>,
+}
+
+impl DropReport {
+    #[must_use]
+    pub const fn total_dropped(&self) -> u64 {
+        self.blank + self.below_minimum + self.rejected + self.evicted
+    }
+
+    #[must_use]
+    pub const fn count(&self, reason: DropReason) -> u64 {
+        match reason {
+            DropReason::Blank => self.blank,
+            DropReason::BelowMinimum => self.below_minimum,
+            DropReason::Rejected => self.rejected,
+            DropReason::Evicted => self.evicted,
+        }
+    }
+
+    fn drop_line(&mut self, reason: DropReason, count: u64, template: &str, score: u8) {
+        match reason {
+            DropReason::Blank => self.blank += count,
+            DropReason::BelowMinimum => self.below_minimum += count,
+            DropReason::Rejected => self.rejected += count,
+            DropReason::Evicted => self.evicted += count,
+        }
+        if reason != DropReason::Blank
+            && self.worst_dropped.as_ref().is_none_or(|w| score > w.score)
+        {
+            self.worst_dropped = Some(DroppedEvent {
+                template: template.to_string(),
+                score,
+                severity: severity_from_score(score),
+                reason,
+            });
+        }
+    }
+}
+
+impl fmt::Display for DropReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seen={} merged={} dropped={} (blank={}, below_min={}, rejected={}, evicted={})",
+            self.seen,
+            self.merged,
+            self.total_dropped(),
+            self.blank,
+            self.below_minimum,
+            self.rejected,
+            self.evicted
+        )?;
+        if let Some(worst) = &self.worst_dropped {
+            write!(
+                f,
+                "; worst dropped {} score={} {:?}: {}",
+                severity_label(worst.severity),
+                worst.score,
+                worst.reason,
+                worst.template
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Heap key: higher score wins; on ties the earlier template wins.
+type Rank = (u8, Reverse<u64>);
+
+/// Keeps the `K` highest-ranked templates from a stream in `O(K)` memory.
+///
+/// Severity is derived from the score, so ranking by score also ranks by
+/// severity.
+#[derive(Debug, Clone)]
+pub struct TopKClassifier {
+    capacity: usize,
+    min_score: u8,
+    kept: HashMap<String, RankedEvent>,
+    heap: BinaryHeap<Reverse<(Rank, String)>>,
+    report: DropReport,
+}
+
+impl TopKClassifier {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            min_score: 0,
+            kept: HashMap::with_capacity(capacity),
+            heap: BinaryHeap::with_capacity(capacity),
+            report: DropReport::default(),
+        }
+    }
+
+    /// Drop lines scoring below `min_score` without queueing them.
+    #[must_use]
+    pub const fn with_min_score(mut self, min_score: u8) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.kept.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.kept.is_empty()
+    }
+
+    #[must_use]
+    pub const fn report(&self) -> &DropReport {
+        &self.report
+    }
+
+    /// Classify one line.
+    pub fn push(&mut self, line: &str) {
+        let index = self.report.seen;
+        self.report.seen += 1;
+
+        if line.trim().is_empty() {
+            self.report.drop_line(DropReason::Blank, 1, "", 0);
+            return;
+        }
+        let template = template_of(line);
+        if let Some(event) = self.kept.get_mut(&template) {
+            event.count += 1;
+            event.last_index = index;
+            self.report.merged += 1;
+            return;
+        }
+
+        let score = score_log_line(line);
+        if score < self.min_score {
+            self.report
+                .drop_line(DropReason::BelowMinimum, 1, &template, score);
+            return;
+        }
+
+        let rank: Rank = (score, Reverse(index));
+        if self.kept.len() >= self.capacity {
+            let outranks = self
+                .heap
+                .peek()
+                .is_some_and(|Reverse((lowest, _))| rank > *lowest);
+            if !outranks {
+                self.report
+                    .drop_line(DropReason::Rejected, 1, &template, score);
+                return;
+            }
+            if let Some(Reverse((_, victim))) = self.heap.pop()
+                && let Some(evicted) = self.kept.remove(&victim)
+            {
+                self.report
+                    .drop_line(DropReason::Evicted, evicted.count, &victim, evicted.score);
+            }
+        }
+
+        self.heap.push(Reverse((rank, template.clone())));
+        self.kept.insert(
+            template.clone(),
+            RankedEvent {
+                template,
+                sample: line.trim().to_string(),
+                score,
+                severity: severity_from_score(score),
+                count: 1,
+                first_index: index,
+                last_index: index,
+            },
+        );
+    }
+
+    /// Kept templates, highest rank first.
+    #[must_use]
+    pub fn ranked(&self) -> Vec<&RankedEvent> {
+        let mut events: Vec<&RankedEvent> = self.kept.values().collect();
+        events.sort_by_key(|e| Reverse((e.score, Reverse(e.first_index))));
+        events
+    }
+
+    /// Consume the classifier, returning the ranking and the drop report.
+    #[must_use]
+    pub fn finish(self) -> (Vec<RankedEvent>, DropReport) {
+        let mut events: Vec<RankedEvent> = self.kept.into_values().collect();
+        events.sort_by_key(|e| Reverse((e.score, Reverse(e.first_index))));
+        (events, self.report)
+    }
+}
+
+impl<S: AsRef<str>> Extend<S> for TopKClassifier {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, lines: I) {
+        for line in lines {
+            self.push(line.as_ref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_mask_ips_and_numbers() {
+        assert_eq!(
+            template_of("failed login from 10.0.0.7 port 52311"),
+            "failed login from <IP> port <N>"
+        );
+        assert_eq!(
+            template_of("sshd[42]: user7 v1.2.3."),
+            "sshd[<N>]: user<N> v<N>.<N>.<N>."
+        );
+        assert_eq!(template_of("  999.1.1.1 "), "<N>.<N>.<N>.<N>");
+        assert_eq!(template_of("no digits"), "no digits");
+    }
+
+    #[test]
+    fn late_critical_event_survives() {
+        let mut topk = TopKClassifier::new(2);
+        for i in 0..10_000 {
+            topk.push(&format!("info: tick {i}"));
+        }
+        topk.push("warning: disk 91% on host 3");
+        topk.push("CRITICAL malware exploit on 10.1.2.3");
+
+        let (ranked, report) = topk.finish();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].severity, Severity::Critical);
+        assert_eq!(ranked[0].template, "CRITICAL malware exploit on <IP>");
+        assert_eq!(ranked[1].template, "warning: disk <N>% on host <N>");
+        assert_eq!(report.seen, 10_002);
+        // The 10k ticks merged into one template, which was later evicted.
+        assert_eq!(report.merged, 9_999);
+        assert_eq!(report.evicted, 10_000);
+    }
+
+    #[test]
+    fn duplicates_are_counted() {
+        let mut topk = TopKClassifier::new(5);
+        topk.extend([
+            "failed login from 10.0.0.1",
+            "",
+            "failed login from 10.0.0.2",
+            "failed login from 192.168.1.20",
+        ]);
+        let ranked = topk.ranked();
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].count, 3);
+        assert_eq!((ranked[0].first_index, ranked[0].last_index), (0, 3));
+        assert_eq!(ranked[0].sample, "failed login from 10.0.0.1");
+        assert_eq!(topk.report().blank, 1);
+    }
+
+    #[test]
+    fn rejected_and_below_minimum_are_reported() {
+        let mut topk = TopKClassifier::new(1).with_min_score(10);
+        topk.extend([
+            "unauthorized access",
+            "info ping",
+            "warning fan",
+            "unauthorized access",
+        ]);
+        let report = topk.report();
+        assert_eq!(report.below_minimum, 1);
+        assert_eq!(report.rejected, 1);
+        assert_eq!(report.merged, 1);
+        assert_eq!(report.total_dropped(), 2);
+        assert_eq!(
+            report.worst_dropped.as_ref().map(|w| (w.reason, w.score)),
+            Some((DropReason::Rejected, 10))
+        );
+        assert!(report.to_string().contains("rejected=1"));
+    }
+
+    #[test]
+    fn ties_keep_the_earliest() {
+        let mut topk = TopKClassifier::new(1);
+        topk.extend(["warning a", "warning b"]);
+        assert_eq!(topk.ranked()[0].template, "warning a");
+        assert_eq!(topk.report().count(DropReason::Rejected), 1);
+    }
+
+    #[test]
+    fn zero_capacity_rejects_everything() {
+        let mut topk = TopKClassifier::new(0);
+        topk.push("CRITICAL malware");
+        assert!(topk.is_empty());
+        assert_eq!(topk.report().rejected, 1);
+    }
+}
@@ -1,7 +1,8 @@
 //! Integration tests for `example_controlflow`.
 
 use example_controlflow::{
-    AccessDecision, Severity, access_decision, accumulate_until_budget, apply_analyst_pin,
+    AccessDecision, AuditAction, CaseState, EscalationQueue, ManualClock, REVIEW_PIN, Severity,
+    SlaPolicy, TopKClassifier, access_decision, accumulate_until_budget, apply_analyst_pin,
     classify_batch, decision_label, first_critical_index, score_log_line, severity_from_score,
     severity_label,
 };
@@ -37,3 +38,61 @@ fn optional_pin_and_critical_search() {
     assert_eq!(first_critical_index(&[5, 95, 10]), Some(1));
     assert_eq!(severity_label(Severity::Low), "LOW");
 }
+
+#[test]
+fn escalation_workflow_with_sla_lapse() {
+    let clock = ManualClock::new(0);
+    let mut queue = EscalationQueue::with_clock(clock.clone(), SlaPolicy::default());
+    let id = queue.open(
+        severity_from_score(score_log_line("warning: failed login")),
+        "login",
+    );
+
+    assert!(queue.start_triage(id, "alice").is_ok());
+    assert_eq!(
+        queue.decide(id, "alice", Some(REVIEW_PIN)),
+        Ok(AccessDecision::Challenge)
+    );
+    assert!(queue.resolve(id, "alice", "not allowed yet").is_err());
+
+    let window = SlaPolicy::default().ack_within(Severity::Low);
+    assert!(window.is_some());
+    clock.advance(window.unwrap_or_default());
+    assert_eq!(queue.check_slas(), vec![id]);
+    assert_eq!(queue.case(id).map(|c| c.severity), Some(Severity::Medium));
+
+    assert!(queue.assign(id, "bob").is_ok());
+    assert!(queue.acknowledge(id, "bob").is_ok());
+    assert!(queue.resolve(id, "bob", "user confirmed").is_ok());
+    assert_eq!(queue.case(id).map(|c| c.state), Some(CaseState::Resolved));
+    assert!(
+        queue
+            .audit()
+            .iter()
+            .any(|e| matches!(e.action, AuditAction::AutoEscalated { .. }))
+    );
+    assert!(
+        queue
+            .audit()
+            .windows(2)
+            .all(|w| w[0].seq < w[1].seq && w[0].at_ms <= w[1].at_ms)
+    );
+}
+
+#[test]
+fn top_k_sees_past_the_budget() {
+    let mut lines: Vec<String> = (0..10_000).map(|i| format!("info ok {i}")).collect();
+    lines.push("CRITICAL malware exploit".to_string());
+
+    let refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let (counted, _, stopped_early) = classify_batch(&refs, 100);
+    assert!(stopped_early && counted == 100);
+
+    let mut topk = TopKClassifier::new(1);
+    topk.extend(&lines);
+    let (ranked, report) = topk.finish();
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].severity, Severity::Critical);
+    assert_eq!(report.seen, 10_001);
+    assert_eq!(report.evicted, 10_000);
+}
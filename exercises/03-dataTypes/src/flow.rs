@@ -0,0 +1,994 @@
+//! TCP flow tracking — 5-tuple flow table, connection state, and stream reassembly.
+//!
+//! Segments from both directions of a connection land in the same [`Flow`]
+//! because [`FlowKey`] normalises endpoint order with [`swap_ports`]. Each
+//! direction owns a [`StreamReassembler`] that turns out-of-order and
+//! overlapping segments back into an in-order byte stream.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::packet::{PROTO_TCP, TcpPorts, format_ipv4, protocol_label, swap_ports};
+
+/// TCP flag bits as they appear in byte 13 of the TCP header.
+pub const TCP_FIN: u8 = 0x01;
+pub const TCP_SYN: u8 = 0x02;
+pub const TCP_RST: u8 = 0x04;
+pub const TCP_PSH: u8 = 0x08;
+pub const TCP_ACK: u8 = 0x10;
+pub const TCP_URG: u8 = 0x20;
+
+/// Decoded TCP segment handed to the flow table.
+///
+/// The payload is borrowed so callers can feed segments straight out of a
+/// capture buffer without copying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpSegment<'a> {
+    /// Source IPv4 address.
+    pub src: [u8; 4],
+    /// Destination IPv4 address.
+    pub dst: [u8; 4],
+    /// Source and destination ports.
+    pub ports: TcpPorts,
+    /// Sequence number of the first payload byte (or of the SYN/FIN).
+    pub seq: u32,
+    /// Acknowledgement number (meaningful when [`TCP_ACK`] is set).
+    pub ack: u32,
+    /// Raw flag byte (`TCP_SYN | TCP_ACK`, …).
+    pub flags: u8,
+    /// Segment payload.
+    pub payload: &'a [u8],
+    /// Capture timestamp in milliseconds.
+    pub timestamp_ms: u64,
+}
+
+impl TcpSegment<'_> {
+    /// Whether every bit in `flag` is set on this segment.
+    #[must_use]
+    pub const fn has(&self, flag: u8) -> bool {
+        self.flags & flag == flag
+    }
+}
+
+/// Direction of a segment relative to the connection initiator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Sent by the side that opened the connection.
+    ClientToServer,
+    /// Sent by the side that accepted the connection.
+    ServerToClient,
+}
+
+/// Direction-independent 5-tuple identifying a flow.
+///
+/// Endpoint `a` is always the lower `(address, port)` pair, so a segment and
+/// its reply produce the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlowKey {
+    /// Lower endpoint address.
+    pub addr_a: [u8; 4],
+    /// Higher endpoint address.
+    pub addr_b: [u8; 4],
+    /// `source_port` belongs to `addr_a`, `dest_port` to `addr_b`.
+    pub ports: TcpPorts,
+    /// IANA protocol number.
+    pub protocol: u8,
+}
+
+impl FlowKey {
+    /// Build the normalised key for a segment.
+    #[must_use]
+    pub fn from_segment(segment: &TcpSegment<'_>) -> Self {
+        let forward = (segment.src, segment.ports.source_port);
+        let reverse = (segment.dst, segment.ports.dest_port);
+        if forward <= reverse {
+            Self {
+                addr_a: segment.src,
+                addr_b: segment.dst,
+                ports: segment.ports,
+                protocol: PROTO_TCP,
+            }
+        } else {
+            Self {
+                addr_a: segment.dst,
+                addr_b: segment.src,
+                ports: swap_ports(segment.ports),
+                protocol: PROTO_TCP,
+            }
+        }
+    }
+}
+
+impl fmt::Display for FlowKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{} <-> {}:{} [{}]",
+            format_ipv4(self.addr_a),
+            self.ports.source_port,
+            format_ipv4(self.addr_b),
+            self.ports.dest_port,
+            protocol_label(self.protocol)
+        )
+    }
+}
+
+/// Simplified TCP connection state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    /// Client SYN seen, waiting for SYN-ACK.
+    SynSent,
+    /// Server SYN-ACK seen, waiting for the final ACK.
+    SynReceived,
+    /// Handshake complete (or flow picked up mid-stream).
+    Established,
+    /// One side has sent FIN.
+    FinWait,
+    /// Both sides have sent FIN.
+    Closed,
+    /// Either side sent RST.
+    Reset,
+    /// Removed by [`FlowTable::expire`] before closing cleanly.
+    TimedOut,
+}
+
+impl TcpState {
+    /// Whether the connection has finished (cleanly or not).
+    #[must_use]
+    pub const fn is_terminal(self) -> bool {
+        matches!(self, Self::Closed | Self::Reset | Self::TimedOut)
+    }
+}
+
+/// How bytes that were already buffered are treated when a later segment
+/// covers the same sequence range with different content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapPolicy {
+    /// Keep the bytes that arrived first (BSD-style).
+    #[default]
+    First,
+    /// Let the newest segment overwrite buffered bytes (Linux-style for data
+    /// not yet delivered).
+    Last,
+}
+
+/// Counters describing what a [`StreamReassembler`] has seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReassemblyStats {
+    /// Segments that arrived ahead of the next expected byte.
+    pub out_of_order: u64,
+    /// Segments that overlapped data still waiting in the buffer.
+    pub overlaps: u64,
+    /// Overlapping bytes whose content disagreed with buffered bytes.
+    pub conflicting_bytes: u64,
+    /// Bytes that repeated data already delivered in order.
+    pub retransmitted_bytes: u64,
+    /// Bytes discarded because the buffer cap was reached or they came
+    /// after the FIN.
+    pub dropped_bytes: u64,
+}
+
+/// In-order byte-stream rebuilder for one direction of a TCP flow.
+#[derive(Debug, Clone)]
+pub struct StreamReassembler {
+    policy: OverlapPolicy,
+    max_buffered: usize,
+    next_seq: Option<u32>,
+    next_offset: u64,
+    pending: BTreeMap<u64, Vec<u8>>,
+    pending_bytes: usize,
+    assembled: Vec<u8>,
+    /// Relative offset of the FIN, once seen; it takes one sequence number.
+    fin_offset: Option<u64>,
+    finished: bool,
+    stats: ReassemblyStats,
+}
+
+impl StreamReassembler {
+    /// Create an empty reassembler that holds at most `max_buffered`
+    /// out-of-order bytes. In-order bytes waiting in
+    /// [`assembled`](Self::assembled) don't count against it.
+    #[must_use]
+    pub const fn new(policy: OverlapPolicy, max_buffered: usize) -> Self {
+        Self {
+            policy,
+            max_buffered,
+            next_seq: None,
+            next_offset: 0,
+            pending: BTreeMap::new(),
+            pending_bytes: 0,
+            assembled: Vec::new(),
+            fin_offset: None,
+            finished: false,
+            stats: ReassemblyStats {
+                out_of_order: 0,
+                overlaps: 0,
+                conflicting_bytes: 0,
+                retransmitted_bytes: 0,
+                dropped_bytes: 0,
+            },
+        }
+    }
+
+    /// Anchor the stream at `seq` (the first payload byte) if not yet anchored.
+    pub const fn set_initial_seq(&mut self, seq: u32) {
+        if self.next_seq.is_none() {
+            self.next_seq = Some(seq);
+        }
+    }
+
+    /// Sequence number of the next byte expected in order.
+    #[must_use]
+    pub const fn next_seq(&self) -> Option<u32> {
+        self.next_seq
+    }
+
+    /// Add a segment's payload starting at sequence number `seq`.
+    ///
+    /// An un-anchored stream anchors itself on the first segment it sees.
+    pub fn insert(&mut self, seq: u32, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let expected = *self.next_seq.get_or_insert(seq);
+
+        // Serial-number arithmetic (RFC 1982): interpret the gap as signed.
+        #[allow(clippy::cast_possible_wrap)]
+        let delta = seq.wrapping_sub(expected) as i32;
+
+        let (start, data) = if delta < 0 {
+            let behind = usize::try_from(delta.unsigned_abs()).unwrap_or(usize::MAX);
+            if behind >= data.len() {
+                self.stats.retransmitted_bytes += data.len() as u64;
+                return;
+            }
+            self.stats.retransmitted_bytes += behind as u64;
+            (self.next_offset, &data[behind..])
+        } else {
+            (self.next_offset + u64::from(delta.unsigned_abs()), data)
+        };
+
+        let data = match self.fin_offset {
+            Some(fin) if start >= fin => {
+                self.stats.dropped_bytes += data.len() as u64;
+                return;
+            }
+            Some(fin) => {
+                let keep = offset_in(start, fin).min(data.len());
+                self.stats.dropped_bytes += (data.len() - keep) as u64;
+                &data[..keep]
+            }
+            None => data,
+        };
+        if start > self.next_offset {
+            if self.pending_bytes + data.len() > self.max_buffered {
+                self.stats.dropped_bytes += data.len() as u64;
+                return;
+            }
+            self.stats.out_of_order += 1;
+        }
+
+        self.merge(start, data);
+        self.flush();
+    }
+
+    /// Record a FIN at `seq`, the sequence number after the last data byte.
+    ///
+    /// The FIN takes one sequence number once every byte before it has
+    /// arrived, so a retransmitted FIN falls behind [`next_seq`](Self::next_seq).
+    pub fn fin(&mut self, seq: u32) {
+        let expected = *self.next_seq.get_or_insert(seq);
+        #[allow(clippy::cast_possible_wrap)]
+        let delta = seq.wrapping_sub(expected) as i32;
+        if delta < 0 || self.fin_offset.is_some() {
+            return;
+        }
+        self.fin_offset = Some(self.next_offset + u64::from(delta.unsigned_abs()));
+        self.flush();
+    }
+
+    /// Whether the FIN has been reached in order.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Bytes delivered in order and not yet taken.
+    #[must_use]
+    pub fn assembled(&self) -> &[u8] {
+        &self.assembled
+    }
+
+    /// Drain the in-order bytes, freeing buffer space.
+    pub fn take_assembled(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.assembled)
+    }
+
+    /// Number of out-of-order segments waiting for a gap to fill.
+    #[must_use]
+    pub fn pending_segments(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Bytes held in memory (pending plus assembled-but-untaken).
+    #[must_use]
+    pub const fn buffered_bytes(&self) -> usize {
+        self.pending_bytes + self.assembled.len()
+    }
+
+    /// Counters for anomalies seen on this stream.
+    #[must_use]
+    pub const fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    /// Merge `data` at relative offset `start` into the pending buffer.
+    fn merge(&mut self, start: u64, data: &[u8]) {
+        let end = start + data.len() as u64;
+        let mut overlapping: Vec<u64> = Vec::new();
+        for (&key, chunk) in self.pending.range(..end).rev() {
+            if key + chunk.len() as u64 <= start {
+                break;
+            }
+            overlapping.push(key);
+        }
+        overlapping.reverse();
+
+        for &key in &overlapping {
+            if let Some(chunk) = self.pending.get(&key) {
+                let from = start.max(key);
+                let to = end.min(key + chunk.len() as u64);
+                let old = &chunk[offset_in(key, from)..offset_in(key, to)];
+                let new = &data[offset_in(start, from)..offset_in(start, to)];
+                let differing = old.iter().zip(new).filter(|(a, b)| a != b).count();
+                self.stats.overlaps += 1;
+                self.stats.conflicting_bytes += differing as u64;
+            }
+        }
+
+        match self.policy {
+            OverlapPolicy::First => {
+                let mut cursor = start;
+                for &key in &overlapping {
+                    let chunk_end = self
+                        .pending
+                        .get(&key)
+                        .map_or(key, |chunk| key + chunk.len() as u64);
+                    if cursor < key {
+                        let piece = data[offset_in(start, cursor)..offset_in(start, key)].to_vec();
+                        self.store(cursor, piece);
+                    }
+                    cursor = cursor.max(chunk_end);
+                }
+                if cursor < end {
+                    self.store(cursor, data[offset_in(start, cursor)..].to_vec());
+                }
+            }
+            OverlapPolicy::Last => {
+                for key in overlapping {
+                    if let Some(chunk) = self.pending.remove(&key) {
+                        self.pending_bytes -= chunk.len();
+                        let chunk_end = key + chunk.len() as u64;
+                        if key < start {
+                            self.store(key, chunk[..offset_in(key, start)].to_vec());
+                        }
+                        if chunk_end > end {
+                            self.store(end, chunk[offset_in(key, end)..].to_vec());
+                        }
+                    }
+                }
+                self.store(start, data.to_vec());
+            }
+        }
+    }
+
+    fn store(&mut self, offset: u64, bytes: Vec<u8>) {
+        self.pending_bytes += bytes.len();
+        self.pending.insert(offset, bytes);
+    }
+
+    /// Move contiguous pending chunks into the assembled stream.
+    fn flush(&mut self) {
+        while let Some(entry) = self.pending.first_entry() {
+            if *entry.key() != self.next_offset {
+                break;
+            }
+            let chunk = entry.remove();
+            let len = chunk.len();
+            self.pending_bytes -= len;
+            self.next_offset += len as u64;
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                self.next_seq = self.next_seq.map(|seq| seq.wrapping_add(len as u32));
+            }
+            self.assembled.extend_from_slice(&chunk);
+        }
+        if !self.finished && self.fin_offset == Some(self.next_offset) {
+            self.finished = true;
+            self.next_seq = self.next_seq.map(|seq| seq.wrapping_add(1));
+        }
+    }
+}
+
+/// Index of absolute offset `at` inside a chunk that starts at `base`.
+fn offset_in(base: u64, at: u64) -> usize {
+    usize::try_from(at - base).unwrap_or(usize::MAX)
+}
+
+/// Packet and byte counters for one direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DirectionStats {
+    /// Segments seen.
+    pub packets: u64,
+    /// Payload bytes seen (including retransmissions).
+    pub bytes: u64,
+}
+
+/// One tracked TCP connection.
+#[derive(Debug, Clone)]
+pub struct Flow {
+    /// Normalised 5-tuple.
+    pub key: FlowKey,
+    /// `(address, port)` of the side that opened the connection.
+    pub client: ([u8; 4], u16),
+    /// Current connection state.
+    pub state: TcpState,
+    /// `true` when the handshake was not observed.
+    pub midstream: bool,
+    /// Timestamp of the first segment.
+    pub first_seen_ms: u64,
+    /// Timestamp of the most recent segment.
+    pub last_seen_ms: u64,
+    /// Client → server counters.
+    pub client_stats: DirectionStats,
+    /// Server → client counters.
+    pub server_stats: DirectionStats,
+    fin_from_client: bool,
+    fin_from_server: bool,
+    client_stream: StreamReassembler,
+    server_stream: StreamReassembler,
+    /// This flow's entry in [`FlowTable`]'s recency index.
+    lru_slot: Option<(u64, u64)>,
+}
+
+impl Flow {
+    fn open(segment: &TcpSegment<'_>, config: &FlowConfig) -> Self {
+        let syn_only = segment.has(TCP_SYN) && !segment.has(TCP_ACK);
+        let syn_ack = segment.has(TCP_SYN | TCP_ACK);
+        let (client, state) = if syn_only {
+            ((segment.src, segment.ports.source_port), TcpState::SynSent)
+        } else if syn_ack {
+            (
+                (segment.dst, segment.ports.dest_port),
+                TcpState::SynReceived,
+            )
+        } else {
+            (
+                (segment.src, segment.ports.source_port),
+                TcpState::Established,
+            )
+        };
+        let stream = StreamReassembler::new(config.overlap_policy, config.max_stream_bytes);
+        Self {
+            key: FlowKey::from_segment(segment),
+            client,
+            state,
+            midstream: !syn_only && !syn_ack,
+            first_seen_ms: segment.timestamp_ms,
+            last_seen_ms: segment.timestamp_ms,
+            client_stats: DirectionStats::default(),
+            server_stats: DirectionStats::default(),
+            fin_from_client: false,
+            fin_from_server: false,
+            client_stream: stream.clone(),
+            server_stream: stream,
+            lru_slot: None,
+        }
+    }
+
+    /// Which way `segment` travels within this flow.
+    #[must_use]
+    pub fn direction_of(&self, segment: &TcpSegment<'_>) -> Direction {
+        if (segment.src, segment.ports.source_port) == self.client {
+            Direction::ClientToServer
+        } else {
+            Direction::ServerToClient
+        }
+    }
+
+    /// Wall-clock span between the first and last segment.
+    #[must_use]
+    pub const fn duration_ms(&self) -> u64 {
+        self.last_seen_ms.saturating_sub(self.first_seen_ms)
+    }
+
+    /// Total segments in both directions.
+    #[must_use]
+    pub const fn packets(&self) -> u64 {
+        self.client_stats.packets + self.server_stats.packets
+    }
+
+    /// Total payload bytes in both directions.
+    #[must_use]
+    pub const fn bytes(&self) -> u64 {
+        self.client_stats.bytes + self.server_stats.bytes
+    }
+
+    /// Reassembled stream for one direction.
+    #[must_use]
+    pub const fn stream(&self, direction: Direction) -> &StreamReassembler {
+        match direction {
+            Direction::ClientToServer => &self.client_stream,
+            Direction::ServerToClient => &self.server_stream,
+        }
+    }
+
+    /// Mutable access, e.g. to [`StreamReassembler::take_assembled`].
+    pub const fn stream_mut(&mut self, direction: Direction) -> &mut StreamReassembler {
+        match direction {
+            Direction::ClientToServer => &mut self.client_stream,
+            Direction::ServerToClient => &mut self.server_stream,
+        }
+    }
+
+    fn update(&mut self, segment: &TcpSegment<'_>) {
+        let direction = self.direction_of(segment);
+        self.last_seen_ms = self.last_seen_ms.max(segment.timestamp_ms);
+
+        let stats = match direction {
+            Direction::ClientToServer => &mut self.client_stats,
+            Direction::ServerToClient => &mut self.server_stats,
+        };
+        stats.packets += 1;
+        stats.bytes += segment.payload.len() as u64;
+
+        self.advance_state(segment, direction);
+
+        let stream = self.stream_mut(direction);
+        let mut seq = segment.seq;
+        if segment.has(TCP_SYN) {
+            // SYN consumes one sequence number; data starts right after it.
+            seq = seq.wrapping_add(1);
+            stream.set_initial_seq(seq);
+        }
+        stream.insert(seq, segment.payload);
+        if segment.has(TCP_FIN) {
+            #[allow(clippy::cast_possible_truncation)]
+            stream.fin(seq.wrapping_add(segment.payload.len() as u32));
+        }
+    }
+
+    const fn advance_state(&mut self, segment: &TcpSegment<'_>, direction: Direction) {
+        if segment.has(TCP_RST) {
+            self.state = TcpState::Reset;
+            return;
+        }
+        if segment.has(TCP_FIN) {
+            match direction {
+                Direction::ClientToServer => self.fin_from_client = true,
+                Direction::ServerToClient => self.fin_from_server = true,
+            }
+        }
+
+        self.state = match (self.state, direction) {
+            (TcpState::SynSent, Direction::ServerToClient) if segment.has(TCP_SYN | TCP_ACK) => {
+                TcpState::SynReceived
+            }
+            (TcpState::SynReceived, Direction::ClientToServer)
+                if segment.has(TCP_ACK) && !segment.has(TCP_SYN) =>
+            {
+                TcpState::Established
+            }
+            (state, _) if state.is_terminal() => state,
+            _ if self.fin_from_client && self.fin_from_server => TcpState::Closed,
+            (TcpState::Established, _) if segment.has(TCP_FIN) => TcpState::FinWait,
+            (state, _) => state,
+        };
+    }
+}
+
+/// Limits and timeouts for a [`FlowTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlowConfig {
+    /// Idle timeout for half-open flows (handshake not complete).
+    pub syn_timeout_ms: u64,
+    /// Idle timeout for established or closing flows.
+    pub idle_timeout_ms: u64,
+    /// How long closed or reset flows linger before removal.
+    pub closed_timeout_ms: u64,
+    /// Maximum concurrent flows; the least recently seen is evicted beyond it.
+    pub max_flows: usize,
+    /// Per-direction cap on out-of-order stream bytes waiting for a gap.
+    pub max_stream_bytes: usize,
+    /// Overlap handling for every stream in the table.
+    pub overlap_policy: OverlapPolicy,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        Self {
+            syn_timeout_ms: 30_000,
+            idle_timeout_ms: 300_000,
+            closed_timeout_ms: 10_000,
+            max_flows: 10_000,
+            max_stream_bytes: 1 << 20,
+            overlap_policy: OverlapPolicy::First,
+        }
+    }
+}
+
+impl FlowConfig {
+    /// Idle timeout that applies to a flow in `state`.
+    #[must_use]
+    pub const fn timeout_for(&self, state: TcpState) -> u64 {
+        match state {
+            TcpState::SynSent | TcpState::SynReceived => self.syn_timeout_ms,
+            TcpState::Established | TcpState::FinWait => self.idle_timeout_ms,
+            TcpState::Closed | TcpState::Reset | TcpState::TimedOut => self.closed_timeout_ms,
+        }
+    }
+}
+
+/// Table of active TCP flows keyed by normalised 5-tuple.
+#[derive(Debug, Clone, Default)]
+pub struct FlowTable {
+    config: FlowConfig,
+    flows: HashMap<FlowKey, Flow>,
+    /// Flows ordered by `(last_seen_ms, insertion)`, so eviction takes the
+    /// first entry instead of scanning the table.
+    lru: BTreeMap<(u64, u64), FlowKey>,
+    next_slot: u64,
+    evicted: u64,
+}
+
+impl FlowTable {
+    /// Create an empty table with the given limits.
+    #[must_use]
+    pub fn new(config: FlowConfig) -> Self {
+        Self {
+            config,
+            flows: HashMap::new(),
+            lru: BTreeMap::new(),
+            next_slot: 0,
+            evicted: 0,
+        }
+    }
+
+    /// Feed one segment into the table and return its flow key.
+    ///
+    /// A fresh SYN on a finished flow starts a new connection (port reuse).
+    pub fn observe(&mut self, segment: &TcpSegment<'_>) -> FlowKey {
+        let key = FlowKey::from_segment(segment);
+        let reuse = segment.has(TCP_SYN)
+            && !segment.has(TCP_ACK)
+            && self
+                .flows
+                .get(&key)
+                .is_some_and(|flow| flow.state.is_terminal());
+
+        if reuse || !self.flows.contains_key(&key) {
+            if !reuse && self.flows.len() >= self.config.max_flows {
+                self.evict_oldest();
+            }
+            let old = self.flows.insert(key, Flow::open(segment, &self.config));
+            if let Some(slot) = old.and_then(|flow| flow.lru_slot) {
+                self.lru.remove(&slot);
+            }
+        }
+        if let Some(flow) = self.flows.get_mut(&key) {
+            flow.update(segment);
+            if flow.lru_slot.map(|(seen, _)| seen) != Some(flow.last_seen_ms) {
+                if let Some(slot) = flow.lru_slot {
+                    self.lru.remove(&slot);
+                }
+                let slot = (flow.last_seen_ms, self.next_slot);
+                self.next_slot += 1;
+                self.lru.insert(slot, key);
+                flow.lru_slot = Some(slot);
+            }
+        }
+        key
+    }
+
+    /// Look up a flow by key.
+    #[must_use]
+    pub fn get(&self, key: &FlowKey) -> Option<&Flow> {
+        self.flows.get(key)
+    }
+
+    /// Mutable lookup, e.g. to drain reassembled streams.
+    pub fn get_mut(&mut self, key: &FlowKey) -> Option<&mut Flow> {
+        self.flows.get_mut(key)
+    }
+
+    /// Iterate over all tracked flows.
+    pub fn flows(&self) -> impl Iterator<Item = &Flow> {
+        self.flows.values()
+    }
+
+    /// Number of tracked flows.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Whether the table is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    /// Flows dropped to respect [`FlowConfig::max_flows`].
+    #[must_use]
+    pub const fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Remove flows idle longer than their state's timeout at `now_ms`.
+    ///
+    /// Flows that never closed come back as [`TcpState::TimedOut`].
+    pub fn expire(&mut self, now_ms: u64) -> Vec<Flow> {
+        let config = self.config;
+        let stale: Vec<FlowKey> = self
+            .flows
+            .iter()
+            .filter(|(_, flow)| {
+                now_ms.saturating_sub(flow.last_seen_ms) >= config.timeout_for(flow.state)
+            })
+            .map(|(key, _)| *key)
+            .collect();
+
+        let mut expired = Vec::with_capacity(stale.len());
+        for key in stale {
+            if let Some(mut flow) = self.flows.remove(&key) {
+                if let Some(slot) = flow.lru_slot.take() {
+                    self.lru.remove(&slot);
+                }
+                if !flow.state.is_terminal() {
+                    flow.state = TcpState::TimedOut;
+                }
+                expired.push(flow);
+            }
+        }
+        expired
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, key)) = self.lru.pop_first() {
+            self.flows.remove(&key);
+            self.evicted += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT: [u8; 4] = [10, 0, 0, 5];
+    const SERVER: [u8; 4] = [192, 168, 1, 1];
+
+    fn client_seg(seq: u32, flags: u8, payload: &[u8], ts: u64) -> TcpSegment<'_> {
+        TcpSegment {
+            src: CLIENT,
+            dst: SERVER,
+            ports: TcpPorts {
+                source_port: 52341,
+                dest_port: 443,
+            },
+            seq,
+            ack: 0,
+            flags,
+            payload,
+            timestamp_ms: ts,
+        }
+    }
+
+    fn server_seg(seq: u32, flags: u8, payload: &[u8], ts: u64) -> TcpSegment<'_> {
+        TcpSegment {
+            src: SERVER,
+            dst: CLIENT,
+            ports: TcpPorts {
+                source_port: 443,
+                dest_port: 52341,
+            },
+            seq,
+            ack: 0,
+            flags,
+            payload,
+            timestamp_ms: ts,
+        }
+    }
+
+    #[test]
+    fn key_is_direction_independent() {
+        let forward = client_seg(1, TCP_SYN, &[], 0);
+        let reverse = server_seg(1, TCP_SYN | TCP_ACK, &[], 0);
+        assert_eq!(
+            FlowKey::from_segment(&forward),
+            FlowKey::from_segment(&reverse)
+        );
+    }
+
+    #[test]
+    fn handshake_data_and_fin_walk_the_state_machine() {
+        let mut table = FlowTable::default();
+        let key = table.observe(&client_seg(100, TCP_SYN, &[], 0));
+        assert_eq!(table.get(&key).map(|f| f.state), Some(TcpState::SynSent));
+
+        table.observe(&server_seg(500, TCP_SYN | TCP_ACK, &[], 5));
+        assert_eq!(
+            table.get(&key).map(|f| f.state),
+            Some(TcpState::SynReceived)
+        );
+
+        table.observe(&client_seg(101, TCP_ACK, &[], 10));
+        table.observe(&client_seg(101, TCP_ACK | TCP_PSH, b"GET /", 12));
+        table.observe(&server_seg(501, TCP_ACK | TCP_PSH, b"200 OK", 20));
+        assert_eq!(
+            table.get(&key).map(|f| f.state),
+            Some(TcpState::Established)
+        );
+
+        table.observe(&client_seg(106, TCP_FIN | TCP_ACK, &[], 30));
+        assert_eq!(table.get(&key).map(|f| f.state), Some(TcpState::FinWait));
+        table.observe(&server_seg(507, TCP_FIN | TCP_ACK, &[], 40));
+
+        let flow = table.get(&key);
+        assert!(flow.is_some());
+        if let Some(flow) = flow {
+            assert_eq!(flow.state, TcpState::Closed);
+            assert!(!flow.midstream);
+            assert_eq!(flow.duration_ms(), 40);
+            assert_eq!(flow.client_stats.packets, 4);
+            assert_eq!(flow.server_stats.bytes, 6);
+            assert_eq!(flow.stream(Direction::ClientToServer).assembled(), b"GET /");
+            assert_eq!(flow.stream(Direction::ClientToServer).next_seq(), Some(107));
+            assert!(flow.stream(Direction::ServerToClient).is_finished());
+            assert_eq!(
+                flow.stream(Direction::ServerToClient).assembled(),
+                b"200 OK"
+            );
+        }
+    }
+
+    #[test]
+    fn rst_resets_and_midstream_is_flagged() {
+        let mut table = FlowTable::default();
+        let key = table.observe(&client_seg(9, TCP_ACK, b"x", 0));
+        assert_eq!(table.get(&key).map(|f| f.midstream), Some(true));
+        table.observe(&server_seg(1, TCP_RST, &[], 1));
+        assert_eq!(table.get(&key).map(|f| f.state), Some(TcpState::Reset));
+    }
+
+    #[test]
+    fn out_of_order_segments_are_reassembled() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 1024);
+        stream.set_initial_seq(1000);
+        stream.insert(1004, b"EFGH");
+        assert!(stream.assembled().is_empty());
+        assert_eq!(stream.pending_segments(), 1);
+        stream.insert(1000, b"ABCD");
+        assert_eq!(stream.assembled(), b"ABCDEFGH");
+        assert_eq!(stream.stats().out_of_order, 1);
+        assert_eq!(stream.next_seq(), Some(1008));
+    }
+
+    #[test]
+    fn overlap_policy_first_keeps_original_bytes() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 1024);
+        stream.set_initial_seq(0);
+        stream.insert(2, b"cdef");
+        stream.insert(4, b"XYgh");
+        stream.insert(0, b"ab");
+        assert_eq!(stream.assembled(), b"abcdefgh");
+        assert_eq!(stream.stats().overlaps, 1);
+        assert_eq!(stream.stats().conflicting_bytes, 2);
+    }
+
+    #[test]
+    fn overlap_policy_last_overwrites_pending_bytes() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::Last, 1024);
+        stream.set_initial_seq(0);
+        stream.insert(2, b"cdef");
+        stream.insert(4, b"XYgh");
+        stream.insert(0, b"ab");
+        assert_eq!(stream.assembled(), b"abcdXYgh");
+    }
+
+    #[test]
+    fn retransmissions_and_wraparound() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 1024);
+        stream.set_initial_seq(u32::MAX - 1);
+        stream.insert(u32::MAX - 1, b"ab");
+        stream.insert(0, b"cd");
+        stream.insert(u32::MAX - 1, b"abc");
+        assert_eq!(stream.assembled(), b"abcd");
+        assert_eq!(stream.stats().retransmitted_bytes, 3);
+    }
+
+    #[test]
+    fn buffer_cap_drops_excess() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 4);
+        stream.set_initial_seq(0);
+        stream.insert(10, b"abc");
+        stream.insert(20, b"def");
+        assert_eq!(stream.stats().dropped_bytes, 3);
+        assert_eq!(stream.buffered_bytes(), 3);
+    }
+
+    #[test]
+    fn undrained_in_order_bytes_do_not_count_against_the_cap() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 4);
+        stream.set_initial_seq(0);
+        stream.insert(0, b"abcdef");
+        stream.insert(6, b"ghij");
+        stream.insert(12, b"mn");
+        assert_eq!(stream.assembled(), b"abcdefghij");
+        assert_eq!(stream.pending_segments(), 1);
+        assert_eq!(stream.stats().dropped_bytes, 0);
+    }
+
+    #[test]
+    fn fin_takes_a_sequence_number_and_ends_the_stream() {
+        let mut stream = StreamReassembler::new(OverlapPolicy::First, 1024);
+        stream.set_initial_seq(0);
+        stream.fin(3);
+        assert!(!stream.is_finished());
+        stream.insert(0, b"abc");
+        assert!(stream.is_finished());
+        assert_eq!(stream.next_seq(), Some(4));
+
+        // A retransmitted FIN and late bytes change nothing.
+        stream.fin(3);
+        stream.insert(4, b"zz");
+        assert_eq!(stream.next_seq(), Some(4));
+        assert_eq!(stream.assembled(), b"abc");
+        assert_eq!(stream.stats().dropped_bytes, 2);
+    }
+
+    #[test]
+    fn expire_and_eviction() {
+        let config = FlowConfig {
+            max_flows: 1,
+            ..FlowConfig::default()
+        };
+        let mut table = FlowTable::new(config);
+        table.observe(&client_seg(1, TCP_SYN, &[], 0));
+        let mut other = client_seg(1, TCP_SYN, &[], 10);
+        other.ports.source_port = 60000;
+        table.observe(&other);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.evicted(), 1);
+        assert_eq!(table.lru.len(), 1);
+
+        let expired = table.expire(10 + config.syn_timeout_ms);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].state, TcpState::TimedOut);
+        assert!(table.is_empty());
+        assert!(table.lru.is_empty());
+    }
+
+    #[test]
+    fn eviction_takes_the_least_recently_seen_flow() {
+        let config = FlowConfig {
+            max_flows: 2,
+            ..FlowConfig::default()
+        };
+        let mut table = FlowTable::new(config);
+        let first = table.observe(&client_seg(1, TCP_SYN, &[], 0));
+        let mut second = client_seg(1, TCP_SYN, &[], 5);
+        second.ports.source_port = 60000;
+        let second = table.observe(&second);
+        table.observe(&client_seg(2, TCP_ACK, &[], 10));
+
+        let mut third = client_seg(1, TCP_SYN, &[], 20);
+        third.ports.source_port = 60001;
+        let third = table.observe(&third);
+        assert_eq!(table.evicted(), 1);
+        assert!(table.get(&first).is_some());
+        assert!(table.get(&second).is_none());
+        assert!(table.get(&third).is_some());
+        assert_eq!(table.lru.len(), table.len());
+    }
+}
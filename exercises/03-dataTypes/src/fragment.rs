@@ -0,0 +1,736 @@
+//! IPv4 fragment reassembly with target-based overlap policies.
+//!
+//! Hosts disagree about which bytes win when fragments overlap, and attackers
+//! exploit that to show an IDS one payload and the target another. The
+//! [`FragmentPolicy`] variants model the classic target behaviours so a sensor
+//! can reassemble the datagram the way the protected host would, while
+//! [`FragmentEvent`] reports the anomalies that evasion attempts leave behind.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::packet::{FLAG_MF, HEADER_LEN, PacketHeader};
+
+/// Largest IPv4 payload that fits in a 65 535-byte datagram.
+pub const MAX_DATAGRAM_PAYLOAD: usize = u16::MAX as usize - HEADER_LEN;
+
+/// Datagram identity: fragments share `(src, dst, identification, protocol)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FragmentKey {
+    /// Source IPv4 address.
+    pub src: [u8; 4],
+    /// Destination IPv4 address.
+    pub dst: [u8; 4],
+    /// IP identification field.
+    pub identification: u16,
+    /// IANA protocol number.
+    pub protocol: u8,
+}
+
+/// One received fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragment<'a> {
+    /// Datagram this fragment belongs to.
+    pub key: FragmentKey,
+    /// Byte offset of `payload` within the datagram.
+    pub offset: usize,
+    /// More Fragments flag — `false` on the last fragment.
+    pub more_fragments: bool,
+    /// Fragment payload (everything after the IP header).
+    pub payload: &'a [u8],
+    /// Capture timestamp in milliseconds.
+    pub timestamp_ms: u64,
+}
+
+impl<'a> Fragment<'a> {
+    /// Build a fragment from a parsed header and the bytes that follow it.
+    #[must_use]
+    pub fn from_header(header: &PacketHeader, payload: &'a [u8], timestamp_ms: u64) -> Self {
+        Self {
+            key: FragmentKey {
+                src: header.src,
+                dst: header.dst,
+                identification: header.identification,
+                protocol: header.protocol,
+            },
+            offset: usize::from(header.fragment_offset) * 8,
+            more_fragments: header.flags & FLAG_MF != 0,
+            payload,
+            timestamp_ms,
+        }
+    }
+
+    /// Whether this is a complete, unfragmented datagram.
+    #[must_use]
+    pub const fn is_whole(&self) -> bool {
+        self.offset == 0 && !self.more_fragments
+    }
+
+    const fn end(&self) -> usize {
+        self.offset + self.payload.len()
+    }
+}
+
+/// Which copy of overlapping bytes the reassembled datagram keeps.
+///
+/// Modelled on the target-based policies used by IDS reassemblers
+/// (Novak, "Target-Based Fragmentation Reassembly").
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FragmentPolicy {
+    /// Original bytes always win (Solaris, HP-UX, early IDS).
+    First,
+    /// Newest bytes always win (Cisco IOS).
+    Last,
+    /// Original wins unless the new fragment starts earlier (FreeBSD, AIX).
+    #[default]
+    Bsd,
+    /// Original wins unless the new fragment starts at or before it (Linux).
+    Linux,
+    /// Original wins unless the new fragment starts earlier and covers it
+    /// completely (Windows).
+    Windows,
+}
+
+impl FragmentPolicy {
+    /// Whether bytes from `new` replace bytes from `original` where they overlap.
+    ///
+    /// Spans are `(start, end)` byte offsets within the datagram.
+    #[must_use]
+    pub const fn new_wins(self, original: (usize, usize), new: (usize, usize)) -> bool {
+        match self {
+            Self::First => false,
+            Self::Last => true,
+            Self::Bsd => new.0 < original.0,
+            Self::Linux => new.0 <= original.0,
+            Self::Windows => new.0 < original.0 && new.1 >= original.1,
+        }
+    }
+}
+
+/// Anomalies observed while reassembling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FragmentEvent {
+    /// First fragment too small to hold a transport header (RFC 1858).
+    TinyFirstFragment {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// Payload bytes in the first fragment.
+        len: usize,
+    },
+    /// A fragment overlapped bytes already received.
+    Overlap {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// Start of the overlapping range.
+        offset: usize,
+        /// Length of the overlapping range.
+        len: usize,
+        /// `true` when the overlapping bytes differ — the evasion signature.
+        conflicting: bool,
+    },
+    /// Fragments claim a datagram larger than IPv4 allows (ping-of-death).
+    Oversize {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// End offset claimed by the offending fragment.
+        end: usize,
+    },
+    /// Two final fragments disagreed about the datagram length.
+    InconsistentLength {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// Length implied by the first final fragment.
+        expected: usize,
+        /// Length implied by the conflicting one.
+        claimed: usize,
+    },
+    /// A fragment carried bytes past the length set by the final fragment;
+    /// they were dropped.
+    BeyondEnd {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// Datagram length set by the final fragment.
+        total_len: usize,
+        /// Bytes dropped past `total_len`.
+        bytes: usize,
+    },
+    /// The datagram did not complete before the timeout.
+    Timeout {
+        /// Affected datagram.
+        key: FragmentKey,
+        /// Distinct payload bytes received before giving up.
+        received: usize,
+    },
+    /// A fragment was dropped to respect memory limits.
+    MemoryLimit {
+        /// Affected datagram.
+        key: FragmentKey,
+    },
+}
+
+/// Limits, timeouts, and policy for a [`FragmentReassembler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentConfig {
+    /// Overlap policy to emulate.
+    pub policy: FragmentPolicy,
+    /// Drop datagrams still incomplete this long after their first fragment
+    /// (RFC 791), however often later fragments trickle in.
+    pub timeout_ms: u64,
+    /// Maximum datagrams under reassembly at once.
+    pub max_datagrams: usize,
+    /// Maximum fragment payload bytes held across all datagrams.
+    pub max_buffered_bytes: usize,
+    /// First fragments shorter than this raise [`FragmentEvent::TinyFirstFragment`].
+    pub min_first_fragment: usize,
+}
+
+impl Default for FragmentConfig {
+    fn default() -> Self {
+        Self {
+            policy: FragmentPolicy::default(),
+            timeout_ms: 30_000,
+            max_datagrams: 1024,
+            max_buffered_bytes: 4 << 20,
+            min_first_fragment: 20,
+        }
+    }
+}
+
+/// A fully reassembled datagram payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Datagram {
+    /// Datagram identity.
+    pub key: FragmentKey,
+    /// Reassembled payload.
+    pub payload: Vec<u8>,
+    /// Number of fragments that contributed (1 for unfragmented packets).
+    pub fragments: usize,
+}
+
+/// A run of bytes in the datagram and the span of the fragment that won it.
+#[derive(Debug, Clone)]
+struct Piece {
+    origin: (usize, usize),
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingDatagram {
+    /// Disjoint pieces keyed by start offset, as `policy` has laid them down.
+    pieces: BTreeMap<usize, Piece>,
+    total_len: Option<usize>,
+    /// Distinct bytes held, all below `total_len` once it is known.
+    covered: usize,
+    fragments: usize,
+    first_seen_ms: u64,
+}
+
+impl PendingDatagram {
+    /// Whether `[0, total_len)` is fully covered.
+    fn is_complete(&self) -> bool {
+        self.total_len == Some(self.covered)
+    }
+
+    /// Lay `data` down at `offset`, letting `policy` decide who wins each
+    /// overlap with the pieces already held. Calls `overlap` with
+    /// `(offset, len, conflicting)` for every piece it overlaps.
+    ///
+    /// Only the pieces under the new span are visited, and each is at least
+    /// one byte, so the work is bounded by the fragment's length.
+    fn insert(
+        &mut self,
+        origin: (usize, usize),
+        offset: usize,
+        data: &[u8],
+        policy: FragmentPolicy,
+        mut overlap: impl FnMut(usize, usize, bool),
+    ) {
+        let end = offset + data.len();
+        if data.is_empty() {
+            return;
+        }
+        let mut hit: Vec<usize> = self
+            .pieces
+            .range(..end)
+            .rev()
+            .take_while(|&(&start, piece)| start + piece.data.len() > offset)
+            .map(|(&start, _)| start)
+            .collect();
+        hit.reverse();
+
+        let mut fresh: Vec<(usize, usize)> = Vec::new();
+        let mut take = |from: usize, to: usize| match fresh.last_mut() {
+            Some(last) if last.1 == from => last.1 = to,
+            _ => fresh.push((from, to)),
+        };
+        let mut cursor = offset;
+        let mut overlapped = 0;
+        for start in hit {
+            let Some(piece) = self.pieces.remove(&start) else {
+                continue;
+            };
+            let piece_end = start + piece.data.len();
+            let from = start.max(offset);
+            let to = piece_end.min(end);
+            let old = &piece.data[from - start..to - start];
+            let new = &data[from - offset..to - offset];
+            overlap(from, to - from, old != new);
+            overlapped += to - from;
+            if cursor < from {
+                take(cursor, from);
+            }
+            if policy.new_wins(piece.origin, origin) {
+                if start < from {
+                    let head = piece.data[..from - start].to_vec();
+                    self.pieces.insert(
+                        start,
+                        Piece {
+                            origin: piece.origin,
+                            data: head,
+                        },
+                    );
+                }
+                if to < piece_end {
+                    let tail = piece.data[to - start..].to_vec();
+                    self.pieces.insert(
+                        to,
+                        Piece {
+                            origin: piece.origin,
+                            data: tail,
+                        },
+                    );
+                }
+                take(from, to);
+            } else {
+                self.pieces.insert(start, piece);
+            }
+            cursor = to;
+        }
+        if cursor < end {
+            take(cursor, end);
+        }
+        for (from, to) in fresh {
+            let data = data[from - offset..to - offset].to_vec();
+            self.pieces.insert(from, Piece { origin, data });
+        }
+        self.covered += data.len() - overlapped;
+    }
+
+    /// Drop everything at or past `total`; returns the bytes dropped.
+    fn truncate(&mut self, total: usize) -> usize {
+        let mut dropped = 0;
+        while let Some(mut entry) = self.pieces.last_entry() {
+            let start = *entry.key();
+            let piece_end = start + entry.get().data.len();
+            if piece_end <= total {
+                break;
+            }
+            if start >= total {
+                dropped += entry.remove().data.len();
+            } else {
+                entry.get_mut().data.truncate(total - start);
+                dropped += piece_end - total;
+                break;
+            }
+        }
+        self.covered -= dropped;
+        dropped
+    }
+
+    fn assemble(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.covered);
+        for piece in self.pieces.into_values() {
+            bytes.extend_from_slice(&piece.data);
+        }
+        bytes
+    }
+}
+
+/// IPv4 reassembler keyed by `(src, dst, identification, protocol)`.
+#[derive(Debug, Clone, Default)]
+pub struct FragmentReassembler {
+    config: FragmentConfig,
+    pending: HashMap<FragmentKey, PendingDatagram>,
+    buffered: usize,
+    events: Vec<FragmentEvent>,
+}
+
+impl FragmentReassembler {
+    /// Create a reassembler with the given limits and policy.
+    #[must_use]
+    pub fn new(config: FragmentConfig) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+            buffered: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Feed one fragment; returns the datagram once every byte has arrived.
+    pub fn push(&mut self, fragment: &Fragment<'_>) -> Option<Datagram> {
+        let key = fragment.key;
+        if fragment.end() > MAX_DATAGRAM_PAYLOAD {
+            self.events.push(FragmentEvent::Oversize {
+                key,
+                end: fragment.end(),
+            });
+            self.discard(&key);
+            return None;
+        }
+        if fragment.offset == 0
+            && fragment.more_fragments
+            && fragment.payload.len() < self.config.min_first_fragment
+        {
+            self.events.push(FragmentEvent::TinyFirstFragment {
+                key,
+                len: fragment.payload.len(),
+            });
+        }
+        if fragment.is_whole() && !self.pending.contains_key(&key) {
+            return Some(Datagram {
+                key,
+                payload: fragment.payload.to_vec(),
+                fragments: 1,
+            });
+        }
+
+        let is_new = !self.pending.contains_key(&key);
+        if (is_new && self.pending.len() >= self.config.max_datagrams)
+            || self.buffered + fragment.payload.len() > self.config.max_buffered_bytes
+        {
+            self.events.push(FragmentEvent::MemoryLimit { key });
+            return None;
+        }
+
+        let entry = self.pending.entry(key).or_insert_with(|| PendingDatagram {
+            pieces: BTreeMap::new(),
+            total_len: None,
+            covered: 0,
+            fragments: 0,
+            first_seen_ms: fragment.timestamp_ms,
+        });
+        entry.fragments += 1;
+        let before = entry.covered;
+
+        let mut dropped = 0;
+        if !fragment.more_fragments {
+            match entry.total_len {
+                Some(expected) if expected != fragment.end() => {
+                    self.events.push(FragmentEvent::InconsistentLength {
+                        key,
+                        expected,
+                        claimed: fragment.end(),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    entry.total_len = Some(fragment.end());
+                    dropped += entry.truncate(fragment.end());
+                }
+            }
+        }
+        let mut end = fragment.end();
+        if let Some(total) = entry.total_len
+            && end > total
+        {
+            dropped += end - total.max(fragment.offset);
+            end = total.max(fragment.offset);
+        }
+        let events = &mut self.events;
+        entry.insert(
+            (fragment.offset, fragment.end()),
+            fragment.offset,
+            &fragment.payload[..end - fragment.offset],
+            self.config.policy,
+            |offset, len, conflicting| {
+                events.push(FragmentEvent::Overlap {
+                    key,
+                    offset,
+                    len,
+                    conflicting,
+                });
+            },
+        );
+        if dropped > 0 {
+            self.events.push(FragmentEvent::BeyondEnd {
+                key,
+                total_len: entry.total_len.unwrap_or(0),
+                bytes: dropped,
+            });
+        }
+        self.buffered = self.buffered + entry.covered - before;
+
+        if !entry.is_complete() {
+            return None;
+        }
+        let done = self.pending.remove(&key)?;
+        self.buffered -= done.covered;
+        Some(Datagram {
+            key,
+            fragments: done.fragments,
+            payload: done.assemble(),
+        })
+    }
+
+    /// Drop datagrams whose first fragment is older than the timeout, raising
+    /// [`FragmentEvent::Timeout`].
+    pub fn expire(&mut self, now_ms: u64) {
+        let timeout = self.config.timeout_ms;
+        let stale: Vec<FragmentKey> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now_ms.saturating_sub(pending.first_seen_ms) >= timeout)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in stale {
+            if let Some(pending) = self.pending.remove(&key) {
+                self.buffered -= pending.covered;
+                self.events.push(FragmentEvent::Timeout {
+                    key,
+                    received: pending.covered,
+                });
+            }
+        }
+    }
+
+    /// Take every event raised since the last call.
+    pub fn drain_events(&mut self) -> Vec<FragmentEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Datagrams still waiting for fragments.
+    #[must_use]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Fragment payload bytes currently buffered.
+    #[must_use]
+    pub const fn buffered_bytes(&self) -> usize {
+        self.buffered
+    }
+
+    fn discard(&mut self, key: &FragmentKey) {
+        if let Some(pending) = self.pending.remove(key) {
+            self.buffered -= pending.covered;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: FragmentKey = FragmentKey {
+        src: [10, 0, 0, 5],
+        dst: [192, 168, 1, 1],
+        identification: 0xBEEF,
+        protocol: 6,
+    };
+
+    fn frag(offset: usize, more: bool, payload: &[u8]) -> Fragment<'_> {
+        Fragment {
+            key: KEY,
+            offset,
+            more_fragments: more,
+            payload,
+            timestamp_ms: 0,
+        }
+    }
+
+    /// Classic overlap test: original `AAAAAAAA|BBBBBBBB`, then a fragment at
+    /// offset 0 covering both with `XXXXXXXXXXXXXXXX`, then the tail.
+    fn run_overlap(policy: FragmentPolicy) -> Vec<u8> {
+        let mut reassembler = FragmentReassembler::new(FragmentConfig {
+            policy,
+            min_first_fragment: 0,
+            ..FragmentConfig::default()
+        });
+        assert!(reassembler.push(&frag(8, true, b"BBBBBBBB")).is_none());
+        assert!(
+            reassembler
+                .push(&frag(0, true, b"XXXXXXXXXXXXXXXX"))
+                .is_none()
+        );
+        let datagram = reassembler.push(&frag(16, false, b"CCCCCCCC"));
+        datagram.map(|d| d.payload).unwrap_or_default()
+    }
+
+    #[test]
+    fn unfragmented_packet_passes_through() {
+        let mut reassembler = FragmentReassembler::default();
+        let datagram = reassembler.push(&frag(0, false, b"whole"));
+        assert_eq!(datagram.map(|d| d.fragments), Some(1));
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn in_order_and_reversed_fragments_reassemble() {
+        let mut reassembler = FragmentReassembler::default();
+        assert!(reassembler.push(&frag(24, false, b"tail")).is_none());
+        assert!(
+            reassembler
+                .push(&frag(8, true, b"-middle-twelve--"))
+                .is_none()
+        );
+        let done = reassembler.push(&frag(0, true, b"head--8b"));
+        assert_eq!(
+            done.map(|d| d.payload),
+            Some(b"head--8b-middle-twelve--tail".to_vec())
+        );
+        assert_eq!(reassembler.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn policies_choose_different_winners() {
+        assert_eq!(
+            run_overlap(FragmentPolicy::First),
+            b"XXXXXXXXBBBBBBBBCCCCCCCC"
+        );
+        assert_eq!(
+            run_overlap(FragmentPolicy::Last),
+            b"XXXXXXXXXXXXXXXXCCCCCCCC"
+        );
+        assert_eq!(
+            run_overlap(FragmentPolicy::Bsd),
+            b"XXXXXXXXXXXXXXXXCCCCCCCC"
+        );
+        assert_eq!(
+            run_overlap(FragmentPolicy::Linux),
+            b"XXXXXXXXXXXXXXXXCCCCCCCC"
+        );
+        assert_eq!(
+            run_overlap(FragmentPolicy::Windows),
+            b"XXXXXXXXXXXXXXXXCCCCCCCC"
+        );
+    }
+
+    #[test]
+    fn same_offset_overlap_splits_bsd_and_linux() {
+        let run = |policy| {
+            let mut reassembler = FragmentReassembler::new(FragmentConfig {
+                policy,
+                min_first_fragment: 0,
+                ..FragmentConfig::default()
+            });
+            reassembler.push(&frag(8, true, b"original"));
+            reassembler.push(&frag(8, true, b"replaced"));
+            reassembler.push(&frag(0, true, b"01234567"));
+            reassembler
+                .push(&frag(16, false, b"!"))
+                .map(|d| d.payload)
+                .unwrap_or_default()
+        };
+        assert_eq!(run(FragmentPolicy::Bsd), b"01234567original!");
+        assert_eq!(run(FragmentPolicy::Linux), b"01234567replaced!");
+    }
+
+    #[test]
+    fn anomalies_are_reported() {
+        let mut reassembler = FragmentReassembler::default();
+        reassembler.push(&frag(0, true, b"tiny8byt"));
+        reassembler.push(&frag(0, true, b"TINY8BYT"));
+        reassembler.push(&frag(65_528, false, b"overflow"));
+        let events = reassembler.drain_events();
+        assert!(events.contains(&FragmentEvent::TinyFirstFragment { key: KEY, len: 8 }));
+        assert!(events.contains(&FragmentEvent::Overlap {
+            key: KEY,
+            offset: 0,
+            len: 8,
+            conflicting: true,
+        }));
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, FragmentEvent::Oversize { end: 65_536, .. }))
+        );
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn bytes_past_the_final_fragment_are_reported() {
+        let mut reassembler = FragmentReassembler::new(FragmentConfig {
+            min_first_fragment: 0,
+            ..FragmentConfig::default()
+        });
+        reassembler.push(&frag(8, true, b"middle--end-junk"));
+        reassembler.push(&frag(16, false, b"end"));
+        let done = reassembler.push(&frag(0, true, b"start---"));
+        assert_eq!(
+            done.map(|d| d.payload),
+            Some(b"start---middle--end".to_vec())
+        );
+        assert!(
+            reassembler
+                .drain_events()
+                .contains(&FragmentEvent::BeyondEnd {
+                    key: KEY,
+                    total_len: 19,
+                    bytes: 5,
+                })
+        );
+        assert_eq!(reassembler.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn many_tiny_fragments_reassemble_in_any_order() {
+        let payload: Vec<u8> = (0..4096_u32).map(|i| (i % 251) as u8).collect();
+        let mut reassembler = FragmentReassembler::new(FragmentConfig {
+            min_first_fragment: 0,
+            ..FragmentConfig::default()
+        });
+        let chunks: Vec<(usize, &[u8])> = payload.chunks(8).enumerate().collect();
+        let mut done = None;
+        for &(i, chunk) in chunks.iter().rev() {
+            let more = i + 1 < chunks.len();
+            done = reassembler.push(&frag(i * 8, more, chunk));
+        }
+        assert_eq!(done.map(|d| (d.fragments, d.payload)), Some((512, payload)));
+    }
+
+    #[test]
+    fn trickled_fragments_time_out_from_the_first() {
+        let mut reassembler = FragmentReassembler::default();
+        for i in 0..3 {
+            reassembler.push(&Fragment {
+                timestamp_ms: i * 10_000,
+                ..frag(8 + 8 * usize::from(i == 2), true, b"8 bytes!")
+            });
+        }
+        reassembler.expire(29_999);
+        assert_eq!(reassembler.pending(), 1);
+        reassembler.expire(30_000);
+        assert_eq!(reassembler.pending(), 0);
+        assert_eq!(
+            reassembler.drain_events().last(),
+            Some(&FragmentEvent::Timeout {
+                key: KEY,
+                received: 16,
+            })
+        );
+    }
+
+    #[test]
+    fn timeout_and_memory_limit() {
+        let mut reassembler = FragmentReassembler::new(FragmentConfig {
+            max_buffered_bytes: 16,
+            ..FragmentConfig::default()
+        });
+        reassembler.push(&frag(0, true, b"0123456789ABCDEFGHIJ"));
+        assert_eq!(
+            reassembler.drain_events(),
+            vec![FragmentEvent::MemoryLimit { key: KEY }]
+        );
+
+        reassembler.push(&frag(8, true, b"8 bytes!"));
+        reassembler.expire(30_000);
+        assert_eq!(
+            reassembler.drain_events(),
+            vec![FragmentEvent::Timeout {
+                key: KEY,
+                received: 8,
+            }]
+        );
+        assert_eq!(reassembler.buffered_bytes(), 0);
+    }
+}
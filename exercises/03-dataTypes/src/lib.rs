@@ -3,13 +3,27 @@
 //! Demonstrates `u8`/`u16` wire fields, bit masks, tuples, and fixed arrays
 //! in a cybersecurity packet-inspection context.
 
+pub mod flow;
+pub mod fragment;
 pub mod packet;
 pub mod ports;
+pub mod registry;
 pub mod tos_protocol;
 
+pub use flow::{
+    Direction, Flow, FlowConfig, FlowKey, FlowTable, OverlapPolicy, StreamReassembler, TcpSegment,
+    TcpState,
+};
+pub use fragment::{
+    Datagram, Fragment, FragmentConfig, FragmentEvent, FragmentKey, FragmentPolicy,
+    FragmentReassembler,
+};
 pub use packet::{
-    PacketHeader, TcpPorts, format_ipv4, format_port_pair, header_words, parse_header, parse_tos,
-    protocol_label, read_u16_be, sample_header_bytes, swap_ports,
+    PacketHeader, TcpPorts, format_ipv4, format_port_pair, header_words, parse_flags_fragment,
+    parse_header, parse_tos, protocol_label, read_u16_be, sample_header_bytes, swap_ports,
+};
+pub use registry::{
+    PortClass, Registry, Service, Transport, classify_port, protocol_name, protocol_number,
 };
 
 pub type Result<T> = anyhow::Result<T>;
@@ -1,5 +1,7 @@
 //! Packet header scalars and compound types for cybersecurity demos.
 
+use crate::registry::{PortClass, classify_port};
+
 /// IANA protocol numbers used in exercises.
 pub const PROTO_TCP: u8 = 6;
 pub const PROTO_UDP: u8 = 17;
@@ -7,8 +9,12 @@ pub const PROTO_UDP: u8 = 17;
 /// Fixed size of our educational IPv4-like header (bytes).
 pub const HEADER_LEN: usize = 20;
 
+/// IPv4 flag bits (top three bits of bytes 6-7): Don't Fragment, More Fragments.
+pub const FLAG_DF: u8 = 0b010;
+pub const FLAG_MF: u8 = 0b001;
+
 /// TCP endpoint pair — compound struct of two `u16` port scalars.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TcpPorts {
     /// Source port on the initiating host.
     pub source_port: u16,
@@ -26,9 +32,11 @@ pub const fn swap_ports(ports: TcpPorts) -> TcpPorts {
 }
 
 /// Whether a port is in the well-known range 0..=1023.
+///
+/// See [`classify_port`] for the registered and ephemeral ranges.
 #[must_use]
 pub const fn is_well_known(port: u16) -> bool {
-    port <= 1023
+    matches!(classify_port(port), PortClass::WellKnown)
 }
 
 /// Map a protocol number to a single-character label.
@@ -64,6 +72,16 @@ pub const fn read_u16_be(hi: u8, lo: u8) -> u16 {
     (hi as u16) << 8 | (lo as u16)
 }
 
+/// Split header bytes 6-7 into `(flags, fragment_offset)`.
+///
+/// The top 3 bits are flags; the low 13 bits are the offset in 8-byte units.
+#[must_use]
+pub const fn parse_flags_fragment(hi: u8, lo: u8) -> (u8, u16) {
+    let flags = hi >> 5;
+    let offset = read_u16_be(hi & 0x1F, lo);
+    (flags, offset)
+}
+
 /// Parsed view of a simplified IPv4-like header.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PacketHeader {
@@ -75,6 +93,12 @@ pub struct PacketHeader {
     pub tos: u8,
     /// Total length of the packet in bytes.
     pub total_length: u16,
+    /// Identification shared by all fragments of one datagram.
+    pub identification: u16,
+    /// Flag bits ([`FLAG_DF`], [`FLAG_MF`]).
+    pub flags: u8,
+    /// Fragment offset in 8-byte units.
+    pub fragment_offset: u16,
     /// Time To Live.
     pub ttl: u8,
     /// Protocol number (6 = TCP, 17 = UDP).
@@ -93,6 +117,7 @@ pub fn sample_header_bytes() -> [u8; HEADER_LEN] {
     let version_ihl: u8 = 0x45;
     let tos: u8 = 0b1011_0100; // dscp=45, ecn=0
     let total_length: u16 = 60;
+    let identification: u16 = 0x1C46;
     let ttl: u8 = 64;
     let protocol: u8 = PROTO_TCP;
     let checksum: u16 = 0xBEEF;
@@ -106,7 +131,9 @@ pub fn sample_header_bytes() -> [u8; HEADER_LEN] {
     {
         bytes[2] = (total_length >> 8) as u8;
         bytes[3] = (total_length & 0xFF) as u8;
-        bytes[6] = 0;
+        bytes[4] = (identification >> 8) as u8;
+        bytes[5] = (identification & 0xFF) as u8;
+        bytes[6] = FLAG_DF << 5;
         bytes[7] = 0;
         bytes[8] = ttl;
         bytes[9] = protocol;
@@ -126,12 +153,16 @@ pub const fn parse_header(bytes: &[u8; HEADER_LEN]) -> Option<PacketHeader> {
     if version != 4 || ihl < 5 {
         return None;
     }
+    let (flags, fragment_offset) = parse_flags_fragment(bytes[6], bytes[7]);
 
     Some(PacketHeader {
         version,
         ihl,
         tos: bytes[1],
         total_length: read_u16_be(bytes[2], bytes[3]),
+        identification: read_u16_be(bytes[4], bytes[5]),
+        flags,
+        fragment_offset,
         ttl: bytes[8],
         protocol: bytes[9],
         checksum: read_u16_be(bytes[10], bytes[11]),
@@ -199,6 +230,13 @@ mod tests {
         assert_eq!(ecn2, 3);
     }
 
+    #[test]
+    fn flags_and_fragment_offset_split() {
+        assert_eq!(parse_flags_fragment(0x40, 0x00), (FLAG_DF, 0));
+        assert_eq!(parse_flags_fragment(0x20, 0xB9), (FLAG_MF, 185));
+        assert_eq!(parse_flags_fragment(0x3F, 0xFF), (FLAG_MF, 0x1FFF));
+    }
+
     #[test]
     fn sample_header_parses() {
         let bytes = sample_header_bytes();
@@ -209,6 +247,9 @@ mod tests {
             assert_eq!(h.ihl, 5);
             assert_eq!(h.protocol, PROTO_TCP);
             assert_eq!(h.src, [10, 0, 0, 5]);
+            assert_eq!(h.identification, 0x1C46);
+            assert_eq!(h.flags, FLAG_DF);
+            assert_eq!(h.fragment_offset, 0);
         }
     }
 
@@ -1,6 +1,7 @@
 //! Port scalar exercise — TCP endpoint fields and flow direction.
 
 use crate::packet::{TcpPorts, format_port_pair, is_well_known, swap_ports};
+use crate::registry::{Registry, Transport, classify_port};
 
 /// Run the ports exercise with demo output.
 pub fn run(verbose: bool) {
@@ -35,6 +36,14 @@ pub fn run(verbose: bool) {
     };
     println!("3. DNS resolver binding: {}", format_port_pair(dns));
 
+    let registry = Registry::builtin();
+    for port in [inbound.dest_port, 3389, inbound.source_port] {
+        let name = registry
+            .service(port, Transport::Tcp)
+            .map_or("unassigned", |service| service.name.as_str());
+        println!("4. Port {port}: {name} ({})", classify_port(port));
+    }
+
     if verbose {
         println!();
         println!("   Why it matters:");
@@ -0,0 +1,508 @@
+//! Port and protocol registry — IANA numbers, service names, and risk tags.
+//!
+//! A compiled-in table covers the IP protocol numbers and TCP/UDP services an
+//! analyst meets daily. [`Registry`] wraps it for two-way lookups and can be
+//! extended at runtime with extra services, risk tags, or a local
+//! services file in the familiar `/etc/services` layout.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::packet::{PROTO_TCP, PROTO_UDP};
+
+/// Risk tag: credentials cross the wire in clear text.
+pub const CLEARTEXT_AUTH: &str = "cleartext-auth";
+/// Risk tag: interactive or remote administration surface.
+pub const REMOTE_ADMIN: &str = "remote-admin";
+/// Risk tag: file-sharing protocol often abused for lateral movement.
+pub const FILE_SHARING: &str = "file-sharing";
+/// Risk tag: database listener that should not face the internet.
+pub const DATABASE: &str = "database";
+/// Risk tag: commonly deployed without authentication.
+pub const NO_AUTH: &str = "no-auth";
+
+/// IANA port range a number falls into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortClass {
+    /// `0..=1023` — system services, usually needing privileges to bind.
+    WellKnown,
+    /// `1024..=49151` — IANA-registered user services.
+    Registered,
+    /// `49152..=65535` — dynamic/private range used for ephemeral ports.
+    Ephemeral,
+}
+
+/// Classify a port by IANA range.
+#[must_use]
+pub const fn classify_port(port: u16) -> PortClass {
+    match port {
+        0..=1023 => PortClass::WellKnown,
+        1024..=49151 => PortClass::Registered,
+        _ => PortClass::Ephemeral,
+    }
+}
+
+impl fmt::Display for PortClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WellKnown => write!(f, "well-known"),
+            Self::Registered => write!(f, "registered"),
+            Self::Ephemeral => write!(f, "ephemeral"),
+        }
+    }
+}
+
+/// Transport protocol a service listens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Transport {
+    Tcp,
+    Udp,
+}
+
+impl Transport {
+    /// IANA protocol number.
+    #[must_use]
+    pub const fn number(self) -> u8 {
+        match self {
+            Self::Tcp => PROTO_TCP,
+            Self::Udp => PROTO_UDP,
+        }
+    }
+
+    /// Lowercase name as used in services files.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text.to_ascii_lowercase().as_str() {
+            "tcp" => Some(Self::Tcp),
+            "udp" => Some(Self::Udp),
+            _ => None,
+        }
+    }
+}
+
+const TCP: &[Transport] = &[Transport::Tcp];
+const UDP: &[Transport] = &[Transport::Udp];
+const BOTH: &[Transport] = &[Transport::Tcp, Transport::Udp];
+
+/// IANA protocol numbers: `(number, keyword, description)`.
+pub const PROTOCOLS: &[(u8, &str, &str)] = &[
+    (0, "HOPOPT", "IPv6 Hop-by-Hop Option"),
+    (1, "ICMP", "Internet Control Message"),
+    (2, "IGMP", "Internet Group Management"),
+    (4, "IPv4", "IPv4 encapsulation"),
+    (6, "TCP", "Transmission Control"),
+    (8, "EGP", "Exterior Gateway Protocol"),
+    (17, "UDP", "User Datagram"),
+    (27, "RDP", "Reliable Data Protocol"),
+    (41, "IPv6", "IPv6 encapsulation"),
+    (43, "IPv6-Route", "Routing Header for IPv6"),
+    (44, "IPv6-Frag", "Fragment Header for IPv6"),
+    (46, "RSVP", "Reservation Protocol"),
+    (47, "GRE", "Generic Routing Encapsulation"),
+    (50, "ESP", "Encap Security Payload"),
+    (51, "AH", "Authentication Header"),
+    (58, "IPv6-ICMP", "ICMP for IPv6"),
+    (59, "IPv6-NoNxt", "No Next Header for IPv6"),
+    (60, "IPv6-Opts", "Destination Options for IPv6"),
+    (88, "EIGRP", "Enhanced Interior Gateway Routing"),
+    (89, "OSPFIGP", "Open Shortest Path First"),
+    (103, "PIM", "Protocol Independent Multicast"),
+    (112, "VRRP", "Virtual Router Redundancy"),
+    (115, "L2TP", "Layer Two Tunneling Protocol"),
+    (132, "SCTP", "Stream Control Transmission"),
+    (136, "UDPLite", "Lightweight UDP"),
+    (137, "MPLS-in-IP", "MPLS in IP"),
+    (143, "Ethernet", "Ethernet"),
+];
+
+/// Common services: `(name, port, transports, risk tags)`.
+const SERVICES: &[(&str, u16, &[Transport], &[&str])] = &[
+    ("ftp-data", 20, TCP, &[CLEARTEXT_AUTH]),
+    ("ftp", 21, TCP, &[CLEARTEXT_AUTH, FILE_SHARING]),
+    ("ssh", 22, TCP, &[REMOTE_ADMIN]),
+    ("telnet", 23, TCP, &[CLEARTEXT_AUTH, REMOTE_ADMIN]),
+    ("smtp", 25, TCP, &[]),
+    ("domain", 53, BOTH, &[]),
+    ("bootps", 67, UDP, &[]),
+    ("bootpc", 68, UDP, &[]),
+    ("tftp", 69, UDP, &[NO_AUTH, FILE_SHARING]),
+    ("http", 80, TCP, &[]),
+    ("kerberos", 88, BOTH, &[]),
+    ("pop3", 110, TCP, &[CLEARTEXT_AUTH]),
+    ("sunrpc", 111, BOTH, &[]),
+    ("ntp", 123, UDP, &[]),
+    ("epmap", 135, TCP, &[REMOTE_ADMIN]),
+    ("netbios-ns", 137, UDP, &[]),
+    ("netbios-dgm", 138, UDP, &[]),
+    ("netbios-ssn", 139, TCP, &[FILE_SHARING]),
+    ("imap", 143, TCP, &[CLEARTEXT_AUTH]),
+    ("snmp", 161, UDP, &[CLEARTEXT_AUTH]),
+    ("snmptrap", 162, UDP, &[]),
+    ("ldap", 389, BOTH, &[CLEARTEXT_AUTH]),
+    ("https", 443, TCP, &[]),
+    ("microsoft-ds", 445, TCP, &[FILE_SHARING]),
+    ("submissions", 465, TCP, &[]),
+    ("syslog", 514, UDP, &[]),
+    ("submission", 587, TCP, &[]),
+    ("ldaps", 636, TCP, &[]),
+    ("rsync", 873, TCP, &[FILE_SHARING]),
+    ("imaps", 993, TCP, &[]),
+    ("pop3s", 995, TCP, &[]),
+    ("ms-sql-s", 1433, TCP, &[DATABASE]),
+    ("pptp", 1723, TCP, &[]),
+    ("mqtt", 1883, TCP, &[NO_AUTH]),
+    ("nfs", 2049, BOTH, &[FILE_SHARING]),
+    ("mysql", 3306, TCP, &[DATABASE]),
+    ("ms-wbt-server", 3389, TCP, &[REMOTE_ADMIN]),
+    ("postgresql", 5432, TCP, &[DATABASE]),
+    ("rfb", 5900, TCP, &[REMOTE_ADMIN]),
+    ("wsman", 5985, TCP, &[REMOTE_ADMIN, CLEARTEXT_AUTH]),
+    ("wsmans", 5986, TCP, &[REMOTE_ADMIN]),
+    ("redis", 6379, TCP, &[DATABASE, NO_AUTH]),
+    ("http-alt", 8080, TCP, &[]),
+    ("memcache", 11211, BOTH, &[NO_AUTH]),
+    ("mongodb", 27017, TCP, &[DATABASE]),
+];
+
+/// Look up a protocol keyword in the compiled-in table.
+#[must_use]
+pub fn protocol_name(number: u8) -> Option<&'static str> {
+    PROTOCOLS
+        .iter()
+        .find(|(n, _, _)| *n == number)
+        .map(|(_, keyword, _)| *keyword)
+}
+
+/// Reverse of [`protocol_name`], case-insensitive.
+#[must_use]
+pub fn protocol_number(keyword: &str) -> Option<u8> {
+    PROTOCOLS
+        .iter()
+        .find(|(_, name, _)| name.eq_ignore_ascii_case(keyword))
+        .map(|(number, _, _)| *number)
+}
+
+/// One service entry: a name bound to a port on a transport.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Service {
+    /// Canonical service name (`ssh`, `https`, …).
+    pub name: String,
+    /// Port number.
+    pub port: u16,
+    /// Transport protocol.
+    pub transport: Transport,
+    /// Alternative names accepted by lookups.
+    pub aliases: Vec<String>,
+    /// Risk tags attached to the service.
+    pub tags: BTreeSet<String>,
+}
+
+impl Service {
+    /// Whether `name` matches the canonical name or an alias (case-insensitive).
+    #[must_use]
+    pub fn answers_to(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the service carries `tag`.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+}
+
+/// A malformed line in a services file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServicesParseError {
+    /// 1-based line number.
+    pub line: usize,
+    /// What was wrong with it.
+    pub reason: String,
+}
+
+impl fmt::Display for ServicesParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "services line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ServicesParseError {}
+
+/// Extensible registry of services and protocols.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    protocols: BTreeMap<u8, (String, String)>,
+    services: BTreeMap<(Transport, u16), Service>,
+}
+
+impl Registry {
+    /// Registry preloaded with the compiled-in protocol and service tables.
+    #[must_use]
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        for &(number, keyword, description) in PROTOCOLS {
+            registry
+                .protocols
+                .insert(number, (keyword.to_owned(), description.to_owned()));
+        }
+        for &(name, port, transports, tags) in SERVICES {
+            for &transport in transports {
+                registry.add_service(Service {
+                    name: name.to_owned(),
+                    port,
+                    transport,
+                    aliases: Vec::new(),
+                    tags: tags.iter().map(|t| (*t).to_owned()).collect(),
+                });
+            }
+        }
+        registry
+    }
+
+    /// Add or replace a service; tags already attached to the port are kept.
+    pub fn add_service(&mut self, mut service: Service) {
+        if let Some(existing) = self.services.get(&(service.transport, service.port)) {
+            service.tags.extend(existing.tags.iter().cloned());
+        }
+        self.services
+            .insert((service.transport, service.port), service);
+    }
+
+    /// Register or rename an IP protocol number.
+    pub fn add_protocol(&mut self, number: u8, keyword: &str, description: &str) {
+        self.protocols
+            .insert(number, (keyword.to_owned(), description.to_owned()));
+    }
+
+    /// Attach a risk tag to a registered service; `false` if unknown.
+    pub fn tag(&mut self, port: u16, transport: Transport, tag: &str) -> bool {
+        self.services
+            .get_mut(&(transport, port))
+            .map(|service| service.tags.insert(tag.to_owned()))
+            .is_some()
+    }
+
+    /// Service bound to `port` on `transport`.
+    #[must_use]
+    pub fn service(&self, port: u16, transport: Transport) -> Option<&Service> {
+        self.services.get(&(transport, port))
+    }
+
+    /// Port for a service name or alias on `transport`.
+    #[must_use]
+    pub fn port_of(&self, name: &str, transport: Transport) -> Option<u16> {
+        self.services
+            .values()
+            .find(|service| service.transport == transport && service.answers_to(name))
+            .map(|service| service.port)
+    }
+
+    /// Protocol keyword for an IANA number.
+    #[must_use]
+    pub fn protocol_name(&self, number: u8) -> Option<&str> {
+        self.protocols
+            .get(&number)
+            .map(|(keyword, _)| keyword.as_str())
+    }
+
+    /// IANA number for a protocol keyword (case-insensitive).
+    #[must_use]
+    pub fn protocol_number(&self, keyword: &str) -> Option<u8> {
+        self.protocols
+            .iter()
+            .find(|(_, (name, _))| name.eq_ignore_ascii_case(keyword))
+            .map(|(number, _)| *number)
+    }
+
+    /// Every service carrying `tag`, ordered by transport then port.
+    #[must_use]
+    pub fn services_tagged(&self, tag: &str) -> Vec<&Service> {
+        self.services
+            .values()
+            .filter(|service| service.has_tag(tag))
+            .collect()
+    }
+
+    /// Number of service entries.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.services.len()
+    }
+
+    /// Whether no services are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.services.is_empty()
+    }
+
+    /// Merge services from `/etc/services`-style text; returns entries added.
+    ///
+    /// Each line is `name port/proto [aliases…] [# comment]`. A comment of the
+    /// form `# tags: cleartext-auth, remote-admin` attaches risk tags.
+    ///
+    /// Every line is parsed before anything is merged, so an error leaves the
+    /// registry unchanged.
+    pub fn load_services(&mut self, text: &str) -> Result<usize, ServicesParseError> {
+        let mut parsed = Vec::new();
+        for (index, raw) in text.lines().enumerate() {
+            parsed.extend(parse_services_line(raw, index + 1)?);
+        }
+        let added = parsed.len();
+        for service in parsed {
+            self.add_service(service);
+        }
+        Ok(added)
+    }
+
+    /// Read and merge a services file from disk.
+    pub fn load_services_file(&mut self, path: &Path) -> crate::Result<usize> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading services file {}", path.display()))?;
+        Ok(self.load_services(&text)?)
+    }
+}
+
+fn parse_services_line(raw: &str, line: usize) -> Result<Option<Service>, ServicesParseError> {
+    let (body, comment) = raw.split_once('#').unwrap_or((raw, ""));
+    let mut fields = body.split_whitespace();
+    let Some(name) = fields.next() else {
+        return Ok(None);
+    };
+    let error = |reason: String| ServicesParseError { line, reason };
+
+    let port_proto = fields
+        .next()
+        .ok_or_else(|| error(format!("missing port/protocol for {name:?}")))?;
+    let (port_text, proto_text) = port_proto
+        .split_once('/')
+        .ok_or_else(|| error(format!("expected port/protocol, got {port_proto:?}")))?;
+    let port = port_text
+        .parse::<u16>()
+        .map_err(|_| error(format!("invalid port {port_text:?}")))?;
+    let transport = Transport::parse(proto_text)
+        .ok_or_else(|| error(format!("unsupported protocol {proto_text:?}")))?;
+
+    let tags = comment
+        .trim()
+        .strip_prefix("tags:")
+        .map(|list| {
+            list.split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(Service {
+        name: name.to_owned(),
+        port,
+        transport,
+        aliases: fields.map(str::to_owned).collect(),
+        tags,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_classes_follow_iana_ranges() {
+        assert_eq!(classify_port(0), PortClass::WellKnown);
+        assert_eq!(classify_port(1023), PortClass::WellKnown);
+        assert_eq!(classify_port(1024), PortClass::Registered);
+        assert_eq!(classify_port(49151), PortClass::Registered);
+        assert_eq!(classify_port(49152), PortClass::Ephemeral);
+        assert_eq!(PortClass::Ephemeral.to_string(), "ephemeral");
+    }
+
+    #[test]
+    fn protocol_lookups_both_ways() {
+        assert_eq!(protocol_name(47), Some("GRE"));
+        assert_eq!(protocol_number("esp"), Some(50));
+        assert_eq!(protocol_name(253), None);
+
+        let mut registry = Registry::builtin();
+        registry.add_protocol(253, "EXP-1", "Experimentation and testing");
+        assert_eq!(registry.protocol_name(253), Some("EXP-1"));
+        assert_eq!(registry.protocol_number("exp-1"), Some(253));
+    }
+
+    #[test]
+    fn service_lookups_both_ways() {
+        let registry = Registry::builtin();
+        assert_eq!(
+            registry
+                .service(22, Transport::Tcp)
+                .map(|s| s.name.as_str()),
+            Some("ssh")
+        );
+        assert_eq!(registry.port_of("DOMAIN", Transport::Udp), Some(53));
+        assert!(registry.service(123, Transport::Tcp).is_none());
+    }
+
+    #[test]
+    fn builtin_risk_tags() {
+        let registry = Registry::builtin();
+        let telnet = registry.service(23, Transport::Tcp);
+        assert!(telnet.is_some_and(|s| s.has_tag(CLEARTEXT_AUTH) && s.has_tag(REMOTE_ADMIN)));
+
+        let admin_ports: Vec<u16> = registry
+            .services_tagged(REMOTE_ADMIN)
+            .iter()
+            .map(|s| s.port)
+            .collect();
+        assert!(admin_ports.contains(&3389));
+        assert!(!admin_ports.contains(&443));
+    }
+
+    #[test]
+    fn services_file_extends_registry() {
+        let text = "\
+# local additions
+grafana        3000/tcp   dashboards      # tags: remote-admin, internal-only
+ssh            22/tcp                     # tags: bastion
+syslog-tls     6514/tcp
+";
+        let mut registry = Registry::builtin();
+        assert_eq!(registry.load_services(text), Ok(3));
+        assert_eq!(registry.port_of("dashboards", Transport::Tcp), Some(3000));
+
+        let grafana = registry.service(3000, Transport::Tcp);
+        assert!(grafana.is_some_and(|s| s.has_tag("internal-only")));
+        // Reloading ssh keeps the built-in tag and adds the local one.
+        let ssh = registry.service(22, Transport::Tcp);
+        assert!(ssh.is_some_and(|s| s.has_tag(REMOTE_ADMIN) && s.has_tag("bastion")));
+    }
+
+    #[test]
+    fn services_file_errors_carry_line_numbers() {
+        let mut registry = Registry::default();
+        let err = registry.load_services("ok 1/tcp\nbroken 70000/tcp\n");
+        assert_eq!(err.map_err(|e| e.line), Err(2));
+        // The good first line was not merged.
+        assert!(registry.is_empty());
+        assert!(registry.load_services("weird 5/sctp").is_err());
+        assert!(registry.load_services("noport").is_err());
+    }
+
+    #[test]
+    fn user_tags_on_existing_services() {
+        let mut registry = Registry::builtin();
+        assert!(registry.tag(8080, Transport::Tcp, "shadow-it"));
+        assert!(!registry.tag(8081, Transport::Tcp, "shadow-it"));
+        assert_eq!(registry.services_tagged("shadow-it").len(), 1);
+    }
+}
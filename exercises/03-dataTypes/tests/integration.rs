@@ -1,8 +1,13 @@
 //! Integration tests for the `exercise_datatypes` crate.
 
+use datatypes_exercises::flow::{TCP_ACK, TCP_FIN, TCP_PSH, TCP_SYN};
+use datatypes_exercises::packet::{FLAG_MF, HEADER_LEN};
+use datatypes_exercises::registry::CLEARTEXT_AUTH;
 use datatypes_exercises::{
-    TcpPorts, format_ipv4, get_exercise_list, header_words, parse_header, parse_tos,
-    protocol_label, run_all, run_exercise, sample_header_bytes, swap_ports,
+    Direction, FlowTable, Fragment, FragmentEvent, FragmentReassembler, PortClass, Registry,
+    TcpPorts, TcpSegment, TcpState, Transport, classify_port, format_ipv4, get_exercise_list,
+    header_words, parse_flags_fragment, parse_header, parse_tos, protocol_label, protocol_name,
+    protocol_number, run_all, run_exercise, sample_header_bytes, swap_ports,
 };
 
 #[test]
@@ -64,3 +69,146 @@ fn test_port_and_tos_api() {
         assert_eq!(format_ipv4(header.src), "10.0.0.5");
     }
 }
+
+#[test]
+fn test_flow_table_reassembles_both_directions() {
+    let client = [10, 0, 0, 5];
+    let server = [192, 168, 1, 1];
+    let out = TcpPorts {
+        source_port: 49152,
+        dest_port: 80,
+    };
+    let segment = |src, dst, ports, seq, flags, payload, ts| TcpSegment {
+        src,
+        dst,
+        ports,
+        seq,
+        ack: 0,
+        flags,
+        payload,
+        timestamp_ms: ts,
+    };
+
+    let mut table = FlowTable::default();
+    let key = table.observe(&segment(client, server, out, 0, TCP_SYN, &[], 0));
+    table.observe(&segment(
+        server,
+        client,
+        swap_ports(out),
+        7000,
+        TCP_SYN | TCP_ACK,
+        &[],
+        1,
+    ));
+    table.observe(&segment(client, server, out, 1, TCP_ACK, &[], 2));
+    // Second half of the request arrives first.
+    table.observe(&segment(
+        client,
+        server,
+        out,
+        5,
+        TCP_PSH | TCP_ACK,
+        b"/ HTTP/1.1",
+        3,
+    ));
+    table.observe(&segment(
+        client,
+        server,
+        out,
+        1,
+        TCP_PSH | TCP_ACK,
+        b"GET ",
+        4,
+    ));
+    table.observe(&segment(client, server, out, 15, TCP_FIN | TCP_ACK, &[], 5));
+    table.observe(&segment(
+        server,
+        client,
+        swap_ports(out),
+        7001,
+        TCP_FIN | TCP_ACK,
+        &[],
+        6,
+    ));
+
+    let flow = table.get(&key);
+    assert!(flow.is_some());
+    if let Some(flow) = flow {
+        assert_eq!(flow.state, TcpState::Closed);
+        assert_eq!(
+            flow.stream(Direction::ClientToServer).assembled(),
+            b"GET / HTTP/1.1"
+        );
+        assert_eq!(flow.packets(), 7);
+        assert_eq!(flow.bytes(), 14);
+    }
+}
+
+#[test]
+fn test_fragments_reassemble_from_parsed_headers() {
+    let header_for = |offset_units: u16, more: bool| {
+        let mut bytes: [u8; HEADER_LEN] = sample_header_bytes();
+        let flags = if more { FLAG_MF } else { 0 };
+        let [hi, lo] = offset_units.to_be_bytes();
+        bytes[6] = flags << 5 | hi;
+        bytes[7] = lo;
+        parse_header(&bytes)
+    };
+    assert_eq!(parse_flags_fragment(0x20, 0x03), (FLAG_MF, 3));
+
+    // The second copy of offset 0 is an evasion attempt; BSD keeps the original.
+    let plan: [(u16, bool, &[u8]); 4] = [
+        (0, true, b"GET /index.html?"),
+        (0, true, b"GET /admin.php?x"),
+        (3, false, b"HTTP/1.0"),
+        (2, true, b"q=rust& "),
+    ];
+    let fragments: Vec<_> = plan
+        .iter()
+        .filter_map(|&(offset, more, payload)| header_for(offset, more).map(|h| (h, payload)))
+        .collect();
+    assert_eq!(fragments.len(), plan.len());
+
+    let mut reassembler = FragmentReassembler::default();
+    let mut datagram = None;
+    for (i, (header, payload)) in fragments.iter().enumerate() {
+        datagram = reassembler.push(&Fragment::from_header(header, payload, i as u64));
+    }
+    assert_eq!(
+        datagram.map(|d| d.payload),
+        Some(b"GET /index.html?q=rust& HTTP/1.0".to_vec())
+    );
+
+    let events = reassembler.drain_events();
+    assert!(events.iter().any(|e| matches!(
+        e,
+        FragmentEvent::Overlap {
+            conflicting: true,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_registry_names_ports_and_risks() {
+    assert_eq!(protocol_name(6), Some("TCP"));
+    assert_eq!(protocol_number("gre"), Some(47));
+    assert_eq!(classify_port(52341), PortClass::Ephemeral);
+
+    let mut registry = Registry::builtin();
+    assert_eq!(registry.port_of("https", Transport::Tcp), Some(443));
+    assert!(
+        registry
+            .services_tagged(CLEARTEXT_AUTH)
+            .iter()
+            .any(|s| s.name == "telnet")
+    );
+
+    let added = registry.load_services("jetdirect 9100/tcp # tags: no-auth");
+    assert_eq!(added, Ok(1));
+    assert!(
+        registry
+            .service(9100, Transport::Tcp)
+            .is_some_and(|s| s.has_tag("no-auth"))
+    );
+}
@@ -0,0 +1,64 @@
+//! Internet checksum throughput: word-at-a-time fast path vs. the per-word loops.
+//!
+//! ```bash
+//! cargo bench -p exercise_functions
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use functions_exercises::inet::{self, InternetChecksum};
+use functions_exercises::{bytes_to_words, internet_checksum};
+
+fn buffer(len: usize) -> Vec<u8> {
+    (0..len)
+        .map(|i| u8::try_from(i % 251).unwrap_or(0))
+        .collect()
+}
+
+fn bench_checksum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("internet_checksum");
+    for len in [20, 64, 576, 1500, 9000, 65_535] {
+        let data = buffer(len);
+        group.throughput(Throughput::Bytes(u64::try_from(len).unwrap_or(u64::MAX)));
+
+        group.bench_with_input(BenchmarkId::new("words_vec", len), &data, |b, data| {
+            b.iter(|| internet_checksum(&bytes_to_words(black_box(data))));
+        });
+        group.bench_with_input(BenchmarkId::new("scalar", len), &data, |b, data| {
+            b.iter(|| inet::checksum_scalar(black_box(data)));
+        });
+        group.bench_with_input(BenchmarkId::new("fast", len), &data, |b, data| {
+            b.iter(|| inet::checksum(black_box(data)));
+        });
+        group.bench_with_input(BenchmarkId::new("streaming_odd", len), &data, |b, data| {
+            b.iter(|| {
+                let mut acc = InternetChecksum::new();
+                for chunk in black_box(data).chunks(333) {
+                    acc.update(chunk);
+                }
+                acc.finish()
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_incremental(c: &mut Criterion) {
+    let header = buffer(20);
+    c.bench_function("ttl_update/recompute", |b| {
+        b.iter(|| {
+            let mut copy = black_box(header.clone());
+            copy[8] = copy[8].wrapping_sub(1);
+            copy[10] = 0;
+            copy[11] = 0;
+            inet::checksum(&copy)
+        });
+    });
+    c.bench_function("ttl_update/rfc1624", |b| {
+        b.iter(|| inet::update_u16(black_box(0xB861), black_box(0x4011), black_box(0x3F11)));
+    });
+}
+
+criterion_group!(benches, bench_checksum, bench_incremental);
+criterion_main!(benches);
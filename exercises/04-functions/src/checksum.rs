@@ -1,4 +1,9 @@
 //! Network checksum helpers — educational Internet checksum folding.
+//!
+//! The word-based helpers here are the teaching version; sealing and
+//! verification run on byte buffers through [`crate::inet`].
+
+use crate::inet;
 
 /// Maximum payload size accepted by the seal helpers (bytes).
 pub const MAX_PAYLOAD: usize = 1500;
@@ -78,8 +83,7 @@ pub fn seal_packet(payload: &[u8]) -> Option<(Vec<u8>, u16)> {
     buf.push(0);
     buf.extend_from_slice(payload);
 
-    let words = bytes_to_words(&buf);
-    let csum = internet_checksum(&words);
+    let csum = inet::checksum(&buf);
     #[allow(clippy::cast_possible_truncation)]
     {
         buf[2] = (csum >> 8) as u8;
@@ -94,9 +98,7 @@ pub fn verify_sealed(packet: &[u8]) -> bool {
     if packet.len() < 4 {
         return false;
     }
-    let words = bytes_to_words(packet);
-    let folded = internet_checksum(&words);
-    folded == 0
+    inet::verify(packet)
 }
 
 /// Read the payload length prefix from a sealed packet.
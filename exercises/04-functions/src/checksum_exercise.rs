@@ -1,6 +1,7 @@
 //! Checksum exercise — hex formatting and word folding.
 
 use crate::checksum::{checksum_hex, fold_checksum, internet_checksum, nibble_mix};
+use crate::inet::{self, InternetChecksum};
 
 /// Run the checksum exercise with demo output.
 pub fn run(verbose: bool) {
@@ -18,11 +19,38 @@ pub fn run(verbose: bool) {
     let mixed = nibble_mix(0x0F, 0x0A);
     println!("4. Nibble mix 0x0F + 0x0A -> 0x{mixed:02X}");
 
+    let mut header = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xC0, 0xA8, 0x00,
+        0x01, 0xC0, 0xA8, 0x00, 0xC7,
+    ];
+    let mut stream = InternetChecksum::new();
+    for chunk in header.chunks(7) {
+        stream.update(chunk);
+    }
+    let csum = stream.finish();
+    header[10..12].copy_from_slice(&csum.to_be_bytes());
+    println!(
+        "5. IPv4 header checksum (streamed in 7-byte chunks): {}",
+        checksum_hex(csum)
+    );
+
+    let old_ttl = u16::from_be_bytes([header[8], header[9]]);
+    header[8] -= 1;
+    let new_ttl = u16::from_be_bytes([header[8], header[9]]);
+    let patched = inet::update_u16(csum, old_ttl, new_ttl);
+    header[10..12].copy_from_slice(&patched.to_be_bytes());
+    println!(
+        "6. After TTL 64 -> 63 (RFC 1624): {} (valid: {})",
+        checksum_hex(patched),
+        inet::verify(&header)
+    );
+
     if verbose {
         println!();
         println!("   Why it matters:");
         println!("   - Checksums detect bit flips in transit");
         println!("   - Carry folding keeps sums in 16-bit range");
         println!("   - Functions compose: fold after sum, format for logs");
+        println!("   - Routers patch checksums per hop instead of re-summing headers");
     }
 }
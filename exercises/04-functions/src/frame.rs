@@ -0,0 +1,649 @@
+//! Framed stream codec — sequenced, checksummed frames over `Read`/`Write`.
+//!
+//! [`seal_packet`](crate::seal_packet) builds one frame in memory. This module
+//! carries a stream of them:
+//!
+//! ```text
+//! [0xA5, 0x5A, flags, seq (4, BE), len (2, BE), crc32c (4, BE), payload...]
+//! ```
+//!
+//! - The CRC-32C covers `flags`, `seq`, `len`, and the payload.
+//! - Messages larger than [`MAX_PAYLOAD`] are split into fragments. Every
+//!   fragment but the last sets [`FLAG_MORE`], and every fragment but the
+//!   first sets [`FLAG_CONT`].
+//! - Each frame (not each message) takes the next sequence number, so lost,
+//!   repeated, or reordered frames show up as gaps or replays.
+//!
+//! The CRC only lets the decoder find frame boundaries and trust a header
+//! without a key. Whether to trust the sender is the job of the payload: put
+//! [`Keyring::seal`](crate::Keyring::seal) or
+//! [`seal_packet_with`](crate::seal_packet_with) output inside each message.
+//!
+//! [`FrameDecoder`] holds the parsing logic without doing any I/O. It is
+//! shared by the blocking [`FrameReader`] and by the tokio reader in
+//! `frame_async`.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+use crate::checksum::MAX_PAYLOAD;
+use crate::integrity::{Checksum, Crc32c};
+
+/// Start-of-frame marker.
+pub const MAGIC: [u8; 2] = [0xA5, 0x5A];
+/// Bytes before the payload.
+pub const FRAME_HEADER_LEN: usize = 13;
+/// More fragments of this message follow.
+pub const FLAG_MORE: u8 = 0x01;
+/// This frame continues a message started by an earlier frame.
+pub const FLAG_CONT: u8 = 0x02;
+/// Default cap on a reassembled message.
+pub const DEFAULT_MAX_MESSAGE: usize = 1 << 20;
+
+/// Encode one frame. `payload` must not exceed [`MAX_PAYLOAD`].
+fn encode_frame(seq: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+    let len = u16::try_from(payload.len()).unwrap_or(u16::MAX);
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + payload.len());
+    frame.extend_from_slice(&MAGIC);
+    frame.push(flags);
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&len.to_be_bytes());
+    let crc = frame_crc(&frame[2..9], payload);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn frame_crc(header: &[u8], payload: &[u8]) -> u32 {
+    let mut crc = Crc32c::new();
+    crc.update(header);
+    crc.update(payload);
+    crc.finalize()
+}
+
+/// Split a message into encoded frames starting at `seq`.
+///
+/// Returns the frames and the next unused sequence number.
+pub(crate) fn encode_message(mut seq: u32, message: &[u8]) -> (Vec<Vec<u8>>, u32) {
+    let mut frames = Vec::new();
+    let chunks: Vec<&[u8]> = if message.is_empty() {
+        vec![message]
+    } else {
+        message.chunks(MAX_PAYLOAD).collect()
+    };
+    let last = chunks.len() - 1;
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        let mut flags = 0;
+        if i < last {
+            flags |= FLAG_MORE;
+        }
+        if i > 0 {
+            flags |= FLAG_CONT;
+        }
+        frames.push(encode_frame(seq, flags, chunk));
+        seq = seq.wrapping_add(1);
+    }
+    (frames, seq)
+}
+
+/// Something the decoder observed in the stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameEvent {
+    /// A complete message; `seq` is the sequence number of its first frame.
+    Message { seq: u32, payload: Vec<u8> },
+    /// Frames `expected..received` never arrived.
+    Gap { expected: u32, received: u32 },
+    /// A frame at or behind the expected sequence number was dropped.
+    Replay { seq: u32 },
+    /// Corrupt bytes were skipped to find the next valid frame.
+    Resync { skipped: usize },
+    /// A partially reassembled message was abandoned, because of a gap, a
+    /// stray continuation, or the size limit.
+    Discarded { first_seq: u32, bytes: usize },
+    /// The stream ended inside a frame or inside a fragmented message.
+    Truncated { bytes: usize },
+}
+
+#[derive(Debug)]
+struct Partial {
+    first_seq: u32,
+    data: Vec<u8>,
+    bytes: usize,
+    overflow: bool,
+}
+
+/// Sans-IO frame parser: [`push`](Self::push) bytes in, pull [`FrameEvent`]s out.
+#[derive(Debug)]
+pub struct FrameDecoder {
+    buf: Vec<u8>,
+    /// Offset of the first unconsumed byte in `buf`; skipped and accepted
+    /// bytes only advance it, and [`push`](Self::push) compacts.
+    start: usize,
+    events: VecDeque<FrameEvent>,
+    expected: Option<u32>,
+    partial: Option<Partial>,
+    skipped: usize,
+    max_message: usize,
+    eof: bool,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_max_message(DEFAULT_MAX_MESSAGE)
+    }
+
+    /// A decoder that discards reassembled messages larger than `max_message`.
+    #[must_use]
+    pub const fn with_max_message(max_message: usize) -> Self {
+        Self {
+            buf: Vec::new(),
+            start: 0,
+            events: VecDeque::new(),
+            expected: None,
+            partial: None,
+            skipped: 0,
+            max_message,
+            eof: false,
+        }
+    }
+
+    /// Append bytes read from the transport.
+    pub fn push(&mut self, data: &[u8]) {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Mark end of stream; leftovers become [`FrameEvent::Truncated`] or
+    /// [`FrameEvent::Resync`].
+    pub const fn finish(&mut self) {
+        self.eof = true;
+    }
+
+    /// Sequence number the next frame should carry, once one has been seen.
+    #[must_use]
+    pub const fn expected_seq(&self) -> Option<u32> {
+        self.expected
+    }
+
+    /// Bytes waiting for the rest of a frame.
+    #[must_use]
+    pub const fn buffered(&self) -> usize {
+        self.buf.len() - self.start
+    }
+
+    /// Next event, or `None` when more input (or [`finish`](Self::finish)) is needed.
+    pub fn next_event(&mut self) -> Option<FrameEvent> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(event);
+            }
+            if !self.parse_one() {
+                return self.events.pop_front();
+            }
+        }
+    }
+
+    /// Try to consume one frame (or skip garbage). Returns `false` when
+    /// nothing further can be done with the buffered bytes.
+    fn parse_one(&mut self) -> bool {
+        let buf = &self.buf[self.start..];
+        if !buf.starts_with(&MAGIC) {
+            return self.skip_to_magic(0);
+        }
+        let Some(header) = buf.get(..FRAME_HEADER_LEN) else {
+            return self.incomplete();
+        };
+        let flags = header[2];
+        let seq = u32::from_be_bytes([header[3], header[4], header[5], header[6]]);
+        let len = usize::from(u16::from_be_bytes([header[7], header[8]]));
+        let crc = u32::from_be_bytes([header[9], header[10], header[11], header[12]]);
+        if len > MAX_PAYLOAD || flags & !(FLAG_MORE | FLAG_CONT) != 0 {
+            return self.skip_to_magic(1);
+        }
+        let end = FRAME_HEADER_LEN + len;
+        let Some(payload) = buf.get(FRAME_HEADER_LEN..end) else {
+            return self.incomplete();
+        };
+        if frame_crc(&header[2..9], payload) != crc {
+            return self.skip_to_magic(1);
+        }
+
+        let payload = payload.to_vec();
+        self.start += end;
+        if self.skipped > 0 {
+            self.events.push_back(FrameEvent::Resync {
+                skipped: self.skipped,
+            });
+            self.skipped = 0;
+        }
+        self.accept(seq, flags, &payload);
+        true
+    }
+
+    /// Skip to the next magic at or after `from`. A rejected header passes
+    /// `from = 1` so its own magic is skipped too.
+    fn skip_to_magic(&mut self, from: usize) -> bool {
+        let buf = &self.buf[self.start..];
+        if buf.is_empty() {
+            return self.incomplete();
+        }
+        let found = buf[from..]
+            .windows(2)
+            .position(|w| w == MAGIC)
+            .map(|at| at + from);
+        // Keep a trailing first-magic byte in case its partner is still in flight.
+        let keep_tail = usize::from(!self.eof && buf.last() == Some(&MAGIC[0]));
+        let skip = found.unwrap_or(buf.len() - keep_tail);
+        if skip == 0 {
+            return self.incomplete();
+        }
+        self.start += skip;
+        self.skipped += skip;
+        true
+    }
+
+    /// Not enough bytes for a whole frame.
+    fn incomplete(&mut self) -> bool {
+        if !self.eof {
+            return false;
+        }
+        let mut lost = std::mem::take(&mut self.buf).len() - self.start;
+        self.start = 0;
+        if let Some(partial) = self.partial.take() {
+            lost += partial.bytes;
+        }
+        if lost > 0 {
+            self.events.push_back(FrameEvent::Truncated { bytes: lost });
+        }
+        if self.skipped > 0 {
+            self.events.push_back(FrameEvent::Resync {
+                skipped: self.skipped,
+            });
+            self.skipped = 0;
+        }
+        false
+    }
+
+    fn discard_partial(&mut self) {
+        if let Some(partial) = self.partial.take() {
+            self.events.push_back(FrameEvent::Discarded {
+                first_seq: partial.first_seq,
+                bytes: partial.bytes,
+            });
+        }
+    }
+
+    fn accept(&mut self, seq: u32, flags: u8, payload: &[u8]) {
+        if let Some(expected) = self.expected {
+            let delta = i32::from_be_bytes(seq.wrapping_sub(expected).to_be_bytes());
+            if delta < 0 {
+                self.events.push_back(FrameEvent::Replay { seq });
+                return;
+            }
+            if delta > 0 {
+                self.events.push_back(FrameEvent::Gap {
+                    expected,
+                    received: seq,
+                });
+                self.discard_partial();
+            }
+        }
+        self.expected = Some(seq.wrapping_add(1));
+
+        let continues = flags & FLAG_CONT != 0;
+        match (&self.partial, continues) {
+            (None, true) => {
+                self.events.push_back(FrameEvent::Discarded {
+                    first_seq: seq,
+                    bytes: payload.len(),
+                });
+                return;
+            }
+            (Some(_), false) => self.discard_partial(),
+            _ => {}
+        }
+
+        let max_message = self.max_message;
+        let partial = self.partial.get_or_insert_with(|| Partial {
+            first_seq: seq,
+            data: Vec::new(),
+            bytes: 0,
+            overflow: false,
+        });
+        partial.bytes += payload.len();
+        if partial.bytes > max_message {
+            partial.overflow = true;
+            partial.data = Vec::new();
+        } else {
+            partial.data.extend_from_slice(payload);
+        }
+
+        if flags & FLAG_MORE != 0 {
+            return;
+        }
+        if let Some(done) = self.partial.take() {
+            self.events.push_back(if done.overflow {
+                FrameEvent::Discarded {
+                    first_seq: done.first_seq,
+                    bytes: done.bytes,
+                }
+            } else {
+                FrameEvent::Message {
+                    seq: done.first_seq,
+                    payload: done.data,
+                }
+            });
+        }
+    }
+}
+
+/// Writes messages as sequenced frames, fragmenting above [`MAX_PAYLOAD`].
+#[derive(Debug)]
+pub struct FrameWriter<W: Write> {
+    inner: W,
+    next_seq: u32,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub const fn new(inner: W) -> Self {
+        Self::with_start_seq(inner, 0)
+    }
+
+    /// Start numbering at `seq`, e.g. when resuming a session.
+    pub const fn with_start_seq(inner: W, seq: u32) -> Self {
+        Self {
+            inner,
+            next_seq: seq,
+        }
+    }
+
+    /// Write one message; returns how many frames it took.
+    pub fn write_message(&mut self, message: &[u8]) -> io::Result<usize> {
+        let (frames, next) = encode_message(self.next_seq, message);
+        for frame in &frames {
+            self.inner.write_all(frame)?;
+        }
+        self.next_seq = next;
+        Ok(frames.len())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Sequence number the next frame will carry.
+    pub const fn next_seq(&self) -> u32 {
+        self.next_seq
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Reads sequenced frames from any [`Read`], reporting stream anomalies.
+#[derive(Debug)]
+pub struct FrameReader<R: Read> {
+    inner: R,
+    decoder: FrameDecoder,
+    done: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self::with_decoder(inner, FrameDecoder::new())
+    }
+
+    /// Use a preconfigured decoder, e.g. one with a smaller message cap.
+    pub const fn with_decoder(inner: R, decoder: FrameDecoder) -> Self {
+        Self {
+            inner,
+            decoder,
+            done: false,
+        }
+    }
+
+    /// Next event from the stream; `Ok(None)` once the input is exhausted.
+    pub fn read_event(&mut self) -> io::Result<Option<FrameEvent>> {
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            if let Some(event) = self.decoder.next_event() {
+                return Ok(Some(event));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            match self.inner.read(&mut chunk) {
+                Ok(0) => {
+                    self.done = true;
+                    self.decoder.finish();
+                }
+                Ok(n) => self.decoder.push(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Next complete message, silently skipping anomaly events.
+    pub fn read_message(&mut self) -> io::Result<Option<Vec<u8>>> {
+        while let Some(event) = self.read_event()? {
+            if let FrameEvent::Message { payload, .. } = event {
+                return Ok(Some(payload));
+            }
+        }
+        Ok(None)
+    }
+
+    pub const fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(bytes: &[u8]) -> Vec<FrameEvent> {
+        let mut reader = FrameReader::new(bytes);
+        let mut out = Vec::new();
+        while let Ok(Some(event)) = reader.read_event() {
+            out.push(event);
+        }
+        out
+    }
+
+    fn frames(messages: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut seq = 0;
+        let mut out = Vec::new();
+        for message in messages {
+            let (mut encoded, next) = encode_message(seq, message);
+            out.append(&mut encoded);
+            seq = next;
+        }
+        out
+    }
+
+    fn message(seq: u32, payload: &[u8]) -> FrameEvent {
+        FrameEvent::Message {
+            seq,
+            payload: payload.to_vec(),
+        }
+    }
+
+    #[test]
+    fn round_trip_and_empty_message() {
+        let mut writer = FrameWriter::new(Vec::new());
+        for payload in [b"alpha".as_slice(), b"", b"gamma"] {
+            assert!(writer.write_message(payload).is_ok());
+        }
+        assert_eq!(writer.next_seq(), 3);
+        let wire = writer.into_inner();
+        assert_eq!(
+            events(&wire),
+            vec![message(0, b"alpha"), message(1, b""), message(2, b"gamma")]
+        );
+    }
+
+    #[test]
+    fn fragments_large_messages() {
+        let big: Vec<u8> = (0..=255u8).cycle().take(MAX_PAYLOAD * 2 + 10).collect();
+        let mut writer = FrameWriter::new(Vec::new());
+        assert_eq!(writer.write_message(&big).ok(), Some(3));
+        assert_eq!(writer.write_message(b"tail").ok(), Some(1));
+        let wire = writer.into_inner();
+        assert_eq!(events(&wire), vec![message(0, &big), message(3, b"tail")]);
+    }
+
+    #[test]
+    fn detects_gap_and_replay() {
+        let f = frames(&[b"a", b"b", b"c", b"d"]);
+        let wire = [f[0].clone(), f[2].clone(), f[1].clone(), f[3].clone()].concat();
+        assert_eq!(
+            events(&wire),
+            vec![
+                message(0, b"a"),
+                FrameEvent::Gap {
+                    expected: 1,
+                    received: 2
+                },
+                message(2, b"c"),
+                FrameEvent::Replay { seq: 1 },
+                message(3, b"d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn gap_inside_fragmented_message_discards_it() {
+        let big = vec![7u8; MAX_PAYLOAD * 2];
+        let f = frames(&[&big, b"next"]);
+        let wire = [f[0].clone(), f[2].clone()].concat();
+        assert_eq!(
+            events(&wire),
+            vec![
+                FrameEvent::Gap {
+                    expected: 1,
+                    received: 2
+                },
+                FrameEvent::Discarded {
+                    first_seq: 0,
+                    bytes: MAX_PAYLOAD
+                },
+                message(2, b"next"),
+            ]
+        );
+    }
+
+    #[test]
+    fn resyncs_after_corruption() {
+        let f = frames(&[b"one", b"two", b"three"]);
+        let mut corrupt = f[1].clone();
+        corrupt[FRAME_HEADER_LEN] ^= 0xFF;
+        let wire = [f[0].clone(), b"noise".to_vec(), corrupt, f[2].clone()].concat();
+        let got = events(&wire);
+        assert_eq!(got[0], message(0, b"one"));
+        assert_eq!(
+            got[1],
+            FrameEvent::Resync {
+                skipped: 5 + FRAME_HEADER_LEN + 3
+            }
+        );
+        assert_eq!(
+            got[2],
+            FrameEvent::Gap {
+                expected: 1,
+                received: 2
+            }
+        );
+        assert_eq!(got[3], message(2, b"three"));
+    }
+
+    #[test]
+    fn skips_a_run_of_bad_headers_in_one_resync() {
+        // Every byte pair looks like magic, but no header ever checks out.
+        let noise = MAGIC.repeat(50_000);
+        let f = frames(&[b"after"]);
+        let wire = [noise.clone(), f[0].clone()].concat();
+        assert_eq!(
+            events(&wire),
+            vec![
+                FrameEvent::Resync {
+                    skipped: noise.len()
+                },
+                message(0, b"after"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_truncation() {
+        let f = frames(&[b"whole", b"cut short"]);
+        let cut = f[1].len() - 4;
+        let wire = [f[0].clone(), f[1][..cut].to_vec()].concat();
+        assert_eq!(
+            events(&wire),
+            vec![message(0, b"whole"), FrameEvent::Truncated { bytes: cut }]
+        );
+
+        let big = vec![1u8; MAX_PAYLOAD + 1];
+        let f = frames(&[&big]);
+        assert_eq!(
+            events(&f[0]),
+            vec![FrameEvent::Truncated { bytes: MAX_PAYLOAD }]
+        );
+    }
+
+    #[test]
+    fn decoder_handles_byte_at_a_time_input_and_size_cap() {
+        let big = vec![3u8; MAX_PAYLOAD + 100];
+        let wire = frames(&[b"x", &big, b"y"]).concat();
+        let mut decoder = FrameDecoder::with_max_message(MAX_PAYLOAD);
+        let mut got = Vec::new();
+        for byte in wire {
+            decoder.push(&[byte]);
+            while let Some(event) = decoder.next_event() {
+                got.push(event);
+            }
+        }
+        decoder.finish();
+        assert_eq!(decoder.next_event(), None);
+        assert_eq!(
+            got,
+            vec![
+                message(0, b"x"),
+                FrameEvent::Discarded {
+                    first_seq: 1,
+                    bytes: MAX_PAYLOAD + 100
+                },
+                message(3, b"y"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sequence_wraps() {
+        let mut writer = FrameWriter::with_start_seq(Vec::new(), u32::MAX);
+        assert!(writer.write_message(b"last").is_ok());
+        assert!(writer.write_message(b"first").is_ok());
+        let wire = writer.into_inner();
+        assert_eq!(
+            events(&wire),
+            vec![message(u32::MAX, b"last"), message(0, b"first")]
+        );
+    }
+}
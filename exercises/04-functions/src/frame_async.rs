@@ -0,0 +1,164 @@
+//! Tokio versions of [`FrameWriter`](crate::frame::FrameWriter) and
+//! [`FrameReader`](crate::frame::FrameReader).
+//!
+//! The wire format and every anomaly check come from the shared
+//! [`FrameDecoder`]; only the I/O differs. Enable with `--features async`.
+
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::frame::{FrameDecoder, FrameEvent, encode_message};
+
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Async counterpart of [`FrameWriter`](crate::frame::FrameWriter).
+#[derive(Debug)]
+pub struct AsyncFrameWriter<W: AsyncWrite + Unpin> {
+    inner: W,
+    next_seq: u32,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncFrameWriter<W> {
+    pub const fn new(inner: W) -> Self {
+        Self::with_start_seq(inner, 0)
+    }
+
+    pub const fn with_start_seq(inner: W, seq: u32) -> Self {
+        Self {
+            inner,
+            next_seq: seq,
+        }
+    }
+
+    /// Write one message; returns how many frames it took.
+    pub async fn write_message(&mut self, message: &[u8]) -> io::Result<usize> {
+        let (frames, next) = encode_message(self.next_seq, message);
+        for frame in &frames {
+            self.inner.write_all(frame).await?;
+        }
+        self.next_seq = next;
+        Ok(frames.len())
+    }
+
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().await
+    }
+
+    /// Flush and close the write half so the peer sees end of stream.
+    pub async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+
+    pub const fn next_seq(&self) -> u32 {
+        self.next_seq
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Async counterpart of [`FrameReader`](crate::frame::FrameReader).
+#[derive(Debug)]
+pub struct AsyncFrameReader<R: AsyncRead + Unpin> {
+    inner: R,
+    decoder: FrameDecoder,
+    /// Read buffer, sized on first use and reused across calls.
+    chunk: Vec<u8>,
+    done: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncFrameReader<R> {
+    pub const fn new(inner: R) -> Self {
+        Self::with_decoder(inner, FrameDecoder::new())
+    }
+
+    pub const fn with_decoder(inner: R, decoder: FrameDecoder) -> Self {
+        Self {
+            inner,
+            decoder,
+            chunk: Vec::new(),
+            done: false,
+        }
+    }
+
+    /// Next event from the stream; `Ok(None)` once the input is exhausted.
+    pub async fn read_event(&mut self) -> io::Result<Option<FrameEvent>> {
+        if self.chunk.is_empty() {
+            self.chunk.resize(READ_CHUNK, 0);
+        }
+        loop {
+            if let Some(event) = self.decoder.next_event() {
+                return Ok(Some(event));
+            }
+            if self.done {
+                return Ok(None);
+            }
+            let n = self.inner.read(&mut self.chunk).await?;
+            if n == 0 {
+                self.done = true;
+                self.decoder.finish();
+            } else {
+                self.decoder.push(&self.chunk[..n]);
+            }
+        }
+    }
+
+    /// Next complete message, silently skipping anomaly events.
+    pub async fn read_message(&mut self) -> io::Result<Option<Vec<u8>>> {
+        while let Some(event) = self.read_event().await? {
+            if let FrameEvent::Message { payload, .. } = event {
+                return Ok(Some(payload));
+            }
+        }
+        Ok(None)
+    }
+
+    pub const fn decoder(&self) -> &FrameDecoder {
+        &self.decoder
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::MAX_PAYLOAD;
+
+    #[tokio::test]
+    async fn duplex_round_trip_with_fragments() {
+        let (client, server) = io::duplex(512);
+        let big: Vec<u8> = (0..=255u8).cycle().take(MAX_PAYLOAD * 3).collect();
+        let expected = big.clone();
+
+        let sender = tokio::spawn(async move {
+            let mut writer = AsyncFrameWriter::new(client);
+            writer.write_message(b"hello").await?;
+            writer.write_message(&big).await?;
+            writer.shutdown().await
+        });
+
+        let mut reader = AsyncFrameReader::new(server);
+        let first = reader.read_message().await.ok().flatten();
+        let second = reader.read_message().await.ok().flatten();
+        let end = reader.read_event().await.ok().flatten();
+
+        assert!(matches!(sender.await, Ok(Ok(()))));
+        assert_eq!(first.as_deref(), Some(b"hello".as_slice()));
+        assert_eq!(second, Some(expected));
+        assert_eq!(end, None);
+    }
+
+    #[tokio::test]
+    async fn reports_truncation_at_eof() {
+        let (frames, _) = encode_message(0, b"never finished");
+        let cut = &frames[0][..frames[0].len() - 3];
+        let mut reader = AsyncFrameReader::new(cut);
+        assert_eq!(
+            reader.read_event().await.ok().flatten(),
+            Some(FrameEvent::Truncated { bytes: cut.len() })
+        );
+    }
+}
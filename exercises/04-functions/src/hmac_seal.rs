@@ -0,0 +1,390 @@
+//! Authenticated sealing — HMAC-SHA-256 over the length-prefixed frame.
+//!
+//! [`seal_packet`](crate::seal_packet) and the [`integrity`](crate::integrity)
+//! algorithms only catch accidents: anyone who edits a packet can recompute
+//! the checksum. A keyed tag cannot be recomputed without the key.
+//!
+//! Frame layout:
+//!
+//! ```text
+//! [len_hi, len_lo, key_id, tag_len, tag (tag_len bytes), payload...]
+//! ```
+//!
+//! The tag covers the length, key id, tag length, and payload. The key id
+//! lets receivers hold several keys while senders rotate to a new one.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::checksum::MAX_PAYLOAD;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Bytes before the tag: length (2), key id (1), tag length (1).
+pub const HEADER_LEN: usize = 4;
+
+/// How many leading bytes of the 32-byte HMAC-SHA-256 output to send.
+///
+/// RFC 2104 §5 advises keeping at least half the hash output and never fewer
+/// than 80 bits; 96 bits is the shortest option offered here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TagLength {
+    /// 12 bytes.
+    Bits96,
+    /// 16 bytes.
+    Bits128,
+    /// 32 bytes (no truncation).
+    Bits256,
+}
+
+impl TagLength {
+    /// Tag size in bytes.
+    #[must_use]
+    pub const fn bytes(self) -> usize {
+        match self {
+            Self::Bits96 => 12,
+            Self::Bits128 => 16,
+            Self::Bits256 => 32,
+        }
+    }
+
+    /// Parse the on-wire tag length byte.
+    #[must_use]
+    pub const fn from_bytes(len: u8) -> Option<Self> {
+        match len {
+            12 => Some(Self::Bits96),
+            16 => Some(Self::Bits128),
+            32 => Some(Self::Bits256),
+            _ => None,
+        }
+    }
+}
+
+/// Why a frame could not be sealed or opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealError {
+    /// Payload is larger than [`MAX_PAYLOAD`].
+    PayloadTooLarge { len: usize },
+    /// Buffer ends before the header, tag, or declared payload does.
+    ShortBuffer { needed: usize, got: usize },
+    /// Buffer has bytes past the declared payload.
+    TrailingBytes { extra: usize },
+    /// The key id is not in the keyring.
+    UnknownKey(u8),
+    /// Tag length byte is not one of the [`TagLength`] sizes.
+    InvalidTagLength(u8),
+    /// Tag is shorter than the receiver accepts (downgrade attempt).
+    TagTooShort { got: usize, min: usize },
+    /// Tag does not match; the frame was altered or sealed with another key.
+    BadTag,
+}
+
+impl Display for SealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PayloadTooLarge { len } => {
+                write!(f, "payload of {len} bytes exceeds {MAX_PAYLOAD}")
+            }
+            Self::ShortBuffer { needed, got } => {
+                write!(f, "short buffer: need {needed} bytes, got {got}")
+            }
+            Self::TrailingBytes { extra } => write!(f, "{extra} trailing bytes after payload"),
+            Self::UnknownKey(id) => write!(f, "unknown key id {id}"),
+            Self::InvalidTagLength(len) => write!(f, "invalid tag length {len}"),
+            Self::TagTooShort { got, min } => {
+                write!(f, "tag of {got} bytes is shorter than the required {min}")
+            }
+            Self::BadTag => write!(f, "authentication tag mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for SealError {}
+
+/// A frame that passed authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opened<'a> {
+    pub key_id: u8,
+    pub tag_len: TagLength,
+    pub payload: &'a [u8],
+}
+
+/// HMAC keys by id, plus the id and tag length used for new seals.
+///
+/// Rotation: [`add_key`](Self::add_key) the new key everywhere, then
+/// [`set_active`](Self::set_active) on senders, and finally
+/// [`remove_key`](Self::remove_key) the old one once in-flight frames drain.
+#[derive(Clone)]
+pub struct Keyring {
+    keys: BTreeMap<u8, Vec<u8>>,
+    active: u8,
+    tag_len: TagLength,
+    min_tag_len: TagLength,
+}
+
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keyring")
+            .field("key_ids", &self.keys.keys().collect::<Vec<_>>())
+            .field("active", &self.active)
+            .field("tag_len", &self.tag_len)
+            .field("min_tag_len", &self.min_tag_len)
+            .finish()
+    }
+}
+
+impl Keyring {
+    /// A keyring with one active key, sealing with `tag_len` and accepting
+    /// tags no shorter than `tag_len`.
+    #[must_use]
+    pub fn new(key_id: u8, key: &[u8], tag_len: TagLength) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(key_id, key.to_vec());
+        Self {
+            keys,
+            active: key_id,
+            tag_len,
+            min_tag_len: tag_len,
+        }
+    }
+
+    /// Add (or replace) a key that can verify frames but is not yet used to seal.
+    pub fn add_key(&mut self, key_id: u8, key: &[u8]) {
+        self.keys.insert(key_id, key.to_vec());
+    }
+
+    /// Seal new frames with `key_id`.
+    pub fn set_active(&mut self, key_id: u8) -> Result<(), SealError> {
+        if !self.keys.contains_key(&key_id) {
+            return Err(SealError::UnknownKey(key_id));
+        }
+        self.active = key_id;
+        Ok(())
+    }
+
+    /// Drop a retired key. The active key cannot be removed.
+    pub fn remove_key(&mut self, key_id: u8) -> bool {
+        key_id != self.active && self.keys.remove(&key_id).is_some()
+    }
+
+    /// Change the tag length used for new seals.
+    pub const fn set_tag_len(&mut self, tag_len: TagLength) {
+        self.tag_len = tag_len;
+    }
+
+    /// Shortest tag [`open`](Self::open) will accept.
+    pub const fn set_min_tag_len(&mut self, min: TagLength) {
+        self.min_tag_len = min;
+    }
+
+    #[must_use]
+    pub const fn active(&self) -> u8 {
+        self.active
+    }
+
+    #[must_use]
+    pub fn key_ids(&self) -> Vec<u8> {
+        self.keys.keys().copied().collect()
+    }
+
+    /// Seal `payload` with the active key.
+    pub fn seal(&self, payload: &[u8]) -> Result<Vec<u8>, SealError> {
+        if payload.len() > MAX_PAYLOAD {
+            return Err(SealError::PayloadTooLarge { len: payload.len() });
+        }
+        let len = u16::try_from(payload.len())
+            .map_err(|_| SealError::PayloadTooLarge { len: payload.len() })?;
+        let key = self
+            .keys
+            .get(&self.active)
+            .ok_or(SealError::UnknownKey(self.active))?;
+        let tag_bytes = self.tag_len.bytes();
+
+        let mut header = [0u8; HEADER_LEN];
+        header[..2].copy_from_slice(&len.to_be_bytes());
+        header[2] = self.active;
+        header[3] = u8::try_from(tag_bytes).unwrap_or(u8::MAX);
+
+        let tag = keyed(key, &header, payload)
+            .ok_or(SealError::UnknownKey(self.active))?
+            .finalize()
+            .into_bytes();
+
+        let mut frame = Vec::with_capacity(HEADER_LEN + tag_bytes + payload.len());
+        frame.extend_from_slice(&header);
+        frame.extend_from_slice(&tag[..tag_bytes]);
+        frame.extend_from_slice(payload);
+        Ok(frame)
+    }
+
+    /// Authenticate a frame and return its payload.
+    ///
+    /// The tag comparison runs in constant time. Structural checks (length,
+    /// key id, tag size) happen first and reveal nothing about the key.
+    pub fn open<'a>(&self, frame: &'a [u8]) -> Result<Opened<'a>, SealError> {
+        let short = |needed| SealError::ShortBuffer {
+            needed,
+            got: frame.len(),
+        };
+        let header = frame.get(..HEADER_LEN).ok_or_else(|| short(HEADER_LEN))?;
+        let tag_len =
+            TagLength::from_bytes(header[3]).ok_or(SealError::InvalidTagLength(header[3]))?;
+        if tag_len < self.min_tag_len {
+            return Err(SealError::TagTooShort {
+                got: tag_len.bytes(),
+                min: self.min_tag_len.bytes(),
+            });
+        }
+        let key_id = header[2];
+        let key = self
+            .keys
+            .get(&key_id)
+            .ok_or(SealError::UnknownKey(key_id))?;
+
+        let body = HEADER_LEN + tag_len.bytes();
+        let end = body + usize::from(u16::from_be_bytes([header[0], header[1]]));
+        let tag = frame.get(HEADER_LEN..body).ok_or_else(|| short(body))?;
+        let payload = frame.get(body..end).ok_or_else(|| short(end))?;
+        if frame.len() > end {
+            return Err(SealError::TrailingBytes {
+                extra: frame.len() - end,
+            });
+        }
+
+        keyed(key, header, payload)
+            .ok_or(SealError::UnknownKey(key_id))?
+            .verify_truncated_left(tag)
+            .map_err(|_| SealError::BadTag)?;
+        Ok(Opened {
+            key_id,
+            tag_len,
+            payload,
+        })
+    }
+}
+
+/// HMAC state over header then payload.
+///
+/// HMAC accepts keys of any length, so `None` never happens in practice; it
+/// is reported as an unusable key rather than unwrapped.
+fn keyed(key: &[u8], header: &[u8], payload: &[u8]) -> Option<HmacSha256> {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(key).ok()?;
+    mac.update(header);
+    mac.update(payload);
+    Some(mac)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"super-secret-sensor-key";
+
+    #[test]
+    fn rfc_4231_case_2() {
+        // Key "Jefe", data "what do ya want for nothing?".
+        let mac = keyed(b"Jefe", b"what do ya want ", b"for nothing?").expect("keyed MAC");
+        let expected = [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95,
+            0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9,
+            0x64, 0xec, 0x38, 0x43,
+        ];
+        assert_eq!(mac.finalize().into_bytes().as_slice(), expected.as_slice());
+    }
+
+    #[test]
+    fn seal_and_open_each_tag_length() {
+        for tag_len in [TagLength::Bits96, TagLength::Bits128, TagLength::Bits256] {
+            let ring = Keyring::new(1, KEY, tag_len);
+            let frame = ring.seal(b"ALERT");
+            assert!(frame.is_ok());
+            let Ok(frame) = frame else { continue };
+            assert_eq!(frame.len(), HEADER_LEN + tag_len.bytes() + 5);
+            assert_eq!(
+                ring.open(&frame),
+                Ok(Opened {
+                    key_id: 1,
+                    tag_len,
+                    payload: b"ALERT",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn any_bit_flip_is_a_bad_tag() {
+        let ring = Keyring::new(1, KEY, TagLength::Bits128);
+        let frame = ring.seal(b"payload").expect("seal");
+        for index in HEADER_LEN..frame.len() {
+            let mut bad = frame.clone();
+            bad[index] ^= 0x80;
+            assert_eq!(ring.open(&bad), Err(SealError::BadTag), "byte {index}");
+        }
+    }
+
+    #[test]
+    fn structural_errors() {
+        let ring = Keyring::new(1, KEY, TagLength::Bits128);
+        assert_eq!(
+            ring.open(&[0, 1]),
+            Err(SealError::ShortBuffer { needed: 4, got: 2 })
+        );
+        assert_eq!(ring.open(&[0, 0, 9, 16]), Err(SealError::UnknownKey(9)));
+        assert_eq!(
+            ring.open(&[0, 0, 1, 20]),
+            Err(SealError::InvalidTagLength(20))
+        );
+        assert_eq!(
+            ring.seal(&[0; MAX_PAYLOAD + 1]),
+            Err(SealError::PayloadTooLarge {
+                len: MAX_PAYLOAD + 1
+            })
+        );
+
+        let mut frame = ring.seal(b"abc").expect("seal");
+        frame.push(0);
+        assert_eq!(
+            ring.open(&frame),
+            Err(SealError::TrailingBytes { extra: 1 })
+        );
+        frame.truncate(frame.len() - 2);
+        assert!(matches!(
+            ring.open(&frame),
+            Err(SealError::ShortBuffer { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_truncation_downgrade() {
+        let sender = Keyring::new(1, KEY, TagLength::Bits96);
+        let receiver = Keyring::new(1, KEY, TagLength::Bits128);
+        let frame = sender.seal(b"x").expect("seal");
+        assert_eq!(
+            receiver.open(&frame),
+            Err(SealError::TagTooShort { got: 12, min: 16 })
+        );
+    }
+
+    #[test]
+    fn key_rotation() {
+        let mut ring = Keyring::new(1, b"old-key", TagLength::Bits128);
+        let old_frame = ring.seal(b"before").expect("seal");
+
+        ring.add_key(2, b"new-key");
+        assert_eq!(ring.set_active(3), Err(SealError::UnknownKey(3)));
+        assert_eq!(ring.set_active(2), Ok(()));
+        let new_frame = ring.seal(b"after").expect("seal");
+        assert_eq!(new_frame[2], 2);
+        assert!(ring.open(&old_frame).is_ok());
+        assert!(ring.open(&new_frame).is_ok());
+
+        assert!(!ring.remove_key(2));
+        assert!(ring.remove_key(1));
+        assert_eq!(ring.open(&old_frame), Err(SealError::UnknownKey(1)));
+        assert_eq!(ring.key_ids(), vec![2]);
+    }
+}
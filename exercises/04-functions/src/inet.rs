@@ -0,0 +1,317 @@
+//! Internet checksum over byte buffers — RFC 1071 sums, RFC 1624 updates.
+//!
+//! [`checksum`] works on any `&[u8]` (odd lengths, odd offsets, no alignment
+//! requirements) and sums eight bytes per step instead of one word at a time.
+//! [`InternetChecksum`] accepts the same data in arbitrary chunks, and the
+//! `update_*` functions patch an existing checksum after a field changes
+//! without touching the rest of the packet.
+
+/// Fold a wide one's-complement accumulator down to 16 bits (no inversion).
+#[must_use]
+const fn fold(mut sum: u64) -> u16 {
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        sum as u16
+    }
+}
+
+/// One's-complement addition with end-around carry.
+///
+/// `2^64 ≡ 1 (mod 0xFFFF)`, so feeding the carry back into bit 0 keeps the
+/// 64-bit accumulator equivalent to a 16-bit one's-complement sum.
+#[must_use]
+const fn add(sum: u64, value: u64) -> u64 {
+    let (total, carry) = sum.overflowing_add(value);
+    total + carry as u64
+}
+
+/// Word-at-a-time sum: eight bytes per step, then pairs, then the odd byte
+/// padded with a zero low byte.
+fn sum_bytes(data: &[u8]) -> u64 {
+    let mut sum = 0u64;
+    let mut blocks = data.chunks_exact(8);
+    for block in blocks.by_ref() {
+        let word = u64::from_be_bytes([
+            block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7],
+        ]);
+        sum = add(sum, (word >> 32) + (word & 0xFFFF_FFFF));
+    }
+    let mut pairs = blocks.remainder().chunks_exact(2);
+    for pair in pairs.by_ref() {
+        sum = add(sum, u64::from(u16::from_be_bytes([pair[0], pair[1]])));
+    }
+    if let Some(&last) = pairs.remainder().first() {
+        sum = add(sum, u64::from(last) << 8);
+    }
+    sum
+}
+
+/// Internet checksum of `data` using the word-at-a-time fast path.
+///
+/// ```
+/// use functions_exercises::inet::checksum;
+///
+/// // RFC 1071 §3 example: the sum is 0xDDF2, so the checksum is 0x220D.
+/// assert_eq!(checksum(&[0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7]), 0x220D);
+/// ```
+#[must_use]
+pub fn checksum(data: &[u8]) -> u16 {
+    !fold(sum_bytes(data))
+}
+
+/// Reference implementation: one big-endian word per iteration.
+///
+/// Kept for the benchmark and as an oracle in tests; prefer [`checksum`].
+#[must_use]
+pub fn checksum_scalar(data: &[u8]) -> u16 {
+    let mut sum: u32 = 0;
+    let mut pairs = data.chunks_exact(2);
+    for pair in pairs.by_ref() {
+        sum = sum.wrapping_add(u32::from(u16::from_be_bytes([pair[0], pair[1]])));
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    if let Some(&last) = pairs.remainder().first() {
+        sum = sum.wrapping_add(u32::from(last) << 8);
+    }
+    !fold(u64::from(sum))
+}
+
+/// Whether `data`, including its embedded checksum field, sums to zero.
+#[must_use]
+pub fn verify(data: &[u8]) -> bool {
+    checksum(data) == 0
+}
+
+/// Streaming Internet checksum: feed bytes in any chunking, read the result.
+///
+/// Splitting a buffer at an odd offset is handled by carrying the dangling
+/// byte into the next [`update`](Self::update), so the result always equals
+/// [`checksum`] over the concatenated input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternetChecksum {
+    sum: u64,
+    pending: Option<u8>,
+    len: usize,
+}
+
+impl InternetChecksum {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sum: 0,
+            pending: None,
+            len: 0,
+        }
+    }
+
+    /// Add the next chunk of the buffer.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len();
+        if let Some(high) = self.pending.take() {
+            let Some((&low, rest)) = data.split_first() else {
+                self.pending = Some(high);
+                return;
+            };
+            self.sum = add(self.sum, u64::from(u16::from_be_bytes([high, low])));
+            data = rest;
+        }
+        let even = data.len() & !1;
+        self.sum = add(self.sum, sum_bytes(&data[..even]));
+        self.pending = data.get(even).copied();
+    }
+
+    /// Add one big-endian 16-bit word, e.g. a pseudo-header field.
+    ///
+    /// Must be called on a word boundary (an even number of bytes so far).
+    pub const fn add_u16(&mut self, word: u16) {
+        self.sum = add(self.sum, word as u64);
+        self.len += 2;
+    }
+
+    /// Add a 32-bit value as two big-endian words, e.g. an IPv4 address.
+    pub const fn add_u32(&mut self, value: u32) {
+        self.sum = add(self.sum, (value >> 16) as u64 + (value & 0xFFFF) as u64);
+        self.len += 4;
+    }
+
+    /// Bytes consumed so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Folded one's-complement sum, before the final inversion.
+    #[must_use]
+    pub const fn sum(&self) -> u16 {
+        let mut sum = self.sum;
+        if let Some(high) = self.pending {
+            sum = add(sum, (high as u64) << 8);
+        }
+        fold(sum)
+    }
+
+    /// The checksum of everything fed so far.
+    #[must_use]
+    pub const fn finish(&self) -> u16 {
+        !self.sum()
+    }
+}
+
+/// RFC 1624 eqn. 3: `HC' = ~(~HC + ~m + m')` for one changed 16-bit word.
+///
+/// Unlike the older RFC 1141 form this never produces `0xFFFF` where a full
+/// recomputation would give `0x0000`.
+#[must_use]
+pub const fn update_u16(csum: u16, old: u16, new: u16) -> u16 {
+    let sum = add(add(!csum as u64, !old as u64), new as u64);
+    !fold(sum)
+}
+
+/// Incremental update for a changed 32-bit field such as an IPv4 address.
+#[must_use]
+pub const fn update_u32(csum: u16, old: u32, new: u32) -> u16 {
+    #[allow(clippy::cast_possible_truncation)]
+    let csum = update_u16(csum, (old >> 16) as u16, (new >> 16) as u16);
+    #[allow(clippy::cast_possible_truncation)]
+    update_u16(csum, old as u16, new as u16)
+}
+
+/// Incremental update for an arbitrary byte range that started at `offset`
+/// within the checksummed buffer.
+///
+/// An odd `offset` shifts the field into the low byte of its first word, so
+/// both sides are summed with that alignment. Returns `None` when `old` and
+/// `new` differ in length.
+#[must_use]
+pub fn update_bytes(csum: u16, offset: usize, old: &[u8], new: &[u8]) -> Option<u16> {
+    if old.len() != new.len() {
+        return None;
+    }
+    let aligned = |bytes: &[u8]| {
+        let mut acc = InternetChecksum::new();
+        if offset % 2 == 1 {
+            acc.update(&[0]);
+        }
+        acc.update(bytes);
+        acc.sum()
+    };
+    Some(update_u16(csum, aligned(old), aligned(new)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 20-byte IPv4 header from the classic Wikipedia example, checksum 0xB861.
+    const IPV4_HEADER: [u8; 20] = [
+        0x45, 0x00, 0x00, 0x73, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0xB8, 0x61, 0xC0, 0xA8, 0x00,
+        0x01, 0xC0, 0xA8, 0x00, 0xC7,
+    ];
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| u8::try_from((i * 37 + 11) % 251).unwrap_or(0))
+            .collect()
+    }
+
+    #[test]
+    fn matches_rfc_1071_and_real_header() {
+        let data = [0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7];
+        assert_eq!(checksum(&data), 0x220D);
+        assert!(verify(&IPV4_HEADER));
+
+        let mut zeroed = IPV4_HEADER;
+        zeroed[10] = 0;
+        zeroed[11] = 0;
+        assert_eq!(checksum(&zeroed), 0xB861);
+    }
+
+    #[test]
+    fn fast_path_matches_scalar_for_every_length_and_offset() {
+        let data = sample(97);
+        for start in 0..8 {
+            for end in start..=data.len() {
+                let slice = &data[start..end];
+                assert_eq!(checksum(slice), checksum_scalar(slice), "{start}..{end}");
+            }
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot_at_every_split() {
+        let data = sample(41);
+        for split in 0..=data.len() {
+            for second in split..=data.len() {
+                let mut acc = InternetChecksum::new();
+                acc.update(&data[..split]);
+                acc.update(&data[split..second]);
+                acc.update(&[]);
+                acc.update(&data[second..]);
+                assert_eq!(acc.finish(), checksum(&data));
+                assert_eq!(acc.len(), data.len());
+            }
+        }
+    }
+
+    #[test]
+    fn word_helpers_match_bytes() {
+        let mut words = InternetChecksum::new();
+        words.add_u32(0xC0A8_0001);
+        words.add_u16(0x0011);
+        assert_eq!(
+            words.finish(),
+            checksum(&[0xC0, 0xA8, 0x00, 0x01, 0x00, 0x11])
+        );
+    }
+
+    #[test]
+    fn ttl_decrement_matches_recompute() {
+        let mut header = IPV4_HEADER;
+        let csum = u16::from_be_bytes([header[10], header[11]]);
+        let old = u16::from_be_bytes([header[8], header[9]]);
+        header[8] -= 1;
+        let new = u16::from_be_bytes([header[8], header[9]]);
+
+        let updated = update_u16(csum, old, new);
+        header[10..12].copy_from_slice(&updated.to_be_bytes());
+        assert!(verify(&header));
+    }
+
+    #[test]
+    fn address_rewrite_matches_recompute() {
+        let mut header = IPV4_HEADER;
+        let csum = u16::from_be_bytes([header[10], header[11]]);
+        let old = u32::from_be_bytes([header[12], header[13], header[14], header[15]]);
+        let new = 0x0A00_0002;
+        header[12..16].copy_from_slice(&u32::to_be_bytes(new));
+
+        header[10..12].copy_from_slice(&update_u32(csum, old, new).to_be_bytes());
+        assert!(verify(&header));
+    }
+
+    #[test]
+    fn byte_range_update_at_odd_offset() {
+        let mut data = sample(33);
+        let csum = checksum(&data);
+        let old = data[5..10].to_vec();
+        let new = [0xDE, 0xAD, 0xBE, 0xEF, 0x01];
+        data[5..10].copy_from_slice(&new);
+
+        assert_eq!(update_bytes(csum, 5, &old, &new), Some(checksum(&data)));
+        assert_eq!(update_bytes(csum, 5, &old, &new[..2]), None);
+    }
+
+    #[test]
+    fn update_never_yields_negative_zero() {
+        // RFC 1624 §3 example: the result must be 0x0000, not 0xFFFF.
+        assert_eq!(update_u16(0xDD2F, 0x5555, 0x3285), 0x0000);
+    }
+}
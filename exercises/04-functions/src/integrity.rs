@@ -0,0 +1,586 @@
+//! Integrity algorithms behind one streaming interface.
+//!
+//! The Internet checksum only catches simple corruption. Swapped 16-bit
+//! words and many multi-bit errors slip through. This module adds stronger
+//! options with the same `update` / `finalize` shape:
+//!
+//! | Algorithm | Width | Catches |
+//! |-----------|-------|---------|
+//! | Internet (RFC 1071) | 16 | Single-bit errors; misses word reordering |
+//! | Fletcher-16 / -32 | 16 / 32 | Adds position sensitivity |
+//! | Adler-32 (RFC 1950) | 32 | zlib's Fletcher variant; weak on short inputs |
+//! | CRC-32 (IEEE 802.3) | 32 | All bursts ≤ 32 bits |
+//! | CRC-32C (Castagnoli) | 32 | Same, better Hamming distance; iSCSI, SCTP |
+//!
+//! None of these resist deliberate tampering; they detect accidents only.
+
+use std::fmt::{self, Display};
+
+use crate::checksum::MAX_PAYLOAD;
+use crate::inet::InternetChecksum;
+
+/// A streaming checksum: feed bytes with `update`, read the value with `finalize`.
+///
+/// Results are widened to `u32`; [`width`](Self::width) says how many of the
+/// low-order bytes are meaningful on the wire.
+pub trait Checksum {
+    /// Short lowercase identifier, e.g. `"crc32c"`.
+    fn name(&self) -> &'static str;
+
+    /// Checksum size in bytes (2 or 4).
+    fn width(&self) -> usize;
+
+    /// Absorb the next chunk; chunk boundaries never affect the result.
+    fn update(&mut self, data: &[u8]);
+
+    /// Checksum of everything fed since construction or the last `reset`.
+    fn finalize(&self) -> u32;
+
+    /// Return to the initial state.
+    fn reset(&mut self);
+
+    /// Encode [`finalize`](Self::finalize) as `width` big-endian bytes.
+    fn finalize_bytes(&self) -> Vec<u8> {
+        let bytes = self.finalize().to_be_bytes();
+        bytes[bytes.len().saturating_sub(self.width())..].to_vec()
+    }
+}
+
+impl Checksum for InternetChecksum {
+    fn name(&self) -> &'static str {
+        "internet"
+    }
+
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Self::update(self, data);
+    }
+
+    fn finalize(&self) -> u32 {
+        u32::from(self.finish())
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Build a reflected CRC-32 lookup table at compile time.
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_IEEE: [u32; 256] = crc32_table(0xEDB8_8320);
+static CRC32_CASTAGNOLI: [u32; 256] = crc32_table(0x82F6_3B78);
+
+fn crc32_update(table: &[u32; 256], mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        let index = usize::from(crc.to_le_bytes()[0] ^ byte);
+        crc = (crc >> 8) ^ table[index];
+    }
+    crc
+}
+
+/// CRC-32 as used by Ethernet, gzip, and PNG (reflected poly `0x04C11DB7`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    fn name(&self) -> &'static str {
+        "crc32"
+    }
+
+    fn width(&self) -> usize {
+        4
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.state = crc32_update(&CRC32_IEEE, self.state, data);
+    }
+
+    fn finalize(&self) -> u32 {
+        !self.state
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// CRC-32C (Castagnoli, reflected poly `0x1EDC6F41`) as used by iSCSI, SCTP, and ext4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32c {
+    state: u32,
+}
+
+impl Crc32c {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Default for Crc32c {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32c {
+    fn name(&self) -> &'static str {
+        "crc32c"
+    }
+
+    fn width(&self) -> usize {
+        4
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.state = crc32_update(&CRC32_CASTAGNOLI, self.state, data);
+    }
+
+    fn finalize(&self) -> u32 {
+        !self.state
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+const ADLER_MOD: u32 = 65_521;
+/// Largest run of bytes before the `u32` sums could overflow (zlib's `NMAX`).
+const ADLER_NMAX: usize = 5552;
+
+/// Adler-32 from RFC 1950 (zlib).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Adler32 {
+    fn name(&self) -> &'static str {
+        "adler32"
+    }
+
+    fn width(&self) -> usize {
+        4
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for block in data.chunks(ADLER_NMAX) {
+            for &byte in block {
+                self.a += u32::from(byte);
+                self.b += self.a;
+            }
+            self.a %= ADLER_MOD;
+            self.b %= ADLER_MOD;
+        }
+    }
+
+    fn finalize(&self) -> u32 {
+        self.b << 16 | self.a
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Fletcher-16 over bytes, sums modulo 255.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fletcher16 {
+    sum1: u32,
+    sum2: u32,
+}
+
+impl Fletcher16 {
+    /// Bytes per block before the deferred modulo must run.
+    const BLOCK: usize = 5802;
+
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { sum1: 0, sum2: 0 }
+    }
+}
+
+impl Checksum for Fletcher16 {
+    fn name(&self) -> &'static str {
+        "fletcher16"
+    }
+
+    fn width(&self) -> usize {
+        2
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for block in data.chunks(Self::BLOCK) {
+            for &byte in block {
+                self.sum1 += u32::from(byte);
+                self.sum2 += self.sum1;
+            }
+            self.sum1 %= 255;
+            self.sum2 %= 255;
+        }
+    }
+
+    fn finalize(&self) -> u32 {
+        self.sum2 << 8 | self.sum1
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Fletcher-32 over little-endian 16-bit words, sums modulo 65535.
+///
+/// An odd trailing byte is treated as a word with a zero high byte. Streaming
+/// input may split a word; the dangling byte waits for the next `update`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Fletcher32 {
+    sum1: u32,
+    sum2: u32,
+    pending: Option<u8>,
+}
+
+impl Fletcher32 {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            sum1: 0,
+            sum2: 0,
+            pending: None,
+        }
+    }
+
+    const fn add_word(&mut self, word: u16) {
+        self.sum1 = (self.sum1 + word as u32) % 65_535;
+        self.sum2 = (self.sum2 + self.sum1) % 65_535;
+    }
+}
+
+impl Checksum for Fletcher32 {
+    fn name(&self) -> &'static str {
+        "fletcher32"
+    }
+
+    fn width(&self) -> usize {
+        4
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        if let Some(low) = self.pending.take() {
+            let Some((&high, rest)) = data.split_first() else {
+                self.pending = Some(low);
+                return;
+            };
+            self.add_word(u16::from_le_bytes([low, high]));
+            data = rest;
+        }
+        let mut words = data.chunks_exact(2);
+        for pair in words.by_ref() {
+            self.add_word(u16::from_le_bytes([pair[0], pair[1]]));
+        }
+        self.pending = words.remainder().first().copied();
+    }
+
+    fn finalize(&self) -> u32 {
+        let mut done = *self;
+        if let Some(low) = done.pending.take() {
+            done.add_word(u16::from(low));
+        }
+        done.sum2 << 16 | done.sum1
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Selectable integrity algorithm, e.g. for [`seal_packet_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Internet,
+    Fletcher16,
+    Fletcher32,
+    Adler32,
+    Crc32,
+    Crc32c,
+}
+
+impl Algorithm {
+    /// Every algorithm, weakest first.
+    pub const ALL: [Self; 6] = [
+        Self::Internet,
+        Self::Fletcher16,
+        Self::Fletcher32,
+        Self::Adler32,
+        Self::Crc32,
+        Self::Crc32c,
+    ];
+
+    /// Fresh streaming state for this algorithm.
+    #[must_use]
+    pub fn hasher(self) -> Box<dyn Checksum> {
+        match self {
+            Self::Internet => Box::new(InternetChecksum::new()),
+            Self::Fletcher16 => Box::new(Fletcher16::new()),
+            Self::Fletcher32 => Box::new(Fletcher32::new()),
+            Self::Adler32 => Box::new(Adler32::new()),
+            Self::Crc32 => Box::new(Crc32::new()),
+            Self::Crc32c => Box::new(Crc32c::new()),
+        }
+    }
+
+    /// One-shot checksum of `data`.
+    #[must_use]
+    pub fn compute(self, data: &[u8]) -> u32 {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
+
+    /// Checksum size in bytes.
+    #[must_use]
+    pub const fn width(self) -> usize {
+        match self {
+            Self::Internet | Self::Fletcher16 => 2,
+            _ => 4,
+        }
+    }
+
+    /// Wire identifier stored in sealed packets.
+    #[must_use]
+    pub const fn id(self) -> u8 {
+        match self {
+            Self::Internet => 1,
+            Self::Fletcher16 => 2,
+            Self::Fletcher32 => 3,
+            Self::Adler32 => 4,
+            Self::Crc32 => 5,
+            Self::Crc32c => 6,
+        }
+    }
+
+    /// Inverse of [`id`](Self::id).
+    #[must_use]
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::Internet),
+            2 => Some(Self::Fletcher16),
+            3 => Some(Self::Fletcher32),
+            4 => Some(Self::Adler32),
+            5 => Some(Self::Crc32),
+            6 => Some(Self::Crc32c),
+            _ => None,
+        }
+    }
+
+    /// One-line note on what the algorithm does and does not catch.
+    #[must_use]
+    pub const fn strength_hint(self) -> &'static str {
+        match self {
+            Self::Internet => "weak: misses reordered words and compensating errors",
+            Self::Fletcher16 => "weak: position-aware but only 16 bits",
+            Self::Fletcher32 => "moderate: position-aware 32-bit sums",
+            Self::Adler32 => "moderate: fast, but poor on short messages",
+            Self::Crc32 => "strong: all burst errors up to 32 bits",
+            Self::Crc32c => "strong: CRC-32 guarantees with better Hamming distance",
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.hasher().name())
+    }
+}
+
+/// Bytes before the checksum field: length (2) and algorithm id (1).
+const SEAL_PREFIX: usize = 3;
+
+/// Seal a payload as `[len_hi, len_lo, algorithm_id, checksum.., payload..]`.
+///
+/// The checksum covers the length, the id, and the payload (everything but
+/// itself), stored big-endian in [`Algorithm::width`] bytes. Returns `None`
+/// when the payload exceeds [`MAX_PAYLOAD`].
+#[must_use]
+pub fn seal_packet_with(payload: &[u8], algorithm: Algorithm) -> Option<Vec<u8>> {
+    if payload.len() > MAX_PAYLOAD {
+        return None;
+    }
+    let len = u16::try_from(payload.len()).ok()?;
+    let mut prefix = [0u8; SEAL_PREFIX];
+    prefix[..2].copy_from_slice(&len.to_be_bytes());
+    prefix[2] = algorithm.id();
+
+    let mut hasher = algorithm.hasher();
+    hasher.update(&prefix);
+    hasher.update(payload);
+
+    let mut packet = Vec::with_capacity(SEAL_PREFIX + algorithm.width() + payload.len());
+    packet.extend_from_slice(&prefix);
+    packet.extend_from_slice(&hasher.finalize_bytes());
+    packet.extend_from_slice(payload);
+    Some(packet)
+}
+
+/// Check a packet from [`seal_packet_with`], returning its algorithm and payload.
+///
+/// `None` for unknown algorithm ids, length mismatches, or checksum failures.
+#[must_use]
+pub fn open_sealed(packet: &[u8]) -> Option<(Algorithm, &[u8])> {
+    let prefix = packet.get(..SEAL_PREFIX)?;
+    let algorithm = Algorithm::from_id(prefix[2])?;
+    let body = SEAL_PREFIX + algorithm.width();
+    let stored = packet.get(SEAL_PREFIX..body)?;
+    let payload = packet.get(body..)?;
+    if usize::from(u16::from_be_bytes([prefix[0], prefix[1]])) != payload.len() {
+        return None;
+    }
+
+    let mut hasher = algorithm.hasher();
+    hasher.update(prefix);
+    hasher.update(payload);
+    (hasher.finalize_bytes() == stored).then_some((algorithm, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    #[test]
+    fn crc_catalogue_check_values() {
+        assert_eq!(Algorithm::Crc32.compute(CHECK), 0xCBF4_3926);
+        assert_eq!(Algorithm::Crc32c.compute(CHECK), 0xE306_9283);
+        assert_eq!(Algorithm::Crc32.compute(b""), 0);
+        // RFC 3720 B.4: 32 bytes of zeros.
+        assert_eq!(Algorithm::Crc32c.compute(&[0; 32]), 0x8A91_36AA);
+        assert_eq!(Algorithm::Crc32c.compute(&[0xFF; 32]), 0x62A8_AB43);
+    }
+
+    #[test]
+    fn adler_and_fletcher_vectors() {
+        assert_eq!(Algorithm::Adler32.compute(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(Algorithm::Adler32.compute(b""), 1);
+        assert_eq!(Algorithm::Fletcher16.compute(b"abcde"), 0xC8F0);
+        assert_eq!(Algorithm::Fletcher16.compute(b"abcdef"), 0x2057);
+        assert_eq!(Algorithm::Fletcher16.compute(b"abcdefgh"), 0x0627);
+        assert_eq!(Algorithm::Fletcher32.compute(b"abcde"), 0xF04F_C729);
+        assert_eq!(Algorithm::Fletcher32.compute(b"abcdef"), 0x5650_2D2A);
+        assert_eq!(Algorithm::Fletcher32.compute(b"abcdefgh"), 0xEBE1_9591);
+    }
+
+    #[test]
+    fn streaming_matches_one_shot_for_every_algorithm() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(12_000).collect();
+        for algorithm in Algorithm::ALL {
+            let expected = algorithm.compute(&data);
+            for chunk in [1, 3, 7, 64, 5553] {
+                let mut hasher = algorithm.hasher();
+                for piece in data.chunks(chunk) {
+                    hasher.update(piece);
+                }
+                assert_eq!(hasher.finalize(), expected, "{algorithm} chunk={chunk}");
+                hasher.reset();
+                hasher.update(CHECK);
+                assert_eq!(hasher.finalize(), algorithm.compute(CHECK));
+            }
+        }
+    }
+
+    #[test]
+    fn ids_round_trip_and_widths_match() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(Algorithm::from_id(algorithm.id()), Some(algorithm));
+            assert_eq!(algorithm.hasher().width(), algorithm.width());
+            assert_eq!(algorithm.hasher().finalize_bytes().len(), algorithm.width());
+        }
+        assert_eq!(Algorithm::from_id(0), None);
+    }
+
+    #[test]
+    fn crc_catches_word_swap_that_internet_checksum_misses() {
+        let original = [0x12, 0x34, 0x56, 0x78];
+        let swapped = [0x56, 0x78, 0x12, 0x34];
+        assert_eq!(
+            Algorithm::Internet.compute(&original),
+            Algorithm::Internet.compute(&swapped)
+        );
+        assert_ne!(
+            Algorithm::Crc32.compute(&original),
+            Algorithm::Crc32.compute(&swapped)
+        );
+    }
+
+    #[test]
+    fn seal_with_each_algorithm_round_trips_and_detects_flips() {
+        for algorithm in Algorithm::ALL {
+            let packet = seal_packet_with(b"ALERT: beacon", algorithm);
+            assert!(packet.is_some());
+            let Some(mut packet) = packet else { continue };
+            assert_eq!(
+                open_sealed(&packet),
+                Some((algorithm, b"ALERT: beacon".as_slice()))
+            );
+
+            let last = packet.len() - 1;
+            packet[last] ^= 0x01;
+            assert_eq!(open_sealed(&packet), None, "{algorithm}");
+        }
+        assert!(seal_packet_with(&[0; MAX_PAYLOAD + 1], Algorithm::Crc32).is_none());
+        assert_eq!(open_sealed(&[0, 0]), None);
+    }
+}
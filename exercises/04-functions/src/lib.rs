@@ -5,12 +5,27 @@
 
 pub mod checksum;
 mod checksum_exercise;
+pub mod frame;
+#[cfg(feature = "async")]
+pub mod frame_async;
+pub mod hmac_seal;
+pub mod inet;
+pub mod integrity;
 mod seal_exercise;
 
 pub use checksum::{
     bytes_to_words, checksum_hex, fold_checksum, internet_checksum, nibble_mix, payload_len,
     seal_packet, verify_sealed,
 };
+pub use frame::{FrameDecoder, FrameEvent, FrameReader, FrameWriter};
+#[cfg(feature = "async")]
+pub use frame_async::{AsyncFrameReader, AsyncFrameWriter};
+pub use hmac_seal::{Keyring, Opened, SealError, TagLength};
+pub use inet::InternetChecksum;
+pub use integrity::{
+    Adler32, Algorithm, Checksum, Crc32, Crc32c, Fletcher16, Fletcher32, open_sealed,
+    seal_packet_with,
+};
 
 pub type Result<T> = anyhow::Result<T>;
 
@@ -37,6 +52,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Composition (`internet_checksum` calls `fold_checksum`)",
                 "Const vs runtime functions",
                 "Nibble masking helpers",
+                "Streaming accumulators and RFC 1624 incremental updates",
             ],
         },
         ExerciseInfo {
@@ -47,6 +63,9 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Tuple returns `(Vec<u8>, u16)`",
                 "Odd-byte padding to 16-bit words",
                 "Verify-by-recompute pattern",
+                "`Checksum` trait objects selected by an enum",
+                "`Result` with a custom error enum for keyed seals",
+                "Generic `FrameWriter<W: Write>` / `FrameReader<R: Read>`",
             ],
         },
     ]
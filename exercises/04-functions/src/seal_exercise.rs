@@ -1,6 +1,10 @@
 //! Seal exercise — length-prefixed packets with embedded checksums.
 
 use crate::checksum::{checksum_hex, payload_len, seal_packet, verify_sealed};
+use crate::frame::{FrameEvent, FrameReader, FrameWriter};
+use crate::hmac_seal::{Keyring, TagLength};
+use crate::inet;
+use crate::integrity::{Algorithm, open_sealed, seal_packet_with};
 
 /// Run the seal/verify exercise with demo output.
 pub fn run(verbose: bool) {
@@ -39,11 +43,83 @@ pub fn run(verbose: bool) {
         );
     }
 
+    if let Some((mut forged, _)) = seal_packet(b"allow 10.0.0.0/8") {
+        forged[4..].copy_from_slice(b"allow 0.0.0.0/0!");
+        forged[2] = 0;
+        forged[3] = 0;
+        let csum = inet::checksum(&forged);
+        forged[2..4].copy_from_slice(&csum.to_be_bytes());
+        println!(
+            "   Edited payload with recomputed checksum: {}",
+            if verify_sealed(&forged) {
+                "VALID (checksums are not authentication)"
+            } else {
+                "INVALID"
+            }
+        );
+    }
+
+    println!("6. Choosing a stronger algorithm:");
+    for algorithm in Algorithm::ALL {
+        if let Some(packet) = seal_packet_with(payload, algorithm) {
+            let width = algorithm.width();
+            let field = packet[3..3 + width]
+                .iter()
+                .fold(0u32, |acc, &b| acc << 8 | u32::from(b));
+            let hex = format!("0x{field:0digits$X}", digits = width * 2);
+            let opened = open_sealed(&packet).is_some();
+            println!(
+                "   {:<10} {hex:<10} opens={opened} — {}",
+                algorithm.to_string(),
+                algorithm.strength_hint()
+            );
+        }
+    }
+
+    let ring = Keyring::new(1, b"demo-sensor-key", TagLength::Bits128);
+    if let Ok(mut frame) = ring.seal(b"allow 10.0.0.0/8") {
+        println!("7. HMAC-SHA-256 seal ({} bytes, key id 1):", frame.len());
+        println!("   open: {:?}", ring.open(&frame).map(|o| o.payload.len()));
+        let start = frame.len() - 16;
+        frame[start..].copy_from_slice(b"allow 0.0.0.0/0!");
+        match ring.open(&frame) {
+            Ok(_) => println!("   forged frame: accepted"),
+            Err(e) => println!("   forged frame: rejected ({e})"),
+        }
+    }
+
+    let mut writer = FrameWriter::new(Vec::new());
+    for message in [
+        b"scan 10.0.0.0/24".as_slice(),
+        b"dropped in transit",
+        b"block",
+    ] {
+        if writer.write_message(message).is_err() {
+            return;
+        }
+    }
+    let wire = writer.into_inner();
+    // Cut the second frame out of the stream to simulate loss.
+    let lost = 13 + b"scan 10.0.0.0/24".len();
+    let damaged = [&wire[..lost], &wire[lost + 13 + 18..]].concat();
+    println!("8. Framed stream with one lost frame:");
+    let mut reader = FrameReader::new(damaged.as_slice());
+    while let Ok(Some(event)) = reader.read_event() {
+        match event {
+            FrameEvent::Message { seq, payload } => {
+                println!("   seq {seq}: {}", String::from_utf8_lossy(&payload));
+            }
+            other => println!("   anomaly: {other:?}"),
+        }
+    }
+
     if verbose {
         println!();
         println!("   Why it matters:");
         println!("   - Length prefixes frame variable payloads on the wire");
         println!("   - Embedded checksums enable integrity checks without side channels");
         println!("   - `Option` signals oversize payloads instead of panicking");
+        println!("   - A trait lets the caller pick strength without changing the framing");
+        println!("   - Only a keyed MAC stops an attacker who can rewrite the packet");
     }
 }
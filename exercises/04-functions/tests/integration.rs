@@ -1,8 +1,10 @@
 //! Integration tests for the `exercise_functions` crate.
 
+use functions_exercises::inet;
 use functions_exercises::{
-    checksum_hex, get_exercise_list, internet_checksum, nibble_mix, run_all, run_exercise,
-    seal_packet, verify_sealed,
+    Algorithm, Checksum, Crc32c, FrameEvent, FrameReader, FrameWriter, InternetChecksum, Keyring,
+    SealError, TagLength, bytes_to_words, checksum_hex, get_exercise_list, internet_checksum,
+    nibble_mix, open_sealed, run_all, run_exercise, seal_packet, seal_packet_with, verify_sealed,
 };
 
 #[test]
@@ -54,3 +56,119 @@ fn test_checksum_and_seal_api() {
         assert!(verify_sealed(&packet));
     }
 }
+
+#[test]
+fn test_inet_checksum_agrees_with_word_api() {
+    let data = b"odd-length packet payload";
+    assert_eq!(
+        inet::checksum(data),
+        internet_checksum(&bytes_to_words(data))
+    );
+
+    let mut stream = InternetChecksum::new();
+    stream.update(&data[..3]);
+    stream.update(&data[3..]);
+    assert_eq!(stream.finish(), inet::checksum(data));
+
+    let csum = inet::checksum(data);
+    let mut rewritten = data.to_vec();
+    rewritten[3..8].copy_from_slice(b"EVEN!");
+    assert_eq!(
+        inet::update_bytes(csum, 3, &data[3..8], b"EVEN!"),
+        Some(inet::checksum(&rewritten))
+    );
+}
+
+#[test]
+fn test_checksum_trait_and_algorithm_choice() {
+    let mut crc = Crc32c::new();
+    crc.update(b"1234");
+    crc.update(b"56789");
+    assert_eq!(crc.finalize(), 0xE306_9283);
+    assert_eq!(crc.finalize_bytes(), vec![0xE3, 0x06, 0x92, 0x83]);
+
+    let mut internet = InternetChecksum::new();
+    Checksum::update(&mut internet, b"test");
+    assert_eq!(
+        Checksum::finalize(&internet),
+        u32::from(inet::checksum(b"test"))
+    );
+
+    let packet = seal_packet_with(b"payload", Algorithm::Crc32);
+    assert!(packet.is_some());
+    if let Some(packet) = packet {
+        assert_eq!(packet.len(), 3 + 4 + 7);
+        assert_eq!(
+            open_sealed(&packet),
+            Some((Algorithm::Crc32, b"payload".as_slice()))
+        );
+    }
+}
+
+#[test]
+fn test_hmac_seal_rejects_what_checksums_accept() {
+    let ring = Keyring::new(7, b"integration-key", TagLength::Bits256);
+    let frame = ring.seal(b"block 203.0.113.9");
+    assert!(frame.is_ok());
+    let Ok(mut frame) = frame else { return };
+
+    let opened = ring.open(&frame);
+    assert!(opened.is_ok_and(|o| o.key_id == 7 && o.payload == b"block 203.0.113.9"));
+
+    let last = frame.len() - 1;
+    frame[last] = b'8';
+    assert_eq!(ring.open(&frame), Err(SealError::BadTag));
+
+    let stranger = Keyring::new(7, b"another-key", TagLength::Bits256);
+    frame[last] = b'9';
+    assert_eq!(stranger.open(&frame), Err(SealError::BadTag));
+    assert_eq!(SealError::UnknownKey(3).to_string(), "unknown key id 3");
+}
+
+#[test]
+fn test_frame_stream_over_cursor() {
+    let mut writer = FrameWriter::new(Vec::new());
+    let big = vec![0xAB_u8; 4000];
+    assert!(writer.write_message(b"first").is_ok());
+    assert!(writer.write_message(&big).is_ok());
+    let mut wire = writer.into_inner();
+    wire.extend_from_slice(b"\x00garbage");
+
+    let mut reader = FrameReader::new(std::io::Cursor::new(wire));
+    assert_eq!(
+        reader.read_message().ok().flatten(),
+        Some(b"first".to_vec())
+    );
+    assert_eq!(reader.read_message().ok().flatten(), Some(big));
+    assert!(matches!(
+        reader.read_event(),
+        Ok(Some(FrameEvent::Resync { skipped: 8 }))
+    ));
+    assert!(matches!(reader.read_event(), Ok(None)));
+}
+
+#[test]
+fn test_frames_carry_sealed_payloads() {
+    let ring = Keyring::new(2, b"stream-key", TagLength::Bits128);
+    let stranger = Keyring::new(2, b"forged-key", TagLength::Bits128);
+    let (Ok(genuine), Ok(forged)) = (
+        ring.seal(b"block 198.51.100.7"),
+        stranger.seal(b"allow all"),
+    ) else {
+        return;
+    };
+
+    let mut writer = FrameWriter::new(Vec::new());
+    assert!(writer.write_message(&genuine).is_ok());
+    assert!(writer.write_message(&forged).is_ok());
+    let mut reader = FrameReader::new(std::io::Cursor::new(writer.into_inner()));
+
+    // The frame CRC only vouches for the transport; the seal decides trust.
+    let first = reader.read_message().ok().flatten().unwrap_or_default();
+    assert!(
+        ring.open(&first)
+            .is_ok_and(|o| o.payload == b"block 198.51.100.7")
+    );
+    let second = reader.read_message().ok().flatten().unwrap_or_default();
+    assert_eq!(ring.open(&second), Err(SealError::BadTag));
+}
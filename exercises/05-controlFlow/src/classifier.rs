@@ -1,5 +1,7 @@
 //! Threat score and log classification helpers.
 
+use crate::rules::{RuleSet, ScoreReport};
+
 /// Severity levels for classified security events.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Severity {
@@ -41,27 +43,19 @@ pub const fn requires_escalation(level: Severity) -> bool {
 }
 
 /// Score a single log line with keyword heuristics (uncapped raw score).
+///
+/// Uses [`RuleSet::builtin`]; load a rule file and call
+/// [`classify_line`] to get tunable rules and explanations.
 #[must_use]
 pub fn score_log_line(line: &str) -> u32 {
-    let lower = line.to_ascii_lowercase();
-    let mut score: u32 = 0;
-
-    for (keyword, points) in [
-        ("critical", 40_u32),
-        ("malware", 35),
-        ("exploit", 30),
-        ("denied", 25),
-        ("fail", 20),
-        ("error", 15),
-        ("warning", 10),
-        ("unauthorized", 20),
-    ] {
-        if lower.contains(keyword) {
-            score = score.saturating_add(points);
-        }
-    }
+    RuleSet::builtin().score(line).score()
+}
 
-    score
+/// Score and classify one line against `rules`, keeping the per-rule report.
+#[must_use]
+pub fn classify_line(line: &str, rules: &RuleSet) -> (Severity, ScoreReport) {
+    let report = rules.score(line);
+    (classify_score(cap_score(report.score())), report)
 }
 
 /// Cap a raw score to the 0..=100 range used by classifiers.
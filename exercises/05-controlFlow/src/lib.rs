@@ -5,12 +5,17 @@
 
 mod batch;
 pub mod classifier;
+pub mod rules;
 mod scoring;
 
 pub use classifier::{
-    Severity, accumulate_until_budget, cap_score, classify_batch, classify_score,
+    Severity, accumulate_until_budget, cap_score, classify_batch, classify_line, classify_score,
     requires_escalation, score_log_line, severity_label, walk_nonempty_lines,
 };
+pub use rules::{Matcher, Rule, RuleMatch, RuleParseError, RuleSet, ScoreReport};
+
+/// Rule file shipped with the crate (`rules/soc.rules`).
+pub const DEFAULT_RULES: &str = include_str!("../rules/soc.rules");
 
 pub type Result<T> = anyhow::Result<T>;
 
@@ -37,6 +42,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "`if` expressions for capping",
                 "Enum severity tiers",
                 "Keyword heuristics",
+                "Rule files with explained matches",
             ],
         },
         ExerciseInfo {
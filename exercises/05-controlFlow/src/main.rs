@@ -4,11 +4,16 @@
 //! cargo run -p exercise_controlflow
 //! cargo run -p exercise_controlflow -- list
 //! cargo run -p exercise_controlflow -- scoring --verbose
+//! cargo run -p exercise_controlflow -- explain --rules rules/soc.rules "Failed password for root"
 //! ```
 
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use controlflow_exercises::{get_exercise_list, run_all, run_exercise};
+use controlflow_exercises::{
+    DEFAULT_RULES, RuleSet, classify_line, get_exercise_list, run_all, run_exercise, severity_label,
+};
 
 /// Control flow exercises via threat scores and log classification
 #[derive(Parser, Debug)]
@@ -39,6 +44,17 @@ enum Commands {
 
     /// Batch log classification with loops
     Batch,
+
+    /// Score log lines against a rule file and show which rules fired
+    Explain {
+        /// Rule file (defaults to the bundled rules/soc.rules)
+        #[arg(short, long)]
+        rules: Option<PathBuf>,
+
+        /// Log lines to score
+        #[arg(required = true)]
+        lines: Vec<String>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -54,11 +70,35 @@ fn main() -> Result<()> {
         Some(Commands::List) => list_exercises(),
         Some(Commands::Scoring) => run_exercise("scoring", verbose)?,
         Some(Commands::Batch) => run_exercise("batch", verbose)?,
+        Some(Commands::Explain { rules, lines }) => explain(rules.as_deref(), &lines)?,
     }
 
     Ok(())
 }
 
+fn explain(path: Option<&std::path::Path>, lines: &[String]) -> Result<()> {
+    let rules = match path {
+        Some(path) => RuleSet::load(path)?,
+        None => RuleSet::parse(DEFAULT_RULES)?,
+    };
+    for line in lines {
+        let (level, report) = classify_line(line, &rules);
+        println!(
+            "[{}] score={} total={:+} — {line}",
+            severity_label(level),
+            report.score(),
+            report.total
+        );
+        if report.matches.is_empty() {
+            println!("    no rules fired");
+        }
+        for hit in &report.matches {
+            println!("    {hit}");
+        }
+    }
+    Ok(())
+}
+
 fn list_exercises() {
     println!("Available exercises:\n");
     for info in get_exercise_list() {
@@ -74,6 +114,7 @@ fn list_exercises() {
     println!("  cargo run -p exercise_controlflow -- scoring [--verbose]");
     println!("  cargo run -p exercise_controlflow -- batch [--verbose]");
     println!("  cargo run -p exercise_controlflow -- all [--verbose]");
+    println!("  cargo run -p exercise_controlflow -- explain [--rules FILE] <LINE>...");
     println!("  cargo run -p exercise_controlflow              # same as 'all'");
 }
 
@@ -91,4 +132,10 @@ mod tests {
     fn test_list_does_not_panic() {
         list_exercises();
     }
+
+    #[test]
+    fn test_explain_with_bundled_rules() {
+        assert!(explain(None, &["nmap sweep".to_string()]).is_ok());
+        assert!(explain(Some(std::path::Path::new("missing.rules")), &[]).is_err());
+    }
 }
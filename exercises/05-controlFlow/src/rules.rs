@@ -0,0 +1,422 @@
+//! Rule-file-driven log line scoring.
+//!
+//! A [`RuleSet`] is an ordered list of [`Rule`]s, each with an id, a signed
+//! point value, and a [`Matcher`]. Scoring a line returns a [`ScoreReport`]
+//! that lists every rule that fired and why, not just the total.
+//!
+//! Rule files are line-oriented; see `rules/soc.rules` for a commented example:
+//!
+//! ```text
+//! # <rule-id>  <points>  <matcher>  <pattern...>
+//! MAL-001      35        nocase     malware
+//! ACC-002      20        regex      (?i)\bfail(ed|ure|s)?\b
+//! NOISE-001   -20        nocase     healthcheck
+//! ```
+
+use std::fmt::{self, Display};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use regex::Regex;
+
+/// How a rule recognizes its pattern in a log line.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Case-sensitive substring.
+    Literal(String),
+    /// ASCII case-insensitive substring.
+    CaseInsensitive(String),
+    /// ASCII case-insensitive whole word: the match may not touch letters,
+    /// digits, or `_` on either side.
+    Word(String),
+    /// Regular expression, used as written.
+    Regex(Regex),
+}
+
+impl Matcher {
+    /// Keyword used for this matcher in rule files.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Literal(_) => "literal",
+            Self::CaseInsensitive(_) => "nocase",
+            Self::Word(_) => "word",
+            Self::Regex(_) => "regex",
+        }
+    }
+
+    /// Pattern text as it appears in the rule file.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        match self {
+            Self::Literal(p) | Self::CaseInsensitive(p) | Self::Word(p) => p,
+            Self::Regex(re) => re.as_str(),
+        }
+    }
+
+    /// Byte range of the first match in `line`, if any.
+    #[must_use]
+    pub fn find(&self, line: &str) -> Option<Range<usize>> {
+        match self {
+            Self::Literal(p) => line.find(p.as_str()).map(|start| start..start + p.len()),
+            Self::CaseInsensitive(p) => find_nocase(line, p, false),
+            Self::Word(p) => find_nocase(line, p, true),
+            Self::Regex(re) => re.find(line).map(|m| m.range()),
+        }
+    }
+}
+
+const fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_'
+}
+
+/// ASCII case-insensitive search; lowercasing keeps byte offsets intact.
+fn find_nocase(line: &str, pattern: &str, whole_word: bool) -> Option<Range<usize>> {
+    if pattern.is_empty() {
+        return None;
+    }
+    let haystack = line.to_ascii_lowercase();
+    let needle = pattern.to_ascii_lowercase();
+    let bytes = haystack.as_bytes();
+    let mut from = 0;
+    while let Some(offset) = haystack[from..].find(&needle) {
+        let start = from + offset;
+        let end = start + needle.len();
+        let bounded = !whole_word
+            || (start.checked_sub(1).is_none_or(|i| !is_word_byte(bytes[i]))
+                && bytes.get(end).is_none_or(|&b| !is_word_byte(b)));
+        if bounded {
+            return Some(start..end);
+        }
+        from = start + haystack[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
+
+/// Split off the first whitespace-delimited field.
+fn next_field(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    text.split_at(end)
+}
+
+/// One scoring rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub id: String,
+    /// Added to the line's score when the rule fires; negative for benign noise.
+    pub points: i32,
+    pub matcher: Matcher,
+}
+
+/// A rule that fired on a line, with the evidence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule_id: String,
+    pub points: i32,
+    /// Byte range of the matched text within the line.
+    pub span: Range<usize>,
+    pub explanation: String,
+}
+
+impl Display for RuleMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:+}): {}",
+            self.rule_id, self.points, self.explanation
+        )
+    }
+}
+
+/// Result of scoring one line against a [`RuleSet`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScoreReport {
+    /// Signed sum of all matched rule points.
+    pub total: i64,
+    /// Rules that fired, in rule-file order.
+    pub matches: Vec<RuleMatch>,
+}
+
+impl ScoreReport {
+    /// Raw score for the classifier: the signed total floored at zero.
+    #[must_use]
+    pub fn score(&self) -> u32 {
+        u32::try_from(self.total.max(0)).unwrap_or(u32::MAX)
+    }
+
+    /// Ids of the rules that fired.
+    #[must_use]
+    pub fn rule_ids(&self) -> Vec<&str> {
+        self.matches.iter().map(|m| m.rule_id.as_str()).collect()
+    }
+
+    /// Whether any benign-noise (negative) rule fired.
+    #[must_use]
+    pub fn suppressed(&self) -> bool {
+        self.matches.iter().any(|m| m.points < 0)
+    }
+}
+
+/// Why a rule file could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleParseError {
+    /// 1-based line number in the rule file.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rule file line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for RuleParseError {}
+
+/// Ordered collection of scoring rules.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+/// The original eight keyword weights, as case-insensitive substring rules.
+static BUILTIN: LazyLock<RuleSet> = LazyLock::new(|| {
+    let mut set = RuleSet::new();
+    for (keyword, points) in [
+        ("critical", 40),
+        ("malware", 35),
+        ("exploit", 30),
+        ("denied", 25),
+        ("fail", 20),
+        ("error", 15),
+        ("warning", 10),
+        ("unauthorized", 20),
+    ] {
+        set.push(Rule {
+            id: format!("KW-{}", keyword.to_ascii_uppercase()),
+            points,
+            matcher: Matcher::CaseInsensitive(keyword.to_string()),
+        });
+    }
+    set
+});
+
+impl RuleSet {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// The built-in keyword table used by [`score_log_line`](crate::score_log_line).
+    #[must_use]
+    pub fn builtin() -> &'static Self {
+        &BUILTIN
+    }
+
+    /// Append a rule; later rules are reported after earlier ones.
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    #[must_use]
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    #[must_use]
+    pub fn get(&self, id: &str) -> Option<&Rule> {
+        self.rules.iter().find(|r| r.id == id)
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Parse rule-file text. Blank lines and `#` comments are ignored.
+    pub fn parse(text: &str) -> Result<Self, RuleParseError> {
+        let mut set = Self::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason: String| RuleParseError {
+                line: index + 1,
+                reason,
+            };
+
+            let (id, rest) = next_field(line);
+            let (points, rest) = next_field(rest);
+            let (kind, rest) = next_field(rest);
+            let pattern = rest.trim();
+            if kind.is_empty() {
+                return Err(err("expected `<id> <points> <matcher> <pattern>`".into()));
+            }
+            if pattern.is_empty() {
+                return Err(err(format!("rule {id} has no pattern")));
+            }
+            if set.get(id).is_some() {
+                return Err(err(format!("duplicate rule id {id}")));
+            }
+            let points: i32 = points
+                .parse()
+                .map_err(|_| err(format!("invalid points {points:?}")))?;
+            let matcher = match kind {
+                "literal" => Matcher::Literal(pattern.to_string()),
+                "nocase" => Matcher::CaseInsensitive(pattern.to_string()),
+                "word" => Matcher::Word(pattern.to_string()),
+                "regex" => Matcher::Regex(
+                    Regex::new(pattern).map_err(|e| err(format!("invalid regex: {e}")))?,
+                ),
+                other => return Err(err(format!("unknown matcher {other:?}"))),
+            };
+            set.push(Rule {
+                id: id.to_string(),
+                points,
+                matcher,
+            });
+        }
+        Ok(set)
+    }
+
+    /// Load and parse a rule file.
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading rule file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing rule file {}", path.display()))
+    }
+
+    /// Score `line`, recording every rule that fired.
+    #[must_use]
+    pub fn score(&self, line: &str) -> ScoreReport {
+        let mut report = ScoreReport::default();
+        for rule in &self.rules {
+            let Some(span) = rule.matcher.find(line) else {
+                continue;
+            };
+            report.total += i64::from(rule.points);
+            report.matches.push(RuleMatch {
+                rule_id: rule.id.clone(),
+                points: rule.points,
+                explanation: format!(
+                    "{} {:?} matched {:?} at {}..{}",
+                    rule.matcher.kind(),
+                    rule.matcher.pattern(),
+                    &line[span.clone()],
+                    span.start,
+                    span.end
+                ),
+                span,
+            });
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r"
+        # comment
+        SEV-001   40  word    critical
+        MAL-001   35  nocase  malware
+        LIT-001   10  literal ERROR
+        RX-001    25  regex   (?i)failed password for (invalid user|root)
+        NOISE-001 -30 nocase  healthcheck
+    ";
+
+    fn ruleset() -> RuleSet {
+        let parsed = RuleSet::parse(SAMPLE);
+        assert!(parsed.is_ok(), "{parsed:?}");
+        parsed.unwrap_or_default()
+    }
+
+    #[test]
+    fn parses_rules_in_order() {
+        let rules = ruleset();
+        assert_eq!(rules.len(), 5);
+        assert_eq!(rules.rules()[3].matcher.kind(), "regex");
+        assert_eq!(
+            rules.get("RX-001").map(|r| r.matcher.pattern()),
+            Some("(?i)failed password for (invalid user|root)")
+        );
+        assert_eq!(rules.get("NOISE-001").map(|r| r.points), Some(-30));
+    }
+
+    #[test]
+    fn matcher_semantics() {
+        let word = Matcher::Word("fail".into());
+        assert_eq!(word.find("auth FAIL from host"), Some(5..9));
+        assert_eq!(word.find("failover complete"), None);
+        assert_eq!(word.find("x_fail"), None);
+        assert_eq!(word.find("failover then fail."), Some(14..18));
+
+        assert_eq!(Matcher::Literal("ERROR".into()).find("error"), None);
+        assert_eq!(
+            Matcher::CaseInsensitive("error".into()).find("An ERROR"),
+            Some(3..8)
+        );
+        assert_eq!(Matcher::Word("é".into()).find("café é"), Some(6..8));
+    }
+
+    #[test]
+    fn explains_each_match() {
+        let report = ruleset().score("sshd: Failed password for root — CRITICAL malware");
+        assert_eq!(report.rule_ids(), vec!["SEV-001", "MAL-001", "RX-001"]);
+        assert_eq!(report.score(), 100);
+        assert!(report.matches[0].explanation.contains("\"CRITICAL\""));
+        assert!(
+            report.matches[2]
+                .to_string()
+                .starts_with("RX-001 (+25): regex")
+        );
+    }
+
+    #[test]
+    fn negative_points_suppress_noise() {
+        let report = ruleset().score("healthcheck ERROR: probe timed out");
+        assert_eq!(report.total, -20);
+        assert_eq!(report.score(), 0);
+        assert!(report.suppressed());
+    }
+
+    #[test]
+    fn parse_errors_carry_line_numbers() {
+        let cases = [
+            ("X 10 word", "has no pattern"),
+            ("X ten word foo", "invalid points"),
+            ("X 10 glob foo*", "unknown matcher"),
+            ("X 10 regex (unclosed", "invalid regex"),
+            ("X 10 word a\nX 5 word b", "duplicate rule id"),
+            ("X", "expected"),
+        ];
+        for (text, reason) in cases {
+            let err = RuleSet::parse(text).err();
+            assert!(
+                err.as_ref().is_some_and(|e| e.reason.contains(reason)),
+                "{text:?} -> {err:?}"
+            );
+        }
+        assert_eq!(
+            RuleSet::parse("\n# c\nX 1 glob y").err().map(|e| e.line),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn builtin_matches_original_keyword_table() {
+        let report = RuleSet::builtin().score("CRITICAL malware exploit denied");
+        assert_eq!(report.score(), 130);
+        assert_eq!(report.matches.len(), 4);
+    }
+}
@@ -1,6 +1,8 @@
 //! Scoring exercise — keyword heuristics and severity mapping.
 
-use crate::classifier::{cap_score, classify_score, score_log_line, severity_label};
+use crate::DEFAULT_RULES;
+use crate::classifier::{cap_score, classify_line, classify_score, score_log_line, severity_label};
+use crate::rules::RuleSet;
 
 /// Run the scoring exercise with demo output.
 pub fn run(verbose: bool) {
@@ -25,11 +27,35 @@ pub fn run(verbose: bool) {
         );
     }
 
+    println!();
+    match RuleSet::parse(DEFAULT_RULES) {
+        Ok(rules) => {
+            println!("   Rule file ({} rules) with explanations:", rules.len());
+            for line in [
+                "sshd: Failed password for root from 203.0.113.9",
+                "nmap scan detected [scanner-allowlist] healthcheck",
+                samples[3],
+            ] {
+                let (level, report) = classify_line(line, &rules);
+                println!(
+                    "   [{}] total={:+} — {line}",
+                    severity_label(level),
+                    report.total
+                );
+                for hit in &report.matches {
+                    println!("      {hit}");
+                }
+            }
+        }
+        Err(e) => println!("   Rule file error: {e}"),
+    }
+
     if verbose {
         println!();
         println!("   Why it matters:");
         println!("   - SIEM rules often start as keyword + weight heuristics");
         println!("   - `match` on ranges maps continuous scores to discrete tiers");
         println!("   - `if` expressions cap scores without extra bindings");
+        println!("   - Rule files let analysts tune weights without a rebuild");
     }
 }
@@ -1,8 +1,8 @@
 //! Integration tests for the `exercise_controlflow` crate.
 
 use controlflow_exercises::{
-    Severity, classify_batch, classify_score, get_exercise_list, run_all, run_exercise,
-    score_log_line, severity_label, walk_nonempty_lines,
+    DEFAULT_RULES, RuleSet, Severity, classify_batch, classify_line, classify_score,
+    get_exercise_list, run_all, run_exercise, score_log_line, severity_label, walk_nonempty_lines,
 };
 
 #[test]
@@ -56,3 +56,39 @@ fn test_classifier_api() {
     let nonempty = walk_nonempty_lines(&lines);
     assert_eq!(nonempty.len(), 2);
 }
+
+#[test]
+fn test_bundled_rule_file_scores_and_explains() {
+    let rules = RuleSet::parse(DEFAULT_RULES);
+    assert!(rules.is_ok());
+    let Ok(rules) = rules else { return };
+
+    let (level, report) = classify_line("sshd: Failed password for root from 203.0.113.9", &rules);
+    assert_eq!(report.rule_ids(), vec!["ACC-002", "CRD-001"]);
+    assert_eq!(level, Severity::High);
+    assert!(report.matches.iter().all(|m| !m.explanation.is_empty()));
+
+    let (quiet, noisy) = classify_line("healthcheck warning: dry-run", &rules);
+    assert_eq!(quiet, Severity::Low);
+    assert!(noisy.suppressed());
+    assert_eq!(noisy.score(), 0);
+
+    // Word matching no longer treats "failover" as a failure.
+    assert!(
+        classify_line("failover complete", &rules)
+            .1
+            .matches
+            .is_empty()
+    );
+    assert!(score_log_line("failover complete") > 0);
+}
+
+#[test]
+fn test_rule_file_loads_from_disk() {
+    let path = std::env::temp_dir().join(format!("controlflow-{}.rules", std::process::id()));
+    assert!(std::fs::write(&path, "T-1 55 word beacon\n").is_ok());
+    let loaded = RuleSet::load(&path);
+    let _ = std::fs::remove_file(&path);
+    assert!(loaded.is_ok_and(|r| r.score("c2 BEACON seen").score() == 55));
+    assert!(RuleSet::load(&path).is_err());
+}
@@ -4,9 +4,11 @@
 //! in a cybersecurity context.
 
 pub mod move_vs_copy;
+pub mod secret;
 pub mod secrets;
 pub mod zeroize;
 
+pub use secret::{REDACTED, Scrub, Secret, constant_time_eq, expose_serialize};
 pub use secrets::{
     clone_secret, consume_secret, copy_threat_score, take_then_return, zeroize_and_consume,
 };
@@ -46,6 +48,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Scope and `Drop`",
                 "Safe scrubbing via `into_bytes`",
                 "Ephemeral credential lifecycle",
+                "`Secret<T>`: redacted, scrub-on-drop wrapper",
             ],
         },
     ]
@@ -0,0 +1,227 @@
+//! `Secret<T>` — an owned secret that scrubs itself on drop.
+//!
+//! [`zeroize_and_consume`](crate::zeroize_and_consume) scrubs a `String` when
+//! you remember to call it. `Secret<T>` does the same automatically in `Drop`,
+//! so a credential stored in a struct field is wiped however that struct dies.
+//! It also:
+//!
+//! - prints `[REDACTED]` from `Debug` and `Display`;
+//! - hands out the inner value only through [`Secret::expose`], which makes
+//!   every read easy to grep for;
+//! - compares in constant time (for equal-length inputs);
+//! - refuses to `Serialize` unless a field opts in with
+//!   `#[serde(serialize_with = "expose_serialize")]`.
+//!
+//! Everything is safe Rust. [`Scrub`] overwrites the whole allocation, and
+//! [`std::hint::black_box`] keeps the compiler from removing those writes as
+//! dead stores.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::hint::black_box;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Text shown in place of secret material.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Overwrite a value's memory with zeros in place.
+pub trait Scrub {
+    fn scrub(&mut self);
+}
+
+impl Scrub for Vec<u8> {
+    fn scrub(&mut self) {
+        // Zero the spare capacity as well: it may hold bytes from an earlier,
+        // longer value.
+        let capacity = self.capacity();
+        self.clear();
+        self.resize(capacity, 0);
+        black_box(self.as_slice());
+        self.clear();
+    }
+}
+
+impl Scrub for String {
+    fn scrub(&mut self) {
+        let mut bytes = std::mem::take(self).into_bytes();
+        bytes.scrub();
+        // Hand the zeroed buffer back so its capacity is reused, not leaked.
+        *self = Self::from_utf8(bytes).unwrap_or_default();
+    }
+}
+
+impl<const N: usize> Scrub for [u8; N] {
+    fn scrub(&mut self) {
+        self.fill(0);
+        black_box(&self[..]);
+    }
+}
+
+/// Compare two byte strings without an early exit on the first mismatch.
+///
+/// The running time depends only on the lengths, not on where the inputs differ.
+/// A length mismatch still returns early, because the length of a secret is not
+/// treated as secret.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0_u8, |acc, (x, y)| acc | (x ^ y));
+    black_box(diff) == 0
+}
+
+/// A secret value that is redacted when printed and scrubbed when dropped.
+pub struct Secret<T: Scrub>(T);
+
+impl<T: Scrub> Secret<T> {
+    /// Take ownership of `value`; the caller's binding is moved in.
+    pub const fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the secret. Keep the borrow short and don't copy out of it.
+    pub const fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Scrub + AsRef<[u8]>> Secret<T> {
+    /// Constant-time comparison against a candidate (e.g. a login attempt).
+    pub fn matches(&self, candidate: impl AsRef<[u8]>) -> bool {
+        constant_time_eq(self.0.as_ref(), candidate.as_ref())
+    }
+}
+
+impl<T: Scrub> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.scrub();
+    }
+}
+
+impl<T: Scrub + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Scrub + AsRef<[u8]>> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(self.0.as_ref(), other.0.as_ref())
+    }
+}
+
+impl<T: Scrub + AsRef<[u8]>> Eq for Secret<T> {}
+
+impl<T: Scrub> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Secret")
+            .field(&format_args!("{REDACTED}"))
+            .finish()
+    }
+}
+
+impl<T: Scrub> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Scrub> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret<String> {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+/// Lets `clap` parse arguments straight into a `Secret<String>`.
+impl FromStr for Secret<String> {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl<'de, T: Scrub + Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Always fails, so a secret can't end up in a log or API response because
+/// someone derived `Serialize` on the struct that holds it.
+impl<T: Scrub> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom(
+            "refusing to serialize Secret; opt in with #[serde(serialize_with = \"expose_serialize\")]",
+        ))
+    }
+}
+
+/// Explicit opt-in for fields that must be written out, such as a credential
+/// store on disk: `#[serde(serialize_with = "expose_serialize")]`.
+pub fn expose_serialize<T, S>(secret: &Secret<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Scrub + Serialize,
+    S: Serializer,
+{
+    secret.expose().serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_and_display_are_redacted() {
+        let secret = Secret::from("hunter2");
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(secret.expose(), "hunter2");
+    }
+
+    #[test]
+    fn scrub_empties_in_place() {
+        let mut bytes = Vec::with_capacity(32);
+        bytes.extend_from_slice(b"a longer previous secret");
+        bytes.truncate(4);
+        bytes.scrub();
+        assert!(bytes.is_empty());
+        assert_eq!(bytes.capacity(), 32);
+
+        let mut text = String::with_capacity(16);
+        text.push_str("s3cr3t");
+        text.scrub();
+        assert!(text.is_empty());
+        assert_eq!(text.capacity(), 16);
+
+        let mut key = [7_u8; 16];
+        key.scrub();
+        assert_eq!(key, [0; 16]);
+    }
+
+    #[test]
+    fn constant_time_equality() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+        assert_eq!(Secret::from("a"), Secret::from("a"));
+        assert_ne!(Secret::from("a"), Secret::from("b"));
+        assert!(Secret::from("pin").matches("pin"));
+        assert!(!Secret::from("pin").matches("pit"));
+    }
+
+    #[test]
+    fn parses_from_str() {
+        let parsed: Result<Secret<String>, _> = "from-cli".parse();
+        assert!(parsed.is_ok_and(|s| s.expose() == "from-cli"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 
+use crate::secret::Secret;
 use crate::secrets::{consume_secret, zeroize_and_consume};
 
 /// Run the zeroize exercise with demo output.
@@ -24,10 +25,21 @@ pub fn run(verbose: bool) -> Result<()> {
     }
     println!("   Inner-scope credential cannot be reused");
 
+    // Secret<T> moves the scrub into Drop, so it can't be forgotten.
+    let api_key = Secret::from("ak_live_51HxZ");
+    println!("4. Secret<String> prints as {api_key} / {api_key:?}");
+    println!(
+        "   expose() for an explicit read: {} bytes, matches={}",
+        api_key.expose().len(),
+        api_key.matches("ak_live_51HxZ")
+    );
+    drop(api_key);
+    println!("   Dropped — buffer scrubbed by Secret's Drop impl");
+
     if verbose {
         println!();
-        println!("   Production tip: use the `zeroize` crate for secrets in");
-        println!("   structs; here we scrub `Vec<u8>` safely without `unsafe`.");
+        println!("   Production tip: the `zeroize`/`secrecy` crates do this for");
+        println!("   more types; `Secret<T>` scrubs `Vec<u8>` safely without `unsafe`.");
     }
 
     Ok(())
@@ -1,9 +1,10 @@
 //! Integration tests for the `exercise_ownership` crate.
 
 use ownership_exercises::{
-    clone_secret, consume_secret, copy_threat_score, get_exercise_list, run_all, run_exercise,
-    take_then_return, zeroize_and_consume,
+    Secret, clone_secret, consume_secret, copy_threat_score, get_exercise_list, run_all,
+    run_exercise, take_then_return, zeroize_and_consume,
 };
+use serde::{Deserialize, Serialize};
 
 #[test]
 fn test_exercise_list_contains_both() {
@@ -70,3 +71,37 @@ fn test_copy_threat_score_api() {
     assert_eq!(copy_threat_score(score), 34);
     assert_eq!(score, 17);
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Credential {
+    user: String,
+    password: Secret<String>,
+}
+
+#[derive(Serialize)]
+struct StoredCredential {
+    user: String,
+    #[serde(serialize_with = "ownership_exercises::expose_serialize")]
+    password: Secret<String>,
+}
+
+#[test]
+fn test_secret_is_redacted_and_refuses_serialization() {
+    let parsed: Result<Credential, _> =
+        serde_json::from_str(r#"{"user":"alice","password":"hunter2"}"#);
+    assert!(parsed.is_ok());
+    let Ok(cred) = parsed else { return };
+
+    let debug = format!("{cred:?}");
+    assert!(debug.contains("[REDACTED]"));
+    assert!(!debug.contains("hunter2"));
+    assert!(cred.password.matches("hunter2"));
+    assert!(serde_json::to_string(&cred).is_err());
+
+    let stored = StoredCredential {
+        user: cred.user,
+        password: cred.password,
+    };
+    let json = serde_json::to_string(&stored);
+    assert!(json.is_ok_and(|j| j.contains("hunter2")));
+}
@@ -1,46 +1,143 @@
 //! User account and role modeling for access-control exercises.
 
+use std::fmt;
+use std::str::FromStr;
+
+use ownership_exercises::{Secret, expose_serialize};
+use serde::{Deserialize, Serialize};
+
+use crate::otp::{
+    Enrollment, OtpError, RecoveryCodes, SecondFactorUsed, TotpConfig, TotpFactor,
+    looks_like_recovery_code,
+};
+use crate::password::{
+    Argon2id, HashError, PasswordHasher, Verification, verify_and_upgrade, verify_password,
+};
+
 /// Privilege tier for a security principal.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Role {
     Viewer,
     Operator,
     Admin,
 }
 
+impl Role {
+    /// Name of the [`Policy`](crate::rbac::Policy) role that every account
+    /// with this built-in role holds on `*`.
+    #[must_use]
+    pub const fn policy_name(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.policy_name())
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Viewer, Self::Operator, Self::Admin]
+            .into_iter()
+            .find(|role| role.policy_name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("unknown role {s:?} (expected viewer, operator or admin)"))
+    }
+}
+
 /// A user account with credential material and lockout tracking.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// The credential is a PHC-format password hash (never the password) held in
+/// a [`Secret`], so `{:?}` on an account prints `[REDACTED]` and the material
+/// is scrubbed when the account is dropped.
+///
+/// Serialization exists for [`AccountStore`](crate::account_store::AccountStore)
+/// files. It writes the password hash and any TOTP secret in the clear.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UserAccount {
     pub username: String,
     pub email: String,
     pub role: Role,
     pub failed_logins: u32,
     pub locked: bool,
-    credential: String,
+    #[serde(serialize_with = "expose_serialize")]
+    credential: Secret<String>,
+    #[serde(default)]
+    totp: Option<TotpFactor>,
+    #[serde(default)]
+    recovery_codes: RecoveryCodes,
 }
 
 impl UserAccount {
     /// Maximum failed attempts before the account is locked.
     pub const LOCK_THRESHOLD: u32 = 3;
 
-    /// Create a new unlocked account with zero failed login attempts.
-    #[must_use]
+    /// A stored hash that no password verifies against (like `!` in
+    /// `/etc/shadow`); use it to disable password login for an account.
+    pub const UNUSABLE_CREDENTIAL: &str = "!";
+
+    /// Create a new unlocked account, hashing `password` with [`Argon2id`]
+    /// defaults.
+    ///
+    /// # Errors
+    ///
+    /// [`HashError`] if the password could not be hashed.
     pub fn new(
         username: impl Into<String>,
         email: impl Into<String>,
-        credential: impl Into<String>,
+        password: impl Into<String>,
+        role: Role,
+    ) -> Result<Self, HashError> {
+        let password = Secret::new(password.into());
+        Self::with_hasher(username, email, password.expose(), role, &Argon2id::DEFAULT)
+    }
+
+    /// Create an account whose password is hashed by `hasher`.
+    pub fn with_hasher(
+        username: impl Into<String>,
+        email: impl Into<String>,
+        password: &str,
+        role: Role,
+        hasher: &dyn PasswordHasher,
+    ) -> Result<Self, HashError> {
+        let hash = hasher.hash(password)?;
+        Ok(Self::from_hash(username, email, hash, role))
+    }
+
+    /// Rebuild an account from a stored PHC hash string.
+    #[must_use]
+    pub fn from_hash(
+        username: impl Into<String>,
+        email: impl Into<String>,
+        password_hash: impl Into<String>,
         role: Role,
     ) -> Self {
         Self {
             username: username.into(),
             email: email.into(),
             role,
-            credential: credential.into(),
+            credential: Secret::new(password_hash.into()),
             failed_logins: 0,
             locked: false,
+            totp: None,
+            recovery_codes: RecoveryCodes::default(),
         }
     }
 
+    /// The stored PHC hash, for persisting the account.
+    #[must_use]
+    pub const fn password_hash(&self) -> &Secret<String> {
+        &self.credential
+    }
+
     /// Whether this account holds administrative privileges.
     #[must_use]
     pub const fn is_admin(&self) -> bool {
@@ -53,10 +150,75 @@ impl UserAccount {
         !self.locked
     }
 
-    /// Check a candidate credential without exposing stored material.
+    /// Check a candidate password against the stored hash (constant-time).
+    ///
+    /// An unusable or malformed stored hash never verifies.
     #[must_use]
     pub fn verify_credential(&self, candidate: &str) -> bool {
-        !self.locked && self.credential == candidate
+        !self.locked && verify_password(candidate, self.credential.expose()).unwrap_or(false)
+    }
+
+    /// Verify like [`Self::verify_credential`] and, on success, transparently
+    /// rehash with `hasher` if the stored hash is below its policy.
+    pub fn verify_and_rehash(
+        &mut self,
+        candidate: &str,
+        hasher: &dyn PasswordHasher,
+    ) -> Result<Verification, HashError> {
+        if self.locked {
+            return Ok(Verification::Invalid);
+        }
+        let outcome = verify_and_upgrade(hasher, candidate, self.credential.expose())?;
+        if let Verification::Rehashed(phc) = &outcome {
+            self.credential = Secret::new(phc.clone());
+        }
+        Ok(outcome)
+    }
+
+    /// Number of recovery codes issued at enrollment.
+    pub const RECOVERY_CODE_COUNT: usize = 10;
+
+    /// Enroll a new TOTP secret and fresh recovery codes, replacing any
+    /// existing ones.
+    ///
+    /// The returned [`Enrollment`] is the only time the secret and the codes
+    /// are available in plain text.
+    pub fn enroll_totp(
+        &mut self,
+        issuer: &str,
+        config: TotpConfig,
+    ) -> Result<Enrollment, OtpError> {
+        let factor = TotpFactor::generate(config)?;
+        let (codes, recovery_codes) = RecoveryCodes::generate(Self::RECOVERY_CODE_COUNT)?;
+        let enrollment = Enrollment {
+            secret: factor.secret_base32(),
+            uri: factor.provisioning_uri(issuer, &self.username),
+            recovery_codes,
+        };
+        self.totp = Some(factor);
+        self.recovery_codes = codes;
+        Ok(enrollment)
+    }
+
+    /// Whether a login must be completed with a one-time code.
+    #[must_use]
+    pub const fn requires_second_factor(&self) -> bool {
+        self.totp.is_some()
+    }
+
+    /// Check a TOTP code or, if it looks like one, a recovery code.
+    pub fn verify_second_factor(
+        &mut self,
+        code: &str,
+        now_ms: u64,
+    ) -> Result<SecondFactorUsed, OtpError> {
+        let totp = self.totp.as_mut().ok_or(OtpError::NotEnrolled)?;
+        if looks_like_recovery_code(code) {
+            let remaining = self.recovery_codes.redeem(code)?;
+            return Ok(SecondFactorUsed::RecoveryCode { remaining });
+        }
+        totp.verify(code, now_ms)
+            .map(|step| SecondFactorUsed::Totp { step })
     }
 
     /// Record a failed login; lock when the threshold is reached.
@@ -84,12 +246,14 @@ impl UserAccount {
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
 
     #[test]
     fn new_account_is_unlocked() {
-        let acct = UserAccount::new("alice", "alice@secops.local", "hunter2", Role::Operator);
+        let acct = UserAccount::new("alice", "alice@secops.local", "hunter2", Role::Operator)
+            .expect("hash");
         assert!(acct.can_authenticate());
         assert!(!acct.is_admin());
         assert_eq!(acct.failed_logins, 0);
@@ -97,13 +261,14 @@ mod tests {
 
     #[test]
     fn admin_role_detected() {
-        let acct = UserAccount::new("root", "root@secops.local", "x", Role::Admin);
+        let acct = UserAccount::new("root", "root@secops.local", "x", Role::Admin).expect("hash");
         assert!(acct.is_admin());
     }
 
     #[test]
     fn verify_credential_respects_lock() {
-        let mut acct = UserAccount::new("bob", "bob@secops.local", "token", Role::Viewer);
+        let mut acct =
+            UserAccount::new("bob", "bob@secops.local", "token", Role::Viewer).expect("hash");
         for _ in 0..UserAccount::LOCK_THRESHOLD {
             acct.record_failed_login();
         }
@@ -113,7 +278,8 @@ mod tests {
 
     #[test]
     fn unlock_restores_access() {
-        let mut acct = UserAccount::new("carol", "carol@secops.local", "y", Role::Viewer);
+        let mut acct =
+            UserAccount::new("carol", "carol@secops.local", "y", Role::Viewer).expect("hash");
         for _ in 0..UserAccount::LOCK_THRESHOLD {
             acct.record_failed_login();
         }
@@ -122,9 +288,63 @@ mod tests {
         assert!(acct.verify_credential("y"));
     }
 
+    #[test]
+    fn debug_redacts_credential() {
+        let acct =
+            UserAccount::new("erin", "erin@secops.local", "hunter2", Role::Viewer).expect("hash");
+        let debug = format!("{acct:?}");
+        assert!(debug.contains("[REDACTED]"));
+        assert!(!debug.contains("hunter2"));
+    }
+
+    #[test]
+    fn stores_a_hash_not_the_password() {
+        let acct =
+            UserAccount::new("fay", "fay@secops.local", "hunter2", Role::Viewer).expect("hash");
+        let stored = acct.password_hash().expose();
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(!stored.contains("hunter2"));
+        assert!(acct.verify_credential("hunter2"));
+        assert!(!acct.verify_credential("hunter3"));
+    }
+
+    #[test]
+    fn legacy_hash_is_upgraded_on_login() {
+        use crate::password::Pbkdf2Sha256;
+
+        let legacy = Pbkdf2Sha256::new(1_000);
+        let policy = Argon2id::new(256, 1, 1);
+        let acct = UserAccount::with_hasher("gus", "gus@secops.local", "pw", Role::Viewer, &legacy);
+        assert!(acct.is_ok());
+        let Ok(mut acct) = acct else { return };
+
+        assert_eq!(
+            acct.verify_and_rehash("nope", &policy),
+            Ok(Verification::Invalid)
+        );
+        assert!(acct.password_hash().expose().starts_with("$pbkdf2-sha256$"));
+        assert!(matches!(
+            acct.verify_and_rehash("pw", &policy),
+            Ok(Verification::Rehashed(_))
+        ));
+        assert!(acct.password_hash().expose().starts_with("$argon2id$"));
+        assert_eq!(
+            acct.verify_and_rehash("pw", &policy),
+            Ok(Verification::Valid)
+        );
+    }
+
+    #[test]
+    fn unusable_hash_never_verifies() {
+        let acct =
+            UserAccount::from_hash("h", "h@x", UserAccount::UNUSABLE_CREDENTIAL, Role::Viewer);
+        assert!(!acct.verify_credential(""));
+        assert!(!acct.verify_credential("!"));
+    }
+
     #[test]
     fn into_audit_line_includes_email() {
-        let acct = UserAccount::new("dave", "dave@secops.local", "z", Role::Admin);
+        let acct = UserAccount::new("dave", "dave@secops.local", "z", Role::Admin).expect("hash");
         let line = acct.into_audit_line();
         assert!(line.contains("dave@secops.local"));
         assert!(line.contains("Admin"));
@@ -0,0 +1,694 @@
+//! Account persistence behind a trait, with an append-only audit log.
+//!
+//! [`AccountStore`] is the interface the CLI and login code program against:
+//!
+//! - [`MemoryAccountStore`] keeps everything in a map, for tests and demos.
+//! - [`FileAccountStore`] persists it in a directory. `audit.jsonl` gets one
+//!   JSON line per change and is only ever appended to. `accounts.json` holds
+//!   the current accounts and is replaced atomically (write to a temp file,
+//!   then rename) on every change. The audit line is written and synced
+//!   first, so a crash can leave a record of a change that never landed but
+//!   never a change without a record.
+//!
+//! Usernames and emails are unique, ignoring ASCII case, so `Alice` can't be
+//! registered next to `alice`. Every create, update, disable, enable and
+//! delete appends an [`AuditRecord`] with the actor, a timestamp, and the role
+//! before and after.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::account::{Role, UserAccount};
+use crate::clock::{Clock, SystemClock};
+
+/// What changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Created,
+    Updated,
+    Disabled,
+    Enabled,
+    Deleted,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Disabled => "disabled",
+            Self::Enabled => "enabled",
+            Self::Deleted => "deleted",
+        })
+    }
+}
+
+/// One entry in the account audit log. Records are never modified or removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub at_ms: u64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub username: String,
+    /// `None` for [`AuditAction::Created`].
+    pub role_before: Option<Role>,
+    /// `None` for [`AuditAction::Deleted`].
+    pub role_after: Option<Role>,
+}
+
+impl fmt::Display for AuditRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} t={} actor={} {} {}",
+            self.seq, self.at_ms, self.actor, self.action, self.username
+        )?;
+        match (self.role_before, self.role_after) {
+            (Some(before), Some(after)) if before != after => {
+                write!(f, " role={before}→{after}")
+            }
+            (_, Some(role)) | (Some(role), None) => write!(f, " role={role}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Why a store operation failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreError {
+    DuplicateUsername(String),
+    DuplicateEmail(String),
+    NotFound(String),
+    /// Empty username or an email without `@`.
+    Invalid(String),
+    Io(String),
+    /// A store file exists but could not be parsed.
+    Corrupt(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateUsername(name) => write!(f, "username {name} is already taken"),
+            Self::DuplicateEmail(email) => write!(f, "email {email} is already registered"),
+            Self::NotFound(name) => write!(f, "no account named {name}"),
+            Self::Invalid(why) => write!(f, "invalid account: {why}"),
+            Self::Io(why) => write!(f, "account store I/O failed: {why}"),
+            Self::Corrupt(why) => write!(f, "account store is corrupt: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+/// Create, read, update, disable and delete accounts, auditing every change.
+pub trait AccountStore {
+    /// Add a new account. Fails if the username or email is taken.
+    fn create(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError>;
+
+    /// Replace the stored account with the same username.
+    fn update(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError>;
+
+    /// Lock the account so it cannot authenticate.
+    fn disable(&mut self, username: &str, actor: &str) -> Result<(), StoreError>;
+
+    /// Unlock the account and clear its failed-login count.
+    fn enable(&mut self, username: &str, actor: &str) -> Result<(), StoreError>;
+
+    /// Remove the account and return it.
+    fn delete(&mut self, username: &str, actor: &str) -> Result<UserAccount, StoreError>;
+
+    /// Look up by username, ignoring ASCII case like the uniqueness check.
+    fn get(&self, username: &str) -> Option<&UserAccount>;
+
+    /// Look up by email, ignoring ASCII case.
+    fn find_by_email(&self, email: &str) -> Option<&UserAccount>;
+
+    /// All accounts, ordered by username ignoring ASCII case.
+    fn accounts(&self) -> Vec<&UserAccount>;
+
+    /// Every change so far, oldest first.
+    fn audit_log(&self) -> &[AuditRecord];
+}
+
+/// Accounts and audit log held in memory.
+#[derive(Debug)]
+pub struct MemoryAccountStore<C: Clock = SystemClock> {
+    clock: C,
+    /// Keyed by [`username_key`], so `Alice` and `alice` are one account.
+    accounts: BTreeMap<String, UserAccount>,
+    audit: Vec<AuditRecord>,
+}
+
+impl MemoryAccountStore<SystemClock> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl Default for MemoryAccountStore<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> MemoryAccountStore<C> {
+    pub const fn with_clock(clock: C) -> Self {
+        Self {
+            clock,
+            accounts: BTreeMap::new(),
+            audit: Vec::new(),
+        }
+    }
+
+    fn validate(&self, account: &UserAccount, replacing: bool) -> Result<(), StoreError> {
+        if account.username.trim().is_empty() {
+            return Err(StoreError::Invalid("username is empty".into()));
+        }
+        if !account.email.contains('@') {
+            return Err(StoreError::Invalid(format!(
+                "email {:?} has no @",
+                account.email
+            )));
+        }
+        let key = username_key(&account.username);
+        if !replacing && self.accounts.contains_key(&key) {
+            return Err(StoreError::DuplicateUsername(account.username.clone()));
+        }
+        for (other_key, other) in &self.accounts {
+            if *other_key != key && other.email.eq_ignore_ascii_case(&account.email) {
+                return Err(StoreError::DuplicateEmail(account.email.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn record(
+        &mut self,
+        actor: &str,
+        action: AuditAction,
+        username: &str,
+        role_before: Option<Role>,
+        role_after: Option<Role>,
+    ) {
+        let seq = self.audit.last().map_or(1, |r| r.seq + 1);
+        self.audit.push(AuditRecord {
+            seq,
+            at_ms: self.clock.now_ms(),
+            actor: actor.to_owned(),
+            action,
+            username: username.to_owned(),
+            role_before,
+            role_after,
+        });
+    }
+
+    fn set_locked(&mut self, username: &str, actor: &str, locked: bool) -> Result<(), StoreError> {
+        let account = self
+            .accounts
+            .get_mut(&username_key(username))
+            .ok_or_else(|| StoreError::NotFound(username.to_owned()))?;
+        let username = account.username.clone();
+        if locked {
+            account.locked = true;
+        } else {
+            account.unlock();
+        }
+        let role = Some(account.role);
+        let action = if locked {
+            AuditAction::Disabled
+        } else {
+            AuditAction::Enabled
+        };
+        self.record(actor, action, &username, role, role);
+        Ok(())
+    }
+}
+
+impl<C: Clock> AccountStore for MemoryAccountStore<C> {
+    fn create(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError> {
+        self.validate(&account, false)?;
+        self.record(
+            actor,
+            AuditAction::Created,
+            &account.username,
+            None,
+            Some(account.role),
+        );
+        self.accounts
+            .insert(username_key(&account.username), account);
+        Ok(())
+    }
+
+    fn update(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError> {
+        let before = self
+            .accounts
+            .get(&username_key(&account.username))
+            .map(|a| a.role)
+            .ok_or_else(|| StoreError::NotFound(account.username.clone()))?;
+        self.validate(&account, true)?;
+        self.record(
+            actor,
+            AuditAction::Updated,
+            &account.username,
+            Some(before),
+            Some(account.role),
+        );
+        self.accounts
+            .insert(username_key(&account.username), account);
+        Ok(())
+    }
+
+    fn disable(&mut self, username: &str, actor: &str) -> Result<(), StoreError> {
+        self.set_locked(username, actor, true)
+    }
+
+    fn enable(&mut self, username: &str, actor: &str) -> Result<(), StoreError> {
+        self.set_locked(username, actor, false)
+    }
+
+    fn delete(&mut self, username: &str, actor: &str) -> Result<UserAccount, StoreError> {
+        let account = self
+            .accounts
+            .remove(&username_key(username))
+            .ok_or_else(|| StoreError::NotFound(username.to_owned()))?;
+        self.record(
+            actor,
+            AuditAction::Deleted,
+            &account.username,
+            Some(account.role),
+            None,
+        );
+        Ok(account)
+    }
+
+    fn get(&self, username: &str) -> Option<&UserAccount> {
+        self.accounts.get(&username_key(username))
+    }
+
+    fn find_by_email(&self, email: &str) -> Option<&UserAccount> {
+        self.accounts
+            .values()
+            .find(|a| a.email.eq_ignore_ascii_case(email))
+    }
+
+    fn accounts(&self) -> Vec<&UserAccount> {
+        self.accounts.values().collect()
+    }
+
+    fn audit_log(&self) -> &[AuditRecord] {
+        &self.audit
+    }
+}
+
+/// Map key for a username: usernames are unique ignoring ASCII case.
+fn username_key(username: &str) -> String {
+    username.to_ascii_lowercase()
+}
+
+/// Contents of `accounts.json`.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    accounts: Vec<UserAccount>,
+}
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Accounts in `<dir>/accounts.json`, audit log in `<dir>/audit.jsonl`.
+///
+/// Both files are created owner-read/write only on Unix, because the snapshot
+/// contains password hashes and TOTP secrets.
+#[derive(Debug)]
+pub struct FileAccountStore<C: Clock = SystemClock> {
+    dir: PathBuf,
+    inner: MemoryAccountStore<C>,
+    /// Audit records already appended to `audit.jsonl`.
+    persisted: usize,
+}
+
+impl FileAccountStore<SystemClock> {
+    /// Open (or start) the store in `dir`.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::open_with_clock(dir, SystemClock)
+    }
+}
+
+impl<C: Clock> FileAccountStore<C> {
+    pub fn open_with_clock(dir: impl AsRef<Path>, clock: C) -> Result<Self, StoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut inner = MemoryAccountStore::with_clock(clock);
+
+        let snapshot_path = dir.join("accounts.json");
+        if snapshot_path.exists() {
+            let text = fs::read_to_string(&snapshot_path)?;
+            let snapshot: Snapshot = serde_json::from_str(&text)
+                .map_err(|e| StoreError::Corrupt(format!("accounts.json: {e}")))?;
+            if snapshot.version != SNAPSHOT_VERSION {
+                return Err(StoreError::Corrupt(format!(
+                    "accounts.json: unsupported version {}",
+                    snapshot.version
+                )));
+            }
+            for account in snapshot.accounts {
+                inner
+                    .accounts
+                    .insert(username_key(&account.username), account);
+            }
+        }
+
+        let audit_path = dir.join("audit.jsonl");
+        if audit_path.exists() {
+            for (index, line) in BufReader::new(File::open(&audit_path)?).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: AuditRecord = serde_json::from_str(&line).map_err(|e| {
+                    StoreError::Corrupt(format!("audit.jsonl line {}: {e}", index + 1))
+                })?;
+                inner.audit.push(record);
+            }
+        }
+
+        let persisted = inner.audit.len();
+        Ok(Self {
+            dir,
+            inner,
+            persisted,
+        })
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Apply `change` in memory, then persist it. If persisting fails the
+    /// accounts are restored, so memory never runs ahead of the disk.
+    fn apply<T>(
+        &mut self,
+        change: impl FnOnce(&mut MemoryAccountStore<C>) -> Result<T, StoreError>,
+    ) -> Result<T, StoreError> {
+        let before = self.inner.accounts.clone();
+        let value = change(&mut self.inner)?;
+        if let Err(e) = self.persist() {
+            self.inner.accounts = before;
+            self.inner.audit.truncate(self.persisted);
+            return Err(e);
+        }
+        Ok(value)
+    }
+
+    /// Append any new audit records (write-ahead), then write the snapshot.
+    fn persist(&mut self) -> Result<(), StoreError> {
+        self.append_audit()?;
+        self.persisted = self.inner.audit.len();
+        self.write_snapshot()
+    }
+
+    fn append_audit(&self) -> Result<(), StoreError> {
+        let mut lines = String::new();
+        for record in &self.inner.audit[self.persisted..] {
+            let line = serde_json::to_string(record)
+                .map_err(|e| StoreError::Io(format!("serializing audit record: {e}")))?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        let mut log = private_file(OpenOptions::new().append(true).create(true))
+            .open(self.dir.join("audit.jsonl"))?;
+        let len = log.metadata()?.len();
+        let written = log
+            .write_all(lines.as_bytes())
+            .and_then(|()| log.sync_all());
+        if let Err(e) = written {
+            // Don't leave a torn line behind; best effort, the write already failed.
+            let _ = log.set_len(len);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn write_snapshot(&self) -> Result<(), StoreError> {
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            accounts: self.inner.accounts.values().cloned().collect(),
+        };
+        let json = serde_json::to_vec_pretty(&snapshot)
+            .map_err(|e| StoreError::Io(format!("serializing accounts: {e}")))?;
+        let tmp = self.dir.join("accounts.json.tmp");
+        {
+            let mut file = private_file(OpenOptions::new().write(true).create(true).truncate(true))
+                .open(&tmp)?;
+            file.write_all(&json)?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, self.dir.join("accounts.json"))?;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn private_file(options: &mut OpenOptions) -> &mut OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600)
+}
+
+#[cfg(not(unix))]
+fn private_file(options: &mut OpenOptions) -> &mut OpenOptions {
+    options
+}
+
+impl<C: Clock> AccountStore for FileAccountStore<C> {
+    fn create(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError> {
+        self.apply(|inner| inner.create(account, actor))
+    }
+
+    fn update(&mut self, account: UserAccount, actor: &str) -> Result<(), StoreError> {
+        self.apply(|inner| inner.update(account, actor))
+    }
+
+    fn disable(&mut self, username: &str, actor: &str) -> Result<(), StoreError> {
+        self.apply(|inner| inner.disable(username, actor))
+    }
+
+    fn enable(&mut self, username: &str, actor: &str) -> Result<(), StoreError> {
+        self.apply(|inner| inner.enable(username, actor))
+    }
+
+    fn delete(&mut self, username: &str, actor: &str) -> Result<UserAccount, StoreError> {
+        self.apply(|inner| inner.delete(username, actor))
+    }
+
+    fn get(&self, username: &str) -> Option<&UserAccount> {
+        self.inner.get(username)
+    }
+
+    fn find_by_email(&self, email: &str) -> Option<&UserAccount> {
+        self.inner.find_by_email(email)
+    }
+
+    fn accounts(&self) -> Vec<&UserAccount> {
+        self.inner.accounts()
+    }
+
+    fn audit_log(&self) -> &[AuditRecord] {
+        self.inner.audit_log()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    fn account(name: &str, email: &str, role: Role) -> UserAccount {
+        UserAccount::from_hash(
+            name,
+            email,
+            "$argon2id$v=19$m=8,t=1,p=1$c2FsdA$aGFzaA",
+            role,
+        )
+    }
+
+    fn store() -> (ManualClock, MemoryAccountStore<ManualClock>) {
+        let clock = ManualClock::new(5_000);
+        (clock.clone(), MemoryAccountStore::with_clock(clock))
+    }
+
+    #[test]
+    fn usernames_and_emails_are_unique_ignoring_case() {
+        let (_, mut store) = store();
+        assert!(
+            store
+                .create(account("alice", "alice@x.local", Role::Viewer), "root")
+                .is_ok()
+        );
+        assert_eq!(
+            store.create(account("Alice", "other@x.local", Role::Viewer), "root"),
+            Err(StoreError::DuplicateUsername("Alice".into()))
+        );
+        assert_eq!(
+            store.create(account("bob", "ALICE@x.local", Role::Viewer), "root"),
+            Err(StoreError::DuplicateEmail("ALICE@x.local".into()))
+        );
+        assert!(matches!(
+            store.create(account(" ", "x@x.local", Role::Viewer), "root"),
+            Err(StoreError::Invalid(_))
+        ));
+        assert!(matches!(
+            store.create(account("carol", "carol", Role::Viewer), "root"),
+            Err(StoreError::Invalid(_))
+        ));
+        assert_eq!(
+            store
+                .find_by_email("Alice@X.local")
+                .map(|a| a.username.as_str()),
+            Some("alice")
+        );
+        assert_eq!(
+            store.get("ALICE").map(|a| a.username.as_str()),
+            Some("alice")
+        );
+        // Failed operations leave no audit trail.
+        assert_eq!(store.audit_log().len(), 1);
+    }
+
+    #[test]
+    fn update_keeps_uniqueness_and_records_role_change() {
+        let (clock, mut store) = store();
+        assert!(
+            store
+                .create(account("alice", "alice@x.local", Role::Viewer), "root")
+                .is_ok()
+        );
+        assert!(
+            store
+                .create(account("bob", "bob@x.local", Role::Viewer), "root")
+                .is_ok()
+        );
+
+        let mut alice = store
+            .get("alice")
+            .cloned()
+            .unwrap_or_else(|| account("", "", Role::Viewer));
+        alice.email = "BOB@x.local".into();
+        assert!(matches!(
+            store.update(alice.clone(), "root"),
+            Err(StoreError::DuplicateEmail(_))
+        ));
+        alice.email = "alice@x.local".into();
+        alice.role = Role::Operator;
+        clock.advance(1_000);
+        assert!(store.update(alice, "root").is_ok());
+        assert_eq!(
+            store.update(account("zed", "z@x.local", Role::Admin), "root"),
+            Err(StoreError::NotFound("zed".into()))
+        );
+
+        let last = store.audit_log().last().cloned();
+        assert!(last.is_some_and(|r| r.action == AuditAction::Updated
+            && r.at_ms == 6_000
+            && r.role_before == Some(Role::Viewer)
+            && r.role_after == Some(Role::Operator)
+            && r.to_string() == "#3 t=6000 actor=root updated alice role=viewer→operator"));
+    }
+
+    #[test]
+    fn disable_enable_delete_are_audited() {
+        let (_, mut store) = store();
+        assert!(
+            store
+                .create(account("alice", "alice@x.local", Role::Admin), "root")
+                .is_ok()
+        );
+        assert!(store.disable("alice", "soc").is_ok());
+        assert!(store.get("alice").is_some_and(|a| !a.can_authenticate()));
+        assert!(store.enable("alice", "soc").is_ok());
+        assert!(
+            store
+                .get("alice")
+                .is_some_and(UserAccount::can_authenticate)
+        );
+        assert!(store.delete("alice", "root").is_ok());
+        assert_eq!(
+            store.disable("alice", "soc"),
+            Err(StoreError::NotFound("alice".into()))
+        );
+
+        let actions: Vec<_> = store.audit_log().iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            [
+                AuditAction::Created,
+                AuditAction::Disabled,
+                AuditAction::Enabled,
+                AuditAction::Deleted
+            ]
+        );
+        assert_eq!(store.audit_log()[3].role_after, None);
+        assert!(store.accounts().is_empty());
+    }
+
+    #[test]
+    fn file_store_writes_audit_ahead_and_rolls_back_on_failure() {
+        let dir = std::env::temp_dir().join(format!("structs-wal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut store =
+            FileAccountStore::open_with_clock(&dir, ManualClock::new(1)).expect("open store");
+        assert!(
+            store
+                .create(account("alice", "alice@x.local", Role::Viewer), "root")
+                .is_ok()
+        );
+
+        // The snapshot can't be written: the audit line lands, the change doesn't.
+        assert!(fs::create_dir(dir.join("accounts.json.tmp")).is_ok());
+        assert!(matches!(
+            store.create(account("bob", "bob@x.local", Role::Viewer), "root"),
+            Err(StoreError::Io(_))
+        ));
+        assert!(store.get("bob").is_none());
+        assert_eq!(store.audit_log().len(), 2);
+        let _ = fs::remove_dir(dir.join("accounts.json.tmp"));
+
+        // The audit log can't be appended: nothing changes anywhere.
+        let audit = dir.join("audit.jsonl");
+        assert!(fs::rename(&audit, dir.join("audit.bak")).is_ok());
+        assert!(fs::create_dir(&audit).is_ok());
+        assert!(matches!(
+            store.disable("alice", "soc"),
+            Err(StoreError::Io(_))
+        ));
+        assert!(
+            store
+                .get("alice")
+                .is_some_and(UserAccount::can_authenticate)
+        );
+        assert_eq!(store.audit_log().len(), 2);
+        let _ = fs::remove_dir(&audit);
+        assert!(fs::rename(dir.join("audit.bak"), &audit).is_ok());
+
+        let reopened = FileAccountStore::open_with_clock(&dir, ManualClock::new(2));
+        assert!(reopened.is_ok_and(|s| s.get("bob").is_none()
+            && s.get("alice").is_some_and(UserAccount::can_authenticate)
+            && s.audit_log().len() == 2));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
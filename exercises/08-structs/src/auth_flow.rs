@@ -1,21 +1,156 @@
 //! End-to-end authentication flow combining accounts and sessions.
 
 use anyhow::{Result, bail};
+use ownership_exercises::REDACTED;
 
+use crate::DEFAULT_POLICY;
 use crate::account::{Role, UserAccount};
+use crate::clock::Clock;
+use crate::otp::{TotpConfig, TotpFactor};
+use crate::password::{Argon2id, Pbkdf2Sha256, Verification};
+use crate::rbac::Policy;
 use crate::session::Session;
+use crate::session_store::{SessionStore, generate_token};
 
-/// Attempt login: verify credentials and mint a session on success.
-pub fn authenticate(account: &UserAccount, candidate: &str) -> Result<Session> {
+/// How long a user has to enter a one-time code after their password.
+pub const SECOND_FACTOR_TTL_MS: u64 = 5 * 60 * 1000;
+
+/// Wrong codes a single [`PendingLogin`] tolerates before it is spent.
+pub const MAX_SECOND_FACTOR_ATTEMPTS: u32 = 5;
+
+fn check_password(account: &UserAccount, candidate: &str) -> Result<()> {
     if !account.can_authenticate() {
         bail!("account {} is locked", account.username);
     }
-    if account.verify_credential(candidate) {
-        let token = format!("sess-{}-{}", account.username, account.email.len());
-        Ok(Session::new(account.username.clone(), token))
-    } else {
+    if !account.verify_credential(candidate) {
         bail!("invalid credential for {}", account.username);
     }
+    Ok(())
+}
+
+fn check_credentials(account: &UserAccount, candidate: &str) -> Result<()> {
+    check_password(account, candidate)?;
+    if account.requires_second_factor() {
+        bail!(
+            "{} has a second factor enrolled; use start_login",
+            account.username
+        );
+    }
+    Ok(())
+}
+
+/// Attempt login: verify credentials and mint a random session token on success.
+///
+/// The token is not tracked anywhere; use [`login`] to register it in a
+/// [`SessionStore`] so it can expire and be revoked. Accounts with a second
+/// factor are refused; they must go through [`start_login`].
+pub fn authenticate(account: &UserAccount, candidate: &str) -> Result<Session> {
+    check_credentials(account, candidate)?;
+    Ok(Session::new(account.username.clone(), generate_token()?))
+}
+
+/// Verify credentials and issue a session tracked by `store`.
+///
+/// Like [`authenticate`], this refuses accounts with a second factor.
+pub fn login<C: Clock>(
+    store: &mut SessionStore<C>,
+    account: &UserAccount,
+    candidate: &str,
+) -> Result<Session> {
+    check_credentials(account, candidate)?;
+    Ok(store.issue(&account.username, account.role)?)
+}
+
+/// Password accepted; a one-time code is still required.
+///
+/// Only [`start_login`] creates one, so holding a `PendingLogin` proves the
+/// password step passed. It expires after [`SECOND_FACTOR_TTL_MS`], allows at
+/// most [`MAX_SECOND_FACTOR_ATTEMPTS`] wrong codes and is spent once a code is
+/// accepted. It is deliberately not `Clone`: a copy would reset the budget.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PendingLogin {
+    user: String,
+    expires_at_ms: u64,
+    attempts_left: u32,
+}
+
+impl PendingLogin {
+    #[must_use]
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    #[must_use]
+    pub const fn expires_at_ms(&self) -> u64 {
+        self.expires_at_ms
+    }
+
+    /// Codes that may still be tried; zero once spent or exhausted.
+    #[must_use]
+    pub const fn attempts_left(&self) -> u32 {
+        self.attempts_left
+    }
+}
+
+/// Result of the password step.
+#[derive(Debug)]
+pub enum LoginStep {
+    /// No second factor enrolled; the session is live.
+    Authenticated(Session),
+    SecondFactorRequired(PendingLogin),
+}
+
+/// Check the password. Accounts without a second factor get a session right
+/// away; the others get a [`PendingLogin`] to complete with [`finish_login`].
+pub fn start_login<C: Clock>(
+    store: &mut SessionStore<C>,
+    account: &UserAccount,
+    candidate: &str,
+) -> Result<LoginStep> {
+    check_password(account, candidate)?;
+    if !account.requires_second_factor() {
+        return Ok(LoginStep::Authenticated(
+            store.issue(&account.username, account.role)?,
+        ));
+    }
+    Ok(LoginStep::SecondFactorRequired(PendingLogin {
+        user: account.username.clone(),
+        expires_at_ms: store.now_ms().saturating_add(SECOND_FACTOR_TTL_MS),
+        attempts_left: MAX_SECOND_FACTOR_ATTEMPTS,
+    }))
+}
+
+/// Complete a pending login with a TOTP code or a recovery code.
+///
+/// Every rejected code uses up one of the challenge's attempts and counts as a
+/// failed login on the account, so guessing codes runs into the same lockout
+/// as guessing passwords.
+pub fn finish_login<C: Clock>(
+    store: &mut SessionStore<C>,
+    pending: &mut PendingLogin,
+    account: &mut UserAccount,
+    code: &str,
+) -> Result<Session> {
+    if pending.user != account.username {
+        bail!("pending login belongs to {}", pending.user);
+    }
+    if pending.attempts_left == 0 {
+        bail!("second-factor challenge for {} is spent", pending.user);
+    }
+    let now = store.now_ms();
+    if now >= pending.expires_at_ms {
+        bail!("second-factor step for {} timed out", pending.user);
+    }
+    if !account.can_authenticate() {
+        bail!("account {} is locked", account.username);
+    }
+    if let Err(e) = account.verify_second_factor(code, now) {
+        pending.attempts_left -= 1;
+        account.record_failed_login();
+        return Err(e.into());
+    }
+    pending.attempts_left = 0;
+    Ok(store.issue(&account.username, account.role)?)
 }
 
 /// Run the authentication-flow exercise.
@@ -23,20 +158,39 @@ pub fn run(verbose: bool) -> Result<()> {
     println!("🔐 Authentication Flow — Accounts & Sessions");
     println!();
 
-    let mut operator = UserAccount::new(
-        "alice",
-        "alice@secops.local",
-        "hunter2-hash",
-        Role::Operator,
-    );
+    let mut operator = UserAccount::new("alice", "alice@secops.local", "hunter2", Role::Operator)?;
     println!("1. Created operator account: {}", operator.username);
     println!("   email: {}", operator.email);
+    println!(
+        "   stored hash: {}",
+        phc_prefix(operator.password_hash().expose())
+    );
     println!("   can authenticate: {}", operator.can_authenticate());
 
-    match authenticate(&operator, "hunter2-hash") {
+    let mut store = SessionStore::new();
+    match login(&mut store, &operator, "hunter2") {
         Ok(session) => {
             println!("2. Login succeeded → session user={}", session.user);
-            println!("   token match: {}", session.matches_token(&session.token));
+            println!(
+                "   token match: {}",
+                session.matches_token(session.token.expose())
+            );
+            println!("   debug view: {session:?}");
+            if let Ok(info) = store.validate(session.token.expose()) {
+                println!("   store validates token → role={:?}", info.role);
+            }
+            match store.rotate(session.token.expose(), Role::Admin) {
+                Ok(elevated) => println!(
+                    "   privilege change rotated token; old valid={}, new valid={}",
+                    store.validate(session.token.expose()).is_ok(),
+                    store.validate(elevated.token.expose()).is_ok()
+                ),
+                Err(e) => println!("   rotation failed: {e}"),
+            }
+            println!(
+                "   revoked {} session(s) for alice",
+                store.revoke_user("alice")
+            );
         }
         Err(e) => println!("2. Login failed: {e}"),
     }
@@ -51,7 +205,7 @@ pub fn run(verbose: bool) -> Result<()> {
         );
     }
 
-    let locked_result = authenticate(&operator, "hunter2-hash");
+    let locked_result = authenticate(&operator, "hunter2");
     if locked_result.is_err() {
         println!("4. Locked account correctly rejects login");
     }
@@ -62,34 +216,261 @@ pub fn run(verbose: bool) -> Result<()> {
         operator.can_authenticate()
     );
 
+    println!();
+    println!("6. Upgrading a legacy PBKDF2 hash on successful login:");
+    let legacy = Pbkdf2Sha256::new(10_000);
+    match UserAccount::with_hasher("bob", "bob@secops.local", "s3cret", Role::Viewer, &legacy) {
+        Ok(mut bob) => {
+            println!("   before: {}", phc_prefix(bob.password_hash().expose()));
+            match bob.verify_and_rehash("s3cret", &Argon2id::DEFAULT) {
+                Ok(Verification::Rehashed(_)) => {
+                    println!("   after:  {}", phc_prefix(bob.password_hash().expose()));
+                }
+                Ok(other) => println!("   no upgrade: {other:?}"),
+                Err(e) => println!("   verification error: {e}"),
+            }
+        }
+        Err(e) => println!("   hashing failed: {e}"),
+    }
+
+    println!();
+    println!("7. Permission checks from the bundled policy:");
+    let policy = Policy::parse(DEFAULT_POLICY)?;
+    let carol = UserAccount::from_hash("carol", "carol@secops.local", "!", Role::Viewer);
+    for (action, resource) in [
+        ("alerts.read", "cluster-b"),
+        ("cluster.restart", "cluster-a/pods/web"),
+        ("cluster.restart", "cluster-ab"),
+        ("cluster.restart", "cluster-a/../cluster-b"),
+        ("users.manage", "cluster-a"),
+    ] {
+        println!("   {}", policy.authorize(&carol, action, resource));
+    }
+
+    println!();
+    println!("8. Adding a TOTP second factor:");
+    let mut mfa_user =
+        UserAccount::new("erin", "erin@secops.local", "correct horse", Role::Operator)?;
+    let enrollment = mfa_user.enroll_totp("SecOps Lab", TotpConfig::default())?;
+    println!(
+        "   provisioning URI: {}",
+        redact_secret_param(enrollment.uri.expose())
+    );
+    println!(
+        "   {} recovery codes issued (shown once, stored hashed)",
+        enrollment.recovery_codes.len()
+    );
+    // The user's authenticator app, holding the same secret.
+    let app = TotpFactor::from_base32(enrollment.secret.expose(), TotpConfig::default())?;
+    let mut store = SessionStore::new();
+    if let LoginStep::SecondFactorRequired(mut pending) =
+        start_login(&mut store, &mfa_user, "correct horse")?
+    {
+        println!("   password accepted → second factor required");
+        let code = app.code_at(store.now_ms())?;
+        let session = finish_login(&mut store, &mut pending, &mut mfa_user, &code)?;
+        println!("   TOTP accepted → session for {}", session.user);
+        if let Err(e) = finish_login(&mut store, &mut pending, &mut mfa_user, &code) {
+            println!("   same challenge again → {e}");
+        }
+    }
+
     if verbose {
         println!();
         println!("   Admin audit line:");
-        let admin = UserAccount::new("root", "root@secops.local", "admin-key", Role::Admin);
+        let admin = UserAccount::new("root", "root@secops.local", "admin-key", Role::Admin)?;
         println!("   {}", admin.into_audit_line());
     }
 
     Ok(())
 }
 
+/// An `otpauth://` URI with the secret replaced by `[REDACTED]`.
+fn redact_secret_param(uri: &str) -> String {
+    uri.split('&')
+        .map(|part| match part.split_once("secret=") {
+            Some((head, _)) => format!("{head}secret={REDACTED}"),
+            None => part.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Algorithm and parameters of a PHC string, without salt or hash.
+fn phc_prefix(phc: &str) -> String {
+    let fields: Vec<&str> = phc.split('$').collect();
+    let shown = fields.len().saturating_sub(2).max(1);
+    format!("{}$…", fields[..shown].join("$"))
+}
+
 #[cfg(test)]
+#[allow(clippy::expect_used)]
 mod tests {
     use super::*;
+    use crate::clock::ManualClock;
+    use crate::session_store::SessionConfig;
 
     #[test]
     fn authenticate_success() {
-        let acct = UserAccount::new("alice", "a@x.local", "secret", Role::Viewer);
+        let acct = UserAccount::new("alice", "a@x.local", "secret", Role::Viewer).expect("hash");
         let session = authenticate(&acct, "secret");
         assert!(session.is_ok());
         if let Ok(s) = session {
             assert_eq!(s.user, "alice");
-            assert!(s.matches_token(&s.token));
+            assert!(s.matches_token(s.token.expose()));
+        }
+    }
+
+    #[test]
+    fn phc_prefix_hides_salt_and_hash() {
+        assert_eq!(
+            phc_prefix("$argon2id$v=19$m=8,t=1,p=1$c2FsdA$aGFzaA"),
+            "$argon2id$v=19$m=8,t=1,p=1$…"
+        );
+        assert_eq!(phc_prefix("!"), "!$…");
+    }
+
+    #[test]
+    fn tokens_are_not_derived_from_the_account() {
+        let acct = UserAccount::new("alice", "a@x.local", "secret", Role::Viewer).expect("hash");
+        let first = authenticate(&acct, "secret");
+        let second = authenticate(&acct, "secret");
+        assert!(first.is_ok() && second.is_ok());
+        if let (Ok(a), Ok(b)) = (first, second) {
+            assert_ne!(a.token, b.token);
+            assert!(!a.token.expose().contains("alice"));
+        }
+    }
+
+    #[test]
+    fn login_registers_session() {
+        let acct = UserAccount::new("alice", "a@x.local", "secret", Role::Operator).expect("hash");
+        let mut store = SessionStore::new();
+        assert!(login(&mut store, &acct, "wrong").is_err());
+        let session = login(&mut store, &acct, "secret");
+        assert!(session.is_ok());
+        if let Ok(s) = session {
+            assert_eq!(
+                store.validate(s.token.expose()).map(|i| i.role),
+                Ok(Role::Operator)
+            );
+        }
+    }
+
+    fn mfa_store() -> (ManualClock, SessionStore<ManualClock>) {
+        let clock = ManualClock::new(1_700_000_000_000);
+        let store = SessionStore::with_clock(clock.clone(), SessionConfig::default());
+        (clock, store)
+    }
+
+    fn start(store: &mut SessionStore<ManualClock>, acct: &UserAccount) -> PendingLogin {
+        match start_login(store, acct, "secret") {
+            Ok(LoginStep::SecondFactorRequired(pending)) => Some(pending),
+            _ => None,
+        }
+        .expect("second factor required")
+    }
+
+    #[test]
+    fn second_factor_is_required_once_enrolled() {
+        let mut acct =
+            UserAccount::new("mfa", "m@x.local", "secret", Role::Operator).expect("hash");
+        let (clock, mut store) = mfa_store();
+        assert!(matches!(
+            start_login(&mut store, &acct, "secret"),
+            Ok(LoginStep::Authenticated(_))
+        ));
+        let enrollment = acct
+            .enroll_totp("Lab", TotpConfig::default())
+            .expect("enroll");
+        let app = TotpFactor::from_base32(enrollment.secret.expose(), TotpConfig::default())
+            .expect("authenticator");
+
+        // Single-step entry points no longer work.
+        assert!(authenticate(&acct, "secret").is_err());
+        assert!(login(&mut store, &acct, "secret").is_err());
+        assert!(start_login(&mut store, &acct, "wrong").is_err());
+
+        let mut pending = start(&mut store, &acct);
+        assert_eq!(pending.user(), "mfa");
+        assert!(finish_login(&mut store, &mut pending, &mut acct, "000000x").is_err());
+        assert_eq!(pending.attempts_left(), MAX_SECOND_FACTOR_ATTEMPTS - 1);
+        let code = app.code_at(clock.now_ms()).expect("code");
+        let session = finish_login(&mut store, &mut pending, &mut acct, &code);
+        assert!(session.is_ok_and(|s| s.user == "mfa"));
+        assert_eq!(pending.attempts_left(), 0);
+        // The same code can't complete a second login.
+        let mut again = start(&mut store, &acct);
+        assert!(finish_login(&mut store, &mut again, &mut acct, &code).is_err());
+    }
+
+    #[test]
+    fn wrong_codes_lock_the_account_and_spend_the_challenge() {
+        let mut acct =
+            UserAccount::new("mfa", "m@x.local", "secret", Role::Operator).expect("hash");
+        let (clock, mut store) = mfa_store();
+        let enrollment = acct
+            .enroll_totp("Lab", TotpConfig::default())
+            .expect("enroll");
+        let app = TotpFactor::from_base32(enrollment.secret.expose(), TotpConfig::default())
+            .expect("authenticator");
+        let code = app.code_at(clock.now_ms()).expect("code");
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+
+        let mut pending = start(&mut store, &acct);
+        for _ in 0..UserAccount::LOCK_THRESHOLD {
+            assert!(acct.can_authenticate());
+            assert!(finish_login(&mut store, &mut pending, &mut acct, wrong).is_err());
         }
+        assert!(acct.locked);
+        assert!(finish_login(&mut store, &mut pending, &mut acct, &code).is_err());
+
+        // Even with the lock lifted, the challenge runs out of attempts.
+        while pending.attempts_left() > 0 {
+            acct.unlock();
+            assert!(finish_login(&mut store, &mut pending, &mut acct, wrong).is_err());
+        }
+        acct.unlock();
+        let spent = finish_login(&mut store, &mut pending, &mut acct, &code);
+        assert!(spent.is_err_and(|e| e.to_string().contains("spent")));
+        assert!(!acct.locked);
+    }
+
+    #[test]
+    fn pending_login_expires_and_is_bound_to_its_user() {
+        let mut acct = UserAccount::new("mfa", "m@x.local", "secret", Role::Viewer).expect("hash");
+        let mut other =
+            UserAccount::new("other", "o@x.local", "secret", Role::Viewer).expect("hash");
+        let enrolled = acct
+            .enroll_totp("Lab", TotpConfig::default())
+            .expect("enroll");
+        assert!(other.enroll_totp("Lab", TotpConfig::default()).is_ok());
+        let (clock, mut store) = mfa_store();
+
+        let mut pending = start(&mut store, &acct);
+        let code = enrolled.recovery_codes[0].expose().clone();
+        assert!(finish_login(&mut store, &mut pending, &mut other, &code).is_err());
+
+        clock.advance(SECOND_FACTOR_TTL_MS);
+        assert!(finish_login(&mut store, &mut pending, &mut acct, &code).is_err());
+
+        // A fresh attempt with a recovery code works, once.
+        let mut pending = start(&mut store, &acct);
+        assert!(finish_login(&mut store, &mut pending, &mut acct, &code).is_ok());
+        assert!(finish_login(&mut store, &mut pending, &mut acct, &code).is_err());
+    }
+
+    #[test]
+    fn totp_secret_is_redacted_in_demo_output() {
+        assert_eq!(
+            redact_secret_param("otpauth://totp/x:y?secret=ABC&issuer=x"),
+            "otpauth://totp/x:y?secret=[REDACTED]&issuer=x"
+        );
     }
 
     #[test]
     fn authenticate_locked_fails() {
-        let mut acct = UserAccount::new("bob", "b@x.local", "x", Role::Viewer);
+        let mut acct = UserAccount::new("bob", "b@x.local", "x", Role::Viewer).expect("hash");
         for _ in 0..UserAccount::LOCK_THRESHOLD {
             acct.record_failed_login();
         }
@@ -0,0 +1,8 @@
+//! Injectable time source for expiry and lockout logic.
+//!
+//! The trait and both clocks come from `examples/05-controlFlow`, which
+//! introduced them for its escalation queue. Production code uses
+//! [`SystemClock`]; tests hand a [`ManualClock`] to the component and keep a
+//! clone to move time forward deterministically.
+
+pub use example_controlflow::clock::{Clock, ManualClock, SystemClock};
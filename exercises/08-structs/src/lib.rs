@@ -4,12 +4,43 @@
 //! private fields, and struct update patterns.
 
 pub mod account;
+pub mod account_store;
 pub mod auth_flow;
+pub mod clock;
 pub mod lockout;
+pub mod lockout_policy;
+pub mod otp;
+pub mod password;
+pub mod rbac;
 pub mod session;
+pub mod session_store;
 
 pub use account::{Role, UserAccount};
+pub use account_store::{
+    AccountStore, AuditAction, AuditRecord, FileAccountStore, MemoryAccountStore, StoreError,
+};
+pub use auth_flow::{LoginStep, PendingLogin, finish_login, start_login};
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use lockout_policy::{
+    LockScope, LockoutConfig, LockoutDecision, LockoutError, LockoutEvent, LockoutEventKind,
+    LockoutPolicy, ResetRules, UnlockReason,
+};
+pub use otp::{
+    Enrollment, OtpAlgorithm, OtpError, RecoveryCodes, SecondFactorUsed, TotpConfig, TotpFactor,
+    base32_decode, base32_encode, hotp,
+};
+pub use password::{
+    Argon2id, HashError, PasswordHasher, Pbkdf2Sha256, Verification, verify_and_upgrade,
+    verify_password,
+};
+pub use rbac::{AccessDecision, DecisionReason, Grant, Policy, PolicyParseError, RoleDef, Scope};
 pub use session::Session;
+pub use session_store::{
+    Expiry, SessionConfig, SessionError, SessionInfo, SessionStore, generate_token,
+};
+
+/// Policy file shipped with the crate (`policies/default.policy`).
+pub const DEFAULT_POLICY: &str = include_str!("../policies/default.policy");
 
 pub type Result<T> = anyhow::Result<T>;
 
@@ -30,9 +61,13 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
             description: "Authenticate users and mint session tokens",
             concepts: vec![
                 "Struct fields and private credentials",
+                "Password hashing behind a trait (PBKDF2, Argon2id)",
                 "Method receivers (&self, &mut self, self)",
                 "Session token matching",
+                "Session store: CSPRNG tokens, hashed at rest, expiry, revocation",
                 "Account lockout after failed attempts",
+                "Permission policy with inherited roles and scoped grants",
+                "TOTP second factor with replay guard and recovery codes",
             ],
         },
         ExerciseInfo {
@@ -43,6 +78,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Mutable state via &mut self",
                 "Role-based privilege checks",
                 "Audit line generation",
+                "LockoutPolicy with backoff and per-IP throttling",
             ],
         },
     ]
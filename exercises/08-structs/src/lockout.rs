@@ -1,8 +1,12 @@
 //! Account lockout policy demonstration.
 
+use std::net::{IpAddr, Ipv4Addr};
+
 use anyhow::Result;
 
 use crate::account::{Role, UserAccount};
+use crate::clock::ManualClock;
+use crate::lockout_policy::{LockScope, LockoutConfig, LockoutPolicy, UnlockReason};
 
 /// Run the lockout-policy exercise.
 pub fn run(verbose: bool) -> Result<()> {
@@ -18,7 +22,7 @@ pub fn run(verbose: bool) -> Result<()> {
         "bot@scanner.local",
         "probe-token",
         Role::Viewer,
-    );
+    )?;
 
     println!();
     println!(
@@ -39,19 +43,80 @@ pub fn run(verbose: bool) -> Result<()> {
         }
     }
 
+    println!();
+    adaptive_policy(&viewer)?;
+
     if verbose {
         println!();
         println!("Post-lockout state:");
         println!("  is_admin: {}", viewer.is_admin());
         println!("  can_authenticate: {}", viewer.can_authenticate());
         let audit =
-            UserAccount::new("audit", "audit@secops.local", "n/a", Role::Admin).into_audit_line();
+            UserAccount::new("audit", "audit@secops.local", "n/a", Role::Admin)?.into_audit_line();
         println!("  sample audit: {audit}");
     }
 
     Ok(())
 }
 
+/// Replay the same probes against a [`LockoutPolicy`] on a simulated clock.
+fn adaptive_policy(target: &UserAccount) -> Result<()> {
+    const SPRAYER: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 9));
+    const OFFICE: IpAddr = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 20));
+
+    let clock = ManualClock::new(0);
+    let config = LockoutConfig {
+        account_threshold: UserAccount::LOCK_THRESHOLD,
+        source_threshold: 8,
+        ..LockoutConfig::default()
+    };
+    let mut policy = LockoutPolicy::with_clock(clock.clone(), config);
+    let user = target.username.as_str();
+
+    println!("Adaptive policy (backoff, per-IP throttling):");
+    for round in 1..=2 {
+        for _ in 0..UserAccount::LOCK_THRESHOLD {
+            policy.attempt(user, SPRAYER, || target.verify_credential("wrong-password"));
+        }
+        let until = policy.locked_until(&LockScope::Account(user.to_owned()));
+        println!(
+            "  round {round}: {user} locked until t={}ms",
+            until.unwrap_or(0)
+        );
+        clock.set(until.unwrap_or(0));
+    }
+
+    for victim in ["alice", "bob"] {
+        policy.attempt(victim, SPRAYER, || false);
+    }
+    println!(
+        "  spraying host throttled until t={}ms; alice from office: {:?}",
+        policy
+            .locked_until(&LockScope::Source(SPRAYER))
+            .unwrap_or(0),
+        policy.check("alice", OFFICE)
+    );
+
+    let admin = UserAccount::new("soc-lead", "lead@secops.local", "n/a", Role::Admin)?;
+    match policy.admin_unlock(
+        &admin,
+        &LockScope::Source(SPRAYER),
+        UnlockReason::FalsePositive,
+    ) {
+        Ok(()) => println!("  admin lifted the source throttle"),
+        Err(e) => println!("  unlock refused: {e}"),
+    }
+
+    println!(
+        "  audit trail ({} events), last three:",
+        policy.events().len()
+    );
+    for event in policy.events().iter().rev().take(3).rev() {
+        println!("    {event}");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
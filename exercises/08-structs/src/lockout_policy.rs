@@ -0,0 +1,937 @@
+//! Adaptive lockout: exponential backoff, per-source throttling and audited
+//! unlocks.
+//!
+//! [`UserAccount::record_failed_login`] locks an account for good after
+//! [`UserAccount::LOCK_THRESHOLD`] failures. [`LockoutPolicy`] replaces that
+//! with time-boxed locks:
+//!
+//! - Failures are counted separately per account and per source IP. A
+//!   password-spraying host trips its own source limit long before it can lock
+//!   out every user it touches.
+//! - Each lock lasts `base_lockout_ms × backoff_factor^n`, where `n` is the
+//!   number of earlier locks, capped at `max_lockout_ms`. It expires on its own;
+//!   no one has to clear it.
+//! - A successful login resets counters according to [`ResetRules`].
+//! - An admin can lift a lock early, but must give an [`UnlockReason`].
+//! - Every failure, lock, block, expiry, reset and unlock appends a
+//!   [`LockoutEvent`] carrying the real `until` timestamp, ready for an
+//!   `AuthStatus::Locked` response or an audit log. Routine allowed checks are
+//!   not logged.
+//!
+//! Memory stays bounded under the traffic this defends against: counters idle
+//! for longer than `failure_window_ms` are swept, and only the newest
+//! `max_events` events are kept.
+//!
+//! [`UserAccount::record_failed_login`]: crate::account::UserAccount::record_failed_login
+//! [`UserAccount::LOCK_THRESHOLD`]: crate::account::UserAccount::LOCK_THRESHOLD
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::IpAddr;
+
+use crate::account::UserAccount;
+use crate::clock::{Clock, SystemClock};
+
+/// Which counters a successful login clears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetRules {
+    /// Forget the account's recent failures.
+    pub account_failures: bool,
+    /// Forget how many times the account has been locked, so the next lock
+    /// starts again at `base_lockout_ms`.
+    pub account_backoff: bool,
+    /// Forget the source's recent failures. Off by default: one valid
+    /// credential should not give a spraying host a fresh budget.
+    pub source_failures: bool,
+}
+
+impl Default for ResetRules {
+    fn default() -> Self {
+        Self {
+            account_failures: true,
+            account_backoff: false,
+            source_failures: false,
+        }
+    }
+}
+
+/// Thresholds and timings for [`LockoutPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutConfig {
+    /// Failures against one account before it is locked.
+    pub account_threshold: u32,
+    /// Failures from one source IP, across all accounts, before it is throttled.
+    pub source_threshold: u32,
+    /// Length of the first lock.
+    pub base_lockout_ms: u64,
+    /// Each later lock lasts this many times longer than the one before.
+    pub backoff_factor: u32,
+    /// Upper bound on any single lock.
+    pub max_lockout_ms: u64,
+    /// A failure older than this no longer counts toward a threshold. A
+    /// counter idle this long after its last failure or lock is forgotten,
+    /// backoff history included.
+    pub failure_window_ms: u64,
+    pub reset: ResetRules,
+    /// Audit events kept; older ones are dropped first.
+    pub max_events: usize,
+}
+
+impl Default for LockoutConfig {
+    /// 5 failures per account, 20 per source, locks of 1 minute doubling up
+    /// to 1 hour, failures counted over 15 minutes, 10 000 events kept.
+    fn default() -> Self {
+        Self {
+            account_threshold: 5,
+            source_threshold: 20,
+            base_lockout_ms: 60 * 1000,
+            backoff_factor: 2,
+            max_lockout_ms: 60 * 60 * 1000,
+            failure_window_ms: 15 * 60 * 1000,
+            reset: ResetRules::default(),
+            max_events: 10_000,
+        }
+    }
+}
+
+impl LockoutConfig {
+    /// Length of a lock after `previous` earlier locks.
+    #[must_use]
+    pub fn lockout_duration_ms(&self, previous: u32) -> u64 {
+        u64::from(self.backoff_factor)
+            .saturating_pow(previous)
+            .saturating_mul(self.base_lockout_ms)
+            .min(self.max_lockout_ms)
+    }
+}
+
+/// What a lock applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LockScope {
+    Account(String),
+    Source(IpAddr),
+}
+
+impl fmt::Display for LockScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(user) => write!(f, "account {user}"),
+            Self::Source(ip) => write!(f, "source {ip}"),
+        }
+    }
+}
+
+/// Why an admin lifted a lock early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnlockReason {
+    /// The owner proved who they are through another channel.
+    IdentityVerified,
+    /// The password was reset, so earlier guesses no longer matter.
+    PasswordReset,
+    /// The failures came from a misconfigured client, not an attacker.
+    FalsePositive,
+    /// The related incident has been investigated and closed.
+    IncidentClosed,
+}
+
+impl UnlockReason {
+    /// Stable code for audit records.
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::IdentityVerified => "IDENTITY_VERIFIED",
+            Self::PasswordReset => "PASSWORD_RESET",
+            Self::FalsePositive => "FALSE_POSITIVE",
+            Self::IncidentClosed => "INCIDENT_CLOSED",
+        }
+    }
+}
+
+impl fmt::Display for UnlockReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Outcome of a check or a recorded attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockoutDecision {
+    Allowed,
+    /// The failure was counted; `remaining` more lock the account.
+    Rejected {
+        remaining: u32,
+    },
+    Locked {
+        scope: LockScope,
+        until_ms: u64,
+    },
+}
+
+impl LockoutDecision {
+    /// End of the lock, if this decision is one.
+    #[must_use]
+    pub const fn locked_until_ms(&self) -> Option<u64> {
+        match self {
+            Self::Locked { until_ms, .. } => Some(*until_ms),
+            Self::Allowed | Self::Rejected { .. } => None,
+        }
+    }
+}
+
+/// What happened, for the audit trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockoutEventKind {
+    /// A pre-login check hit an active lock; the password was not checked.
+    Blocked {
+        scope: LockScope,
+        until_ms: u64,
+    },
+    FailureRecorded {
+        account_failures: u32,
+        source_failures: u32,
+    },
+    /// A threshold was reached. `lockouts` counts this lock too.
+    Locked {
+        scope: LockScope,
+        until_ms: u64,
+        lockouts: u32,
+    },
+    LockExpired {
+        scope: LockScope,
+    },
+    /// A successful login cleared counters per [`ResetRules`].
+    SuccessReset,
+    AdminUnlocked {
+        scope: LockScope,
+        admin: String,
+        reason: UnlockReason,
+    },
+}
+
+/// One entry in the lockout audit trail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockoutEvent {
+    pub seq: u64,
+    pub at_ms: u64,
+    pub user: Option<String>,
+    pub source: Option<IpAddr>,
+    pub kind: LockoutEventKind,
+}
+
+impl LockoutEvent {
+    /// End of the lock this event reports, if any.
+    #[must_use]
+    pub const fn locked_until_ms(&self) -> Option<u64> {
+        match &self.kind {
+            LockoutEventKind::Blocked { until_ms, .. }
+            | LockoutEventKind::Locked { until_ms, .. } => Some(*until_ms),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LockoutEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} t={}", self.seq, self.at_ms)?;
+        if let Some(user) = &self.user {
+            write!(f, " user={user}")?;
+        }
+        if let Some(ip) = self.source {
+            write!(f, " src={ip}")?;
+        }
+        match &self.kind {
+            LockoutEventKind::Blocked { scope, until_ms } => {
+                write!(f, " blocked by {scope} lock until {until_ms}")
+            }
+            LockoutEventKind::FailureRecorded {
+                account_failures,
+                source_failures,
+            } => write!(
+                f,
+                " failure (account={account_failures}, source={source_failures})"
+            ),
+            LockoutEventKind::Locked {
+                scope,
+                until_ms,
+                lockouts,
+            } => write!(f, " locked {scope} until {until_ms} (lockout #{lockouts})"),
+            LockoutEventKind::LockExpired { scope } => write!(f, " {scope} lock expired"),
+            LockoutEventKind::SuccessReset => f.write_str(" success, counters reset"),
+            LockoutEventKind::AdminUnlocked {
+                scope,
+                admin,
+                reason,
+            } => write!(f, " {scope} unlocked by {admin} [{reason}]"),
+        }
+    }
+}
+
+/// Why an unlock request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockoutError {
+    NotAdmin { actor: String },
+    NotLocked(LockScope),
+}
+
+impl fmt::Display for LockoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAdmin { actor } => write!(f, "{actor} is not an administrator"),
+            Self::NotLocked(scope) => write!(f, "{scope} is not locked"),
+        }
+    }
+}
+
+impl std::error::Error for LockoutError {}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counter {
+    failures: u32,
+    last_failure_ms: u64,
+    lockouts: u32,
+    locked_until_ms: Option<u64>,
+    /// Latest failure or lock end, whichever is later.
+    last_active_ms: u64,
+}
+
+impl Counter {
+    /// No active lock, and nothing has happened for longer than `window_ms`.
+    ///
+    /// A lock that ran out counts as inactive even if no `check` has cleared
+    /// it yet.
+    fn is_idle(&self, now: u64, window_ms: u64) -> bool {
+        self.active_lock(now).is_none() && now.saturating_sub(self.last_active_ms) > window_ms
+    }
+
+    fn active_lock(&self, now: u64) -> Option<u64> {
+        self.locked_until_ms.filter(|&until| until > now)
+    }
+
+    /// Clear a lock whose time is up. Returns whether one was cleared.
+    const fn expire(&mut self, now: u64) -> bool {
+        match self.locked_until_ms {
+            Some(until) if until <= now => {
+                self.locked_until_ms = None;
+                self.failures = 0;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    const fn record_failure(&mut self, now: u64, window_ms: u64) -> u32 {
+        if now.saturating_sub(self.last_failure_ms) > window_ms {
+            self.failures = 0;
+        }
+        self.failures = self.failures.saturating_add(1);
+        self.last_failure_ms = now;
+        if now > self.last_active_ms {
+            self.last_active_ms = now;
+        }
+        self.failures
+    }
+
+    fn lock(&mut self, now: u64, config: &LockoutConfig) -> u64 {
+        let until = now.saturating_add(config.lockout_duration_ms(self.lockouts));
+        self.lockouts = self.lockouts.saturating_add(1);
+        self.failures = 0;
+        self.locked_until_ms = Some(until);
+        self.last_active_ms = until;
+        until
+    }
+}
+
+/// Tracks failed logins per account and per source IP and decides who may try.
+///
+/// Call [`check`](Self::check) before verifying a password and
+/// [`record_failure`](Self::record_failure) or
+/// [`record_success`](Self::record_success) after, or let
+/// [`attempt`](Self::attempt) do all three.
+#[derive(Debug)]
+pub struct LockoutPolicy<C: Clock = SystemClock> {
+    clock: C,
+    config: LockoutConfig,
+    accounts: HashMap<String, Counter>,
+    sources: HashMap<IpAddr, Counter>,
+    events: VecDeque<LockoutEvent>,
+    next_seq: u64,
+    next_sweep_ms: u64,
+}
+
+impl LockoutPolicy<SystemClock> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock, LockoutConfig::default())
+    }
+}
+
+impl Default for LockoutPolicy<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> LockoutPolicy<C> {
+    pub fn with_clock(clock: C, config: LockoutConfig) -> Self {
+        Self {
+            clock,
+            config,
+            accounts: HashMap::new(),
+            sources: HashMap::new(),
+            events: VecDeque::new(),
+            next_seq: 1,
+            next_sweep_ms: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &LockoutConfig {
+        &self.config
+    }
+
+    /// The newest `max_events` events, oldest first.
+    #[must_use]
+    pub const fn events(&self) -> &VecDeque<LockoutEvent> {
+        &self.events
+    }
+
+    /// Accounts and sources with a counter in memory.
+    #[must_use]
+    pub fn tracked(&self) -> (usize, usize) {
+        (self.accounts.len(), self.sources.len())
+    }
+
+    /// Failures currently counted against `user`.
+    #[must_use]
+    pub fn account_failures(&self, user: &str) -> u32 {
+        self.accounts.get(user).map_or(0, |c| c.failures)
+    }
+
+    /// End of the active lock on `scope`, if there is one.
+    #[must_use]
+    pub fn locked_until(&self, scope: &LockScope) -> Option<u64> {
+        let now = self.clock.now_ms();
+        match scope {
+            LockScope::Account(user) => self.accounts.get(user)?.active_lock(now),
+            LockScope::Source(ip) => self.sources.get(ip)?.active_lock(now),
+        }
+    }
+
+    /// Decide whether `user` may attempt a login from `source` right now.
+    ///
+    /// Expired locks are cleared here. An account lock is reported before a
+    /// source lock.
+    pub fn check(&mut self, user: &str, source: IpAddr) -> LockoutDecision {
+        let now = self.clock.now_ms();
+        if self.accounts.get_mut(user).is_some_and(|c| c.expire(now)) {
+            self.log(
+                now,
+                user,
+                source,
+                LockoutEventKind::LockExpired {
+                    scope: LockScope::Account(user.to_owned()),
+                },
+            );
+        }
+        if self.sources.get_mut(&source).is_some_and(|c| c.expire(now)) {
+            self.log(
+                now,
+                user,
+                source,
+                LockoutEventKind::LockExpired {
+                    scope: LockScope::Source(source),
+                },
+            );
+        }
+
+        let blocked = self
+            .accounts
+            .get(user)
+            .and_then(|c| c.active_lock(now))
+            .map(|until| (LockScope::Account(user.to_owned()), until))
+            .or_else(|| {
+                self.sources
+                    .get(&source)
+                    .and_then(|c| c.active_lock(now))
+                    .map(|until| (LockScope::Source(source), until))
+            });
+
+        let Some((scope, until_ms)) = blocked else {
+            return LockoutDecision::Allowed;
+        };
+        self.log(
+            now,
+            user,
+            source,
+            LockoutEventKind::Blocked {
+                scope: scope.clone(),
+                until_ms,
+            },
+        );
+        LockoutDecision::Locked { scope, until_ms }
+    }
+
+    /// Count a failed login and lock the account or source if a threshold is
+    /// reached. An account lock takes precedence in the returned decision.
+    pub fn record_failure(&mut self, user: &str, source: IpAddr) -> LockoutDecision {
+        let now = self.clock.now_ms();
+        let window = self.config.failure_window_ms;
+        self.sweep(now);
+        let account = self.accounts.entry(user.to_owned()).or_default();
+        let account_failures = account.record_failure(now, window);
+        let source_counter = self.sources.entry(source).or_default();
+        let source_failures = source_counter.record_failure(now, window);
+        self.log(
+            now,
+            user,
+            source,
+            LockoutEventKind::FailureRecorded {
+                account_failures,
+                source_failures,
+            },
+        );
+
+        let source_lock = (source_failures >= self.config.source_threshold)
+            .then(|| self.lock(now, user, source, LockScope::Source(source)));
+        if account_failures >= self.config.account_threshold {
+            return self.lock(now, user, source, LockScope::Account(user.to_owned()));
+        }
+        source_lock.unwrap_or(LockoutDecision::Rejected {
+            remaining: self.config.account_threshold - account_failures,
+        })
+    }
+
+    /// Apply the [`ResetRules`] after a successful login.
+    pub fn record_success(&mut self, user: &str, source: IpAddr) -> LockoutDecision {
+        let now = self.clock.now_ms();
+        let rules = self.config.reset;
+        if let Some(account) = self.accounts.get_mut(user) {
+            if rules.account_failures {
+                account.failures = 0;
+            }
+            if rules.account_backoff {
+                account.lockouts = 0;
+            }
+        }
+        if rules.source_failures
+            && let Some(counter) = self.sources.get_mut(&source)
+        {
+            counter.failures = 0;
+        }
+        self.log(now, user, source, LockoutEventKind::SuccessReset);
+        LockoutDecision::Allowed
+    }
+
+    /// Check, verify and record in one step.
+    ///
+    /// `verify` runs only if no lock applies, so a locked account gives no
+    /// hint about whether the guessed password was right, and blocked
+    /// attempts don't extend the lock.
+    pub fn attempt(
+        &mut self,
+        user: &str,
+        source: IpAddr,
+        verify: impl FnOnce() -> bool,
+    ) -> LockoutDecision {
+        if let locked @ LockoutDecision::Locked { .. } = self.check(user, source) {
+            return locked;
+        }
+        if verify() {
+            self.record_success(user, source)
+        } else {
+            self.record_failure(user, source)
+        }
+    }
+
+    /// Lift an active lock before it expires.
+    ///
+    /// Clears the failures and the backoff history for `scope`, so the next
+    /// lock starts again at `base_lockout_ms`.
+    pub fn admin_unlock(
+        &mut self,
+        admin: &UserAccount,
+        scope: &LockScope,
+        reason: UnlockReason,
+    ) -> Result<(), LockoutError> {
+        if !admin.is_admin() {
+            return Err(LockoutError::NotAdmin {
+                actor: admin.username.clone(),
+            });
+        }
+        let now = self.clock.now_ms();
+        let counter = match scope {
+            LockScope::Account(user) => self.accounts.get_mut(user),
+            LockScope::Source(ip) => self.sources.get_mut(ip),
+        };
+        let Some(counter) = counter.filter(|c| c.active_lock(now).is_some()) else {
+            return Err(LockoutError::NotLocked(scope.clone()));
+        };
+        *counter = Counter::default();
+
+        let (user, source) = match scope {
+            LockScope::Account(user) => (Some(user.clone()), None),
+            LockScope::Source(ip) => (None, Some(*ip)),
+        };
+        self.push(
+            now,
+            user,
+            source,
+            LockoutEventKind::AdminUnlocked {
+                scope: scope.clone(),
+                admin: admin.username.clone(),
+                reason,
+            },
+        );
+        Ok(())
+    }
+
+    fn lock(&mut self, now: u64, user: &str, source: IpAddr, scope: LockScope) -> LockoutDecision {
+        let counter = match &scope {
+            LockScope::Account(name) => self.accounts.entry(name.clone()).or_default(),
+            LockScope::Source(ip) => self.sources.entry(*ip).or_default(),
+        };
+        let until_ms = counter.lock(now, &self.config);
+        let lockouts = counter.lockouts;
+        self.log(
+            now,
+            user,
+            source,
+            LockoutEventKind::Locked {
+                scope: scope.clone(),
+                until_ms,
+                lockouts,
+            },
+        );
+        LockoutDecision::Locked { scope, until_ms }
+    }
+
+    /// Forget idle counters, at most once per failure window so the cost
+    /// stays amortized over the failures that created them.
+    fn sweep(&mut self, now: u64) {
+        if now < self.next_sweep_ms {
+            return;
+        }
+        let window = self.config.failure_window_ms;
+        self.accounts.retain(|_, c| !c.is_idle(now, window));
+        self.sources.retain(|_, c| !c.is_idle(now, window));
+        self.next_sweep_ms = now.saturating_add(window);
+    }
+
+    fn log(&mut self, now: u64, user: &str, source: IpAddr, kind: LockoutEventKind) {
+        self.push(now, Some(user.to_owned()), Some(source), kind);
+    }
+
+    fn push(
+        &mut self,
+        at_ms: u64,
+        user: Option<String>,
+        source: Option<IpAddr>,
+        kind: LockoutEventKind,
+    ) {
+        if self.config.max_events == 0 {
+            return;
+        }
+        while self.events.len() >= self.config.max_events {
+            self.events.pop_front();
+        }
+        self.events.push_back(LockoutEvent {
+            seq: self.next_seq,
+            at_ms,
+            user,
+            source,
+            kind,
+        });
+        self.next_seq += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::account::Role;
+    use crate::clock::ManualClock;
+
+    const ATTACKER: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 9));
+    const OFFICE: IpAddr = IpAddr::V4(Ipv4Addr::new(198, 51, 100, 20));
+
+    fn new_policy(config: LockoutConfig) -> (ManualClock, LockoutPolicy<ManualClock>) {
+        let clock = ManualClock::new(1_000_000);
+        (clock.clone(), LockoutPolicy::with_clock(clock, config))
+    }
+
+    fn fail(
+        policy: &mut LockoutPolicy<ManualClock>,
+        user: &str,
+        ip: IpAddr,
+        n: u32,
+    ) -> LockoutDecision {
+        let mut last = LockoutDecision::Allowed;
+        for _ in 0..n {
+            last = policy.attempt(user, ip, || false);
+        }
+        last
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let config = LockoutConfig {
+            base_lockout_ms: 1_000,
+            backoff_factor: 3,
+            max_lockout_ms: 20_000,
+            ..LockoutConfig::default()
+        };
+        assert_eq!(config.lockout_duration_ms(0), 1_000);
+        assert_eq!(config.lockout_duration_ms(1), 3_000);
+        assert_eq!(config.lockout_duration_ms(2), 9_000);
+        assert_eq!(config.lockout_duration_ms(3), 20_000);
+        assert_eq!(config.lockout_duration_ms(u32::MAX), 20_000);
+    }
+
+    #[test]
+    fn account_lock_expires_and_the_next_one_is_longer() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 3,
+            ..LockoutConfig::default()
+        });
+        assert_eq!(
+            fail(&mut policy, "alice", ATTACKER, 2),
+            LockoutDecision::Rejected { remaining: 1 }
+        );
+        let scope = LockScope::Account("alice".to_owned());
+        assert_eq!(
+            fail(&mut policy, "alice", ATTACKER, 1),
+            LockoutDecision::Locked {
+                scope: scope.clone(),
+                until_ms: 1_060_000
+            }
+        );
+        // Locked from every source, and the password is never checked.
+        let mut verified = false;
+        let blocked = policy.attempt("alice", OFFICE, || {
+            verified = true;
+            true
+        });
+        assert_eq!(blocked.locked_until_ms(), Some(1_060_000));
+        assert!(!verified);
+
+        clock.set(1_060_000);
+        assert_eq!(policy.check("alice", OFFICE), LockoutDecision::Allowed);
+        assert!(policy.events().iter().any(|e| e.kind
+            == LockoutEventKind::LockExpired {
+                scope: scope.clone()
+            }));
+        let second = fail(&mut policy, "alice", ATTACKER, 3);
+        assert_eq!(second.locked_until_ms(), Some(1_060_000 + 120_000));
+    }
+
+    #[test]
+    fn source_throttle_does_not_lock_sprayed_accounts() {
+        let (_, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 3,
+            source_threshold: 4,
+            ..LockoutConfig::default()
+        });
+        for user in ["a", "b", "c"] {
+            assert!(matches!(
+                fail(&mut policy, user, ATTACKER, 1),
+                LockoutDecision::Rejected { .. }
+            ));
+        }
+        let throttled = fail(&mut policy, "d", ATTACKER, 1);
+        assert_eq!(
+            throttled,
+            LockoutDecision::Locked {
+                scope: LockScope::Source(ATTACKER),
+                until_ms: 1_060_000
+            }
+        );
+        assert!(matches!(
+            policy.check("e", ATTACKER),
+            LockoutDecision::Locked {
+                scope: LockScope::Source(_),
+                ..
+            }
+        ));
+        // The real users can still sign in from elsewhere.
+        assert_eq!(policy.check("a", OFFICE), LockoutDecision::Allowed);
+    }
+
+    #[test]
+    fn old_failures_fall_out_of_the_window() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 2,
+            failure_window_ms: 1_000,
+            ..LockoutConfig::default()
+        });
+        fail(&mut policy, "alice", ATTACKER, 1);
+        clock.advance(1_001);
+        assert_eq!(
+            fail(&mut policy, "alice", ATTACKER, 1),
+            LockoutDecision::Rejected { remaining: 1 }
+        );
+    }
+
+    #[test]
+    fn success_reset_rules() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 2,
+            ..LockoutConfig::default()
+        });
+        fail(&mut policy, "alice", ATTACKER, 2);
+        clock.advance(60_000);
+        fail(&mut policy, "alice", ATTACKER, 1);
+        assert_eq!(
+            policy.attempt("alice", OFFICE, || true),
+            LockoutDecision::Allowed
+        );
+        assert_eq!(policy.account_failures("alice"), 0);
+        // Backoff history survives a success by default.
+        let relock = fail(&mut policy, "alice", ATTACKER, 2);
+        assert_eq!(relock.locked_until_ms(), Some(1_060_000 + 120_000));
+
+        let (clock, mut forgiving) = new_policy(LockoutConfig {
+            account_threshold: 2,
+            reset: ResetRules {
+                account_backoff: true,
+                ..ResetRules::default()
+            },
+            ..LockoutConfig::default()
+        });
+        fail(&mut forgiving, "alice", ATTACKER, 2);
+        clock.advance(60_000);
+        forgiving.attempt("alice", OFFICE, || true);
+        let relock = fail(&mut forgiving, "alice", ATTACKER, 2);
+        assert_eq!(relock.locked_until_ms(), Some(1_060_000 + 60_000));
+    }
+
+    #[test]
+    fn admin_unlock_requires_admin_and_an_active_lock() {
+        let (_, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 1,
+            ..LockoutConfig::default()
+        });
+        let admin = UserAccount::from_hash("root", "r@x.local", "!", Role::Admin);
+        let viewer = UserAccount::from_hash("eve", "e@x.local", "!", Role::Viewer);
+        let scope = LockScope::Account("alice".to_owned());
+
+        assert_eq!(
+            policy.admin_unlock(&admin, &scope, UnlockReason::FalsePositive),
+            Err(LockoutError::NotLocked(scope.clone()))
+        );
+        fail(&mut policy, "alice", ATTACKER, 1);
+        assert_eq!(
+            policy.admin_unlock(&viewer, &scope, UnlockReason::FalsePositive),
+            Err(LockoutError::NotAdmin {
+                actor: "eve".to_owned()
+            })
+        );
+        assert!(
+            policy
+                .admin_unlock(&admin, &scope, UnlockReason::IdentityVerified)
+                .is_ok()
+        );
+        assert_eq!(policy.locked_until(&scope), None);
+        assert_eq!(policy.check("alice", OFFICE), LockoutDecision::Allowed);
+
+        let unlock = policy
+            .events()
+            .iter()
+            .find(|e| matches!(e.kind, LockoutEventKind::AdminUnlocked { .. }));
+        assert!(unlock.is_some_and(|e| {
+            e.to_string()
+                .ends_with("account alice unlocked by root [IDENTITY_VERIFIED]")
+        }));
+    }
+
+    #[test]
+    fn every_decision_is_audited_in_order() {
+        let (_, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 1,
+            ..LockoutConfig::default()
+        });
+        policy.attempt("alice", ATTACKER, || false);
+        policy.attempt("alice", ATTACKER, || false);
+        let kinds: Vec<_> = policy.events().iter().map(|e| &e.kind).collect();
+        assert!(matches!(
+            kinds.as_slice(),
+            [
+                LockoutEventKind::FailureRecorded { .. },
+                LockoutEventKind::Locked { lockouts: 1, .. },
+                LockoutEventKind::Blocked { .. },
+            ]
+        ));
+        let seqs: Vec<u64> = policy.events().iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, [1, 2, 3]);
+        assert_eq!(policy.events()[2].locked_until_ms(), Some(1_060_000));
+    }
+
+    #[test]
+    fn sprayed_names_and_addresses_do_not_grow_memory() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            failure_window_ms: 1_000,
+            max_events: 50,
+            ..LockoutConfig::default()
+        });
+        for round in 0..10u8 {
+            for host in 0..=255u8 {
+                let ip = IpAddr::V4(Ipv4Addr::new(10, round, 0, host));
+                policy.attempt(&format!("user-{round}-{host}"), ip, || false);
+            }
+            clock.advance(1_001);
+        }
+        // Only the last round, not yet idle, is still tracked.
+        assert_eq!(policy.tracked(), (256, 256));
+        assert_eq!(policy.events().len(), 50);
+        assert_eq!(policy.events().back().map(|e| e.seq), Some(2_560));
+
+        // Routine allowed checks leave no trace.
+        for _ in 0..100 {
+            assert_eq!(policy.check("alice", OFFICE), LockoutDecision::Allowed);
+        }
+        assert_eq!(policy.events().back().map(|e| e.seq), Some(2_560));
+    }
+
+    #[test]
+    fn locked_counters_survive_the_sweep_until_idle() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 1,
+            failure_window_ms: 1_000,
+            base_lockout_ms: 5_000,
+            ..LockoutConfig::default()
+        });
+        fail(&mut policy, "alice", ATTACKER, 1);
+        clock.advance(2_000);
+        fail(&mut policy, "bob", OFFICE, 1);
+        assert!(
+            policy
+                .locked_until(&LockScope::Account("alice".into()))
+                .is_some()
+        );
+        // Backoff is remembered within a window of the lock ending.
+        clock.set(1_005_500);
+        let relock = fail(&mut policy, "alice", ATTACKER, 1);
+        assert_eq!(relock.locked_until_ms(), Some(1_005_500 + 10_000));
+    }
+
+    #[test]
+    fn expired_locks_are_swept_without_a_check() {
+        let (clock, mut policy) = new_policy(LockoutConfig {
+            account_threshold: 1,
+            failure_window_ms: 1_000,
+            base_lockout_ms: 5_000,
+            ..LockoutConfig::default()
+        });
+        fail(&mut policy, "alice", ATTACKER, 1);
+        assert_eq!(policy.tracked(), (1, 1));
+        // Nobody checks alice again; her lock ends and a window passes.
+        clock.advance(5_000 + 1_001);
+        fail(&mut policy, "bob", OFFICE, 1);
+        assert_eq!(policy.tracked(), (1, 1));
+        assert_eq!(
+            policy.locked_until(&LockScope::Account("alice".into())),
+            None
+        );
+    }
+}
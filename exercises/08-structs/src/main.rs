@@ -4,11 +4,20 @@
 //! cargo run -p exercise_structs
 //! cargo run -p exercise_structs -- list
 //! cargo run -p exercise_structs -- auth-flow --verbose
+//! echo 's3cret-pass' | cargo run -p exercise_structs -- accounts add alice alice@corp.local --role admin
+//! cargo run -p exercise_structs -- accounts audit --user alice
 //! ```
 
-use anyhow::Result;
+use std::io::BufRead;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
-use structs_exercises::{get_exercise_list, run_all, run_exercise};
+use ownership_exercises::Secret;
+use structs_exercises::{
+    AccountStore, Argon2id, FileAccountStore, Role, UserAccount, get_exercise_list, run_all,
+    run_exercise,
+};
 
 /// Struct exercises — user accounts, credentials, and sessions
 #[derive(Parser, Debug)]
@@ -38,6 +47,47 @@ enum Commands {
 
     /// Simulate credential-stuffing lockout policy
     Lockout,
+
+    /// Manage accounts in a file-backed store
+    Accounts {
+        /// Directory holding accounts.json and audit.jsonl
+        #[arg(short, long, default_value = ".accounts")]
+        store: PathBuf,
+
+        /// Who is making the change, recorded in the audit log
+        /// (defaults to $USER)
+        #[arg(short, long)]
+        actor: Option<String>,
+
+        #[command(subcommand)]
+        action: AccountsCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AccountsCommand {
+    /// Create an account; the password is read from the first line of stdin
+    Add {
+        username: String,
+        email: String,
+        #[arg(short, long, default_value = "viewer")]
+        role: Role,
+    },
+
+    /// Show every account
+    List,
+
+    /// Disable an account so it cannot log in
+    Lock { username: String },
+
+    /// Re-enable an account and clear its failed logins
+    Unlock { username: String },
+
+    /// Show the audit log, optionally for one account
+    Audit {
+        #[arg(short, long)]
+        user: Option<String>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -53,11 +103,94 @@ fn main() -> Result<()> {
         Some(Commands::List) => list_exercises(),
         Some(Commands::AuthFlow) => run_exercise("auth-flow", verbose)?,
         Some(Commands::Lockout) => run_exercise("lockout", verbose)?,
+        Some(Commands::Accounts {
+            store,
+            actor,
+            action,
+        }) => {
+            let actor = actor
+                .or_else(|| std::env::var("USER").ok())
+                .unwrap_or_else(|| "cli".to_string());
+            let password = match action {
+                AccountsCommand::Add { .. } => Some(read_password()?),
+                _ => None,
+            };
+            let mut store = FileAccountStore::open(&store)
+                .with_context(|| format!("opening account store {}", store.display()))?;
+            let password = password.as_ref().map(|p| p.expose().as_str());
+            for line in run_accounts(&mut store, &actor, action, password)? {
+                println!("{line}");
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Read one line from stdin; both the raw line and the result are scrubbed
+/// on drop.
+fn read_password() -> Result<Secret<String>> {
+    let mut raw = String::new();
+    let read = std::io::stdin().lock().read_line(&mut raw);
+    let line = Secret::new(raw);
+    read.context("reading password from stdin")?;
+    let password = Secret::new(line.expose().trim_end_matches(['\r', '\n']).to_string());
+    if password.expose().is_empty() {
+        bail!("no password on stdin");
+    }
+    Ok(password)
+}
+
+/// Apply one `accounts` subcommand and return the lines to print.
+fn run_accounts(
+    store: &mut dyn AccountStore,
+    actor: &str,
+    action: AccountsCommand,
+    password: Option<&str>,
+) -> Result<Vec<String>> {
+    let lines = match action {
+        AccountsCommand::Add {
+            username,
+            email,
+            role,
+        } => {
+            let Some(password) = password else {
+                bail!("a password is required to add an account");
+            };
+            let account =
+                UserAccount::with_hasher(&username, email, password, role, &Argon2id::DEFAULT)?;
+            store.create(account, actor)?;
+            vec![format!("created {username} ({role})")]
+        }
+        AccountsCommand::List => store
+            .accounts()
+            .into_iter()
+            .map(|a| {
+                let state = if a.locked { "locked" } else { "active" };
+                format!("{:<16} {:<28} {:<8} {state}", a.username, a.email, a.role)
+            })
+            .collect(),
+        AccountsCommand::Lock { username } => {
+            store.disable(&username, actor)?;
+            vec![format!("locked {username}")]
+        }
+        AccountsCommand::Unlock { username } => {
+            store.enable(&username, actor)?;
+            vec![format!("unlocked {username}")]
+        }
+        AccountsCommand::Audit { user } => store
+            .audit_log()
+            .iter()
+            .filter(|r| {
+                user.as_ref()
+                    .is_none_or(|u| r.username.eq_ignore_ascii_case(u))
+            })
+            .map(ToString::to_string)
+            .collect(),
+    };
+    Ok(lines)
+}
+
 fn list_exercises() {
     println!("Available exercises:\n");
     for info in get_exercise_list() {
@@ -72,6 +205,9 @@ fn list_exercises() {
     println!("  cargo run -p exercise_structs -- list");
     println!("  cargo run -p exercise_structs -- auth-flow [--verbose]");
     println!("  cargo run -p exercise_structs -- lockout [--verbose]");
+    println!(
+        "  cargo run -p exercise_structs -- accounts [--store DIR] [--actor NAME] add|list|lock|unlock|audit"
+    );
     println!("  cargo run -p exercise_structs -- all [--verbose]");
     println!("  cargo run -p exercise_structs              # same as 'all'");
 }
@@ -90,4 +226,47 @@ mod tests {
     fn list_does_not_panic() {
         list_exercises();
     }
+
+    #[test]
+    fn accounts_commands_drive_the_store() {
+        let mut store = structs_exercises::MemoryAccountStore::new();
+        let add = AccountsCommand::Add {
+            username: "alice".into(),
+            email: "alice@corp.local".into(),
+            role: Role::Admin,
+        };
+        assert!(run_accounts(&mut store, "root", add, None).is_err());
+
+        let add = AccountsCommand::Add {
+            username: "alice".into(),
+            email: "alice@corp.local".into(),
+            role: Role::Admin,
+        };
+        assert!(run_accounts(&mut store, "root", add, Some("s3cret-pass")).is_ok());
+        let add = AccountsCommand::Add {
+            username: "Alice".into(),
+            email: "other@corp.local".into(),
+            role: Role::Viewer,
+        };
+        assert!(run_accounts(&mut store, "root", add, Some("s3cret-pass")).is_err());
+        let lock = AccountsCommand::Lock {
+            username: "ALICE".into(),
+        };
+        assert!(run_accounts(&mut store, "soc", lock, None).is_ok());
+
+        let listed = run_accounts(&mut store, "soc", AccountsCommand::List, None);
+        assert!(listed.is_ok_and(|l| l.len() == 1 && l[0].ends_with("locked")));
+
+        let audit = AccountsCommand::Audit {
+            user: Some("alice".into()),
+        };
+        let lines = run_accounts(&mut store, "soc", audit, None).unwrap_or_default();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains("actor=soc disabled alice"));
+
+        let unlock = AccountsCommand::Unlock {
+            username: "bob".into(),
+        };
+        assert!(run_accounts(&mut store, "soc", unlock, None).is_err());
+    }
 }
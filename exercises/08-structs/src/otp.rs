@@ -0,0 +1,550 @@
+//! One-time passwords (RFC 4226 HOTP, RFC 6238 TOTP) and recovery codes.
+//!
+//! - Secrets are random bytes as long as the hash output, shown to the user as
+//!   unpadded base32. They are also shown as an `otpauth://` URI for
+//!   authenticator apps.
+//! - [`TotpFactor::verify`] accepts codes up to `skew` time steps either
+//!   side of the current step, to allow for clock drift. It remembers the
+//!   last step it accepted and refuses that step or any earlier one, so an
+//!   intercepted code can't be replayed.
+//! - [`RecoveryCodes`] stores only `SHA-256(code)`. Each code works once.
+
+use std::fmt;
+use std::fmt::Write as _;
+
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use ownership_exercises::{Secret, constant_time_eq, expose_serialize};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Encode bytes as RFC 4648 base32 without `=` padding.
+#[must_use]
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | u64::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    out
+}
+
+/// Decode base32, ignoring case, spaces, dashes and trailing `=` padding.
+pub fn base32_decode(text: &str) -> Result<Vec<u8>, OtpError> {
+    let mut out = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    for c in text.trim_end_matches('=').chars() {
+        if c == ' ' || c == '-' {
+            continue;
+        }
+        let upper = c.to_ascii_uppercase();
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| char::from(a) == upper)
+            .ok_or(OtpError::InvalidBase32)?;
+        buffer = (buffer << 5) | value as u64;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(((buffer >> bits) & 0xff) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// HMAC hash behind the one-time password.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OtpAlgorithm {
+    /// What almost every authenticator app supports.
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    /// Name used in `otpauth://` URIs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA1",
+            Self::Sha256 => "SHA256",
+            Self::Sha512 => "SHA512",
+        }
+    }
+
+    /// Recommended secret length: the hash output size.
+    #[must_use]
+    pub const fn key_len(self) -> usize {
+        match self {
+            Self::Sha1 => 20,
+            Self::Sha256 => 32,
+            Self::Sha512 => 64,
+        }
+    }
+
+    fn mac(self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        fn run<M: Mac + KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+            <M as KeyInit>::new_from_slice(key)
+                .map(|mac| mac.chain_update(message).finalize().into_bytes().to_vec())
+                .unwrap_or_default()
+        }
+        match self {
+            Self::Sha1 => run::<Hmac<Sha1>>(key, message),
+            Self::Sha256 => run::<Hmac<Sha256>>(key, message),
+            Self::Sha512 => run::<Hmac<Sha512>>(key, message),
+        }
+    }
+}
+
+/// Why provisioning or verification failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OtpError {
+    InvalidBase32,
+    /// Only 6 to 8 digits are allowed (RFC 4226 §5.3).
+    InvalidDigits(u32),
+    /// Wrong length or not all digits.
+    MalformedCode,
+    /// Well-formed, but no step in the drift window produces it.
+    Mismatch,
+    /// Matches a time step at or before the last accepted one.
+    Replayed,
+    /// Not a recovery code this account still holds.
+    UnknownRecoveryCode,
+    /// The account has no second factor enrolled.
+    NotEnrolled,
+    /// The CSPRNG failed; nothing was provisioned.
+    Rng(String),
+}
+
+impl fmt::Display for OtpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBase32 => f.write_str("secret is not valid base32"),
+            Self::InvalidDigits(d) => write!(f, "{d} digits is outside the allowed 6..=8"),
+            Self::MalformedCode => f.write_str("code has the wrong length or non-digit characters"),
+            Self::Mismatch => f.write_str("code does not match"),
+            Self::Replayed => f.write_str("code was already used"),
+            Self::UnknownRecoveryCode => f.write_str("recovery code is unknown or already used"),
+            Self::NotEnrolled => f.write_str("no second factor enrolled"),
+            Self::Rng(why) => write!(f, "random secret generation failed: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for OtpError {}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, OtpError> {
+    let mut bytes = vec![0_u8; len];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| OtpError::Rng(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// RFC 4226 HOTP value for `counter`, zero-padded to `digits`.
+pub fn hotp(
+    key: &[u8],
+    counter: u64,
+    digits: u32,
+    algorithm: OtpAlgorithm,
+) -> Result<String, OtpError> {
+    if !(6..=8).contains(&digits) {
+        return Err(OtpError::InvalidDigits(digits));
+    }
+    let digest = algorithm.mac(key, &counter.to_be_bytes());
+    // Dynamic truncation: the low nibble of the last byte picks 4 bytes.
+    let offset = digest.last().map_or(0, |b| usize::from(b & 0x0f));
+    let word = digest
+        .get(offset..offset + 4)
+        .and_then(|w| <[u8; 4]>::try_from(w).ok())
+        .map_or(0, u32::from_be_bytes);
+    let value = (word & 0x7fff_ffff) % 10_u32.pow(digits);
+    Ok(format!("{value:0width$}", width = digits as usize))
+}
+
+/// Parameters shared by the server and the authenticator app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotpConfig {
+    pub algorithm: OtpAlgorithm,
+    pub digits: u32,
+    pub step_secs: u64,
+    /// Steps accepted before and after the current one.
+    pub skew: u64,
+}
+
+impl Default for TotpConfig {
+    /// SHA-1, 6 digits, 30-second steps, ±1 step of drift.
+    fn default() -> Self {
+        Self {
+            algorithm: OtpAlgorithm::Sha1,
+            digits: 6,
+            step_secs: 30,
+            skew: 1,
+        }
+    }
+}
+
+impl TotpConfig {
+    /// Time step containing `unix_ms`.
+    #[must_use]
+    pub const fn step_at(&self, unix_ms: u64) -> u64 {
+        let step_secs = if self.step_secs == 0 {
+            1
+        } else {
+            self.step_secs
+        };
+        unix_ms / 1000 / step_secs
+    }
+}
+
+/// A TOTP secret plus the replay guard.
+///
+/// Serializing writes the raw secret, so only an account store should do it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TotpFactor {
+    #[serde(serialize_with = "expose_serialize")]
+    secret: Secret<Vec<u8>>,
+    config: TotpConfig,
+    last_step: Option<u64>,
+}
+
+impl TotpFactor {
+    /// Provision a fresh random secret.
+    pub fn generate(config: TotpConfig) -> Result<Self, OtpError> {
+        Self::from_secret(random_bytes(config.algorithm.key_len())?, config)
+    }
+
+    /// Rebuild a factor from a stored base32 secret.
+    pub fn from_base32(secret: &str, config: TotpConfig) -> Result<Self, OtpError> {
+        Self::from_secret(base32_decode(secret)?, config)
+    }
+
+    fn from_secret(secret: Vec<u8>, config: TotpConfig) -> Result<Self, OtpError> {
+        if !(6..=8).contains(&config.digits) {
+            return Err(OtpError::InvalidDigits(config.digits));
+        }
+        Ok(Self {
+            secret: Secret::new(secret),
+            config,
+            last_step: None,
+        })
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &TotpConfig {
+        &self.config
+    }
+
+    /// The most recent time step a code was accepted for.
+    #[must_use]
+    pub const fn last_step(&self) -> Option<u64> {
+        self.last_step
+    }
+
+    /// The secret in base32, for manual entry into an authenticator app.
+    #[must_use]
+    pub fn secret_base32(&self) -> Secret<String> {
+        Secret::new(base32_encode(self.secret.expose()))
+    }
+
+    /// `otpauth://totp/…` URI, usually rendered as a QR code.
+    ///
+    /// It contains the secret, so treat it like one.
+    #[must_use]
+    pub fn provisioning_uri(&self, issuer: &str, account: &str) -> Secret<String> {
+        let secret = self.secret_base32();
+        Secret::new(format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&period={}",
+            percent_encode(issuer),
+            percent_encode(account),
+            secret.expose(),
+            percent_encode(issuer),
+            self.config.algorithm.name(),
+            self.config.digits,
+            self.config.step_secs,
+        ))
+    }
+
+    /// The code an authenticator shows at `unix_ms`.
+    pub fn code_at(&self, unix_ms: u64) -> Result<String, OtpError> {
+        self.code_for_step(self.config.step_at(unix_ms))
+    }
+
+    fn code_for_step(&self, step: u64) -> Result<String, OtpError> {
+        hotp(
+            self.secret.expose(),
+            step,
+            self.config.digits,
+            self.config.algorithm,
+        )
+    }
+
+    /// Check `code` against every step in the drift window around `now_ms`.
+    ///
+    /// On success the matched step is returned and remembered. After that,
+    /// codes for that step or any earlier one fail with
+    /// [`OtpError::Replayed`].
+    pub fn verify(&mut self, code: &str, now_ms: u64) -> Result<u64, OtpError> {
+        let code = code.trim();
+        if code.len() != self.config.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(OtpError::MalformedCode);
+        }
+        let current = self.config.step_at(now_ms);
+        let first = current.saturating_sub(self.config.skew);
+        let last = current.saturating_add(self.config.skew);
+        let mut matched = None;
+        // Check every step, even after a hit, so timing does not reveal
+        // which one matched.
+        for step in first..=last {
+            let expected = self.code_for_step(step)?;
+            if constant_time_eq(expected.as_bytes(), code.as_bytes()) && matched.is_none() {
+                matched = Some(step);
+            }
+        }
+        let step = matched.ok_or(OtpError::Mismatch)?;
+        if self.last_step.is_some_and(|used| step <= used) {
+            return Err(OtpError::Replayed);
+        }
+        self.last_step = Some(step);
+        Ok(step)
+    }
+}
+
+/// Percent-encode everything outside RFC 3986 "unreserved".
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(byte));
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    out
+}
+
+/// Characters per recovery code (16 × 5 bits = 80 bits).
+pub const RECOVERY_CODE_LEN: usize = 16;
+
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !matches!(c, '-' | ' '))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// One-time recovery codes, stored as SHA-256 hashes.
+///
+/// A fast hash is enough because each code has 80 random bits; it is not a
+/// password someone chose.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryCodes {
+    hashes: Vec<[u8; 32]>,
+}
+
+impl RecoveryCodes {
+    /// Generate `count` codes. The plaintext codes are returned once, formatted
+    /// `xxxx-xxxx-xxxx-xxxx`, and are not kept.
+    pub fn generate(count: usize) -> Result<(Self, Vec<Secret<String>>), OtpError> {
+        let mut hashes = Vec::with_capacity(count);
+        let mut codes = Vec::with_capacity(count);
+        for _ in 0..count {
+            let raw = base32_encode(&random_bytes(RECOVERY_CODE_LEN * 5 / 8)?).to_ascii_lowercase();
+            hashes.push(Sha256::digest(raw.as_bytes()).into());
+            let grouped: Vec<&str> = raw
+                .as_bytes()
+                .chunks(4)
+                .filter_map(|c| std::str::from_utf8(c).ok())
+                .collect();
+            codes.push(Secret::new(grouped.join("-")));
+        }
+        Ok((Self { hashes }, codes))
+    }
+
+    #[must_use]
+    pub const fn remaining(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Use up `code` if it is still valid. Dashes, spaces and case are ignored.
+    pub fn redeem(&mut self, code: &str) -> Result<usize, OtpError> {
+        let digest: [u8; 32] = Sha256::digest(normalize_recovery_code(code).as_bytes()).into();
+        let mut found = None;
+        for (index, stored) in self.hashes.iter().enumerate() {
+            if constant_time_eq(stored, &digest) && found.is_none() {
+                found = Some(index);
+            }
+        }
+        let position = found.ok_or(OtpError::UnknownRecoveryCode)?;
+        self.hashes.swap_remove(position);
+        Ok(self.hashes.len())
+    }
+}
+
+/// What [`TotpFactor::provisioning_uri`] and the recovery codes look like at
+/// enrollment. Shown to the user once.
+#[derive(Debug)]
+pub struct Enrollment {
+    pub secret: Secret<String>,
+    pub uri: Secret<String>,
+    pub recovery_codes: Vec<Secret<String>>,
+}
+
+/// Which factor completed a login.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecondFactorUsed {
+    Totp { step: u64 },
+    RecoveryCode { remaining: usize },
+}
+
+/// Heuristic: recovery codes are longer than any OTP and may contain letters.
+#[must_use]
+pub fn looks_like_recovery_code(code: &str) -> bool {
+    normalize_recovery_code(code).len() == RECOVERY_CODE_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SHA1_KEY: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn base32_matches_rfc4648_vectors() {
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "MY"),
+            ("fo", "MZXQ"),
+            ("foo", "MZXW6"),
+            ("foob", "MZXW6YQ"),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI"),
+        ] {
+            assert_eq!(base32_encode(plain.as_bytes()), encoded);
+            assert_eq!(base32_decode(encoded), Ok(plain.as_bytes().to_vec()));
+        }
+        assert_eq!(base32_decode("mzxw 6ytb-oi======"), Ok(b"foobar".to_vec()));
+        assert_eq!(base32_decode("MZ1"), Err(OtpError::InvalidBase32));
+    }
+
+    #[test]
+    fn hotp_matches_rfc4226_appendix_d() {
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in (0_u64..).zip(expected) {
+            assert_eq!(
+                hotp(RFC_SHA1_KEY, counter, 6, OtpAlgorithm::Sha1),
+                Ok(code.to_owned())
+            );
+        }
+        assert_eq!(
+            hotp(RFC_SHA1_KEY, 0, 9, OtpAlgorithm::Sha1),
+            Err(OtpError::InvalidDigits(9))
+        );
+    }
+
+    #[test]
+    fn totp_matches_rfc6238_appendix_b() {
+        let sha256_key = b"12345678901234567890123456789012";
+        let sha512_key = b"1234567890123456789012345678901234567890123456789012345678901234";
+        let cases: [(&[u8], OtpAlgorithm, u64, &str); 6] = [
+            (RFC_SHA1_KEY, OtpAlgorithm::Sha1, 59, "94287082"),
+            (RFC_SHA1_KEY, OtpAlgorithm::Sha1, 1_111_111_109, "07081804"),
+            (RFC_SHA1_KEY, OtpAlgorithm::Sha1, 2_000_000_000, "69279037"),
+            (sha256_key, OtpAlgorithm::Sha256, 59, "46119246"),
+            (sha256_key, OtpAlgorithm::Sha256, 1_234_567_890, "91819424"),
+            (sha512_key, OtpAlgorithm::Sha512, 59, "90693936"),
+        ];
+        for (key, algorithm, unix_secs, code) in cases {
+            let config = TotpConfig {
+                algorithm,
+                digits: 8,
+                ..TotpConfig::default()
+            };
+            let factor = TotpFactor::from_base32(&base32_encode(key), config);
+            assert!(factor.is_ok());
+            let Ok(factor) = factor else { return };
+            assert_eq!(factor.code_at(unix_secs * 1000), Ok(code.to_owned()));
+        }
+    }
+
+    #[test]
+    fn drift_window_and_replay_guard() {
+        let factor = TotpFactor::from_base32(&base32_encode(RFC_SHA1_KEY), TotpConfig::default());
+        assert!(factor.is_ok());
+        let Ok(mut factor) = factor else { return };
+        let now = 1_000_000_000_000;
+        let step = factor.config().step_at(now);
+        let prev = factor.code_at(now - 30_000).unwrap_or_default();
+        let next = factor.code_at(now + 30_000).unwrap_or_default();
+        let far = factor.code_at(now + 90_000).unwrap_or_default();
+
+        assert_eq!(factor.verify(&far, now), Err(OtpError::Mismatch));
+        assert_eq!(factor.verify(&prev, now), Ok(step - 1));
+        assert_eq!(factor.verify(&prev, now), Err(OtpError::Replayed));
+        assert_eq!(factor.verify(&next, now), Ok(step + 1));
+        // The current step is now older than the last accepted one.
+        let current = factor.code_at(now).unwrap_or_default();
+        assert_eq!(factor.verify(&current, now), Err(OtpError::Replayed));
+        assert_eq!(factor.verify("12345", now), Err(OtpError::MalformedCode));
+        assert_eq!(factor.verify("12a456", now), Err(OtpError::MalformedCode));
+    }
+
+    #[test]
+    fn provisioning_uri_is_encoded_and_redacted() {
+        let factor = TotpFactor::generate(TotpConfig::default());
+        assert!(factor.is_ok());
+        let Ok(factor) = factor else { return };
+        let secret = factor.secret_base32();
+        assert_eq!(secret.expose().len(), 32);
+        assert_eq!(base32_decode(secret.expose()).map(|k| k.len()), Ok(20));
+
+        let uri = factor.provisioning_uri("SecOps Lab", "alice@secops.local");
+        assert!(
+            uri.expose()
+                .starts_with("otpauth://totp/SecOps%20Lab:alice%40secops.local?secret=")
+        );
+        assert!(
+            uri.expose()
+                .contains(&format!("secret={}&", secret.expose()))
+        );
+        assert!(uri.expose().ends_with("&algorithm=SHA1&digits=6&period=30"));
+        assert!(!format!("{factor:?}").contains(secret.expose()));
+    }
+
+    #[test]
+    fn recovery_codes_are_hashed_and_single_use() {
+        let generated = RecoveryCodes::generate(3);
+        assert!(generated.is_ok());
+        let Ok((mut codes, plain)) = generated else {
+            return;
+        };
+        assert_eq!(codes.remaining(), 3);
+        let first = plain[0].expose().clone();
+        assert_eq!(first.len(), RECOVERY_CODE_LEN + 3);
+        assert!(looks_like_recovery_code(&first));
+        assert!(!format!("{codes:?}").contains(&first.replace('-', "")));
+
+        assert_eq!(codes.redeem(&first.to_ascii_uppercase()), Ok(2));
+        assert_eq!(codes.redeem(&first), Err(OtpError::UnknownRecoveryCode));
+        assert_eq!(codes.redeem(plain[1].expose()), Ok(1));
+    }
+}
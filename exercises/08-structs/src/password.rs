@@ -0,0 +1,351 @@
+//! Password hashing for stored credentials.
+//!
+//! [`PasswordHasher`] is a small pluggable trait with two implementations:
+//!
+//! - [`Pbkdf2Sha256`] — PBKDF2-HMAC-SHA256 (NIST SP 800-132, FIPS-friendly);
+//! - [`Argon2id`] — memory-hard, the OWASP first choice.
+//!
+//! Hashes are stored as PHC strings, e.g.
+//! `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`. Each string carries its
+//! algorithm, parameters and salt, so [`verify_password`] can check a hash
+//! made by either hasher with any parameters. The final hash comparison is
+//! constant-time. [`PasswordHasher::needs_rehash`] reports whether a stored
+//! hash is weaker than current policy, so it can be upgraded after a
+//! successful login (see [`verify_and_upgrade`]).
+
+use std::fmt;
+
+use password_hash::rand_core::OsRng;
+use password_hash::{PasswordHash, PasswordHasher as _, PasswordVerifier as _, SaltString};
+
+/// PHC identifier for PBKDF2-HMAC-SHA256.
+pub const PBKDF2_SHA256_ID: &str = "pbkdf2-sha256";
+/// PHC identifier for Argon2id.
+pub const ARGON2ID_ID: &str = "argon2id";
+
+const PBKDF2_OUTPUT_LEN: usize = 32;
+
+/// Why hashing or verification could not be performed.
+///
+/// A wrong password is not an error: verification returns `Ok(false)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HashError {
+    /// The stored string is not a well-formed PHC hash.
+    Malformed(String),
+    /// The PHC string names an algorithm we don't implement.
+    UnsupportedAlgorithm(String),
+    /// Cost parameters are out of range for the algorithm.
+    InvalidParams(String),
+    /// The underlying primitive failed.
+    Failed(String),
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(why) => write!(f, "malformed password hash: {why}"),
+            Self::UnsupportedAlgorithm(id) => write!(f, "unsupported hash algorithm: {id}"),
+            Self::InvalidParams(why) => write!(f, "invalid hash parameters: {why}"),
+            Self::Failed(why) => write!(f, "password hashing failed: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for HashError {}
+
+impl From<password_hash::Error> for HashError {
+    fn from(err: password_hash::Error) -> Self {
+        use password_hash::Error as E;
+        match err {
+            E::Algorithm => Self::UnsupportedAlgorithm(err.to_string()),
+            E::ParamNameDuplicated
+            | E::ParamNameInvalid
+            | E::ParamValueInvalid(_)
+            | E::ParamsMaxExceeded
+            | E::OutputSize { .. } => Self::InvalidParams(err.to_string()),
+            E::B64Encoding(_)
+            | E::PhcStringField
+            | E::PhcStringTrailingData
+            | E::SaltInvalid(_)
+            | E::Version => Self::Malformed(err.to_string()),
+            _ => Self::Failed(err.to_string()),
+        }
+    }
+}
+
+/// A password hashing scheme that produces PHC strings.
+pub trait PasswordHasher {
+    /// PHC algorithm identifier this hasher writes.
+    fn algorithm(&self) -> &'static str;
+
+    /// Hash `password` with a fresh random salt.
+    fn hash(&self, password: &str) -> Result<String, HashError>;
+
+    /// Whether `phc` should be replaced by a hash from this hasher: another
+    /// algorithm, weaker parameters, or unparseable.
+    fn needs_rehash(&self, phc: &str) -> bool;
+}
+
+/// PBKDF2-HMAC-SHA256 with a configurable iteration count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pbkdf2Sha256 {
+    pub rounds: u32,
+}
+
+impl Pbkdf2Sha256 {
+    /// OWASP 2023 recommendation for PBKDF2-HMAC-SHA256.
+    pub const DEFAULT_ROUNDS: u32 = 600_000;
+
+    #[must_use]
+    pub const fn new(rounds: u32) -> Self {
+        Self { rounds }
+    }
+}
+
+impl Default for Pbkdf2Sha256 {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_ROUNDS)
+    }
+}
+
+impl PasswordHasher for Pbkdf2Sha256 {
+    fn algorithm(&self) -> &'static str {
+        PBKDF2_SHA256_ID
+    }
+
+    fn hash(&self, password: &str) -> Result<String, HashError> {
+        let salt = SaltString::generate(&mut OsRng);
+        let params = pbkdf2::Params {
+            rounds: self.rounds,
+            output_length: PBKDF2_OUTPUT_LEN,
+        };
+        let hash = pbkdf2::Pbkdf2.hash_password_customized(
+            password.as_bytes(),
+            Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+            None,
+            params,
+            &salt,
+        )?;
+        Ok(hash.to_string())
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(phc) else {
+            return true;
+        };
+        if hash.algorithm.as_str() != PBKDF2_SHA256_ID {
+            return true;
+        }
+        pbkdf2::Params::try_from(&hash).map_or(true, |p| {
+            p.rounds < self.rounds || p.output_length < PBKDF2_OUTPUT_LEN
+        })
+    }
+}
+
+/// Argon2id (version 0x13) with configurable memory, time and parallelism.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2id {
+    /// Memory cost in KiB.
+    pub m_cost: u32,
+    /// Number of passes.
+    pub t_cost: u32,
+    /// Degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Argon2id {
+    /// OWASP 2023 minimum: 19 MiB, 2 passes, 1 lane.
+    pub const DEFAULT: Self = Self::new(19 * 1024, 2, 1);
+
+    #[must_use]
+    pub const fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> Self {
+        Self {
+            m_cost,
+            t_cost,
+            p_cost,
+        }
+    }
+
+    fn context(&self) -> Result<argon2::Argon2<'static>, HashError> {
+        let params = argon2::Params::new(self.m_cost, self.t_cost, self.p_cost, None)
+            .map_err(|e| HashError::InvalidParams(e.to_string()))?;
+        Ok(argon2::Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            params,
+        ))
+    }
+}
+
+impl Default for Argon2id {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl PasswordHasher for Argon2id {
+    fn algorithm(&self) -> &'static str {
+        ARGON2ID_ID
+    }
+
+    fn hash(&self, password: &str) -> Result<String, HashError> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self.context()?.hash_password(password.as_bytes(), &salt)?;
+        Ok(hash.to_string())
+    }
+
+    fn needs_rehash(&self, phc: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(phc) else {
+            return true;
+        };
+        if hash.algorithm.as_str() != ARGON2ID_ID
+            || hash.version != Some(argon2::Version::V0x13 as u32)
+        {
+            return true;
+        }
+        argon2::Params::try_from(&hash).map_or(true, |p| {
+            p.m_cost() < self.m_cost || p.t_cost() < self.t_cost || p.p_cost() < self.p_cost
+        })
+    }
+}
+
+/// Check `password` against any supported PHC string.
+///
+/// Returns `Ok(false)` for a wrong password and `Err` when the stored hash
+/// cannot be used at all.
+pub fn verify_password(password: &str, phc: &str) -> Result<bool, HashError> {
+    let hash = PasswordHash::new(phc)?;
+    let outcome = match hash.algorithm.as_str() {
+        PBKDF2_SHA256_ID => pbkdf2::Pbkdf2.verify_password(password.as_bytes(), &hash),
+        ARGON2ID_ID => argon2::Argon2::default().verify_password(password.as_bytes(), &hash),
+        other => return Err(HashError::UnsupportedAlgorithm(other.to_string())),
+    };
+    match outcome {
+        Ok(()) => Ok(true),
+        Err(password_hash::Error::Password) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Result of [`verify_and_upgrade`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    Invalid,
+    Valid,
+    /// Valid, and the stored hash was outdated; persist the new PHC string.
+    Rehashed(String),
+}
+
+impl Verification {
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        !matches!(self, Self::Invalid)
+    }
+}
+
+/// Verify `password`, then rehash it with `hasher` if the stored hash is
+/// below current policy. Only possible now, while the plaintext is at hand.
+pub fn verify_and_upgrade(
+    hasher: &dyn PasswordHasher,
+    password: &str,
+    phc: &str,
+) -> Result<Verification, HashError> {
+    if !verify_password(password, phc)? {
+        return Ok(Verification::Invalid);
+    }
+    if hasher.needs_rehash(phc) {
+        return hasher.hash(password).map(Verification::Rehashed);
+    }
+    Ok(Verification::Valid)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    // Cheap parameters so debug-mode tests stay fast.
+    const FAST_PBKDF2: Pbkdf2Sha256 = Pbkdf2Sha256::new(1_000);
+    const FAST_ARGON2: Argon2id = Argon2id::new(256, 1, 1);
+
+    #[test]
+    fn pbkdf2_round_trip_and_phc_format() {
+        let phc = FAST_PBKDF2.hash("hunter2");
+        assert!(phc.is_ok());
+        let Ok(phc) = phc else { return };
+        assert!(phc.starts_with("$pbkdf2-sha256$i=1000,l=32$"));
+        assert_eq!(verify_password("hunter2", &phc), Ok(true));
+        assert_eq!(verify_password("hunter3", &phc), Ok(false));
+    }
+
+    #[test]
+    fn argon2id_round_trip_and_phc_format() {
+        let phc = FAST_ARGON2.hash("hunter2");
+        assert!(phc.is_ok());
+        let Ok(phc) = phc else { return };
+        assert!(phc.starts_with("$argon2id$v=19$m=256,t=1,p=1$"));
+        assert_eq!(verify_password("hunter2", &phc), Ok(true));
+        assert_eq!(verify_password("Hunter2", &phc), Ok(false));
+    }
+
+    #[test]
+    fn salts_differ_per_hash() {
+        let a = FAST_ARGON2.hash("same");
+        let b = FAST_ARGON2.hash("same");
+        assert!(a.is_ok() && b.is_ok());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn known_pbkdf2_vector() {
+        // RFC 7914 §11: PBKDF2-HMAC-SHA256("passwd", "salt", c=1), first 32 bytes.
+        let phc = "$pbkdf2-sha256$i=1,l=32$c2FsdA$VawEblbjCJ/sFpHCJUS2BflBhSFt3gRl5oudV8INrLw";
+        assert_eq!(verify_password("passwd", phc), Ok(true));
+    }
+
+    #[test]
+    fn needs_rehash_detects_weaker_params_and_other_algorithms() {
+        let weak = Pbkdf2Sha256::new(500).hash("pw").expect("hash");
+        assert!(FAST_PBKDF2.needs_rehash(&weak));
+        assert!(!Pbkdf2Sha256::new(500).needs_rehash(&weak));
+        assert!(FAST_ARGON2.needs_rehash(&weak));
+
+        let argon = FAST_ARGON2.hash("pw").expect("hash");
+        assert!(!FAST_ARGON2.needs_rehash(&argon));
+        assert!(Argon2id::new(512, 1, 1).needs_rehash(&argon));
+        assert!(FAST_ARGON2.needs_rehash("not a phc string"));
+    }
+
+    #[test]
+    fn upgrade_only_on_success() {
+        let legacy = FAST_PBKDF2.hash("correct").expect("hash");
+        assert_eq!(
+            verify_and_upgrade(&FAST_ARGON2, "wrong", &legacy),
+            Ok(Verification::Invalid)
+        );
+        let upgraded = verify_and_upgrade(&FAST_ARGON2, "correct", &legacy);
+        assert!(
+            matches!(&upgraded, Ok(Verification::Rehashed(phc)) if phc.starts_with("$argon2id$"))
+        );
+        assert_eq!(
+            verify_and_upgrade(&FAST_PBKDF2, "correct", &legacy),
+            Ok(Verification::Valid)
+        );
+    }
+
+    #[test]
+    fn errors_for_unusable_hashes() {
+        assert!(matches!(
+            verify_password("x", "plaintext"),
+            Err(HashError::Malformed(_))
+        ));
+        assert!(matches!(
+            verify_password("x", "$scrypt$ln=15,r=8,p=1$c2FsdA$AAAAAAAAAAAAAAAAAAAAAA"),
+            Err(HashError::UnsupportedAlgorithm(_))
+        ));
+        assert!(matches!(
+            Argon2id::new(1, 0, 0).hash("x"),
+            Err(HashError::InvalidParams(_))
+        ));
+    }
+}
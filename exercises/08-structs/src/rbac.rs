@@ -0,0 +1,534 @@
+//! Permission-based access control with role inheritance and scoped grants.
+//!
+//! [`UserAccount::is_admin`] is all-or-nothing. A [`Policy`] instead names
+//! individual permissions, groups them into roles that inherit from a parent,
+//! and grants roles to users on a single resource subtree or on `*`.
+//! [`Policy::authorize`] denies by default and returns the reason for every
+//! decision.
+//!
+//! Policy files are line-oriented; see `policies/default.policy`:
+//!
+//! ```text
+//! permission cluster.restart
+//! role  operator  viewer    alerts.ack cluster.restart
+//! grant carol     operator  cluster-a
+//! ```
+//!
+//! [`UserAccount::is_admin`]: crate::account::UserAccount::is_admin
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::account::UserAccount;
+
+/// Where a grant applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Every resource (`*` in a policy file).
+    Global,
+    /// This resource and everything below it.
+    Resource(String),
+}
+
+impl Scope {
+    /// Whether `resource` falls inside this scope.
+    ///
+    /// Matching is by whole path segment: `cluster-a` covers `cluster-a/pods`
+    /// but not `cluster-ab`.
+    #[must_use]
+    pub fn covers(&self, resource: &str) -> bool {
+        match self {
+            Self::Global => true,
+            Self::Resource(root) => resource
+                .strip_prefix(root.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/')),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Global => f.write_str("*"),
+            Self::Resource(root) => f.write_str(root),
+        }
+    }
+}
+
+/// A named set of permissions, extending an optional parent role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleDef {
+    pub name: String,
+    pub parent: Option<String>,
+    /// Permissions added by this role, excluding inherited ones.
+    pub permissions: BTreeSet<String>,
+}
+
+/// A role held by a user on a scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    pub principal: String,
+    pub role: String,
+    pub scope: Scope,
+}
+
+/// Why access was allowed or denied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecisionReason {
+    /// `role`, held on `scope`, includes the permission; `via` is the role in
+    /// its inheritance chain that declares it.
+    Granted {
+        role: String,
+        via: String,
+        scope: Scope,
+    },
+    /// The action is not a permission this policy knows.
+    UnknownAction,
+    /// The resource is empty or has an empty, `.`, `..` or `*` segment.
+    InvalidResource,
+    /// None of the user's grants covers the resource.
+    NoGrantForResource,
+    /// Grants cover the resource, but none of these roles has the permission.
+    MissingPermission { roles: Vec<String> },
+}
+
+impl fmt::Display for DecisionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Granted { role, via, scope } if role == via => {
+                write!(f, "granted by role {role} on {scope}")
+            }
+            Self::Granted { role, via, scope } => {
+                write!(
+                    f,
+                    "granted by role {role} (inherited from {via}) on {scope}"
+                )
+            }
+            Self::UnknownAction => f.write_str("unknown action"),
+            Self::InvalidResource => f.write_str("invalid resource name"),
+            Self::NoGrantForResource => f.write_str("no grant covers this resource"),
+            Self::MissingPermission { roles } => {
+                write!(f, "roles [{}] lack this permission", roles.join(", "))
+            }
+        }
+    }
+}
+
+/// Outcome of [`Policy::authorize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessDecision {
+    pub principal: String,
+    pub action: String,
+    pub resource: String,
+    pub reason: DecisionReason,
+}
+
+impl AccessDecision {
+    #[must_use]
+    pub const fn is_allowed(&self) -> bool {
+        matches!(self.reason, DecisionReason::Granted { .. })
+    }
+}
+
+impl fmt::Display for AccessDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.is_allowed() { "ALLOW" } else { "DENY" };
+        write!(
+            f,
+            "{verdict} {} {} on {}: {}",
+            self.principal, self.action, self.resource, self.reason
+        )
+    }
+}
+
+/// Why a policy file could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyParseError {
+    /// 1-based line number in the policy file.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for PolicyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "policy file line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for PolicyParseError {}
+
+/// Permissions, roles and grants.
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    permissions: BTreeSet<String>,
+    roles: HashMap<String, RoleDef>,
+    grants: Vec<Grant>,
+}
+
+impl Policy {
+    /// Parse policy-file text. Blank lines and `#` comments are ignored.
+    pub fn parse(text: &str) -> Result<Self, PolicyParseError> {
+        let mut policy = Self::default();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |reason: String| PolicyParseError {
+                line: index + 1,
+                reason,
+            };
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("permission") => {
+                    let (Some(name), None) = (fields.next(), fields.next()) else {
+                        return Err(err("expected `permission <name>`".into()));
+                    };
+                    check_name(name).map_err(err)?;
+                    if !policy.permissions.insert(name.to_owned()) {
+                        return Err(err(format!("duplicate permission {name}")));
+                    }
+                }
+                Some("role") => {
+                    let (Some(name), Some(parent)) = (fields.next(), fields.next()) else {
+                        return Err(err(
+                            "expected `role <name> <parent|-> <permission...>`".into()
+                        ));
+                    };
+                    check_name(name).map_err(err)?;
+                    if policy.roles.contains_key(name) {
+                        return Err(err(format!("duplicate role {name}")));
+                    }
+                    let parent = match parent {
+                        "-" => None,
+                        p if policy.roles.contains_key(p) => Some(p.to_owned()),
+                        p => {
+                            return Err(err(format!(
+                                "role {name} extends {p}, which is not declared above it"
+                            )));
+                        }
+                    };
+                    let mut permissions = BTreeSet::new();
+                    for perm in fields {
+                        if !policy.permissions.contains(perm) {
+                            return Err(err(format!("unknown permission {perm}")));
+                        }
+                        permissions.insert(perm.to_owned());
+                    }
+                    policy.roles.insert(
+                        name.to_owned(),
+                        RoleDef {
+                            name: name.to_owned(),
+                            parent,
+                            permissions,
+                        },
+                    );
+                }
+                Some("grant") => {
+                    let (Some(principal), Some(role), Some(scope), None) =
+                        (fields.next(), fields.next(), fields.next(), fields.next())
+                    else {
+                        return Err(err("expected `grant <user> <role> <resource|*>`".into()));
+                    };
+                    check_name(principal).map_err(err)?;
+                    if !policy.roles.contains_key(role) {
+                        return Err(err(format!("unknown role {role}")));
+                    }
+                    let scope = if scope == "*" {
+                        Scope::Global
+                    } else if valid_resource(scope) {
+                        Scope::Resource(scope.to_owned())
+                    } else {
+                        return Err(err(format!("invalid resource {scope:?}")));
+                    };
+                    policy.grants.push(Grant {
+                        principal: principal.to_owned(),
+                        role: role.to_owned(),
+                        scope,
+                    });
+                }
+                Some(other) => return Err(err(format!("unknown directive {other:?}"))),
+                None => {}
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Load and parse a policy file.
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("reading policy file {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing policy file {}", path.display()))
+    }
+
+    #[must_use]
+    pub fn role(&self, name: &str) -> Option<&RoleDef> {
+        self.roles.get(name)
+    }
+
+    #[must_use]
+    pub fn grants(&self) -> &[Grant] {
+        &self.grants
+    }
+
+    /// Every permission `role` holds, inherited ones included.
+    #[must_use]
+    pub fn role_permissions(&self, role: &str) -> BTreeSet<&str> {
+        self.chain(role)
+            .flat_map(|def| def.permissions.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Decide whether `account` may perform `action` on `resource`.
+    ///
+    /// The account's explicit grants are considered together with a global
+    /// grant of its built-in [`Role`](crate::account::Role). Anything not explicitly allowed is
+    /// denied.
+    #[must_use]
+    pub fn authorize(&self, account: &UserAccount, action: &str, resource: &str) -> AccessDecision {
+        let decide = |reason| AccessDecision {
+            principal: account.username.clone(),
+            action: action.to_owned(),
+            resource: resource.to_owned(),
+            reason,
+        };
+        if !self.permissions.contains(action) {
+            return decide(DecisionReason::UnknownAction);
+        }
+        if !valid_resource(resource) {
+            return decide(DecisionReason::InvalidResource);
+        }
+
+        let builtin = Grant {
+            principal: account.username.clone(),
+            role: account.role.policy_name().to_owned(),
+            scope: Scope::Global,
+        };
+        let mut considered = Vec::new();
+        for grant in std::iter::once(&builtin).chain(&self.grants) {
+            if grant.principal != account.username
+                || !grant.scope.covers(resource)
+                || !self.roles.contains_key(&grant.role)
+            {
+                continue;
+            }
+            if let Some(via) = self
+                .chain(&grant.role)
+                .find(|def| def.permissions.contains(action))
+            {
+                return decide(DecisionReason::Granted {
+                    role: grant.role.clone(),
+                    via: via.name.clone(),
+                    scope: grant.scope.clone(),
+                });
+            }
+            if !considered.contains(&grant.role) {
+                considered.push(grant.role.clone());
+            }
+        }
+
+        if considered.is_empty() {
+            decide(DecisionReason::NoGrantForResource)
+        } else {
+            decide(DecisionReason::MissingPermission { roles: considered })
+        }
+    }
+
+    /// `role`, then its parent, and so on up the chain.
+    fn chain<'a>(&'a self, role: &str) -> impl Iterator<Item = &'a RoleDef> {
+        std::iter::successors(self.roles.get(role), |def| {
+            def.parent.as_deref().and_then(|p| self.roles.get(p))
+        })
+    }
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let ok = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if ok && name != "-" {
+        Ok(())
+    } else {
+        Err(format!("invalid name {name:?}"))
+    }
+}
+
+/// Non-empty `/`-separated segments, none of them empty, `.`, `..` or
+/// containing `*`.
+fn valid_resource(resource: &str) -> bool {
+    !resource.is_empty()
+        && resource
+            .split('/')
+            .all(|seg| !seg.is_empty() && seg != "." && seg != ".." && !seg.contains('*'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_POLICY;
+    use crate::account::Role;
+
+    fn default_policy() -> Policy {
+        Policy::parse(DEFAULT_POLICY).unwrap_or_default()
+    }
+
+    fn user(name: &str, role: Role) -> UserAccount {
+        UserAccount::from_hash(name, format!("{name}@secops.local"), "!", role)
+    }
+
+    #[test]
+    fn bundled_policy_parses() {
+        let policy = Policy::parse(DEFAULT_POLICY);
+        assert!(policy.is_ok());
+        let Ok(policy) = policy else { return };
+        assert_eq!(policy.grants().len(), 2);
+        assert!(policy.role_permissions("admin").contains("alerts.read"));
+        assert!(!policy.role_permissions("viewer").contains("alerts.ack"));
+    }
+
+    #[test]
+    fn scope_matches_whole_segments() {
+        let scope = Scope::Resource("cluster-a".to_owned());
+        assert!(scope.covers("cluster-a"));
+        assert!(scope.covers("cluster-a/pods/web"));
+        assert!(!scope.covers("cluster-ab"));
+        assert!(!scope.covers("cluster-a-prod"));
+        assert!(!scope.covers("cluster"));
+        assert!(Scope::Global.covers("anything/at/all"));
+    }
+
+    #[test]
+    fn inheritance_reports_the_declaring_role() {
+        let policy = default_policy();
+        let admin = user("root", Role::Admin);
+        let decision = policy.authorize(&admin, "logs.read", "cluster-z");
+        assert!(decision.is_allowed());
+        assert_eq!(
+            decision.reason,
+            DecisionReason::Granted {
+                role: "admin".to_owned(),
+                via: "viewer".to_owned(),
+                scope: Scope::Global,
+            }
+        );
+        assert_eq!(
+            decision.to_string(),
+            "ALLOW root logs.read on cluster-z: granted by role admin (inherited from viewer) on *"
+        );
+    }
+
+    #[test]
+    fn scoped_operator_stays_in_scope() {
+        let policy = default_policy();
+        let carol = user("carol", Role::Viewer);
+        assert!(
+            policy
+                .authorize(&carol, "cluster.restart", "cluster-a/pods/web")
+                .is_allowed()
+        );
+        for resource in ["cluster-b", "cluster-ab", "cluster-a-prod"] {
+            let decision = policy.authorize(&carol, "cluster.restart", resource);
+            assert_eq!(
+                decision.reason,
+                DecisionReason::MissingPermission {
+                    roles: vec!["viewer".to_owned()]
+                },
+                "{resource}"
+            );
+        }
+        // Her global viewer role still applies everywhere.
+        assert!(
+            policy
+                .authorize(&carol, "alerts.read", "cluster-b")
+                .is_allowed()
+        );
+    }
+
+    #[test]
+    fn escalation_attempts_are_denied() {
+        let policy = default_policy();
+        let carol = user("carol", Role::Viewer);
+        let dave = user("dave", Role::Viewer);
+
+        // Path tricks to climb out of a scope.
+        for resource in [
+            "cluster-a/../cluster-b",
+            "cluster-a/./x",
+            "cluster-a//x",
+            "*",
+            "",
+        ] {
+            assert_eq!(
+                policy.authorize(&carol, "cluster.restart", resource).reason,
+                DecisionReason::InvalidResource,
+                "{resource:?}"
+            );
+        }
+        // A subtree admin is not an admin of the parent.
+        assert!(
+            policy
+                .authorize(&dave, "users.manage", "cluster-b/staging/db")
+                .is_allowed()
+        );
+        assert!(
+            !policy
+                .authorize(&dave, "users.manage", "cluster-b")
+                .is_allowed()
+        );
+        // Names are exact: another user's grant doesn't leak by case.
+        let impostor = user("Carol", Role::Viewer);
+        assert!(
+            !policy
+                .authorize(&impostor, "cluster.restart", "cluster-a")
+                .is_allowed()
+        );
+        // Unknown actions are denied, never treated as wildcards.
+        assert_eq!(
+            policy.authorize(&carol, "cluster.*", "cluster-a").reason,
+            DecisionReason::UnknownAction
+        );
+    }
+
+    #[test]
+    fn no_grant_when_builtin_role_is_undefined() {
+        let policy = Policy::parse("permission logs.read\nrole reader - logs.read\n");
+        assert!(policy.is_ok());
+        let Ok(policy) = policy else { return };
+        assert_eq!(
+            policy
+                .authorize(&user("root", Role::Admin), "logs.read", "x")
+                .reason,
+            DecisionReason::NoGrantForResource
+        );
+    }
+
+    #[test]
+    fn parse_rejects_bad_policies() {
+        let cases = [
+            ("role a b", 1, "not declared above"),
+            ("role a - x", 1, "unknown permission"),
+            ("permission p\nrole a - p\nrole a - p", 3, "duplicate role"),
+            ("role a a", 1, "not declared above"),
+            ("permission p\nrole a - p\ngrant bob b *", 3, "unknown role"),
+            (
+                "permission p\nrole a - p\ngrant bob a x/../y",
+                3,
+                "invalid resource",
+            ),
+            ("permission p\nrole a - p\ngrant bob a", 3, "expected"),
+            ("permission p*", 1, "invalid name"),
+            ("deny bob", 1, "unknown directive"),
+        ];
+        for (text, line, reason) in cases {
+            let err = Policy::parse(text).err();
+            assert!(
+                err.as_ref()
+                    .is_some_and(|e| e.line == line && e.reason.contains(reason)),
+                "{text:?} → {err:?}"
+            );
+        }
+    }
+}
@@ -1,10 +1,14 @@
 //! Session token modeling for authenticated requests.
 
+use ownership_exercises::Secret;
+
 /// An active session binding a user identity to an opaque token.
+///
+/// The token is redacted in `Debug` output; read it with `token.expose()`.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Session {
     pub user: String,
-    pub token: String,
+    pub token: Secret<String>,
 }
 
 impl Session {
@@ -13,14 +17,14 @@ impl Session {
     pub fn new(user: impl Into<String>, token: impl Into<String>) -> Self {
         Self {
             user: user.into(),
-            token: token.into(),
+            token: Secret::new(token.into()),
         }
     }
 
-    /// Constant-time-safe enough for teaching: compare token strings.
+    /// Compare a presented token in constant time.
     #[must_use]
     pub fn matches_token(&self, candidate: &str) -> bool {
-        self.token == candidate
+        self.token.matches(candidate)
     }
 }
 
@@ -40,5 +44,6 @@ mod tests {
         let session = Session::new("bob", "sess-9f3a");
         assert!(session.matches_token("sess-9f3a"));
         assert!(!session.matches_token("wrong"));
+        assert!(!format!("{session:?}").contains("sess-9f3a"));
     }
 }
@@ -0,0 +1,412 @@
+//! Server-side session store with unguessable, expiring tokens.
+//!
+//! - Tokens are 256 bits from the operating system CSPRNG, written as
+//!   `sess_<64 hex>`.
+//! - The store keeps only `SHA-256(token)`. Anyone who dumps it still can't
+//!   replay a session. A fast hash is enough because the input is random, not
+//!   a password.
+//! - Lookup is keyed by that hash, so the map never compares the secret token
+//!   itself. Timing can reveal at most a prefix of a digest, which is no help
+//!   in finding a token that hashes to it.
+//! - Every session has an absolute lifetime and an idle timeout. Sessions can
+//!   be revoked one at a time, per user, or all at once, and rotated when
+//!   privileges change.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+use crate::account::Role;
+use crate::clock::{Clock, SystemClock};
+use crate::session::Session;
+
+/// Prefix on every issued token, handy for secret scanners.
+pub const TOKEN_PREFIX: &str = "sess_";
+/// Random bytes per token.
+pub const TOKEN_BYTES: usize = 32;
+
+type TokenHash = [u8; 32];
+
+/// Generate a fresh random token (not registered anywhere).
+pub fn generate_token() -> Result<String, SessionError> {
+    let mut bytes = [0_u8; TOKEN_BYTES];
+    OsRng
+        .try_fill_bytes(&mut bytes)
+        .map_err(|e| SessionError::Rng(e.to_string()))?;
+    let mut token = String::with_capacity(TOKEN_PREFIX.len() + 2 * TOKEN_BYTES);
+    token.push_str(TOKEN_PREFIX);
+    for byte in bytes {
+        let _ = write!(token, "{byte:02x}");
+    }
+    Ok(token)
+}
+
+fn hash_token(token: &str) -> TokenHash {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+/// Lifetime limits for issued sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// Hard limit from issue time, regardless of activity.
+    pub absolute_ttl_ms: u64,
+    /// Limit since the session was last used.
+    pub idle_ttl_ms: u64,
+}
+
+impl Default for SessionConfig {
+    /// 8 hours absolute, 30 minutes idle.
+    fn default() -> Self {
+        Self {
+            absolute_ttl_ms: 8 * 60 * 60 * 1000,
+            idle_ttl_ms: 30 * 60 * 1000,
+        }
+    }
+}
+
+/// Which limit ended a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    Absolute,
+    Idle,
+}
+
+/// Why a token was not accepted or a session could not be issued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// Never issued, already revoked, or rotated away.
+    UnknownToken,
+    Expired(Expiry),
+    /// The CSPRNG failed; no token was issued.
+    Rng(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownToken => f.write_str("unknown or revoked session token"),
+            Self::Expired(Expiry::Absolute) => f.write_str("session reached its absolute lifetime"),
+            Self::Expired(Expiry::Idle) => f.write_str("session expired after inactivity"),
+            Self::Rng(why) => write!(f, "random token generation failed: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// What the store knows about a live session. Never contains the token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub user: String,
+    pub role: Role,
+    pub issued_at_ms: u64,
+    pub last_seen_ms: u64,
+    pub expires_at_ms: u64,
+}
+
+/// Issues, validates and revokes sessions, storing only token hashes.
+#[derive(Debug)]
+pub struct SessionStore<C: Clock = SystemClock> {
+    clock: C,
+    config: SessionConfig,
+    sessions: HashMap<TokenHash, SessionInfo>,
+}
+
+impl SessionStore<SystemClock> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock, SessionConfig::default())
+    }
+}
+
+impl Default for SessionStore<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> SessionStore<C> {
+    pub fn with_clock(clock: C, config: SessionConfig) -> Self {
+        Self {
+            clock,
+            config,
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// Current time on the store's clock.
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+
+    pub const fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Number of stored sessions (including expired ones not yet purged).
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Issue a new session for `user`.
+    pub fn issue(&mut self, user: &str, role: Role) -> Result<Session, SessionError> {
+        let now = self.clock.now_ms();
+        let expires_at_ms = now.saturating_add(self.config.absolute_ttl_ms);
+        self.insert(user, role, now, expires_at_ms)
+    }
+
+    /// Check a presented token and refresh its idle timer.
+    ///
+    /// Expired sessions are removed as they are found.
+    pub fn validate(&mut self, token: &str) -> Result<SessionInfo, SessionError> {
+        let now = self.clock.now_ms();
+        let key = self.find(token)?;
+        let idle_ttl = self.config.idle_ttl_ms;
+        let expiry = self
+            .sessions
+            .get(&key)
+            .and_then(|info| expiry_of(info, now, idle_ttl));
+        if let Some(expiry) = expiry {
+            self.sessions.remove(&key);
+            return Err(SessionError::Expired(expiry));
+        }
+        let info = self
+            .sessions
+            .get_mut(&key)
+            .ok_or(SessionError::UnknownToken)?;
+        info.last_seen_ms = now;
+        Ok(info.clone())
+    }
+
+    /// Replace a valid session with a new token carrying `role`.
+    ///
+    /// Call this whenever privileges change (login step-up, role grant) so a
+    /// token captured before the change can't inherit the new rights. The new
+    /// session keeps the original absolute deadline. The old token is revoked
+    /// only once the new one is stored, so a failed rotation leaves the
+    /// caller signed in.
+    pub fn rotate(&mut self, token: &str, role: Role) -> Result<Session, SessionError> {
+        let info = self.validate(token)?;
+        let now = self.clock.now_ms();
+        let session = self.insert(&info.user, role, now, info.expires_at_ms)?;
+        self.revoke(token);
+        Ok(session)
+    }
+
+    /// Revoke one session; `true` if it existed.
+    pub fn revoke(&mut self, token: &str) -> bool {
+        self.find(token)
+            .is_ok_and(|key| self.sessions.remove(&key).is_some())
+    }
+
+    /// Revoke every session for `user` (e.g. after a password change).
+    pub fn revoke_user(&mut self, user: &str) -> usize {
+        let before = self.sessions.len();
+        self.sessions.retain(|_, info| info.user != user);
+        before - self.sessions.len()
+    }
+
+    /// Revoke everything (e.g. after a signing-key or store compromise).
+    pub fn revoke_all(&mut self) -> usize {
+        let count = self.sessions.len();
+        self.sessions.clear();
+        count
+    }
+
+    /// Drop expired sessions; returns how many were removed.
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now_ms();
+        let idle_ttl = self.config.idle_ttl_ms;
+        let before = self.sessions.len();
+        self.sessions
+            .retain(|_, info| expiry_of(info, now, idle_ttl).is_none());
+        before - self.sessions.len()
+    }
+
+    /// Live sessions for `user`, without tokens.
+    pub fn sessions_for<'a>(&'a self, user: &'a str) -> impl Iterator<Item = &'a SessionInfo> {
+        self.sessions.values().filter(move |info| info.user == user)
+    }
+
+    fn insert(
+        &mut self,
+        user: &str,
+        role: Role,
+        now: u64,
+        expires_at_ms: u64,
+    ) -> Result<Session, SessionError> {
+        let token = generate_token()?;
+        self.sessions.insert(
+            hash_token(&token),
+            SessionInfo {
+                user: user.to_string(),
+                role,
+                issued_at_ms: now,
+                last_seen_ms: now,
+                expires_at_ms,
+            },
+        );
+        Ok(Session::new(user, token))
+    }
+
+    fn find(&self, token: &str) -> Result<TokenHash, SessionError> {
+        let key = hash_token(token);
+        if self.sessions.contains_key(&key) {
+            Ok(key)
+        } else {
+            Err(SessionError::UnknownToken)
+        }
+    }
+}
+
+const fn expiry_of(info: &SessionInfo, now: u64, idle_ttl_ms: u64) -> Option<Expiry> {
+    if now >= info.expires_at_ms {
+        Some(Expiry::Absolute)
+    } else if now.saturating_sub(info.last_seen_ms) >= idle_ttl_ms {
+        Some(Expiry::Idle)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const MIN: u64 = 60 * 1000;
+
+    fn store() -> (ManualClock, SessionStore<ManualClock>) {
+        let clock = ManualClock::new(1_000_000);
+        let config = SessionConfig {
+            absolute_ttl_ms: 60 * MIN,
+            idle_ttl_ms: 10 * MIN,
+        };
+        (clock.clone(), SessionStore::with_clock(clock, config))
+    }
+
+    fn issue(store: &mut SessionStore<ManualClock>, user: &str, role: Role) -> String {
+        store
+            .issue(user, role)
+            .map(|s| s.token.expose().clone())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn tokens_are_random_and_well_formed() {
+        let a = generate_token().unwrap_or_default();
+        let b = generate_token().unwrap_or_default();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), TOKEN_PREFIX.len() + 64);
+        assert!(a.starts_with(TOKEN_PREFIX));
+        assert!(
+            a[TOKEN_PREFIX.len()..]
+                .bytes()
+                .all(|c| c.is_ascii_hexdigit())
+        );
+    }
+
+    #[test]
+    fn store_keeps_only_hashes() {
+        let (_, mut store) = store();
+        let token = issue(&mut store, "alice", Role::Viewer);
+        let dump = format!("{store:?}");
+        assert!(!dump.contains(&token));
+        assert!(!dump.contains(&token[TOKEN_PREFIX.len()..]));
+        assert_eq!(
+            store.validate(&token).map(|i| i.user),
+            Ok("alice".to_string())
+        );
+        assert_eq!(
+            store.validate("sess_guess"),
+            Err(SessionError::UnknownToken)
+        );
+    }
+
+    #[test]
+    fn idle_timeout_is_refreshed_by_use() {
+        let (clock, mut store) = store();
+        let token = issue(&mut store, "alice", Role::Viewer);
+        clock.advance(9 * MIN);
+        assert!(store.validate(&token).is_ok());
+        clock.advance(9 * MIN);
+        assert!(store.validate(&token).is_ok());
+        clock.advance(10 * MIN);
+        assert_eq!(
+            store.validate(&token),
+            Err(SessionError::Expired(Expiry::Idle))
+        );
+        assert_eq!(store.validate(&token), Err(SessionError::UnknownToken));
+    }
+
+    #[test]
+    fn absolute_lifetime_wins_over_activity() {
+        let (clock, mut store) = store();
+        let token = issue(&mut store, "alice", Role::Viewer);
+        for _ in 0..6 {
+            clock.advance(9 * MIN);
+            assert!(store.validate(&token).is_ok());
+        }
+        clock.advance(6 * MIN);
+        assert_eq!(
+            store.validate(&token),
+            Err(SessionError::Expired(Expiry::Absolute))
+        );
+    }
+
+    #[test]
+    fn revocation_scopes() {
+        let (_, mut store) = store();
+        let a1 = issue(&mut store, "alice", Role::Viewer);
+        let a2 = issue(&mut store, "alice", Role::Viewer);
+        let b1 = issue(&mut store, "bob", Role::Operator);
+        let c1 = issue(&mut store, "carol", Role::Admin);
+
+        assert!(store.revoke(&a1));
+        assert!(!store.revoke(&a1));
+        assert_eq!(store.sessions_for("alice").count(), 1);
+        assert_eq!(store.revoke_user("alice"), 1);
+        assert!(store.validate(&a2).is_err());
+        assert!(store.validate(&b1).is_ok());
+        assert_eq!(store.revoke_all(), 2);
+        assert!(store.validate(&c1).is_err());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn rotation_invalidates_old_token_and_keeps_deadline() {
+        let (clock, mut store) = store();
+        let old = issue(&mut store, "alice", Role::Viewer);
+        let issued = store.validate(&old).map(|i| i.expires_at_ms);
+        clock.advance(5 * MIN);
+
+        let rotated = store.rotate(&old, Role::Admin);
+        assert!(rotated.is_ok());
+        let Ok(rotated) = rotated else { return };
+        assert_eq!(store.validate(&old), Err(SessionError::UnknownToken));
+
+        let info = store.validate(rotated.token.expose());
+        assert_eq!(info.as_ref().map(|i| i.role), Ok(Role::Admin));
+        assert_eq!(info.map(|i| i.expires_at_ms), issued);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn purge_removes_only_expired() {
+        let (clock, mut store) = store();
+        let _stale = issue(&mut store, "alice", Role::Viewer);
+        clock.advance(8 * MIN);
+        let fresh = issue(&mut store, "bob", Role::Viewer);
+        clock.advance(3 * MIN);
+        assert_eq!(store.purge_expired(), 1);
+        assert!(store.validate(&fresh).is_ok());
+    }
+}
@@ -1,9 +1,20 @@
 //! Integration tests for `exercise_structs`.
 
+#![allow(clippy::expect_used)]
+
+use std::net::{IpAddr, Ipv4Addr};
+
 use structs_exercises::account::{Role, UserAccount};
 use structs_exercises::auth_flow::authenticate;
+use structs_exercises::auth_flow::login;
 use structs_exercises::session::Session;
-use structs_exercises::{get_exercise_list, run_all, run_exercise};
+use structs_exercises::{
+    AccountStore, Argon2id, AuditAction, Clock, DecisionReason, Expiry, FileAccountStore,
+    LockScope, LockoutConfig, LockoutDecision, LockoutPolicy, LoginStep, ManualClock, OtpError,
+    PasswordHasher, Pbkdf2Sha256, Policy, SessionConfig, SessionError, SessionStore, StoreError,
+    TotpConfig, TotpFactor, UnlockReason, Verification, finish_login, get_exercise_list, run_all,
+    run_exercise, start_login, verify_password,
+};
 
 #[test]
 fn exercise_list_complete() {
@@ -27,12 +38,12 @@ fn run_all_exercises() {
 
 #[test]
 fn account_session_integration() {
-    let acct = UserAccount::new("ops", "ops@secops.local", "key-42", Role::Operator);
+    let acct = UserAccount::new("ops", "ops@secops.local", "key-42", Role::Operator).expect("hash");
     let session = authenticate(&acct, "key-42");
     assert!(session.is_ok());
     if let Ok(s) = session {
         assert_eq!(s.user, "ops");
-        assert!(s.matches_token(&s.token));
+        assert!(s.matches_token(s.token.expose()));
     }
 }
 
@@ -47,8 +58,267 @@ fn session_partial_eq() {
 
 #[test]
 fn admin_audit_consumes_account() {
-    let acct = UserAccount::new("admin", "admin@secops.local", "x", Role::Admin);
+    let acct = UserAccount::new("admin", "admin@secops.local", "x", Role::Admin).expect("hash");
     let line = acct.into_audit_line();
     assert!(line.contains("admin@secops.local"));
     assert!(line.contains("Admin"));
 }
+
+#[test]
+fn pluggable_hashers_share_one_verifier() {
+    let hashers: Vec<Box<dyn PasswordHasher>> = vec![
+        Box::new(Pbkdf2Sha256::new(2_000)),
+        Box::new(Argon2id::new(512, 1, 1)),
+    ];
+    for hasher in &hashers {
+        let phc = hasher.hash("correct horse");
+        assert!(phc.is_ok());
+        let Ok(phc) = phc else { return };
+        assert!(phc.starts_with(&format!("${}$", hasher.algorithm())));
+        assert_eq!(verify_password("correct horse", &phc), Ok(true));
+        assert_eq!(verify_password("battery staple", &phc), Ok(false));
+    }
+}
+
+#[test]
+fn stored_hash_round_trips_and_upgrades() {
+    let policy = Argon2id::new(512, 1, 1);
+    let original = UserAccount::with_hasher(
+        "ops",
+        "ops@secops.local",
+        "key-42",
+        Role::Operator,
+        &Pbkdf2Sha256::new(2_000),
+    );
+    assert!(original.is_ok());
+    let Ok(original) = original else { return };
+
+    // Persist and reload the account from its PHC string.
+    let stored = original.password_hash().expose().clone();
+    let mut reloaded = UserAccount::from_hash("ops", "ops@secops.local", stored, Role::Operator);
+    assert!(authenticate(&reloaded, "key-42").is_ok());
+
+    let outcome = reloaded.verify_and_rehash("key-42", &policy);
+    assert!(matches!(outcome, Ok(Verification::Rehashed(_))));
+    assert!(!policy.needs_rehash(reloaded.password_hash().expose()));
+    assert!(reloaded.verify_credential("key-42"));
+}
+
+#[test]
+fn session_store_lifecycle_with_manual_clock() {
+    let clock = ManualClock::new(0);
+    let config = SessionConfig {
+        absolute_ttl_ms: 60_000,
+        idle_ttl_ms: 20_000,
+    };
+    let mut store = SessionStore::with_clock(clock.clone(), config);
+    let acct = UserAccount::new("ops", "ops@secops.local", "key-42", Role::Operator).expect("hash");
+
+    let session = login(&mut store, &acct, "key-42");
+    assert!(session.is_ok());
+    let Ok(session) = session else { return };
+    let token = session.token.expose().clone();
+    assert!(token.starts_with("sess_") && !token.contains("ops"));
+
+    clock.advance(15_000);
+    assert!(store.validate(&token).is_ok());
+    let elevated = store.rotate(&token, Role::Admin);
+    assert!(elevated.is_ok());
+    let Ok(elevated) = elevated else { return };
+    assert_eq!(store.validate(&token), Err(SessionError::UnknownToken));
+
+    clock.advance(25_000);
+    assert_eq!(
+        store.validate(elevated.token.expose()),
+        Err(SessionError::Expired(Expiry::Idle))
+    );
+    assert!(store.is_empty());
+}
+
+#[test]
+fn lockout_policy_backs_off_and_admin_unlocks() {
+    let source = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 77));
+    let clock = ManualClock::new(0);
+    let config = LockoutConfig {
+        account_threshold: 2,
+        base_lockout_ms: 10_000,
+        ..LockoutConfig::default()
+    };
+    let mut policy = LockoutPolicy::with_clock(clock.clone(), config);
+    let acct = UserAccount::from_hash("ops", "ops@secops.local", "!", Role::Operator);
+    let scope = LockScope::Account("ops".to_owned());
+
+    for _ in 0..2 {
+        policy.attempt("ops", source, || acct.verify_credential("guess"));
+    }
+    assert_eq!(policy.locked_until(&scope), Some(10_000));
+    clock.set(10_000);
+    for _ in 0..2 {
+        policy.attempt("ops", source, || acct.verify_credential("guess"));
+    }
+    assert_eq!(policy.locked_until(&scope), Some(30_000));
+
+    let admin = UserAccount::from_hash("root", "root@secops.local", "!", Role::Admin);
+    assert!(
+        policy
+            .admin_unlock(&admin, &scope, UnlockReason::PasswordReset)
+            .is_ok()
+    );
+    assert_eq!(policy.check("ops", source), LockoutDecision::Allowed);
+    assert!(
+        policy
+            .events()
+            .iter()
+            .any(|e| e.to_string().contains("[PASSWORD_RESET]"))
+    );
+}
+
+#[test]
+fn policy_file_loads_from_disk_and_scopes_grants() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("policies/default.policy");
+    let policy = Policy::load(&path);
+    assert!(policy.is_ok());
+    let Ok(policy) = policy else { return };
+
+    let carol = UserAccount::from_hash("carol", "carol@secops.local", "!", Role::Viewer);
+    assert!(
+        policy
+            .authorize(&carol, "cluster.deploy", "cluster-a")
+            .is_allowed()
+    );
+    let denied = policy.authorize(&carol, "cluster.deploy", "cluster-b");
+    assert!(!denied.is_allowed());
+    assert!(matches!(
+        denied.reason,
+        DecisionReason::MissingPermission { .. }
+    ));
+    assert!(
+        denied
+            .to_string()
+            .starts_with("DENY carol cluster.deploy on cluster-b")
+    );
+
+    let operator = UserAccount::from_hash("ops", "ops@secops.local", "!", Role::Operator);
+    assert!(
+        !policy
+            .authorize(&operator, "policy.edit", "cluster-a")
+            .is_allowed()
+    );
+    assert!(Policy::load(std::path::Path::new("no/such.policy")).is_err());
+}
+
+#[test]
+fn totp_login_with_drift_replay_and_recovery_code() {
+    let clock = ManualClock::new(1_700_000_000_000);
+    let mut store = SessionStore::with_clock(clock.clone(), SessionConfig::default());
+    let mut acct =
+        UserAccount::new("sre", "sre@secops.local", "pager", Role::Operator).expect("hash");
+    let enrollment = acct
+        .enroll_totp("SecOps Lab", TotpConfig::default())
+        .expect("enroll");
+    assert!(
+        enrollment
+            .uri
+            .expose()
+            .starts_with("otpauth://totp/SecOps%20Lab:sre?secret=")
+    );
+    assert_eq!(
+        enrollment.recovery_codes.len(),
+        UserAccount::RECOVERY_CODE_COUNT
+    );
+    let app = TotpFactor::from_base32(enrollment.secret.expose(), TotpConfig::default())
+        .expect("authenticator");
+
+    let mut pending = || match start_login(&mut store, &acct, "pager") {
+        Ok(LoginStep::SecondFactorRequired(p)) => Some(p),
+        _ => None,
+    };
+    let mut first = pending().expect("first challenge");
+    let mut second = pending().expect("second challenge");
+    let mut third = pending().expect("third challenge");
+
+    // The phone runs 30 s behind: still inside the ±1 step window.
+    let lagging = app.code_at(clock.now_ms() - 30_000).expect("code");
+    assert!(finish_login(&mut store, &mut first, &mut acct, &lagging).is_ok());
+    let replay = finish_login(&mut store, &mut second, &mut acct, &lagging);
+    assert!(replay.is_err_and(|e| e.downcast_ref() == Some(&OtpError::Replayed)));
+
+    let code = enrollment.recovery_codes[3].expose().clone();
+    assert!(finish_login(&mut store, &mut third, &mut acct, &code).is_ok());
+    assert_eq!(
+        acct.verify_second_factor(&code, clock.now_ms()),
+        Err(OtpError::UnknownRecoveryCode)
+    );
+}
+
+#[test]
+fn file_account_store_survives_reopen() {
+    let dir = std::env::temp_dir().join(format!("structs-accounts-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let clock = ManualClock::new(1_000);
+
+    let mut store = FileAccountStore::open_with_clock(&dir, clock.clone()).expect("open store");
+    let mut acct = UserAccount::with_hasher(
+        "erin",
+        "erin@corp.local",
+        "hunter22",
+        Role::Operator,
+        &Argon2id::new(256, 1, 1),
+    )
+    .expect("hash");
+    let enrollment = acct
+        .enroll_totp("SecOps Lab", TotpConfig::default())
+        .expect("enroll");
+    assert!(store.create(acct, "root").is_ok());
+    clock.advance(500);
+    assert!(store.disable("erin", "soc").is_ok());
+    assert_eq!(
+        store.create(
+            UserAccount::from_hash("ERIN", "x@corp.local", "!", Role::Viewer),
+            "root"
+        ),
+        Err(StoreError::DuplicateUsername("ERIN".into()))
+    );
+    drop(store);
+
+    let mut store = FileAccountStore::open_with_clock(&dir, clock.clone()).expect("reopen store");
+    let mut erin = store
+        .find_by_email("ERIN@corp.local")
+        .cloned()
+        .expect("erin survives reopen");
+    assert!(erin.locked);
+    assert!(erin.requires_second_factor());
+    assert!(store.enable("erin", "soc").is_ok());
+    assert!(
+        store
+            .get("erin")
+            .is_some_and(|a| a.verify_credential("hunter22"))
+    );
+
+    // The TOTP secret round-trips, so the enrolled app still works.
+    let app = TotpFactor::from_base32(enrollment.secret.expose(), TotpConfig::default())
+        .expect("authenticator");
+    let code = app.code_at(clock.now_ms()).expect("code");
+    assert!(erin.verify_second_factor(&code, clock.now_ms()).is_ok());
+
+    let actions: Vec<_> = store
+        .audit_log()
+        .iter()
+        .map(|r| (r.seq, r.action))
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            (1, AuditAction::Created),
+            (2, AuditAction::Disabled),
+            (3, AuditAction::Enabled)
+        ]
+    );
+    let log = std::fs::read_to_string(dir.join("audit.jsonl")).expect("audit log");
+    assert_eq!(log.lines().count(), 3);
+
+    std::fs::write(dir.join("audit.jsonl"), "{not json}\n").unwrap_or_default();
+    let corrupt = FileAccountStore::open(&dir);
+    assert!(matches!(corrupt, Err(StoreError::Corrupt(why)) if why.contains("line 1")));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,536 @@
+//! Web server access logs: Common, Combined and JSON formats.
+//!
+//! ```text
+//! 203.0.113.7 - alice [10/Oct/2024:13:55:36 -0700] "GET /a.gif HTTP/1.1" 200 2326   <- Common
+//!     ... 200 2326 "https://ref.example/" "Mozilla/5.0"                             <- Combined
+//! {"remote_addr":"203.0.113.7","time_local":"10/Oct/2024:13:55:36 -0700",...}      <- JSON
+//! ```
+//!
+//! Common and Combined are the Apache/Nginx defaults. Quoted fields may
+//! contain `\"` and `\\` escapes. A `-` in an optional field becomes `None`.
+//! Fields after the ones a format defines are ignored. Nginx's
+//! `"$http_x_forwarded_for"` suffix, for example, does not stop a line
+//! parsing as Combined.
+//!
+//! JSON has no standard layout. [`AccessLogEntry::parse_json`] accepts the
+//! usual Nginx variable names (`remote_addr`, `time_local`/`time_iso8601`,
+//! `request`, `status`, `body_bytes_sent`, `http_referer`,
+//! `http_user_agent`) and a few short aliases.
+
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::Value;
+
+use crate::http_status::{HttpStatus, StatusClass};
+
+/// `%d/%b/%Y:%H:%M:%S %z`, as in `[10/Oct/2024:13:55:36 -0700]`.
+const CLF_TIME: &str = "%d/%b/%Y:%H:%M:%S %z";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `host ident user [time] "request" status bytes`
+    Common,
+    /// Common plus `"referer" "user-agent"`.
+    Combined,
+    /// One JSON object per line.
+    Json,
+}
+
+impl LogFormat {
+    /// Guess the format from a sample line: JSON if it starts with `{`,
+    /// otherwise Combined if it parses as Combined, otherwise Common.
+    #[must_use]
+    pub fn detect(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.starts_with('{') {
+            return Some(Self::Json);
+        }
+        [Self::Combined, Self::Common]
+            .into_iter()
+            .find(|&format| AccessLogEntry::parse(line, format).is_ok())
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "common" | "clf" => Ok(Self::Common),
+            "combined" => Ok(Self::Combined),
+            "json" => Ok(Self::Json),
+            other => Err(format!(
+                "unknown log format {other:?} (expected common, combined or json)"
+            )),
+        }
+    }
+}
+
+/// The quoted `"METHOD target PROTOCOL"` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestLine {
+    Http {
+        method: String,
+        target: String,
+        protocol: String,
+    },
+    /// Logged as `-`, e.g. for a connection that timed out (408).
+    Missing,
+    /// Not an HTTP request line. Often TLS handshakes or other protocols
+    /// sent to a plain-HTTP port.
+    Malformed(String),
+}
+
+impl RequestLine {
+    fn parse(raw: &str) -> Self {
+        if raw == "-" || raw.is_empty() {
+            return Self::Missing;
+        }
+        let mut parts = raw.split(' ');
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(protocol), None)
+                if !method.is_empty()
+                    && method.bytes().all(|b| b.is_ascii_uppercase())
+                    && !target.is_empty()
+                    && protocol.starts_with("HTTP/") =>
+            {
+                Self::Http {
+                    method: method.to_owned(),
+                    target: target.to_owned(),
+                    protocol: protocol.to_owned(),
+                }
+            }
+            _ => Self::Malformed(raw.to_owned()),
+        }
+    }
+
+    /// The request target, if this was an HTTP request.
+    #[must_use]
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            Self::Http { target, .. } => Some(target),
+            Self::Missing | Self::Malformed(_) => None,
+        }
+    }
+}
+
+/// One request from an access log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessLogEntry {
+    pub client: IpAddr,
+    pub ident: Option<String>,
+    /// The authenticated user, if any.
+    pub user: Option<String>,
+    pub time: DateTime<FixedOffset>,
+    pub request: RequestLine,
+    /// The raw code. It may be unregistered; see [`Self::status`].
+    pub status: u16,
+    /// Response body size, if one was logged.
+    pub bytes: Option<u64>,
+    pub referer: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Why one log line could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessLogError {
+    MissingField(&'static str),
+    /// A `[` or `"` field with no closing delimiter.
+    UnterminatedField(&'static str),
+    /// The client field is not an IP address. Hostname lookups must be
+    /// disabled on the server.
+    InvalidClient(String),
+    InvalidTimestamp(String),
+    InvalidStatus(String),
+    InvalidBytes(String),
+    Json(String),
+}
+
+impl fmt::Display for AccessLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing {field}"),
+            Self::UnterminatedField(field) => write!(f, "unterminated {field}"),
+            Self::InvalidClient(raw) => write!(f, "client {raw:?} is not an IP address"),
+            Self::InvalidTimestamp(raw) => write!(f, "invalid timestamp {raw:?}"),
+            Self::InvalidStatus(raw) => write!(f, "invalid status {raw:?}"),
+            Self::InvalidBytes(raw) => write!(f, "invalid byte count {raw:?}"),
+            Self::Json(why) => write!(f, "invalid JSON: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for AccessLogError {}
+
+impl AccessLogEntry {
+    /// Parse one line in the given format.
+    pub fn parse(line: &str, format: LogFormat) -> Result<Self, AccessLogError> {
+        match format {
+            LogFormat::Common => Self::parse_clf(line, false),
+            LogFormat::Combined => Self::parse_clf(line, true),
+            LogFormat::Json => Self::parse_json(line),
+        }
+    }
+
+    fn parse_clf(line: &str, combined: bool) -> Result<Self, AccessLogError> {
+        let mut fields = Fields { rest: line };
+        let client = parse_client(fields.bare("client")?)?;
+        let ident = dash_to_none(fields.bare("ident")?);
+        let user = dash_to_none(fields.bare("user")?);
+        let time = parse_time(fields.bracketed("timestamp")?)?;
+        let request = RequestLine::parse(&fields.quoted("request")?);
+        let status = parse_status(fields.bare("status")?)?;
+        let bytes = parse_bytes(fields.bare("bytes")?)?;
+        let (referer, user_agent) = if combined {
+            let referer = fields.quoted("referer")?;
+            let user_agent = fields.quoted("user agent")?;
+            (dash_to_none(&referer), dash_to_none(&user_agent))
+        } else {
+            (None, None)
+        };
+        Ok(Self {
+            client,
+            ident,
+            user,
+            time,
+            request,
+            status,
+            bytes,
+            referer,
+            user_agent,
+        })
+    }
+
+    /// Parse one JSON object. `request` may be given whole or as separate
+    /// `method`, `uri`/`path` and `protocol` keys.
+    pub fn parse_json(line: &str) -> Result<Self, AccessLogError> {
+        let value: Value =
+            serde_json::from_str(line).map_err(|e| AccessLogError::Json(e.to_string()))?;
+        let Value::Object(object) = value else {
+            return Err(AccessLogError::Json("expected an object".into()));
+        };
+        let text = |keys: &[&str]| -> Option<String> {
+            keys.iter()
+                .find_map(|k| object.get(*k))
+                .and_then(|v| match v {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .filter(|s| !s.is_empty() && s != "-")
+        };
+
+        let client =
+            text(&["remote_addr", "client", "ip"]).ok_or(AccessLogError::MissingField("client"))?;
+        let time = text(&["time_local", "time_iso8601", "time", "timestamp"])
+            .ok_or(AccessLogError::MissingField("timestamp"))?;
+        let split_request = || match (
+            text(&["method", "request_method"]),
+            text(&["uri", "request_uri", "path"]),
+        ) {
+            (Some(method), Some(target)) => RequestLine::Http {
+                method,
+                target,
+                protocol: text(&["protocol", "server_protocol"])
+                    .unwrap_or_else(|| "HTTP/1.1".into()),
+            },
+            _ => RequestLine::Missing,
+        };
+        let request = text(&["request"]).map_or_else(split_request, |raw| RequestLine::parse(&raw));
+        let status = text(&["status"]).ok_or(AccessLogError::MissingField("status"))?;
+
+        Ok(Self {
+            client: parse_client(&client)?,
+            ident: None,
+            user: text(&["remote_user", "user"]),
+            time: DateTime::parse_from_rfc3339(&time).or_else(|_| parse_time(&time))?,
+            request,
+            status: parse_status(&status)?,
+            bytes: text(&["body_bytes_sent", "bytes_sent", "bytes"])
+                .map(|b| parse_bytes(&b))
+                .transpose()?
+                .flatten(),
+            referer: text(&["http_referer", "referer"]),
+            user_agent: text(&["http_user_agent", "user_agent"]),
+        })
+    }
+
+    /// The registered status, if the code is in the IANA registry.
+    #[must_use]
+    pub const fn status(&self) -> Option<HttpStatus> {
+        HttpStatus::from_code(self.status)
+    }
+
+    #[must_use]
+    pub const fn status_class(&self) -> Option<StatusClass> {
+        StatusClass::from_code(self.status)
+    }
+
+    /// The request target, if the request line was valid HTTP.
+    #[must_use]
+    pub fn target(&self) -> Option<&str> {
+        self.request.target()
+    }
+}
+
+/// A line that failed to parse, with its 1-based line number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedLine {
+    pub line: usize,
+    pub error: AccessLogError,
+}
+
+/// Every parsed entry from a log, plus the lines that were rejected.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessLog {
+    pub entries: Vec<AccessLogEntry>,
+    pub rejected: Vec<RejectedLine>,
+}
+
+impl AccessLog {
+    /// Parse every non-blank line in `format`. One bad line does not stop the
+    /// rest from being read.
+    #[must_use]
+    pub fn parse(text: &str, format: LogFormat) -> Self {
+        let mut log = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match AccessLogEntry::parse(line, format) {
+                Ok(entry) => log.entries.push(entry),
+                Err(error) => log.rejected.push(RejectedLine {
+                    line: index + 1,
+                    error,
+                }),
+            }
+        }
+        log
+    }
+
+    /// Parse with the format detected from the first non-blank line.
+    #[must_use]
+    pub fn parse_detected(text: &str) -> Self {
+        let format = text
+            .lines()
+            .find(|l| !l.trim().is_empty())
+            .and_then(LogFormat::detect)
+            .unwrap_or(LogFormat::Combined);
+        Self::parse(text, format)
+    }
+}
+
+/// Cursor over space-separated CLF fields.
+struct Fields<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Fields<'a> {
+    fn bare(&mut self, name: &'static str) -> Result<&'a str, AccessLogError> {
+        let rest = self.rest.trim_start_matches(' ');
+        let end = rest.find(' ').unwrap_or(rest.len());
+        let (field, rest) = rest.split_at(end);
+        self.rest = rest;
+        if field.is_empty() {
+            return Err(AccessLogError::MissingField(name));
+        }
+        Ok(field)
+    }
+
+    fn bracketed(&mut self, name: &'static str) -> Result<&'a str, AccessLogError> {
+        let rest = self.rest.trim_start_matches(' ');
+        let inner = rest
+            .strip_prefix('[')
+            .ok_or(AccessLogError::MissingField(name))?;
+        let end = inner
+            .find(']')
+            .ok_or(AccessLogError::UnterminatedField(name))?;
+        self.rest = &inner[end + 1..];
+        Ok(&inner[..end])
+    }
+
+    /// A `"…"` field with `\"` and `\\` unescaped. Other escapes, such as
+    /// Apache's `\xhh`, are kept as written.
+    fn quoted(&mut self, name: &'static str) -> Result<String, AccessLogError> {
+        let rest = self.rest.trim_start_matches(' ');
+        let inner = rest
+            .strip_prefix('"')
+            .ok_or(AccessLogError::MissingField(name))?;
+        let mut value = String::new();
+        let mut chars = inner.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &inner[i + 1..];
+                    return Ok(value);
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\'))) => value.push(escaped),
+                    Some((_, other)) => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+        Err(AccessLogError::UnterminatedField(name))
+    }
+}
+
+fn dash_to_none(field: &str) -> Option<String> {
+    (field != "-" && !field.is_empty()).then(|| field.to_owned())
+}
+
+fn parse_client(raw: &str) -> Result<IpAddr, AccessLogError> {
+    raw.parse()
+        .map_err(|_| AccessLogError::InvalidClient(raw.to_owned()))
+}
+
+fn parse_time(raw: &str) -> Result<DateTime<FixedOffset>, AccessLogError> {
+    DateTime::parse_from_str(raw, CLF_TIME)
+        .map_err(|_| AccessLogError::InvalidTimestamp(raw.to_owned()))
+}
+
+fn parse_status(raw: &str) -> Result<u16, AccessLogError> {
+    raw.parse()
+        .ok()
+        .filter(|code| (100..=999).contains(code))
+        .ok_or_else(|| AccessLogError::InvalidStatus(raw.to_owned()))
+}
+
+fn parse_bytes(raw: &str) -> Result<Option<u64>, AccessLogError> {
+    if raw == "-" {
+        return Ok(None);
+    }
+    raw.parse()
+        .map(Some)
+        .map_err(|_| AccessLogError::InvalidBytes(raw.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMBINED: &str = r#"203.0.113.7 - alice [10/Oct/2024:13:55:36 -0700] "GET /search?q=\"rust\" HTTP/1.1" 200 2326 "https://ref.example/" "Mozilla/5.0 (X11; Linux)" "10.0.0.1""#;
+
+    #[test]
+    fn parses_combined_with_escapes_and_trailing_fields() {
+        let entry = AccessLogEntry::parse(COMBINED, LogFormat::Combined);
+        assert!(entry.is_ok());
+        let Ok(entry) = entry else { return };
+        assert_eq!(entry.client.to_string(), "203.0.113.7");
+        assert_eq!(entry.ident, None);
+        assert_eq!(entry.user.as_deref(), Some("alice"));
+        assert_eq!(entry.time.to_rfc3339(), "2024-10-10T13:55:36-07:00");
+        assert_eq!(entry.target(), Some("/search?q=\"rust\""));
+        assert_eq!(entry.status(), Some(HttpStatus::Ok));
+        assert_eq!(entry.bytes, Some(2326));
+        assert_eq!(entry.referer.as_deref(), Some("https://ref.example/"));
+        assert_eq!(
+            entry.user_agent.as_deref(),
+            Some("Mozilla/5.0 (X11; Linux)")
+        );
+    }
+
+    #[test]
+    fn parses_common_with_missing_request_and_bytes() {
+        let line = r#"2001:db8::1 - - [01/Jan/2025:00:00:00 +0000] "-" 408 -"#;
+        let entry = AccessLogEntry::parse(line, LogFormat::Common);
+        assert!(entry.is_ok());
+        let Ok(entry) = entry else { return };
+        assert!(entry.client.is_ipv6());
+        assert_eq!(entry.request, RequestLine::Missing);
+        assert_eq!(entry.status(), Some(HttpStatus::RequestTimeout));
+        assert_eq!(entry.bytes, None);
+        assert_eq!(entry.user_agent, None);
+
+        let tls = r#"198.51.100.9 - - [01/Jan/2025:00:00:01 +0000] "\x16\x03\x01\x00\xa5" 400 157"#;
+        let entry = AccessLogEntry::parse(tls, LogFormat::Common);
+        assert!(matches!(
+            entry.map(|e| e.request),
+            Ok(RequestLine::Malformed(raw)) if raw.starts_with("\\x16")
+        ));
+    }
+
+    #[test]
+    fn parses_nginx_json() {
+        let line = r#"{"remote_addr":"192.0.2.10","remote_user":"-","time_iso8601":"2024-10-10T20:55:36+00:00","request":"POST /login HTTP/1.1","status":"401","body_bytes_sent":"0","http_user_agent":"curl/8.5"}"#;
+        let entry = AccessLogEntry::parse(line, LogFormat::Json);
+        assert!(entry.is_ok());
+        let Ok(entry) = entry else { return };
+        assert_eq!(entry.user, None);
+        assert_eq!(entry.status(), Some(HttpStatus::Unauthorized));
+        assert_eq!(entry.bytes, Some(0));
+        assert_eq!(entry.target(), Some("/login"));
+
+        let split = r#"{"ip":"192.0.2.10","time":"10/Oct/2024:13:55:36 -0700","method":"GET","path":"/x","status":404}"#;
+        let entry = AccessLogEntry::parse(split, LogFormat::Json);
+        assert!(entry.is_ok_and(|e| e.target() == Some("/x") && e.status == 404));
+    }
+
+    #[test]
+    fn reports_field_errors() {
+        let cases = [
+            (
+                r"host.example - - [x] ",
+                AccessLogError::InvalidClient("host.example".into()),
+            ),
+            (
+                r#"192.0.2.1 - - [10/Oct/2024:13:55:36 -0700 "GET / HTTP/1.1" 200 1"#,
+                AccessLogError::UnterminatedField("timestamp"),
+            ),
+            (
+                r#"192.0.2.1 - - [10/Oct/2024] "GET / HTTP/1.1" 200 1"#,
+                AccessLogError::InvalidTimestamp("10/Oct/2024".into()),
+            ),
+            (
+                r#"192.0.2.1 - - [10/Oct/2024:13:55:36 -0700] "GET / HTTP/1.1 200 1"#,
+                AccessLogError::UnterminatedField("request"),
+            ),
+            (
+                r#"192.0.2.1 - - [10/Oct/2024:13:55:36 -0700] "GET / HTTP/1.1" 2000 1"#,
+                AccessLogError::InvalidStatus("2000".into()),
+            ),
+            (
+                r#"192.0.2.1 - - [10/Oct/2024:13:55:36 -0700] "GET / HTTP/1.1" 200"#,
+                AccessLogError::MissingField("bytes"),
+            ),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(
+                AccessLogEntry::parse(line, LogFormat::Common),
+                Err(expected),
+                "{line}"
+            );
+        }
+        assert!(matches!(
+            AccessLogEntry::parse("[1,2]", LogFormat::Json),
+            Err(AccessLogError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn detects_format_and_collects_rejects() {
+        assert_eq!(LogFormat::detect(COMBINED), Some(LogFormat::Combined));
+        assert_eq!(
+            LogFormat::detect(
+                r#"192.0.2.1 - - [10/Oct/2024:13:55:36 -0700] "GET / HTTP/1.1" 200 5"#
+            ),
+            Some(LogFormat::Common)
+        );
+        assert_eq!(LogFormat::detect(r#"{"a":1}"#), Some(LogFormat::Json));
+        assert_eq!(LogFormat::detect("garbage"), None);
+        assert_eq!("CLF".parse(), Ok(LogFormat::Common));
+
+        let text = format!("{COMBINED}\n\nnot a log line\n{COMBINED}\n");
+        let log = AccessLog::parse_detected(&text);
+        assert_eq!(log.entries.len(), 2);
+        assert_eq!(log.rejected.len(), 1);
+        assert_eq!(log.rejected[0].line, 3);
+    }
+}
@@ -1,9 +1,24 @@
 //! Authentication status walkthrough exercise.
 
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chrono::{DateTime, TimeDelta};
+use structs_exercises::{
+    LoginStep, PendingLogin, Role, SessionStore, TotpConfig, UserAccount, start_login,
+};
 
+use crate::auth_events::{AuthEvent, AuthEventStore, AuthState, utc_day};
 use crate::auth_status::AuthStatus;
 
+/// Run a password step for an account with TOTP enrolled.
+fn second_factor_challenge() -> Result<PendingLogin> {
+    let mut account = UserAccount::new("bob", "bob@secops.local", "hunter2", Role::Operator)?;
+    account.enroll_totp("SecOps Lab", TotpConfig::default())?;
+    match start_login(&mut SessionStore::new(), &account, "hunter2")? {
+        LoginStep::SecondFactorRequired(challenge) => Ok(challenge),
+        LoginStep::Authenticated(_) => bail!("expected a second-factor challenge"),
+    }
+}
+
 /// Run the auth-status exercise.
 pub fn run(verbose: bool) -> Result<()> {
     println!("🔑 Auth Status — Enum Variants for Identity Outcomes");
@@ -16,17 +31,17 @@ pub fn run(verbose: bool) -> Result<()> {
         AuthStatus::Failure {
             reason: "invalid MFA code".to_owned(),
         },
-        AuthStatus::Pending,
-        AuthStatus::Locked {
-            until: "2026-07-10T08:00:00Z".to_owned(),
+        AuthStatus::Pending {
+            challenge: second_factor_challenge()?,
         },
+        AuthStatus::locked_until_ms(1_783_670_400_000),
     ];
 
     for status in &outcomes {
         let icon = match status {
             AuthStatus::Success { .. } => "[ok]",
             AuthStatus::Failure { .. } => "[no]",
-            AuthStatus::Pending => "[..]",
+            AuthStatus::Pending { .. } => "[..]",
             AuthStatus::Locked { .. } => "[lock]",
         };
         println!(
@@ -41,6 +56,72 @@ pub fn run(verbose: bool) -> Result<()> {
         println!("Exhaustive match ensures every variant is handled at compile time.");
     }
 
+    println!();
+    event_log(&outcomes, verbose)
+}
+
+/// Record the outcomes as events, reject an illegal one, and query the log.
+fn event_log(outcomes: &[AuthStatus], verbose: bool) -> Result<()> {
+    println!("📜 Auth Events — Validated Transitions and Replay");
+    let start = DateTime::from_timestamp(1_783_670_400, 0).unwrap_or_default();
+    let at = |minutes| start + TimeDelta::minutes(minutes);
+    let mut store = AuthEventStore::new();
+
+    // Failure, Pending, Success, sign out, then the lockout.
+    for (minute, index) in [(0, 1), (1, 2), (2, 0)] {
+        store.record_status("alice", &outcomes[index], at(minute))?;
+    }
+    store.apply("alice", AuthEvent::LoggedOut, at(3))?;
+    store.record_status("alice", &outcomes[3], at(4))?;
+    match store.apply("alice", AuthEvent::LoginSucceeded, at(10)) {
+        Ok(_) => bail!("a locked account must not log in"),
+        Err(e) => println!("[rejected] {e}"),
+    }
+    store.apply("alice", AuthEvent::Unlocked, at(20))?;
+    store.apply("alice", AuthEvent::LoginSucceeded, at(21))?;
+
+    for i in 0..6 {
+        store.apply("mallory", AuthEvent::PasswordAccepted, at(30 + i * 2))?;
+        store.apply(
+            "mallory",
+            AuthEvent::LoginFailed {
+                reason: "invalid MFA code".to_owned(),
+            },
+            at(31 + i * 2),
+        )?;
+    }
+
+    println!(
+        "alice: {} at +2m, {} at +5m, {} now",
+        store.state_at("alice", at(2)),
+        store.state_at("alice", at(5)),
+        store.state("alice")
+    );
+    let today = utc_day(start.date_naive());
+    for (user, count) in
+        store.users_with_transition(AuthState::Pending, AuthState::Failed, &today, 5)
+    {
+        println!("[alert] {user}: pending → failed {count} times today");
+    }
+
+    if verbose {
+        for t in store.transitions() {
+            println!(
+                "  #{} {} {}: {} → {}",
+                t.record.seq,
+                t.record.at.format("%H:%M"),
+                t.record.user,
+                t.from,
+                t.to
+            );
+        }
+    }
+    let replayed = AuthEventStore::from_jsonl(&store.to_jsonl())?;
+    println!(
+        "Replayed {} events; alice is {}",
+        replayed.events().len(),
+        replayed.state("alice")
+    );
     Ok(())
 }
 
@@ -51,5 +132,6 @@ mod tests {
     #[test]
     fn auth_demo_runs() {
         assert!(run(false).is_ok());
+        assert!(run(true).is_ok());
     }
 }
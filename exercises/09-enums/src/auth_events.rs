@@ -0,0 +1,567 @@
+//! Authentication as a validated state machine backed by an event log.
+//!
+//! [`AuthStatus`] is a snapshot. This module records how each user got there.
+//! [`AuthEventStore::apply`] first checks an [`AuthEvent`] against the user's
+//! current [`AuthState`]. If the transition is legal, the event is appended
+//! to the log. Otherwise it is rejected and nothing is recorded.
+//!
+//! ```text
+//! Idle | Failed | Pending ──PasswordAccepted──▶ Pending
+//!                          ──LoginSucceeded───▶ Authenticated
+//!                          ──LoginFailed──────▶ Failed
+//! Authenticated ───────────LoggedOut──────────▶ Idle
+//! any but Locked ──────────LockedOut──────────▶ Locked
+//! Locked ──────────────────Unlocked───────────▶ Idle
+//! ```
+//!
+//! A locked account only leaves `Locked` through an explicit `Unlocked`
+//! event. That stays true after the lock's deadline has passed, so every
+//! unlock shows up in the log.
+//!
+//! The log is the source of truth. State at any moment is rebuilt by
+//! replaying events ([`AuthEventStore::state_at`]), and a stored stream is
+//! re-validated as it is loaded ([`AuthEventStore::replay`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::Range;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::auth_status::AuthStatus;
+
+/// Where a user is in the login flow.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub enum AuthState {
+    /// Signed out, or never seen.
+    #[default]
+    Idle,
+    /// Password accepted; waiting on a second factor.
+    Pending,
+    Authenticated,
+    /// The last attempt failed.
+    Failed,
+    Locked,
+}
+
+impl fmt::Display for AuthState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Idle => "idle",
+            Self::Pending => "pending",
+            Self::Authenticated => "authenticated",
+            Self::Failed => "failed",
+            Self::Locked => "locked",
+        })
+    }
+}
+
+/// Something that happened to a user's authentication.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuthEvent {
+    /// Password correct; a second factor is required.
+    PasswordAccepted,
+    LoginSucceeded,
+    LoginFailed {
+        reason: String,
+    },
+    LockedOut {
+        until: DateTime<Utc>,
+    },
+    /// Lock cleared, by expiry or by an administrator.
+    Unlocked,
+    LoggedOut,
+}
+
+impl AuthEvent {
+    /// The event that produces this status.
+    #[must_use]
+    pub fn from_status(status: &AuthStatus) -> Self {
+        match status {
+            AuthStatus::Success { .. } => Self::LoginSucceeded,
+            AuthStatus::Failure { reason } => Self::LoginFailed {
+                reason: reason.clone(),
+            },
+            AuthStatus::Pending { .. } => Self::PasswordAccepted,
+            AuthStatus::Locked { until } => Self::LockedOut { until: *until },
+        }
+    }
+
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::PasswordAccepted => "PasswordAccepted",
+            Self::LoginSucceeded => "LoginSucceeded",
+            Self::LoginFailed { .. } => "LoginFailed",
+            Self::LockedOut { .. } => "LockedOut",
+            Self::Unlocked => "Unlocked",
+            Self::LoggedOut => "LoggedOut",
+        }
+    }
+
+    /// The state this event leads to from `from`, or `None` if the
+    /// transition is illegal.
+    #[must_use]
+    pub const fn next_state(&self, from: AuthState) -> Option<AuthState> {
+        use AuthState::{Authenticated, Failed, Idle, Locked, Pending};
+        match (from, self) {
+            (Idle | Failed | Pending, Self::PasswordAccepted) => Some(Pending),
+            (Idle | Failed | Pending, Self::LoginSucceeded) => Some(Authenticated),
+            (Idle | Failed | Pending, Self::LoginFailed { .. }) => Some(Failed),
+            (Idle | Failed | Pending | Authenticated, Self::LockedOut { .. }) => Some(Locked),
+            (Locked, Self::Unlocked) | (Authenticated, Self::LoggedOut) => Some(Idle),
+            _ => None,
+        }
+    }
+}
+
+/// One entry in the event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthEventRecord {
+    /// Position in the whole log, starting at 1.
+    pub seq: u64,
+    pub user: String,
+    pub at: DateTime<Utc>,
+    pub event: AuthEvent,
+}
+
+/// A replayed record together with the states it moved between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition<'a> {
+    pub record: &'a AuthEventRecord,
+    pub from: AuthState,
+    pub to: AuthState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthEventError {
+    /// The event is not allowed from the user's current state.
+    IllegalTransition {
+        user: String,
+        from: AuthState,
+        event: &'static str,
+    },
+    /// The event is older than the user's latest event.
+    OutOfOrder {
+        user: String,
+        latest: DateTime<Utc>,
+        at: DateTime<Utc>,
+    },
+    /// A replayed stream skips or repeats sequence numbers.
+    SequenceGap { expected: u64, found: u64 },
+    /// A stored line could not be decoded.
+    Corrupt { line: usize, reason: String },
+}
+
+impl fmt::Display for AuthEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IllegalTransition { user, from, event } => {
+                write!(f, "{user}: {event} is not allowed while {from}")
+            }
+            Self::OutOfOrder { user, latest, at } => write!(
+                f,
+                "{user}: event at {} is older than the latest at {}",
+                at.to_rfc3339(),
+                latest.to_rfc3339()
+            ),
+            Self::SequenceGap { expected, found } => {
+                write!(f, "expected event #{expected}, found #{found}")
+            }
+            Self::Corrupt { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for AuthEventError {}
+
+/// Append-only authentication event log plus each user's current state.
+#[derive(Debug, Clone, Default)]
+pub struct AuthEventStore {
+    events: Vec<AuthEventRecord>,
+    current: HashMap<String, (AuthState, DateTime<Utc>)>,
+}
+
+impl AuthEventStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate `event` against `user`'s current state and append it.
+    pub fn apply(
+        &mut self,
+        user: &str,
+        event: AuthEvent,
+        at: DateTime<Utc>,
+    ) -> Result<&AuthEventRecord, AuthEventError> {
+        let (from, latest) = self
+            .current
+            .get(user)
+            .copied()
+            .unwrap_or((AuthState::Idle, DateTime::<Utc>::MIN_UTC));
+        if at < latest {
+            return Err(AuthEventError::OutOfOrder {
+                user: user.to_owned(),
+                latest,
+                at,
+            });
+        }
+        let to = event
+            .next_state(from)
+            .ok_or_else(|| AuthEventError::IllegalTransition {
+                user: user.to_owned(),
+                from,
+                event: event.name(),
+            })?;
+        self.current.insert(user.to_owned(), (to, at));
+        let seq = self.events.len() as u64 + 1;
+        self.events.push(AuthEventRecord {
+            seq,
+            user: user.to_owned(),
+            at,
+            event,
+        });
+        Ok(&self.events[self.events.len() - 1])
+    }
+
+    /// Record the event that produces `status`.
+    pub fn record_status(
+        &mut self,
+        user: &str,
+        status: &AuthStatus,
+        at: DateTime<Utc>,
+    ) -> Result<&AuthEventRecord, AuthEventError> {
+        self.apply(user, AuthEvent::from_status(status), at)
+    }
+
+    /// Rebuild a store from a stored stream, re-checking every transition.
+    pub fn replay(
+        records: impl IntoIterator<Item = AuthEventRecord>,
+    ) -> Result<Self, AuthEventError> {
+        let mut store = Self::new();
+        for record in records {
+            let expected = store.events.len() as u64 + 1;
+            if record.seq != expected {
+                return Err(AuthEventError::SequenceGap {
+                    expected,
+                    found: record.seq,
+                });
+            }
+            store.apply(&record.user, record.event, record.at)?;
+        }
+        Ok(store)
+    }
+
+    /// The log as JSON Lines, one record per line.
+    #[must_use]
+    pub fn to_jsonl(&self) -> String {
+        self.events
+            .iter()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Decode and [`replay`](Self::replay) a JSON Lines log.
+    pub fn from_jsonl(text: &str) -> Result<Self, AuthEventError> {
+        let records = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| AuthEventError::Corrupt {
+                    line: index + 1,
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<AuthEventRecord>, _>>()?;
+        Self::replay(records)
+    }
+
+    #[must_use]
+    pub fn events(&self) -> &[AuthEventRecord] {
+        &self.events
+    }
+
+    /// `user`'s state after every event so far.
+    #[must_use]
+    pub fn state(&self, user: &str) -> AuthState {
+        self.current
+            .get(user)
+            .map_or(AuthState::Idle, |(state, _)| *state)
+    }
+
+    /// `user`'s state at `at`, rebuilt by replaying their events up to and
+    /// including that instant.
+    #[must_use]
+    pub fn state_at(&self, user: &str, at: DateTime<Utc>) -> AuthState {
+        self.history(user)
+            .take_while(|r| r.at <= at)
+            .fold(AuthState::Idle, |state, r| {
+                r.event.next_state(state).unwrap_or(state)
+            })
+    }
+
+    /// Every event for `user`, oldest first.
+    pub fn history<'a>(&'a self, user: &'a str) -> impl Iterator<Item = &'a AuthEventRecord> {
+        self.events.iter().filter(move |r| r.user == user)
+    }
+
+    /// Replay the whole log, pairing each record with the states it moved
+    /// between.
+    #[must_use]
+    pub fn transitions(&self) -> Vec<Transition<'_>> {
+        let mut states: HashMap<&str, AuthState> = HashMap::new();
+        self.events
+            .iter()
+            .map(|record| {
+                let state = states.entry(record.user.as_str()).or_default();
+                let from = *state;
+                *state = record.event.next_state(from).unwrap_or(from);
+                Transition {
+                    record,
+                    from,
+                    to: *state,
+                }
+            })
+            .collect()
+    }
+
+    /// How many times each user moved `from` → `to` inside `window`.
+    #[must_use]
+    pub fn count_transitions(
+        &self,
+        from: AuthState,
+        to: AuthState,
+        window: &Range<DateTime<Utc>>,
+    ) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for t in self.transitions() {
+            if t.from == from && t.to == to && window.contains(&t.record.at) {
+                *counts.entry(t.record.user.as_str()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Users who moved `from` → `to` more than `more_than` times inside
+    /// `window`, most frequent first. For example, users who failed a second
+    /// factor more than 5 times today:
+    ///
+    /// ```
+    /// # use enums_exercises::auth_events::{AuthEventStore, AuthState, utc_day};
+    /// # let store = AuthEventStore::new();
+    /// # let today = chrono::Utc::now().date_naive();
+    /// let suspects =
+    ///     store.users_with_transition(AuthState::Pending, AuthState::Failed, &utc_day(today), 5);
+    /// # assert!(suspects.is_empty());
+    /// ```
+    #[must_use]
+    pub fn users_with_transition(
+        &self,
+        from: AuthState,
+        to: AuthState,
+        window: &Range<DateTime<Utc>>,
+        more_than: usize,
+    ) -> Vec<(&str, usize)> {
+        let mut users: Vec<_> = self
+            .count_transitions(from, to, window)
+            .into_iter()
+            .filter(|&(_, count)| count > more_than)
+            .collect();
+        users.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        users
+    }
+}
+
+/// Midnight to midnight UTC on `date`.
+#[must_use]
+pub fn utc_day(date: NaiveDate) -> Range<DateTime<Utc>> {
+    let start = date.and_time(chrono::NaiveTime::MIN).and_utc();
+    start..start + chrono::TimeDelta::days(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    fn t(minutes: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_741_939_200, 0).unwrap_or_default() + TimeDelta::minutes(minutes)
+    }
+
+    fn failed() -> AuthEvent {
+        AuthEvent::LoginFailed {
+            reason: "bad code".into(),
+        }
+    }
+
+    #[test]
+    fn locked_cannot_succeed_without_unlock() {
+        let mut store = AuthEventStore::new();
+        assert!(
+            store
+                .apply("bob", AuthEvent::LockedOut { until: t(15) }, t(0))
+                .is_ok()
+        );
+        assert_eq!(
+            store
+                .apply("bob", AuthEvent::LoginSucceeded, t(1))
+                .map(|r| r.seq),
+            Err(AuthEventError::IllegalTransition {
+                user: "bob".into(),
+                from: AuthState::Locked,
+                event: "LoginSucceeded",
+            })
+        );
+        // Still illegal after the deadline: the unlock must be recorded.
+        assert!(
+            store
+                .apply("bob", AuthEvent::PasswordAccepted, t(30))
+                .is_err()
+        );
+        assert!(store.apply("bob", AuthEvent::Unlocked, t(30)).is_ok());
+        assert!(store.apply("bob", AuthEvent::LoginSucceeded, t(31)).is_ok());
+        assert_eq!(store.state("bob"), AuthState::Authenticated);
+        // Rejected events are not stored.
+        assert_eq!(store.events().len(), 3);
+    }
+
+    #[test]
+    fn transition_table() {
+        use AuthState::{Authenticated, Failed, Idle, Locked, Pending};
+        let lock = AuthEvent::LockedOut { until: t(0) };
+        let cases = [
+            (Idle, AuthEvent::LoggedOut, None),
+            (Idle, AuthEvent::Unlocked, None),
+            (Pending, AuthEvent::LoginSucceeded, Some(Authenticated)),
+            (Pending, failed(), Some(Failed)),
+            (Failed, AuthEvent::PasswordAccepted, Some(Pending)),
+            (Authenticated, AuthEvent::LoginSucceeded, None),
+            (Authenticated, failed(), None),
+            (Authenticated, AuthEvent::LoggedOut, Some(Idle)),
+            (Authenticated, lock.clone(), Some(Locked)),
+            (Locked, lock, None),
+            (Locked, AuthEvent::LoggedOut, None),
+            (Locked, AuthEvent::Unlocked, Some(Idle)),
+        ];
+        for (from, event, expected) in cases {
+            assert_eq!(
+                event.next_state(from),
+                expected,
+                "{from} + {}",
+                event.name()
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_events_older_than_latest() {
+        let mut store = AuthEventStore::new();
+        assert!(store.apply("eve", failed(), t(10)).is_ok());
+        assert!(matches!(
+            store.apply("eve", failed(), t(9)),
+            Err(AuthEventError::OutOfOrder { .. })
+        ));
+        // Other users have their own clocks.
+        assert!(store.apply("bob", failed(), t(5)).is_ok());
+    }
+
+    #[test]
+    fn state_at_replays_history() {
+        let mut store = AuthEventStore::new();
+        let _ = store.apply("carol", AuthEvent::PasswordAccepted, t(0));
+        let _ = store.apply("carol", failed(), t(1));
+        let _ = store.apply("carol", AuthEvent::PasswordAccepted, t(2));
+        let _ = store.apply("carol", AuthEvent::LoginSucceeded, t(3));
+        let _ = store.apply("carol", AuthEvent::LoggedOut, t(60));
+        assert_eq!(store.state_at("carol", t(-1)), AuthState::Idle);
+        assert_eq!(store.state_at("carol", t(1)), AuthState::Failed);
+        assert_eq!(store.state_at("carol", t(2)), AuthState::Pending);
+        assert_eq!(store.state_at("carol", t(30)), AuthState::Authenticated);
+        assert_eq!(store.state_at("carol", t(60)), AuthState::Idle);
+        assert_eq!(store.history("carol").count(), 5);
+    }
+
+    #[test]
+    fn repeated_transition_query() {
+        let mut store = AuthEventStore::new();
+        for (user, failures) in [("mallory", 7), ("dave", 6), ("erin", 5)] {
+            for i in 0..failures {
+                let _ = store.apply(user, AuthEvent::PasswordAccepted, t(i * 2));
+                let _ = store.apply(user, failed(), t(i * 2 + 1));
+            }
+        }
+        // Password failures (Idle/Failed → Failed) don't count.
+        let _ = store.apply("trent", failed(), t(0));
+        for i in 1..10 {
+            let _ = store.apply("trent", failed(), t(i));
+        }
+        // Yesterday's failures are outside the window.
+        let _ = store.apply("zed", AuthEvent::PasswordAccepted, t(-120));
+        let _ = store.apply("zed", failed(), t(-119));
+
+        let today = utc_day(t(0).date_naive());
+        assert_eq!(
+            store.users_with_transition(AuthState::Pending, AuthState::Failed, &today, 5),
+            [("mallory", 7), ("dave", 6)]
+        );
+        let counts = store.count_transitions(AuthState::Failed, AuthState::Failed, &today);
+        assert_eq!(counts.get("trent"), Some(&9));
+    }
+
+    #[test]
+    fn jsonl_round_trip_revalidates() {
+        let mut store = AuthEventStore::new();
+        let _ = store.record_status(
+            "alice",
+            &AuthStatus::Failure {
+                reason: "bad password".into(),
+            },
+            t(0),
+        );
+        let _ = store.record_status(
+            "alice",
+            &AuthStatus::locked_until_ms(1_741_940_000_000),
+            t(1),
+        );
+        let _ = store.apply("alice", AuthEvent::Unlocked, t(20));
+        let text = store.to_jsonl();
+        assert_eq!(text.lines().count(), 3);
+
+        let restored = AuthEventStore::from_jsonl(&text);
+        assert!(
+            restored
+                .as_ref()
+                .is_ok_and(|r| r.events() == store.events())
+        );
+        assert!(restored.is_ok_and(|r| r.state("alice") == AuthState::Idle));
+
+        // Dropping the unlock and forging a success must fail on replay.
+        let mut tampered: Vec<_> = store.events().to_vec();
+        tampered[2].event = AuthEvent::LoginSucceeded;
+        assert!(matches!(
+            AuthEventStore::replay(tampered),
+            Err(AuthEventError::IllegalTransition {
+                from: AuthState::Locked,
+                ..
+            })
+        ));
+        let gap: Vec<_> = store.events().iter().skip(1).cloned().collect();
+        assert_eq!(
+            AuthEventStore::replay(gap).map(|s| s.events().len()),
+            Err(AuthEventError::SequenceGap {
+                expected: 1,
+                found: 2
+            })
+        );
+        assert!(matches!(
+            AuthEventStore::from_jsonl("{}\n"),
+            Err(AuthEventError::Corrupt { line: 1, .. })
+        ));
+    }
+}
@@ -1,15 +1,52 @@
 //! Authentication outcome modeling with enum variants.
 
+use chrono::{DateTime, SecondsFormat, Utc};
+use structs_exercises::{LockoutDecision, PendingLogin};
+
 /// Result of an authentication attempt against an identity provider.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum AuthStatus {
-    Success { user: String },
-    Failure { reason: String },
-    Pending,
-    Locked { until: String },
+    Success {
+        user: String,
+    },
+    Failure {
+        reason: String,
+    },
+    /// Password accepted; the login completes once a one-time code for
+    /// `challenge` is verified.
+    Pending {
+        challenge: PendingLogin,
+    },
+    Locked {
+        until: DateTime<Utc>,
+    },
 }
 
 impl AuthStatus {
+    /// Locked until `until_ms` milliseconds after the Unix epoch, as reported
+    /// by a lockout policy.
+    #[must_use]
+    pub fn locked_until_ms(until_ms: u64) -> Self {
+        let until = i64::try_from(until_ms)
+            .ok()
+            .and_then(DateTime::from_timestamp_millis)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        Self::Locked { until }
+    }
+
+    /// Map a failed or blocked [`LockoutDecision`] to the status shown to the
+    /// client. `Allowed` has no status of its own: the password check decides.
+    #[must_use]
+    pub fn from_lockout(decision: &LockoutDecision) -> Option<Self> {
+        match decision {
+            LockoutDecision::Allowed => None,
+            LockoutDecision::Rejected { .. } => Some(Self::Failure {
+                reason: "invalid credentials".to_owned(),
+            }),
+            LockoutDecision::Locked { until_ms, .. } => Some(Self::locked_until_ms(*until_ms)),
+        }
+    }
+
     /// Whether this status grants authenticated access.
     #[must_use]
     pub const fn is_authenticated(&self) -> bool {
@@ -22,14 +59,24 @@ impl AuthStatus {
         match self {
             Self::Success { user } => format!("authenticated as {user}"),
             Self::Failure { reason } => format!("denied: {reason}"),
-            Self::Pending => "awaiting second factor".to_owned(),
-            Self::Locked { until } => format!("account locked until {until}"),
+            Self::Pending { challenge } => {
+                format!("second factor required for {}", challenge.user())
+            }
+            Self::Locked { until } => format!(
+                "account locked until {}",
+                until.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use structs_exercises::{
+        LoginStep, PasswordHasher, Pbkdf2Sha256, Role, SessionStore, TotpConfig, UserAccount,
+        start_login,
+    };
+
     use super::*;
 
     #[test]
@@ -43,10 +90,49 @@ mod tests {
 
     #[test]
     fn locked_not_authenticated() {
-        let status = AuthStatus::Locked {
-            until: "2026-07-10T00:00:00Z".to_owned(),
-        };
+        let status = AuthStatus::locked_until_ms(1_783_641_600_000);
         assert!(!status.is_authenticated());
         assert!(status.summary().contains("locked"));
+        assert_eq!(
+            status.summary(),
+            "account locked until 2026-07-10T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn pending_carries_the_second_factor_challenge() {
+        let phc = Pbkdf2Sha256::new(1).hash("pw");
+        assert!(phc.is_ok());
+        let Ok(phc) = phc else { return };
+        let mut account = UserAccount::from_hash("erin", "e@x.local", phc, Role::Viewer);
+        assert!(account.enroll_totp("Lab", TotpConfig::default()).is_ok());
+        let mut store = SessionStore::new();
+        let step = start_login(&mut store, &account, "pw");
+        assert!(matches!(step, Ok(LoginStep::SecondFactorRequired(_))));
+        let Ok(LoginStep::SecondFactorRequired(challenge)) = step else {
+            return;
+        };
+        let status = AuthStatus::Pending { challenge };
+        assert!(!status.is_authenticated());
+        assert_eq!(status.summary(), "second factor required for erin");
+    }
+
+    #[test]
+    fn lockout_decisions_map_to_statuses() {
+        assert_eq!(AuthStatus::from_lockout(&LockoutDecision::Allowed), None);
+        assert!(matches!(
+            AuthStatus::from_lockout(&LockoutDecision::Rejected { remaining: 2 }),
+            Some(AuthStatus::Failure { .. })
+        ));
+        let locked = AuthStatus::from_lockout(&LockoutDecision::Locked {
+            scope: structs_exercises::LockScope::Account("alice".to_owned()),
+            until_ms: 60_000,
+        });
+        assert_eq!(locked, Some(AuthStatus::locked_until_ms(60_000)));
+        assert!(locked.is_some_and(|s| s.summary().ends_with("1970-01-01T00:01:00Z")));
+        assert!(matches!(
+            AuthStatus::locked_until_ms(u64::MAX),
+            AuthStatus::Locked { until } if until == DateTime::<Utc>::MAX_UTC
+        ));
     }
 }
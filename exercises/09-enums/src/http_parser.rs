@@ -0,0 +1,923 @@
+//! Zero-copy HTTP/1.1 message head parser.
+//!
+//! Built for bytes that have already been captured, such as reassembled TCP
+//! streams or raw requests pulled from proxy logs. Parsed heads borrow from
+//! the input buffer instead of copying it. When a buffer ends mid-message
+//! the parser returns [`Parsed::Partial`], so the caller can append more
+//! bytes and try again.
+//!
+//! The parser is strict where leniency enables request smuggling (RFC 9112
+//! §11.2):
+//!
+//! - Lines must end in CRLF. A bare LF is an error.
+//! - Whitespace between a field name and its colon is an error.
+//! - Obsolete line folding is an error.
+//! - Chunk sizes must be plain hex.
+//!
+//! [`RequestHead::body_framing`] reports a [`SmugglingRisk`] when
+//! `Content-Length` and `Transfer-Encoding` disagree about where the body
+//! ends.
+
+use std::fmt;
+
+use crate::http_status::{HttpStatus, StatusClass};
+
+/// Size limits applied while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Start line plus all header lines, including their CRLFs.
+    pub max_head_bytes: usize,
+    pub max_headers: usize,
+    /// Total decoded size of a chunked body.
+    pub max_body_bytes: u64,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_head_bytes: 16 * 1024,
+            max_headers: 100,
+            max_body_bytes: 8 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpVersion {
+    Http10,
+    Http11,
+}
+
+impl HttpVersion {
+    fn parse(token: &[u8]) -> Result<Self, HttpParseError> {
+        match token {
+            b"HTTP/1.0" => Ok(Self::Http10),
+            b"HTTP/1.1" => Ok(Self::Http11),
+            _ => Err(HttpParseError::UnsupportedVersion),
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Http10 => "HTTP/1.0",
+            Self::Http11 => "HTTP/1.1",
+        })
+    }
+}
+
+/// One header field, borrowed from the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header<'a> {
+    pub name: &'a str,
+    /// Raw value bytes with surrounding whitespace trimmed. The value may
+    /// contain bytes that are not UTF-8.
+    pub value: &'a [u8],
+}
+
+impl<'a> Header<'a> {
+    /// The value as text, if it is valid UTF-8.
+    #[must_use]
+    pub fn value_str(&self) -> Option<&'a str> {
+        std::str::from_utf8(self.value).ok()
+    }
+}
+
+/// Result of parsing a buffer that may not hold a whole message yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Parsed<T> {
+    /// `consumed` bytes of the buffer made up `value`. Anything after them
+    /// belongs to the body or the next message.
+    Complete { value: T, consumed: usize },
+    /// The buffer ends before the message does.
+    Partial,
+}
+
+impl<T> Parsed<T> {
+    /// The value and the bytes it consumed, or `None` if the input was partial.
+    #[must_use]
+    pub fn complete(self) -> Option<(T, usize)> {
+        match self {
+            Self::Complete { value, consumed } => Some((value, consumed)),
+            Self::Partial => None,
+        }
+    }
+}
+
+/// How the body that follows a head is delimited (RFC 9112 §6.3).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFraming {
+    /// No body.
+    None,
+    ContentLength(u64),
+    /// Decode with [`ChunkedBody::decode`].
+    Chunked,
+    /// The body runs until the server closes the connection (responses only).
+    CloseDelimited,
+}
+
+/// Header combinations that different HTTP implementations frame
+/// differently, letting an attacker hide one request inside another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmugglingRisk {
+    /// Both headers present: the CL.TE and TE.CL attacks.
+    ContentLengthWithTransferEncoding,
+    /// Several `Content-Length` values that are not all equal.
+    ConflictingContentLength,
+    /// A `Content-Length` that is not a plain decimal number, e.g. `+5`.
+    InvalidContentLength,
+    /// A request whose transfer codings don't end in `chunked`, including
+    /// obfuscations like `xchunked` or `chunked, identity`.
+    ChunkedNotFinal,
+    /// `chunked` listed more than once.
+    ChunkedRepeated,
+    /// `Transfer-Encoding` on an HTTP/1.0 request, which 1.0 servers ignore.
+    TransferEncodingInHttp10,
+}
+
+impl fmt::Display for SmugglingRisk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ContentLengthWithTransferEncoding => {
+                "both Content-Length and Transfer-Encoding are present"
+            }
+            Self::ConflictingContentLength => "Content-Length values disagree",
+            Self::InvalidContentLength => "Content-Length is not a decimal number",
+            Self::ChunkedNotFinal => "Transfer-Encoding does not end in chunked",
+            Self::ChunkedRepeated => "chunked is applied more than once",
+            Self::TransferEncodingInHttp10 => "Transfer-Encoding on an HTTP/1.0 request",
+        })
+    }
+}
+
+impl std::error::Error for SmugglingRisk {}
+
+/// Why a message could not be parsed. Line numbers are 1-based and count
+/// the start line as line 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpParseError {
+    HeadTooLarge {
+        limit: usize,
+    },
+    TooManyHeaders {
+        limit: usize,
+    },
+    BodyTooLarge {
+        limit: u64,
+    },
+    /// A line ended in LF without a CR before it.
+    BareLineFeed,
+    InvalidRequestLine,
+    InvalidStatusLine,
+    UnsupportedVersion,
+    InvalidHeader {
+        line: usize,
+    },
+    /// `Name : value`, which RFC 9112 §5.1 says must be rejected.
+    WhitespaceBeforeColon {
+        line: usize,
+    },
+    /// A header line starting with whitespace (RFC 9112 §5.2).
+    ObsoleteLineFolding {
+        line: usize,
+    },
+    InvalidChunkSize,
+    /// Chunk data not followed by CRLF.
+    InvalidChunkTerminator,
+}
+
+impl fmt::Display for HttpParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::HeadTooLarge { limit } => write!(f, "message head exceeds {limit} bytes"),
+            Self::TooManyHeaders { limit } => write!(f, "more than {limit} header fields"),
+            Self::BodyTooLarge { limit } => write!(f, "chunked body exceeds {limit} bytes"),
+            Self::BareLineFeed => f.write_str("line ends in LF without CR"),
+            Self::InvalidRequestLine => f.write_str("malformed request line"),
+            Self::InvalidStatusLine => f.write_str("malformed status line"),
+            Self::UnsupportedVersion => f.write_str("only HTTP/1.0 and HTTP/1.1 are supported"),
+            Self::InvalidHeader { line } => write!(f, "line {line}: malformed header field"),
+            Self::WhitespaceBeforeColon { line } => {
+                write!(f, "line {line}: whitespace between field name and colon")
+            }
+            Self::ObsoleteLineFolding { line } => {
+                write!(f, "line {line}: obsolete line folding")
+            }
+            Self::InvalidChunkSize => f.write_str("malformed chunk size"),
+            Self::InvalidChunkTerminator => f.write_str("chunk data not followed by CRLF"),
+        }
+    }
+}
+
+impl std::error::Error for HttpParseError {}
+
+/// A parsed request line and header fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHead<'a> {
+    pub method: &'a str,
+    pub target: &'a str,
+    pub version: HttpVersion,
+    pub headers: Vec<Header<'a>>,
+}
+
+impl<'a> RequestHead<'a> {
+    /// Parse a request head from the start of `buf`. Empty lines before
+    /// the request line are skipped, as RFC 9112 §2.2 allows.
+    pub fn parse(buf: &'a [u8], limits: &ParseLimits) -> Result<Parsed<Self>, HttpParseError> {
+        let Some((start, headers, consumed)) = split_head(buf, limits)? else {
+            return Ok(Parsed::Partial);
+        };
+        let mut parts = start.split(|&b| b == b' ');
+        let (Some(method), Some(target), Some(version), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(HttpParseError::InvalidRequestLine);
+        };
+        if method.is_empty() || !method.iter().all(|&b| is_tchar(b)) {
+            return Err(HttpParseError::InvalidRequestLine);
+        }
+        if target.is_empty() || !target.iter().all(u8::is_ascii_graphic) {
+            return Err(HttpParseError::InvalidRequestLine);
+        }
+        let version = HttpVersion::parse(version)?;
+        Ok(Parsed::Complete {
+            value: Self {
+                method: ascii_str(method).ok_or(HttpParseError::InvalidRequestLine)?,
+                target: ascii_str(target).ok_or(HttpParseError::InvalidRequestLine)?,
+                version,
+                headers,
+            },
+            consumed,
+        })
+    }
+
+    /// The first header with this name, ignoring ASCII case.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&Header<'a>> {
+        self.headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+    }
+
+    /// How the request body is delimited, or the smuggling risk that makes
+    /// it ambiguous. A request with neither header has no body.
+    pub fn body_framing(&self) -> Result<BodyFraming, SmugglingRisk> {
+        body_framing(&self.headers, Some(self.version))
+    }
+}
+
+/// A parsed status line and header fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseHead<'a> {
+    pub version: HttpVersion,
+    /// Any three-digit code, registered or not.
+    pub code: u16,
+    pub reason: &'a str,
+    pub headers: Vec<Header<'a>>,
+}
+
+impl<'a> ResponseHead<'a> {
+    /// Parse a response head from the start of `buf`.
+    pub fn parse(buf: &'a [u8], limits: &ParseLimits) -> Result<Parsed<Self>, HttpParseError> {
+        let Some((start, headers, consumed)) = split_head(buf, limits)? else {
+            return Ok(Parsed::Partial);
+        };
+        let mut parts = start.splitn(3, |&b| b == b' ');
+        let (Some(version), Some(code)) = (parts.next(), parts.next()) else {
+            return Err(HttpParseError::InvalidStatusLine);
+        };
+        let version = HttpVersion::parse(version)?;
+        let code = match code {
+            [a @ b'1'..=b'9', b @ b'0'..=b'9', c @ b'0'..=b'9'] => {
+                u16::from(a - b'0') * 100 + u16::from(b - b'0') * 10 + u16::from(c - b'0')
+            }
+            _ => return Err(HttpParseError::InvalidStatusLine),
+        };
+        // Some servers omit the space before an empty reason phrase.
+        let reason = parts.next().unwrap_or_default();
+        if reason.iter().any(|&b| b.is_ascii_control() && b != b'\t') {
+            return Err(HttpParseError::InvalidStatusLine);
+        }
+        Ok(Parsed::Complete {
+            value: Self {
+                version,
+                code,
+                reason: std::str::from_utf8(reason)
+                    .map_err(|_| HttpParseError::InvalidStatusLine)?,
+                headers,
+            },
+            consumed,
+        })
+    }
+
+    /// The registered status, if the code is in the IANA registry.
+    #[must_use]
+    pub const fn status(&self) -> Option<HttpStatus> {
+        HttpStatus::from_code(self.code)
+    }
+
+    #[must_use]
+    pub const fn class(&self) -> Option<StatusClass> {
+        StatusClass::from_code(self.code)
+    }
+
+    /// The first header with this name, ignoring ASCII case.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&Header<'a>> {
+        self.headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+    }
+
+    /// How the response body is delimited. 1xx, 204 and 304 responses never
+    /// have a body. Responses to `HEAD` don't either, but the head alone
+    /// can't tell, so callers must check that themselves.
+    pub fn body_framing(&self) -> Result<BodyFraming, SmugglingRisk> {
+        if matches!(self.code, 100..=199 | 204 | 304) {
+            return Ok(BodyFraming::None);
+        }
+        body_framing(&self.headers, None)
+    }
+}
+
+/// A decoded `Transfer-Encoding: chunked` body. The chunks borrow from the
+/// input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedBody<'a> {
+    pub chunks: Vec<&'a [u8]>,
+    pub trailers: Vec<Header<'a>>,
+}
+
+impl<'a> ChunkedBody<'a> {
+    /// Decode a chunked body from the start of `buf`, up to and including
+    /// the trailer section. Chunk extensions are skipped.
+    pub fn decode(buf: &'a [u8], limits: &ParseLimits) -> Result<Parsed<Self>, HttpParseError> {
+        let mut chunks = Vec::new();
+        let mut total = 0u64;
+        let mut pos = 0;
+        loop {
+            let Some((line, next)) = read_line(buf, pos)? else {
+                if buf.len() - pos > limits.max_head_bytes {
+                    return Err(HttpParseError::InvalidChunkSize);
+                }
+                return Ok(Parsed::Partial);
+            };
+            let size = parse_chunk_size(line)?;
+            total = total
+                .checked_add(size)
+                .filter(|&t| t <= limits.max_body_bytes)
+                .ok_or(HttpParseError::BodyTooLarge {
+                    limit: limits.max_body_bytes,
+                })?;
+            pos = next;
+
+            if size == 0 {
+                let Some((trailers, consumed)) = parse_fields(buf, pos, pos, 1, limits)? else {
+                    return Ok(Parsed::Partial);
+                };
+                return Ok(Parsed::Complete {
+                    value: Self { chunks, trailers },
+                    consumed,
+                });
+            }
+
+            let size = usize::try_from(size).map_err(|_| HttpParseError::BodyTooLarge {
+                limit: limits.max_body_bytes,
+            })?;
+            let Some(rest) = buf.get(pos..) else {
+                return Ok(Parsed::Partial);
+            };
+            if rest.len() < size + 2 {
+                return Ok(Parsed::Partial);
+            }
+            if &rest[size..size + 2] != b"\r\n" {
+                return Err(HttpParseError::InvalidChunkTerminator);
+            }
+            chunks.push(&rest[..size]);
+            pos += size + 2;
+        }
+    }
+
+    /// Decoded body length.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|c| c.len()).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().all(|c| c.is_empty())
+    }
+
+    /// Copy the chunks into one contiguous body.
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.chunks.concat()
+    }
+}
+
+/// RFC 9110 §5.6.2 token characters.
+const fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+const fn is_ows(b: u8) -> bool {
+    b == b' ' || b == b'\t'
+}
+
+fn ascii_str(bytes: &[u8]) -> Option<&str> {
+    std::str::from_utf8(bytes).ok().filter(|s| s.is_ascii())
+}
+
+fn trim_ows(mut bytes: &[u8]) -> &[u8] {
+    while let [first, rest @ ..] = bytes
+        && is_ows(*first)
+    {
+        bytes = rest;
+    }
+    while let [rest @ .., last] = bytes
+        && is_ows(*last)
+    {
+        bytes = rest;
+    }
+    bytes
+}
+
+/// The line starting at `start` without its CRLF, and the offset just past
+/// it. `None` if the buffer ends first.
+fn read_line(buf: &[u8], start: usize) -> Result<Option<(&[u8], usize)>, HttpParseError> {
+    let Some(rest) = buf.get(start..) else {
+        return Ok(None);
+    };
+    let Some(lf) = rest.iter().position(|&b| b == b'\n') else {
+        return Ok(None);
+    };
+    rest[..lf]
+        .strip_suffix(b"\r")
+        .map(|line| Some((line, start + lf + 1)))
+        .ok_or(HttpParseError::BareLineFeed)
+}
+
+/// Start line, header fields, and total bytes consumed.
+type Head<'a> = (&'a [u8], Vec<Header<'a>>, usize);
+
+/// Empty lines before the start line are skipped (RFC 9112 §2.2) but still
+/// count toward `max_head_bytes`, so a stream of bare CRLFs can't be buffered
+/// without limit.
+fn split_head<'a>(buf: &'a [u8], limits: &ParseLimits) -> Result<Option<Head<'a>>, HttpParseError> {
+    let mut start_at = 0;
+    while buf[start_at..].starts_with(b"\r\n") && start_at <= limits.max_head_bytes {
+        start_at += 2;
+    }
+    let too_large = HttpParseError::HeadTooLarge {
+        limit: limits.max_head_bytes,
+    };
+    let Some((start, next)) = read_line(buf, start_at)? else {
+        return if buf.len() > limits.max_head_bytes {
+            Err(too_large)
+        } else {
+            Ok(None)
+        };
+    };
+    if next > limits.max_head_bytes {
+        return Err(too_large);
+    }
+    Ok(
+        parse_fields(buf, next, 0, 2, limits)?
+            .map(|(headers, consumed)| (start, headers, consumed)),
+    )
+}
+
+/// Parse header lines from `pos` through the empty line that ends them.
+/// `origin` is where the head started, for the size limit, and
+/// `first_line` numbers the first field line in errors.
+fn parse_fields<'a>(
+    buf: &'a [u8],
+    mut pos: usize,
+    origin: usize,
+    first_line: usize,
+    limits: &ParseLimits,
+) -> Result<Option<(Vec<Header<'a>>, usize)>, HttpParseError> {
+    let mut headers = Vec::new();
+    for line_no in first_line.. {
+        let Some((line, next)) = read_line(buf, pos)? else {
+            return if buf.len() - origin > limits.max_head_bytes {
+                Err(HttpParseError::HeadTooLarge {
+                    limit: limits.max_head_bytes,
+                })
+            } else {
+                Ok(None)
+            };
+        };
+        if next - origin > limits.max_head_bytes {
+            return Err(HttpParseError::HeadTooLarge {
+                limit: limits.max_head_bytes,
+            });
+        }
+        pos = next;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() == limits.max_headers {
+            return Err(HttpParseError::TooManyHeaders {
+                limit: limits.max_headers,
+            });
+        }
+        headers.push(parse_field(line, line_no)?);
+    }
+    Ok(Some((headers, pos)))
+}
+
+fn parse_field(line: &[u8], line_no: usize) -> Result<Header<'_>, HttpParseError> {
+    let invalid = HttpParseError::InvalidHeader { line: line_no };
+    if line.first().is_some_and(|&b| is_ows(b)) {
+        return Err(HttpParseError::ObsoleteLineFolding { line: line_no });
+    }
+    let colon = line.iter().position(|&b| b == b':').ok_or(invalid)?;
+    let (name, value) = (&line[..colon], &line[colon + 1..]);
+    if name.last().is_some_and(|&b| is_ows(b)) {
+        return Err(HttpParseError::WhitespaceBeforeColon { line: line_no });
+    }
+    if name.is_empty() || !name.iter().all(|&b| is_tchar(b)) {
+        return Err(invalid);
+    }
+    let value = trim_ows(value);
+    if value.iter().any(|&b| b == 0 || b == b'\r' || b == b'\n') {
+        return Err(invalid);
+    }
+    Ok(Header {
+        name: ascii_str(name).ok_or(invalid)?,
+        value,
+    })
+}
+
+/// `1*HEXDIG [ BWS ";" chunk-ext ]`, at most 16 digits.
+fn parse_chunk_size(line: &[u8]) -> Result<u64, HttpParseError> {
+    let digits = line
+        .iter()
+        .position(|&b| b == b';')
+        .map_or(line, |semi| trim_ows(&line[..semi]));
+    if digits.is_empty() || digits.len() > 16 {
+        return Err(HttpParseError::InvalidChunkSize);
+    }
+    digits.iter().try_fold(0u64, |acc, &b| {
+        let digit = char::from(b)
+            .to_digit(16)
+            .ok_or(HttpParseError::InvalidChunkSize)?;
+        Ok(acc << 4 | u64::from(digit))
+    })
+}
+
+/// Shared RFC 9112 §6.3 rules. `request_version` is `None` for responses.
+fn body_framing(
+    headers: &[Header<'_>],
+    request_version: Option<HttpVersion>,
+) -> Result<BodyFraming, SmugglingRisk> {
+    let values = |name: &'static str| {
+        headers
+            .iter()
+            .filter(move |h| h.name.eq_ignore_ascii_case(name))
+            .flat_map(|h| h.value.split(|&b| b == b','))
+            .map(trim_ows)
+    };
+    let has_te = headers
+        .iter()
+        .any(|h| h.name.eq_ignore_ascii_case("transfer-encoding"));
+    let has_cl = headers
+        .iter()
+        .any(|h| h.name.eq_ignore_ascii_case("content-length"));
+
+    if has_te {
+        if request_version == Some(HttpVersion::Http10) {
+            return Err(SmugglingRisk::TransferEncodingInHttp10);
+        }
+        if has_cl {
+            return Err(SmugglingRisk::ContentLengthWithTransferEncoding);
+        }
+        let codings: Vec<&[u8]> = values("transfer-encoding").collect();
+        let chunked = |c: &&[u8]| c.eq_ignore_ascii_case(b"chunked");
+        if codings.iter().filter(|c| chunked(c)).count() > 1 {
+            return Err(SmugglingRisk::ChunkedRepeated);
+        }
+        return match (codings.last().is_some_and(chunked), request_version) {
+            (true, _) => Ok(BodyFraming::Chunked),
+            (false, Some(_)) => Err(SmugglingRisk::ChunkedNotFinal),
+            (false, None) => Ok(BodyFraming::CloseDelimited),
+        };
+    }
+
+    if has_cl {
+        let mut length = None;
+        for value in values("content-length") {
+            let parsed = ascii_str(value)
+                .filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|v| v.parse::<u64>().ok())
+                .ok_or(SmugglingRisk::InvalidContentLength)?;
+            if length.is_some_and(|l| l != parsed) {
+                return Err(SmugglingRisk::ConflictingContentLength);
+            }
+            length = Some(parsed);
+        }
+        return length
+            .map(BodyFraming::ContentLength)
+            .ok_or(SmugglingRisk::InvalidContentLength);
+    }
+
+    Ok(if request_version.is_some() {
+        BodyFraming::None
+    } else {
+        BodyFraming::CloseDelimited
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn request(raw: &str) -> Result<RequestHead<'_>, HttpParseError> {
+        match RequestHead::parse(raw.as_bytes(), &ParseLimits::default())? {
+            Parsed::Complete { value, .. } => Ok(value),
+            Parsed::Partial => Err(HttpParseError::InvalidRequestLine),
+        }
+    }
+
+    #[test]
+    fn parses_request_head_without_copying() {
+        let raw = b"\r\nGET /login?next=%2F HTTP/1.1\r\nHost: portal.corp\r\nX-Empty:\r\nUser-Agent:  curl/8.5 \r\n\r\nbody";
+        let (value, consumed) = RequestHead::parse(raw, &ParseLimits::default())
+            .expect("valid head")
+            .complete()
+            .expect("complete head");
+        assert_eq!(&raw[consumed..], b"body");
+        assert_eq!(value.method, "GET");
+        assert_eq!(value.target, "/login?next=%2F");
+        assert_eq!(value.version, HttpVersion::Http11);
+        assert_eq!(value.headers.len(), 3);
+        assert_eq!(
+            value.header("user-agent").and_then(Header::value_str),
+            Some("curl/8.5")
+        );
+        assert_eq!(value.header("x-empty").map(|h| h.value), Some(&b""[..]));
+        // Borrowed straight from the input buffer.
+        assert!(raw.as_ptr_range().contains(&value.target.as_ptr()));
+        assert_eq!(value.body_framing(), Ok(BodyFraming::None));
+    }
+
+    #[test]
+    fn incomplete_input_is_partial_at_every_split() {
+        let raw = b"POST /api HTTP/1.1\r\nHost: a\r\nContent-Length: 2\r\n\r\n";
+        for end in 0..raw.len() {
+            assert_eq!(
+                RequestHead::parse(&raw[..end], &ParseLimits::default()),
+                Ok(Parsed::Partial),
+                "split at {end}"
+            );
+        }
+        let full = RequestHead::parse(raw, &ParseLimits::default());
+        assert!(matches!(full, Ok(Parsed::Complete { consumed, .. }) if consumed == raw.len()));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let cases = [
+            (
+                "GET  / HTTP/1.1\r\n\r\n",
+                HttpParseError::InvalidRequestLine,
+            ),
+            (
+                "GET / HTTP/1.1 x\r\n\r\n",
+                HttpParseError::InvalidRequestLine,
+            ),
+            ("G(T / HTTP/1.1\r\n\r\n", HttpParseError::InvalidRequestLine),
+            (
+                "GET /a\x01 HTTP/1.1\r\n\r\n",
+                HttpParseError::InvalidRequestLine,
+            ),
+            ("GET / HTTP/2.0\r\n\r\n", HttpParseError::UnsupportedVersion),
+            ("GET / HTTP/1.1\nHost: a\n\n", HttpParseError::BareLineFeed),
+            (
+                "GET / HTTP/1.1\r\nHost: a\r\nTransfer-Encoding : chunked\r\n\r\n",
+                HttpParseError::WhitespaceBeforeColon { line: 3 },
+            ),
+            (
+                "GET / HTTP/1.1\r\nX-A: 1\r\n  folded\r\n\r\n",
+                HttpParseError::ObsoleteLineFolding { line: 3 },
+            ),
+            (
+                "GET / HTTP/1.1\r\nno colon\r\n\r\n",
+                HttpParseError::InvalidHeader { line: 2 },
+            ),
+            (
+                "GET / HTTP/1.1\r\nX-A: a\rb\r\n\r\n",
+                HttpParseError::InvalidHeader { line: 2 },
+            ),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(request(raw), Err(expected), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn enforces_limits() {
+        let limits = ParseLimits {
+            max_head_bytes: 64,
+            max_headers: 2,
+            max_body_bytes: 8,
+        };
+        let many = b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n";
+        assert_eq!(
+            RequestHead::parse(many, &limits),
+            Err(HttpParseError::TooManyHeaders { limit: 2 })
+        );
+        let long = format!("GET / HTTP/1.1\r\nX: {}", "a".repeat(80));
+        assert_eq!(
+            RequestHead::parse(long.as_bytes(), &limits),
+            Err(HttpParseError::HeadTooLarge { limit: 64 })
+        );
+        let padded = format!("{}GET / HTTP/1.1\r\n\r\n", "\r\n".repeat(30));
+        assert_eq!(
+            RequestHead::parse(padded.as_bytes(), &limits),
+            Err(HttpParseError::HeadTooLarge { limit: 64 })
+        );
+        let flood = "\r\n".repeat(1_000);
+        assert_eq!(
+            RequestHead::parse(flood.as_bytes(), &limits),
+            Err(HttpParseError::HeadTooLarge { limit: 64 })
+        );
+        assert_eq!(
+            ChunkedBody::decode(b"5\r\nhello\r\n5\r\nworld\r\n0\r\n\r\n", &limits),
+            Err(HttpParseError::BodyTooLarge { limit: 8 })
+        );
+    }
+
+    #[test]
+    fn parses_status_lines() {
+        let limits = ParseLimits::default();
+        let (value, _) = ResponseHead::parse(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+            &limits,
+        )
+        .expect("valid head")
+        .complete()
+        .expect("complete head");
+        assert_eq!(value.status(), Some(HttpStatus::NotFound));
+        assert_eq!(value.reason, "Not Found");
+        assert_eq!(value.body_framing(), Ok(BodyFraming::ContentLength(0)));
+
+        let (value, _) = ResponseHead::parse(b"HTTP/1.0 299\r\n\r\n", &limits)
+            .expect("valid head")
+            .complete()
+            .expect("complete head");
+        assert_eq!((value.code, value.status()), (299, None));
+        assert_eq!(value.class(), Some(StatusClass::Success));
+        assert_eq!(value.body_framing(), Ok(BodyFraming::CloseDelimited));
+
+        for bad in [
+            &b"HTTP/1.1 20 OK\r\n\r\n"[..],
+            b"HTTP/1.1 abc OK\r\n\r\n",
+            b"HTTP/1.1\r\n\r\n",
+        ] {
+            assert_eq!(
+                ResponseHead::parse(bad, &limits),
+                Err(HttpParseError::InvalidStatusLine)
+            );
+        }
+    }
+
+    #[test]
+    fn response_framing_rules() {
+        let framing = |raw: &str| match ResponseHead::parse(raw.as_bytes(), &ParseLimits::default())
+        {
+            Ok(Parsed::Complete { value, .. }) => value.body_framing(),
+            _ => Err(SmugglingRisk::InvalidContentLength),
+        };
+        assert_eq!(
+            framing("HTTP/1.1 304 Not Modified\r\nContent-Length: 10\r\n\r\n"),
+            Ok(BodyFraming::None)
+        );
+        assert_eq!(
+            framing("HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip, chunked\r\n\r\n"),
+            Ok(BodyFraming::Chunked)
+        );
+        assert_eq!(
+            framing("HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\n"),
+            Ok(BodyFraming::CloseDelimited)
+        );
+    }
+
+    #[test]
+    fn detects_smuggling_ambiguities() {
+        let framing = |raw: &str| request(raw).map(|r| r.body_framing());
+        let cases = [
+            (
+                "POST / HTTP/1.1\r\nContent-Length: 4\r\nTransfer-Encoding: chunked\r\n\r\n",
+                Err(SmugglingRisk::ContentLengthWithTransferEncoding),
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length: 4\r\nContent-Length: 6\r\n\r\n",
+                Err(SmugglingRisk::ConflictingContentLength),
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length: 4, 6\r\n\r\n",
+                Err(SmugglingRisk::ConflictingContentLength),
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length: 4, 4\r\n\r\n",
+                Ok(BodyFraming::ContentLength(4)),
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length: +4\r\n\r\n",
+                Err(SmugglingRisk::InvalidContentLength),
+            ),
+            (
+                "POST / HTTP/1.1\r\nContent-Length:\r\n\r\n",
+                Err(SmugglingRisk::InvalidContentLength),
+            ),
+            (
+                "POST / HTTP/1.1\r\nTransfer-Encoding: xchunked\r\n\r\n",
+                Err(SmugglingRisk::ChunkedNotFinal),
+            ),
+            (
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked, identity\r\n\r\n",
+                Err(SmugglingRisk::ChunkedNotFinal),
+            ),
+            (
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n",
+                Err(SmugglingRisk::ChunkedRepeated),
+            ),
+            (
+                "POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n",
+                Err(SmugglingRisk::TransferEncodingInHttp10),
+            ),
+            (
+                "POST / HTTP/1.1\r\ntransfer-encoding: Chunked\r\n\r\n",
+                Ok(BodyFraming::Chunked),
+            ),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(framing(raw), Ok(expected), "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn decodes_chunked_bodies() {
+        let limits = ParseLimits::default();
+        let raw = b"4;ext=1\r\nWiki\r\n5 ;x\r\npedia\r\nE\r\n in\r\n\r\nchunks.\r\n0\r\nX-Checksum: abc\r\n\r\nNEXT";
+        let (value, consumed) = ChunkedBody::decode(raw, &limits)
+            .expect("valid body")
+            .complete()
+            .expect("complete body");
+        assert_eq!(value.to_vec(), b"Wikipedia in\r\n\r\nchunks.");
+        assert_eq!(value.len(), 23);
+        assert_eq!(value.trailers.len(), 1);
+        assert_eq!(&raw[consumed..], b"NEXT");
+
+        for end in 0..consumed {
+            assert_eq!(
+                ChunkedBody::decode(&raw[..end], &limits),
+                Ok(Parsed::Partial)
+            );
+        }
+        for bad in [
+            &b"0x4\r\nWiki\r\n0\r\n\r\n"[..],
+            b" 4\r\nWiki\r\n",
+            b"-1\r\n",
+            b"\r\n",
+            b"11111111111111111\r\n",
+        ] {
+            assert_eq!(
+                ChunkedBody::decode(bad, &limits),
+                Err(HttpParseError::InvalidChunkSize),
+                "{bad:?}"
+            );
+        }
+        assert_eq!(
+            ChunkedBody::decode(b"4\r\nWikipedia\r\n", &limits),
+            Err(HttpParseError::InvalidChunkTerminator)
+        );
+        assert_eq!(
+            ChunkedBody::decode(b"0\r\n\r\n", &limits)
+                .map(|p| matches!(p, Parsed::Complete { value, .. } if value.is_empty())),
+            Ok(true)
+        );
+    }
+}
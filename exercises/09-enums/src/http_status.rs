@@ -1,51 +1,219 @@
-//! HTTP-like response status codes.
-
-/// Simplified HTTP status for API gateway exercises.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum HttpStatus {
-    Ok,
-    Created,
-    BadRequest,
-    Unauthorized,
-    Forbidden,
-    NotFound,
-    InternalServerError,
+//! HTTP response status codes from the IANA registry.
+//!
+//! Every permanently registered code in the IANA "HTTP Status Code Registry"
+//! (RFC 9110 and its extensions) has a variant. Codes marked "(Unused)" there, such as
+//! 306 and 418, have no variant, so `from_code` returns `None` for them. Use
+//! [`StatusClass::from_code`] to classify any code between 100 and 599.
+
+use std::fmt;
+
+/// The five status classes from RFC 9110 §15, named after the first digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StatusClass {
+    /// 1xx
+    Informational,
+    /// 2xx
+    Success,
+    /// 3xx
+    Redirection,
+    /// 4xx
+    ClientError,
+    /// 5xx
+    ServerError,
 }
 
-impl HttpStatus {
-    /// Map a numeric status code to the enum, if recognized.
+impl StatusClass {
+    /// Classify any code in 100..=599, whether or not it is registered.
     #[must_use]
     pub const fn from_code(code: u16) -> Option<Self> {
         match code {
-            200 => Some(Self::Ok),
-            201 => Some(Self::Created),
-            400 => Some(Self::BadRequest),
-            401 => Some(Self::Unauthorized),
-            403 => Some(Self::Forbidden),
-            404 => Some(Self::NotFound),
-            500 => Some(Self::InternalServerError),
+            100..=199 => Some(Self::Informational),
+            200..=299 => Some(Self::Success),
+            300..=399 => Some(Self::Redirection),
+            400..=499 => Some(Self::ClientError),
+            500..=599 => Some(Self::ServerError),
             _ => None,
         }
     }
+}
+
+impl fmt::Display for StatusClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Informational => "informational",
+            Self::Success => "success",
+            Self::Redirection => "redirection",
+            Self::ClientError => "client error",
+            Self::ServerError => "server error",
+        })
+    }
+}
+
+/// Declares `HttpStatus` with its code and reason phrase in one place, so
+/// the enum, `from_code`, `code`, `reason_phrase` and `ALL` cannot drift.
+macro_rules! http_statuses {
+    ($($(#[$meta:meta])* $variant:ident = $code:literal, $reason:literal;)+) => {
+        /// A registered HTTP status code.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum HttpStatus {
+            $($(#[$meta])* $variant,)+
+        }
+
+        impl HttpStatus {
+            /// Every registered status, in code order.
+            pub const ALL: &'static [Self] = &[$(Self::$variant,)+];
+
+            /// Map a numeric status code to the enum, if registered.
+            #[must_use]
+            pub const fn from_code(code: u16) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+
+            /// Numeric code for this status.
+            #[must_use]
+            pub const fn code(self) -> u16 {
+                match self {
+                    $(Self::$variant => $code,)+
+                }
+            }
+
+            /// The registry's reason phrase, e.g. "Not Found".
+            #[must_use]
+            pub const fn reason_phrase(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $reason,)+
+                }
+            }
+        }
+    };
+}
+
+http_statuses! {
+    Continue = 100, "Continue";
+    SwitchingProtocols = 101, "Switching Protocols";
+    /// `WebDAV`, RFC 2518.
+    Processing = 102, "Processing";
+    EarlyHints = 103, "Early Hints";
+
+    Ok = 200, "OK";
+    Created = 201, "Created";
+    Accepted = 202, "Accepted";
+    NonAuthoritativeInformation = 203, "Non-Authoritative Information";
+    NoContent = 204, "No Content";
+    ResetContent = 205, "Reset Content";
+    PartialContent = 206, "Partial Content";
+    /// `WebDAV`, RFC 4918.
+    MultiStatus = 207, "Multi-Status";
+    /// `WebDAV`, RFC 5842.
+    AlreadyReported = 208, "Already Reported";
+    /// Delta encoding, RFC 3229.
+    ImUsed = 226, "IM Used";
 
-    /// Numeric code for this status.
+    MultipleChoices = 300, "Multiple Choices";
+    MovedPermanently = 301, "Moved Permanently";
+    Found = 302, "Found";
+    SeeOther = 303, "See Other";
+    NotModified = 304, "Not Modified";
+    /// Deprecated: clients must not honour it.
+    UseProxy = 305, "Use Proxy";
+    TemporaryRedirect = 307, "Temporary Redirect";
+    PermanentRedirect = 308, "Permanent Redirect";
+
+    BadRequest = 400, "Bad Request";
+    Unauthorized = 401, "Unauthorized";
+    PaymentRequired = 402, "Payment Required";
+    Forbidden = 403, "Forbidden";
+    NotFound = 404, "Not Found";
+    MethodNotAllowed = 405, "Method Not Allowed";
+    NotAcceptable = 406, "Not Acceptable";
+    ProxyAuthenticationRequired = 407, "Proxy Authentication Required";
+    RequestTimeout = 408, "Request Timeout";
+    Conflict = 409, "Conflict";
+    Gone = 410, "Gone";
+    LengthRequired = 411, "Length Required";
+    PreconditionFailed = 412, "Precondition Failed";
+    ContentTooLarge = 413, "Content Too Large";
+    UriTooLong = 414, "URI Too Long";
+    UnsupportedMediaType = 415, "Unsupported Media Type";
+    RangeNotSatisfiable = 416, "Range Not Satisfiable";
+    ExpectationFailed = 417, "Expectation Failed";
+    MisdirectedRequest = 421, "Misdirected Request";
+    UnprocessableContent = 422, "Unprocessable Content";
+    /// `WebDAV`, RFC 4918.
+    Locked = 423, "Locked";
+    /// `WebDAV`, RFC 4918.
+    FailedDependency = 424, "Failed Dependency";
+    TooEarly = 425, "Too Early";
+    UpgradeRequired = 426, "Upgrade Required";
+    PreconditionRequired = 428, "Precondition Required";
+    TooManyRequests = 429, "Too Many Requests";
+    RequestHeaderFieldsTooLarge = 431, "Request Header Fields Too Large";
+    UnavailableForLegalReasons = 451, "Unavailable For Legal Reasons";
+
+    InternalServerError = 500, "Internal Server Error";
+    NotImplemented = 501, "Not Implemented";
+    BadGateway = 502, "Bad Gateway";
+    ServiceUnavailable = 503, "Service Unavailable";
+    GatewayTimeout = 504, "Gateway Timeout";
+    HttpVersionNotSupported = 505, "HTTP Version Not Supported";
+    VariantAlsoNegotiates = 506, "Variant Also Negotiates";
+    /// `WebDAV`, RFC 4918.
+    InsufficientStorage = 507, "Insufficient Storage";
+    /// `WebDAV`, RFC 5842.
+    LoopDetected = 508, "Loop Detected";
+    /// Listed as obsoleted in the registry; some servers still send it.
+    NotExtended = 510, "Not Extended";
+    NetworkAuthenticationRequired = 511, "Network Authentication Required";
+}
+
+impl HttpStatus {
     #[must_use]
-    pub const fn code(self) -> u16 {
-        match self {
-            Self::Ok => 200,
-            Self::Created => 201,
-            Self::BadRequest => 400,
-            Self::Unauthorized => 401,
-            Self::Forbidden => 403,
-            Self::NotFound => 404,
-            Self::InternalServerError => 500,
+    pub const fn class(self) -> StatusClass {
+        match StatusClass::from_code(self.code()) {
+            Some(class) => class,
+            // Every registered code is in 100..=599.
+            None => StatusClass::ServerError,
         }
     }
 
+    /// Whether the status is in the 1xx informational class.
+    #[must_use]
+    pub const fn is_informational(self) -> bool {
+        matches!(self.class(), StatusClass::Informational)
+    }
+
     /// Whether the status is in the 2xx success class.
     #[must_use]
     pub const fn is_success(self) -> bool {
-        matches!(self, Self::Ok | Self::Created)
+        matches!(self.class(), StatusClass::Success)
+    }
+
+    /// Whether the status is in the 3xx redirection class.
+    #[must_use]
+    pub const fn is_redirection(self) -> bool {
+        matches!(self.class(), StatusClass::Redirection)
+    }
+
+    /// Whether the status is in the 4xx client error class.
+    #[must_use]
+    pub const fn is_client_error(self) -> bool {
+        matches!(self.class(), StatusClass::ClientError)
+    }
+
+    /// Whether the status is in the 5xx server error class.
+    #[must_use]
+    pub const fn is_server_error(self) -> bool {
+        matches!(self.class(), StatusClass::ServerError)
+    }
+}
+
+/// `404 Not Found`
+impl fmt::Display for HttpStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.reason_phrase())
     }
 }
 
@@ -70,4 +238,49 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn registry_is_complete_and_ordered() {
+        assert_eq!(HttpStatus::ALL.len(), 61);
+        assert!(
+            HttpStatus::ALL
+                .windows(2)
+                .all(|w| w[0].code() < w[1].code())
+        );
+        for &status in HttpStatus::ALL {
+            assert_eq!(HttpStatus::from_code(status.code()), Some(status));
+        }
+        let registered = (0..=999).filter_map(HttpStatus::from_code).count();
+        assert_eq!(registered, HttpStatus::ALL.len());
+        // Reserved or unused in the registry.
+        for code in [306, 418, 420, 427, 430, 509, 599] {
+            assert_eq!(HttpStatus::from_code(code), None, "{code}");
+        }
+    }
+
+    #[test]
+    fn classes_follow_first_digit() {
+        assert!(HttpStatus::EarlyHints.is_informational());
+        assert!(HttpStatus::NoContent.is_success());
+        assert!(HttpStatus::PermanentRedirect.is_redirection());
+        assert!(HttpStatus::TooManyRequests.is_client_error());
+        assert!(HttpStatus::LoopDetected.is_server_error());
+        assert!(!HttpStatus::NotModified.is_success());
+        assert_eq!(StatusClass::from_code(599), Some(StatusClass::ServerError));
+        assert_eq!(StatusClass::from_code(600), None);
+        assert_eq!(StatusClass::from_code(99), None);
+        for &status in HttpStatus::ALL {
+            assert_eq!(StatusClass::from_code(status.code()), Some(status.class()));
+        }
+    }
+
+    #[test]
+    fn display_uses_reason_phrase() {
+        assert_eq!(HttpStatus::NotFound.to_string(), "404 Not Found");
+        assert_eq!(
+            HttpStatus::HttpVersionNotSupported.to_string(),
+            "505 HTTP Version Not Supported"
+        );
+        assert_eq!(StatusClass::ClientError.to_string(), "client error");
+    }
 }
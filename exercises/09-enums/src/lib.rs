@@ -1,19 +1,37 @@
 //! Enum exercises — auth status, network events, HTTP codes, and tokens.
 
+pub mod access_log;
 pub mod auth_demo;
+pub mod auth_events;
 pub mod auth_status;
+pub mod http_parser;
 pub mod http_status;
 pub mod maybe_token;
 pub mod network;
 pub mod network_demo;
+pub mod web_attacks;
 
+pub use access_log::{
+    AccessLog, AccessLogEntry, AccessLogError, LogFormat, RejectedLine, RequestLine,
+};
+pub use auth_events::{
+    AuthEvent, AuthEventError, AuthEventRecord, AuthEventStore, AuthState, Transition, utc_day,
+};
 pub use auth_status::AuthStatus;
-pub use http_status::HttpStatus;
+pub use http_parser::{
+    BodyFraming, ChunkedBody, Header, HttpParseError, HttpVersion, ParseLimits, Parsed,
+    RequestHead, ResponseHead, SmugglingRisk,
+};
+pub use http_status::{HttpStatus, StatusClass};
 pub use maybe_token::MaybeToken;
 pub use network::{NetworkEvent, sum_packet_bytes};
+pub use web_attacks::{DetectorConfig, Severity as WebSeverity, WebAttackDetector, WebFinding};
 
 pub type Result<T> = anyhow::Result<T>;
 
+/// Combined-format access log shipped with the crate (`logs/access.log`).
+pub const SAMPLE_ACCESS_LOG: &str = include_str!("../logs/access.log");
+
 /// Metadata for a single hands-on exercise.
 #[derive(Debug, Clone)]
 pub struct ExerciseInfo {
@@ -34,6 +52,8 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Exhaustive match on AuthStatus",
                 "is_authenticated and summary helpers",
                 "Locked{until} for timed lockouts",
+                "AuthState transitions validated by AuthEvent::next_state",
+                "Event-sourced AuthEventStore with replay and state_at",
             ],
         },
         ExerciseInfo {
@@ -42,7 +62,12 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
             concepts: vec![
                 "Tuple variants (PacketReceived, PacketSent)",
                 "sum_packet_bytes over event slices",
-                "HttpStatus::from_code mapping",
+                "HttpStatus::from_code over the IANA registry",
+                "StatusClass for unregistered codes",
+                "Zero-copy HTTP/1.1 parsing with Parsed::Partial",
+                "SmugglingRisk from Content-Length/Transfer-Encoding",
+                "Common/Combined/JSON access logs into AccessLogEntry",
+                "WebFinding variants from signature and windowed detectors",
                 "MaybeToken::unwrap_or fallback",
             ],
         },
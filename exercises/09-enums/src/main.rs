@@ -4,11 +4,15 @@
 //! cargo run -p exercise_enums
 //! cargo run -p exercise_enums -- list
 //! cargo run -p exercise_enums -- auth-status --verbose
+//! cargo run -p exercise_enums -- web-log /var/log/nginx/access.log --format combined
 //! ```
 
-use anyhow::Result;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use enums_exercises::{get_exercise_list, run_all, run_exercise};
+use enums_exercises::network_demo::scan_access_log;
+use enums_exercises::{LogFormat, SAMPLE_ACCESS_LOG, get_exercise_list, run_all, run_exercise};
 
 /// Enum exercises — auth, network events, HTTP status, tokens
 #[derive(Parser, Debug)]
@@ -38,6 +42,16 @@ enum Commands {
 
     /// Network events, HTTP codes, and token fallbacks
     Network,
+
+    /// Scan a web access log for attack patterns
+    WebLog {
+        /// Log file to scan (defaults to the bundled sample)
+        path: Option<PathBuf>,
+
+        /// common, combined or json (detected from the first line if omitted)
+        #[arg(short, long)]
+        format: Option<LogFormat>,
+    },
 }
 
 fn main() -> Result<()> {
@@ -53,6 +67,14 @@ fn main() -> Result<()> {
         Some(Commands::List) => list_exercises(),
         Some(Commands::AuthStatus) => run_exercise("auth-status", verbose)?,
         Some(Commands::Network) => run_exercise("network", verbose)?,
+        Some(Commands::WebLog { path, format }) => {
+            let text = match &path {
+                Some(path) => std::fs::read_to_string(path)
+                    .with_context(|| format!("reading {}", path.display()))?,
+                None => SAMPLE_ACCESS_LOG.to_owned(),
+            };
+            scan_access_log(&text, format, verbose);
+        }
     }
 
     Ok(())
@@ -72,6 +94,7 @@ fn list_exercises() {
     println!("  cargo run -p exercise_enums -- list");
     println!("  cargo run -p exercise_enums -- auth-status [--verbose]");
     println!("  cargo run -p exercise_enums -- network [--verbose]");
+    println!("  cargo run -p exercise_enums -- web-log [PATH] [--format common|combined|json]");
     println!("  cargo run -p exercise_enums -- all [--verbose]");
     println!("  cargo run -p exercise_enums              # same as 'all'");
 }
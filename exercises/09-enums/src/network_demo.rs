@@ -2,10 +2,14 @@
 
 use anyhow::Result;
 
+use crate::SAMPLE_ACCESS_LOG;
+use crate::access_log::{AccessLog, LogFormat};
 use crate::auth_status::AuthStatus;
-use crate::http_status::HttpStatus;
+use crate::http_parser::{BodyFraming, ChunkedBody, ParseLimits, Parsed, RequestHead};
+use crate::http_status::{HttpStatus, StatusClass};
 use crate::maybe_token::MaybeToken;
 use crate::network::{NetworkEvent, sum_packet_bytes};
+use crate::web_attacks::{WebAttackDetector, WebFinding};
 
 /// Run the network-incident exercise.
 pub fn run(verbose: bool) -> Result<()> {
@@ -33,17 +37,47 @@ pub fn run(verbose: bool) -> Result<()> {
 
     println!();
     println!("HTTP gateway responses:");
-    for code in [200u16, 401, 403, 404, 500] {
-        if let Some(status) = HttpStatus::from_code(code) {
-            let kind = if status.is_success() {
-                "success"
-            } else {
-                "error"
-            };
-            println!("  HTTP {} ({kind})", status.code());
+    for code in [200u16, 304, 401, 403, 404, 418, 429, 500, 503] {
+        match (HttpStatus::from_code(code), StatusClass::from_code(code)) {
+            (Some(status), _) => println!("  HTTP {status} ({})", status.class()),
+            (None, Some(class)) => println!("  HTTP {code} (unregistered {class})"),
+            (None, None) => println!("  HTTP {code} (not a status code)"),
         }
     }
 
+    println!();
+    println!("Captured requests:");
+    let limits = ParseLimits::default();
+    let captures: [&[u8]; 2] = [
+        b"POST /upload HTTP/1.1\r\nHost: files.corp\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+        b"POST / HTTP/1.1\r\nHost: portal.corp\r\nContent-Length: 6\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\nG",
+    ];
+    for raw in captures {
+        let Parsed::Complete {
+            value: head,
+            consumed,
+        } = RequestHead::parse(raw, &limits)?
+        else {
+            println!("  (incomplete capture)");
+            continue;
+        };
+        print!("  {} {} {}: ", head.method, head.target, head.version);
+        match head.body_framing() {
+            Ok(BodyFraming::Chunked) => match ChunkedBody::decode(&raw[consumed..], &limits)? {
+                Parsed::Complete { value, .. } => {
+                    println!("chunked body, {} bytes", value.len());
+                }
+                Parsed::Partial => println!("chunked body (incomplete)"),
+            },
+            Ok(framing) => println!("{framing:?}"),
+            Err(risk) => println!("⚠️  request smuggling risk: {risk}"),
+        }
+    }
+
+    println!();
+    println!("Web access log (logs/access.log):");
+    scan_access_log(SAMPLE_ACCESS_LOG, None, verbose);
+
     let token = MaybeToken::None;
     let bearer = token.unwrap_or("anonymous".to_owned());
     println!();
@@ -59,12 +93,74 @@ pub fn run(verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// Parse an access log (detecting the format when `format` is `None`), run
+/// it through a default [`WebAttackDetector`], and print the findings.
+pub fn scan_access_log(text: &str, format: Option<LogFormat>, verbose: bool) -> Vec<WebFinding> {
+    let log = format.map_or_else(
+        || AccessLog::parse_detected(text),
+        |format| AccessLog::parse(text, format),
+    );
+    let findings = WebAttackDetector::new().scan(&log.entries);
+    println!(
+        "  {} entries, {} rejected lines, {} findings",
+        log.entries.len(),
+        log.rejected.len(),
+        findings.len()
+    );
+    for finding in &findings {
+        println!("  [{:?}] {}", finding.severity(), finding.describe());
+        if verbose && let Some(status) = finding.auth_status() {
+            println!("         → {}", status.summary());
+        }
+    }
+    if verbose {
+        for rejected in &log.rejected {
+            println!("  line {}: {}", rejected.line, rejected.error);
+        }
+    }
+    findings
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::web_attacks::Severity;
 
     #[test]
     fn network_demo_runs() {
         assert!(run(false).is_ok());
     }
+
+    #[test]
+    fn sample_access_log_has_every_finding_kind() {
+        let findings = scan_access_log(SAMPLE_ACCESS_LOG, None, true);
+        let count = |pred: fn(&WebFinding) -> bool| findings.iter().filter(|f| pred(f)).count();
+        assert_eq!(
+            count(|f| matches!(f, WebFinding::ScannerUserAgent { .. })),
+            3
+        );
+        assert_eq!(count(|f| matches!(f, WebFinding::PathTraversal { .. })), 2);
+        assert_eq!(
+            count(|f| matches!(f, WebFinding::SqlInjectionProbe { .. })),
+            2
+        );
+        assert_eq!(count(|f| matches!(f, WebFinding::XssProbe { .. })), 1);
+        assert_eq!(
+            count(|f| matches!(f, WebFinding::AuthFailureBurst { .. })),
+            1
+        );
+        assert_eq!(
+            count(|f| matches!(f, WebFinding::NotFoundEnumeration { .. })),
+            1
+        );
+        assert!(
+            findings
+                .iter()
+                .all(|f| f.client().to_string() != "10.20.0.14")
+        );
+        assert_eq!(
+            findings.iter().map(WebFinding::severity).max(),
+            Some(Severity::High)
+        );
+    }
 }
@@ -0,0 +1,754 @@
+//! Attack heuristics over parsed access-log entries.
+//!
+//! [`WebAttackDetector`] is fed entries one at a time, in log order, and
+//! returns typed [`WebFinding`]s. There are two kinds of check:
+//!
+//! - **Per request.** Path traversal, SQL injection and XSS signatures in
+//!   the target, and known scanner user agents. Targets are percent-decoded
+//!   twice and lowercased before matching, so `%252e%252e%252f` is still
+//!   caught.
+//! - **Per client over a sliding window.** Bursts of 401/403 responses, and
+//!   404s across many distinct paths (content enumeration). After a finding
+//!   fires, that client's window is cleared, so a long attack is reported
+//!   once per burst rather than once per request.
+//!
+//! These are signatures, not a WAF. They are meant to surface probing in
+//! historical logs, and a determined attacker can evade them.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::access_log::AccessLogEntry;
+use crate::auth_status::AuthStatus;
+use crate::http_status::HttpStatus;
+use crate::network::NetworkEvent;
+
+const TRAVERSAL_SIGNATURES: &[&str] = &[
+    "../",
+    "..\\",
+    "/etc/passwd",
+    "/etc/shadow",
+    "/proc/self/environ",
+    "c:\\windows",
+    "win.ini",
+    "boot.ini",
+];
+
+/// Overlong UTF-8 encodings of `.`, `/` and `\`, matched before decoding.
+const OVERLONG_SIGNATURES: &[&str] = &["%c0%ae", "%c0%af", "%c1%9c", "%c1%1c"];
+
+const SQLI_SIGNATURES: &[&str] = &[
+    "' or '1'='1",
+    "' or 1=1",
+    "\" or 1=1",
+    " or 1=1--",
+    "union select",
+    "union all select",
+    "information_schema",
+    "sleep(",
+    "benchmark(",
+    "waitfor delay",
+    "pg_sleep(",
+    "xp_cmdshell",
+    "load_file(",
+    "extractvalue(",
+    "';--",
+    "' --",
+];
+
+const XSS_SIGNATURES: &[&str] = &[
+    "<script",
+    "javascript:",
+    "onerror=",
+    "onload=",
+    "<svg",
+    "<iframe",
+    "<img",
+    "document.cookie",
+    "alert(",
+];
+
+/// Lowercased user-agent substring and the tool it identifies.
+const SCANNER_AGENTS: &[(&str, &str)] = &[
+    ("sqlmap", "sqlmap"),
+    ("nikto", "Nikto"),
+    ("nmap", "Nmap"),
+    ("masscan", "masscan"),
+    ("zgrab", "ZGrab"),
+    ("nuclei", "Nuclei"),
+    ("wpscan", "WPScan"),
+    ("dirbuster", "DirBuster"),
+    ("gobuster", "Gobuster"),
+    ("fuzz faster u fool", "ffuf"),
+    ("ffuf", "ffuf"),
+    ("wfuzz", "Wfuzz"),
+    ("acunetix", "Acunetix"),
+    ("nessus", "Nessus"),
+    ("openvas", "OpenVAS"),
+    ("burpcollaborator", "Burp Suite"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Something suspicious seen in an access log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebFinding {
+    PathTraversal {
+        client: IpAddr,
+        target: String,
+        signature: &'static str,
+        status: u16,
+        at: DateTime<FixedOffset>,
+    },
+    SqlInjectionProbe {
+        client: IpAddr,
+        target: String,
+        signature: &'static str,
+        status: u16,
+        at: DateTime<FixedOffset>,
+    },
+    XssProbe {
+        client: IpAddr,
+        target: String,
+        signature: &'static str,
+        status: u16,
+        at: DateTime<FixedOffset>,
+    },
+    /// Reported once per client and tool.
+    ScannerUserAgent {
+        client: IpAddr,
+        tool: &'static str,
+        at: DateTime<FixedOffset>,
+    },
+    /// Many 401/403 responses to one client inside the window.
+    AuthFailureBurst {
+        client: IpAddr,
+        failures: usize,
+        first: DateTime<FixedOffset>,
+        last: DateTime<FixedOffset>,
+    },
+    /// 404s across many distinct paths from one client inside the window.
+    NotFoundEnumeration {
+        client: IpAddr,
+        distinct_paths: usize,
+        first: DateTime<FixedOffset>,
+        last: DateTime<FixedOffset>,
+    },
+}
+
+impl WebFinding {
+    #[must_use]
+    pub const fn client(&self) -> IpAddr {
+        match self {
+            Self::PathTraversal { client, .. }
+            | Self::SqlInjectionProbe { client, .. }
+            | Self::XssProbe { client, .. }
+            | Self::ScannerUserAgent { client, .. }
+            | Self::AuthFailureBurst { client, .. }
+            | Self::NotFoundEnumeration { client, .. } => *client,
+        }
+    }
+
+    #[must_use]
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::PathTraversal { .. } | Self::SqlInjectionProbe { .. } => Severity::High,
+            Self::XssProbe { .. }
+            | Self::AuthFailureBurst { .. }
+            | Self::NotFoundEnumeration { .. } => Severity::Medium,
+            Self::ScannerUserAgent { .. } => Severity::Low,
+        }
+    }
+
+    /// Human-readable description for dashboards.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let status = |code: u16| {
+            HttpStatus::from_code(code).map_or_else(|| code.to_string(), |s| s.to_string())
+        };
+        match self {
+            Self::PathTraversal {
+                client,
+                target,
+                signature,
+                status: code,
+                ..
+            } => format!(
+                "path traversal from {client}: {target} ({signature:?}, {})",
+                status(*code)
+            ),
+            Self::SqlInjectionProbe {
+                client,
+                target,
+                signature,
+                status: code,
+                ..
+            } => format!(
+                "SQL injection probe from {client}: {target} ({signature:?}, {})",
+                status(*code)
+            ),
+            Self::XssProbe {
+                client,
+                target,
+                signature,
+                status: code,
+                ..
+            } => format!(
+                "XSS probe from {client}: {target} ({signature:?}, {})",
+                status(*code)
+            ),
+            Self::ScannerUserAgent { client, tool, .. } => {
+                format!("scanner {tool} running from {client}")
+            }
+            Self::AuthFailureBurst {
+                client,
+                failures,
+                first,
+                last,
+            } => format!(
+                "{failures} auth failures (401/403) from {client} in {}s",
+                (*last - *first).num_seconds()
+            ),
+            Self::NotFoundEnumeration {
+                client,
+                distinct_paths,
+                first,
+                last,
+            } => format!(
+                "404 enumeration from {client}: {distinct_paths} distinct paths in {}s",
+                (*last - *first).num_seconds()
+            ),
+        }
+    }
+
+    /// The auth outcome a finding implies, for feeding into auth dashboards.
+    /// Only an auth-failure burst has one.
+    #[must_use]
+    pub fn auth_status(&self) -> Option<AuthStatus> {
+        match self {
+            Self::AuthFailureBurst { .. } => Some(AuthStatus::Failure {
+                reason: self.describe(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The network event a blocking proxy would log for this finding: the
+    /// client's connection closed, with the finding as the reason.
+    ///
+    /// Only per-request findings (signatures and scanner agents) map. The
+    /// windowed findings span many requests and connections, so there is no
+    /// single connection to close.
+    #[must_use]
+    pub fn to_network_event(&self) -> Option<NetworkEvent> {
+        match self {
+            Self::PathTraversal { .. }
+            | Self::SqlInjectionProbe { .. }
+            | Self::XssProbe { .. }
+            | Self::ScannerUserAgent { .. } => Some(NetworkEvent::ConnectionClosed {
+                peer: self.client().to_string(),
+                reason: Some(self.describe()),
+            }),
+            Self::AuthFailureBurst { .. } | Self::NotFoundEnumeration { .. } => None,
+        }
+    }
+}
+
+/// Thresholds for the windowed checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectorConfig {
+    pub auth_failure_threshold: usize,
+    pub auth_failure_window: TimeDelta,
+    /// Distinct 404 paths, not 404 responses.
+    pub not_found_threshold: usize,
+    pub not_found_window: TimeDelta,
+    /// A scanner is reported again once it has been quiet this long.
+    pub scanner_quiet_period: TimeDelta,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            auth_failure_threshold: 10,
+            auth_failure_window: TimeDelta::seconds(60),
+            not_found_threshold: 20,
+            not_found_window: TimeDelta::seconds(60),
+            scanner_quiet_period: TimeDelta::hours(1),
+        }
+    }
+}
+
+/// 404s from one client inside the window, with a count per path so the
+/// distinct-path total is kept up to date as hits enter and leave.
+#[derive(Debug, Default)]
+struct NotFoundWindow {
+    hits: VecDeque<(DateTime<FixedOffset>, String)>,
+    per_path: HashMap<String, usize>,
+}
+
+impl NotFoundWindow {
+    fn push(&mut self, at: DateTime<FixedOffset>, path: &str) {
+        *self.per_path.entry(path.to_owned()).or_default() += 1;
+        self.hits.push_back((at, path.to_owned()));
+    }
+
+    fn pop_front(&mut self) {
+        let Some((_, path)) = self.hits.pop_front() else {
+            return;
+        };
+        if let Some(count) = self.per_path.get_mut(&path) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_path.remove(&path);
+            }
+        }
+    }
+
+    fn distinct_paths(&self) -> usize {
+        self.per_path.len()
+    }
+}
+
+/// Streaming detector. Feed entries in log order with [`Self::observe`].
+///
+/// State is per client IP. A client's entry is dropped when its window
+/// empties, and clients that stop sending are swept once their windows have
+/// passed, so memory tracks recent clients rather than every IP ever seen.
+#[derive(Debug, Default)]
+pub struct WebAttackDetector {
+    config: DetectorConfig,
+    auth_failures: HashMap<IpAddr, VecDeque<DateTime<FixedOffset>>>,
+    not_found: HashMap<IpAddr, NotFoundWindow>,
+    /// Last time each (client, tool) pair was seen.
+    scanners_seen: HashMap<(IpAddr, &'static str), DateTime<FixedOffset>>,
+    last_sweep: Option<DateTime<FixedOffset>>,
+}
+
+impl WebAttackDetector {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_config(config: DetectorConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Per-client entries currently held in memory, across all checks.
+    #[must_use]
+    pub fn tracked(&self) -> usize {
+        self.auth_failures.len() + self.not_found.len() + self.scanners_seen.len()
+    }
+
+    /// Run every entry through [`Self::observe`] and collect the findings.
+    pub fn scan<'e>(
+        &mut self,
+        entries: impl IntoIterator<Item = &'e AccessLogEntry>,
+    ) -> Vec<WebFinding> {
+        entries
+            .into_iter()
+            .flat_map(|entry| self.observe(entry))
+            .collect()
+    }
+
+    /// Check one entry, returning any findings it completes.
+    pub fn observe(&mut self, entry: &AccessLogEntry) -> Vec<WebFinding> {
+        let mut findings = Vec::new();
+        let (client, at, status) = (entry.client, entry.time, entry.status);
+        self.sweep(at);
+
+        if let Some(target) = entry.target() {
+            let raw = target.to_ascii_lowercase();
+            let decoded = normalize(target);
+            let probe = |signatures: &[&'static str]| {
+                signatures.iter().copied().find(|sig| decoded.contains(sig))
+            };
+            let traversal = OVERLONG_SIGNATURES
+                .iter()
+                .copied()
+                .find(|sig| raw.contains(sig))
+                .or_else(|| probe(TRAVERSAL_SIGNATURES));
+            if let Some(signature) = traversal {
+                findings.push(WebFinding::PathTraversal {
+                    client,
+                    target: target.to_owned(),
+                    signature,
+                    status,
+                    at,
+                });
+            }
+            if let Some(signature) = probe(SQLI_SIGNATURES) {
+                findings.push(WebFinding::SqlInjectionProbe {
+                    client,
+                    target: target.to_owned(),
+                    signature,
+                    status,
+                    at,
+                });
+            }
+            if let Some(signature) = probe(XSS_SIGNATURES) {
+                findings.push(WebFinding::XssProbe {
+                    client,
+                    target: target.to_owned(),
+                    signature,
+                    status,
+                    at,
+                });
+            }
+        }
+
+        if let Some(agent) = &entry.user_agent {
+            let agent = agent.to_ascii_lowercase();
+            if let Some(&(_, tool)) = SCANNER_AGENTS
+                .iter()
+                .find(|(needle, _)| agent.contains(needle))
+            {
+                let quiet = self.config.scanner_quiet_period;
+                let previous = self.scanners_seen.insert((client, tool), at);
+                if previous.is_none_or(|seen| at - seen > quiet) {
+                    findings.push(WebFinding::ScannerUserAgent { client, tool, at });
+                }
+            }
+        }
+
+        if matches!(
+            entry.status(),
+            Some(HttpStatus::Unauthorized | HttpStatus::Forbidden)
+        ) {
+            findings.extend(self.record_auth_failure(client, at));
+        }
+        if entry.status() == Some(HttpStatus::NotFound) {
+            let path = entry.target().unwrap_or("-");
+            findings.extend(self.record_not_found(client, at, path));
+        }
+        findings
+    }
+
+    fn record_auth_failure(
+        &mut self,
+        client: IpAddr,
+        at: DateTime<FixedOffset>,
+    ) -> Option<WebFinding> {
+        let window = self.auth_failures.entry(client).or_default();
+        window.push_back(at);
+        while window
+            .front()
+            .is_some_and(|&first| at - first > self.config.auth_failure_window)
+        {
+            window.pop_front();
+        }
+        if window.len() < self.config.auth_failure_threshold {
+            return None;
+        }
+        let failures = window.len();
+        let first = window.front().copied().unwrap_or(at);
+        self.auth_failures.remove(&client);
+        Some(WebFinding::AuthFailureBurst {
+            client,
+            failures,
+            first,
+            last: at,
+        })
+    }
+
+    fn record_not_found(
+        &mut self,
+        client: IpAddr,
+        at: DateTime<FixedOffset>,
+        path: &str,
+    ) -> Option<WebFinding> {
+        let window = self.not_found.entry(client).or_default();
+        window.push(at, path);
+        while window
+            .hits
+            .front()
+            .is_some_and(|(first, _)| at - *first > self.config.not_found_window)
+        {
+            window.pop_front();
+        }
+        let distinct_paths = window.distinct_paths();
+        if distinct_paths < self.config.not_found_threshold {
+            return None;
+        }
+        let first = window.hits.front().map_or(at, |(first, _)| *first);
+        self.not_found.remove(&client);
+        Some(WebFinding::NotFoundEnumeration {
+            client,
+            distinct_paths,
+            first,
+            last: at,
+        })
+    }
+
+    /// Drop clients whose newest event has left every window. Runs at most
+    /// once per longest window, so the cost is spread over the entries.
+    fn sweep(&mut self, at: DateTime<FixedOffset>) {
+        let config = self.config;
+        let every = config
+            .auth_failure_window
+            .max(config.not_found_window)
+            .max(config.scanner_quiet_period);
+        if self.last_sweep.is_some_and(|last| at - last < every) {
+            return;
+        }
+        self.last_sweep = Some(at);
+        self.auth_failures.retain(|_, window| {
+            window
+                .back()
+                .is_some_and(|&last| at - last <= config.auth_failure_window)
+        });
+        self.not_found.retain(|_, window| {
+            window
+                .hits
+                .back()
+                .is_some_and(|(last, _)| at - *last <= config.not_found_window)
+        });
+        self.scanners_seen
+            .retain(|_, seen| at - *seen <= config.scanner_quiet_period);
+    }
+}
+
+/// Percent-decode twice (catching double encoding), map `+` to a space,
+/// lowercase, and turn SQL `/**/` comments into spaces.
+fn normalize(target: &str) -> String {
+    let once = percent_decode(&target.replace('+', " "));
+    percent_decode(&once).to_lowercase().replace("/**/", " ")
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| {
+                Some((
+                    hex_value(*bytes.get(i + 1)?)?,
+                    hex_value(*bytes.get(i + 2)?)?,
+                ))
+            })
+            .flatten();
+        if let Some((hi, lo)) = escaped {
+            out.push(hi << 4 | lo);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+const fn hex_value(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access_log::RequestLine;
+
+    /// A GET request `second` seconds after 2024-10-10T14:00:00Z.
+    fn entry(client: &str, second: i64, target: &str, status: u16, agent: &str) -> AccessLogEntry {
+        AccessLogEntry {
+            client: client
+                .parse()
+                .unwrap_or(IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED)),
+            ident: None,
+            user: None,
+            time: DateTime::from_timestamp(1_728_568_800 + second, 0)
+                .unwrap_or_default()
+                .fixed_offset(),
+            request: RequestLine::Http {
+                method: "GET".into(),
+                target: target.into(),
+                protocol: "HTTP/1.1".into(),
+            },
+            status,
+            bytes: Some(0),
+            referer: None,
+            user_agent: Some(agent.into()),
+        }
+    }
+
+    fn kinds(findings: &[WebFinding]) -> Vec<&'static str> {
+        findings
+            .iter()
+            .map(|f| match f {
+                WebFinding::PathTraversal { .. } => "traversal",
+                WebFinding::SqlInjectionProbe { .. } => "sqli",
+                WebFinding::XssProbe { .. } => "xss",
+                WebFinding::ScannerUserAgent { .. } => "scanner",
+                WebFinding::AuthFailureBurst { .. } => "auth",
+                WebFinding::NotFoundEnumeration { .. } => "enum",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn signature_probes_survive_encoding() {
+        let mut detector = WebAttackDetector::new();
+        let cases = [
+            ("/static/../../etc/passwd", "traversal"),
+            ("/img?f=%252e%252e%252fetc%252fpasswd", "traversal"),
+            ("/%c0%ae%c0%ae/secret", "traversal"),
+            ("/items?id=1%27%20OR%201=1--", "sqli"),
+            ("/items?id=1+UNION/**/SELECT+password+FROM+users", "sqli"),
+            ("/search?q=%3CScRiPt%3Ealert(1)%3C/script%3E", "xss"),
+            ("/go?next=javascript:alert(document.cookie)", "xss"),
+        ];
+        for (target, expected) in cases {
+            let findings = detector.observe(&entry("192.0.2.1", 0, target, 200, "Mozilla/5.0"));
+            assert_eq!(kinds(&findings), [expected], "{target}");
+        }
+        let clean = entry(
+            "192.0.2.1",
+            0,
+            "/search?q=union+station+timetable",
+            200,
+            "Mozilla/5.0",
+        );
+        assert!(detector.observe(&clean).is_empty());
+    }
+
+    #[test]
+    fn scanner_reported_once_per_client() {
+        let mut detector = WebAttackDetector::new();
+        let agent = "Mozilla/5.00 (Nikto/2.5.0) (Evasions:None) (Test:000001)";
+        let findings = detector.scan(&[
+            entry("192.0.2.1", 0, "/", 200, agent),
+            entry("192.0.2.1", 1, "/admin", 200, agent),
+            entry("192.0.2.2", 2, "/", 200, agent),
+        ]);
+        assert_eq!(kinds(&findings), ["scanner", "scanner"]);
+        assert_eq!(findings[0].severity(), Severity::Low);
+        assert_eq!(
+            findings[1].describe(),
+            "scanner Nikto running from 192.0.2.2"
+        );
+        assert_eq!(
+            findings[1].to_network_event(),
+            Some(NetworkEvent::ConnectionClosed {
+                peer: "192.0.2.2".into(),
+                reason: Some("scanner Nikto running from 192.0.2.2".into()),
+            })
+        );
+
+        // Back after a quiet hour: reported again.
+        let later = detector.observe(&entry("192.0.2.1", 3_602, "/", 200, agent));
+        assert_eq!(kinds(&later), ["scanner"]);
+    }
+
+    #[test]
+    fn idle_clients_are_forgotten() {
+        let config = DetectorConfig {
+            scanner_quiet_period: TimeDelta::seconds(60),
+            ..DetectorConfig::default()
+        };
+        let mut detector = WebAttackDetector::with_config(config);
+        for host in 0..=255u8 {
+            let client = format!("10.0.0.{host}");
+            detector.observe(&entry(&client, 0, "/login", 401, "curl"));
+            detector.observe(&entry(&client, 0, "/.env", 404, "sqlmap/1.8"));
+        }
+        assert_eq!(detector.tracked(), 3 * 256);
+
+        detector.observe(&entry("192.0.2.1", 61, "/", 200, "Mozilla/5.0"));
+        assert_eq!(detector.tracked(), 0);
+    }
+
+    #[test]
+    fn auth_failure_burst_uses_sliding_window() {
+        let config = DetectorConfig {
+            auth_failure_threshold: 3,
+            auth_failure_window: TimeDelta::seconds(10),
+            ..DetectorConfig::default()
+        };
+        let mut detector = WebAttackDetector::with_config(config);
+        // Spread out: never three inside ten seconds.
+        let slow: Vec<_> = (0..5)
+            .map(|i| entry("192.0.2.9", i * 8, "/login", 401, "curl"))
+            .collect();
+        assert!(detector.scan(&slow).is_empty());
+
+        let fast = [
+            entry("198.51.100.4", 100, "/login", 401, "curl"),
+            entry("198.51.100.4", 101, "/admin", 403, "curl"),
+            entry("203.0.113.3", 101, "/login", 401, "curl"),
+            entry("198.51.100.4", 103, "/login", 401, "curl"),
+        ];
+        let findings = detector.scan(&fast);
+        assert_eq!(kinds(&findings), ["auth"]);
+        assert_eq!(findings[0].client().to_string(), "198.51.100.4");
+        assert_eq!(
+            findings[0].describe(),
+            "3 auth failures (401/403) from 198.51.100.4 in 3s"
+        );
+        assert!(matches!(
+            findings[0].auth_status(),
+            Some(AuthStatus::Failure { reason }) if reason.starts_with("3 auth failures")
+        ));
+        assert_eq!(findings[0].to_network_event(), None);
+    }
+
+    #[test]
+    fn not_found_enumeration_counts_distinct_paths() {
+        let config = DetectorConfig {
+            not_found_threshold: 4,
+            ..DetectorConfig::default()
+        };
+        let mut detector = WebAttackDetector::with_config(config);
+        // The same missing favicon over and over is not enumeration.
+        let favicon: Vec<_> = (0..10)
+            .map(|i| entry("192.0.2.5", i, "/favicon.ico", 404, "Mozilla/5.0"))
+            .collect();
+        assert!(detector.scan(&favicon).is_empty());
+
+        let probes: Vec<_> = ["/.env", "/.git/config", "/backup.zip", "/wp-login.php"]
+            .iter()
+            .zip(20..)
+            .map(|(path, second)| entry("192.0.2.6", second, path, 404, "Mozilla/5.0"))
+            .collect();
+        let findings = detector.scan(&probes);
+        assert_eq!(kinds(&findings), ["enum"]);
+        assert!(matches!(
+            findings[0],
+            WebFinding::NotFoundEnumeration {
+                distinct_paths: 4,
+                ..
+            }
+        ));
+        // The tripped client starts over from nothing.
+        assert!(!detector.not_found.contains_key(&findings[0].client()));
+
+        // Paths leaving the window stop counting toward the distinct total.
+        let slow: Vec<_> = ["/a", "/b", "/c", "/d"]
+            .iter()
+            .zip((200..).step_by(40))
+            .map(|(path, second)| entry("192.0.2.7", second, path, 404, "Mozilla/5.0"))
+            .collect();
+        assert!(detector.scan(&slow).is_empty());
+    }
+
+    #[test]
+    fn percent_decode_leaves_invalid_escapes() {
+        assert_eq!(percent_decode("%41%zz%4"), "A%zz%4");
+        assert_eq!(normalize("%2541"), "a");
+    }
+}
@@ -1,8 +1,13 @@
 //! Integration tests for `exercise_enums`.
 
+#![allow(clippy::expect_used)]
+
 use enums_exercises::{
-    AuthStatus, HttpStatus, MaybeToken, NetworkEvent, get_exercise_list, run_all, run_exercise,
-    sum_packet_bytes,
+    AccessLog, AuthEvent, AuthEventError, AuthEventStore, AuthState, AuthStatus, BodyFraming,
+    ChunkedBody, DetectorConfig, Header, HttpParseError, HttpStatus, LogFormat, MaybeToken,
+    NetworkEvent, ParseLimits, RequestHead, ResponseHead, SmugglingRisk, StatusClass,
+    WebAttackDetector, WebFinding, get_exercise_list, run_all, run_exercise, sum_packet_bytes,
+    utc_day,
 };
 
 #[test]
@@ -27,11 +32,43 @@ fn run_all_exercises() {
 
 #[test]
 fn auth_status_locked_variant() {
-    let status = AuthStatus::Locked {
-        until: "2026-07-10".to_owned(),
-    };
+    let status = AuthStatus::locked_until_ms(1_783_641_600_000);
     assert!(!status.is_authenticated());
     assert!(status.summary().contains("locked"));
+    assert!(status.summary().contains("2026-07-10"));
+}
+
+#[test]
+fn lockout_policy_decision_becomes_locked_status() {
+    use std::net::{IpAddr, Ipv4Addr};
+    use structs_exercises::{LockoutConfig, LockoutEvent, LockoutPolicy, ManualClock};
+
+    let source = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 9));
+    let clock = ManualClock::new(1_783_641_600_000);
+    let config = LockoutConfig {
+        account_threshold: 2,
+        ..LockoutConfig::default()
+    };
+    let mut policy = LockoutPolicy::with_clock(clock, config);
+    let first = policy.attempt("alice", source, || false);
+    assert!(matches!(
+        AuthStatus::from_lockout(&first),
+        Some(AuthStatus::Failure { .. })
+    ));
+    let second = policy.attempt("alice", source, || false);
+    let status = AuthStatus::from_lockout(&second);
+    assert_eq!(
+        status.map(|s| s.summary()),
+        Some("account locked until 2026-07-10T00:01:00Z".to_owned())
+    );
+    let audited = policy
+        .events()
+        .back()
+        .and_then(LockoutEvent::locked_until_ms);
+    assert_eq!(
+        audited.map(AuthStatus::locked_until_ms),
+        AuthStatus::from_lockout(&second)
+    );
 }
 
 #[test]
@@ -51,6 +88,70 @@ fn http_from_code_all_required() {
     }
 }
 
+#[test]
+fn http_registry_classes() {
+    assert_eq!(
+        HttpStatus::from_code(451),
+        Some(HttpStatus::UnavailableForLegalReasons)
+    );
+    assert!(
+        HttpStatus::ALL
+            .iter()
+            .all(|s| Some(s.class()) == StatusClass::from_code(s.code()))
+    );
+    assert_eq!(HttpStatus::from_code(418), None);
+    assert_eq!(StatusClass::from_code(418), Some(StatusClass::ClientError));
+}
+
+#[test]
+fn http_pipelined_stream_with_smuggled_request() {
+    // A reassembled keep-alive stream: a chunked upload followed by a
+    // request whose framing is ambiguous.
+    let stream = b"POST /upload HTTP/1.1\r\nHost: a\r\nTransfer-Encoding: chunked\r\n\r\n\
+3\r\nabc\r\n0\r\n\r\n\
+POST / HTTP/1.1\r\nHost: a\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+    let limits = ParseLimits::default();
+
+    let (head, consumed) = RequestHead::parse(stream, &limits)
+        .expect("valid head")
+        .complete()
+        .expect("complete head");
+    assert_eq!(head.body_framing(), Ok(BodyFraming::Chunked));
+    let (body, body_len) = ChunkedBody::decode(&stream[consumed..], &limits)
+        .expect("valid body")
+        .complete()
+        .expect("complete body");
+    assert_eq!(body.to_vec(), b"abc");
+
+    let rest = &stream[consumed + body_len..];
+    let (head, _) = RequestHead::parse(rest, &limits)
+        .expect("valid second head")
+        .complete()
+        .expect("complete second head");
+    assert_eq!(head.target, "/");
+    assert_eq!(
+        head.body_framing(),
+        Err(SmugglingRisk::ContentLengthWithTransferEncoding)
+    );
+
+    let (response, _) = ResponseHead::parse(
+        b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 30\r\n\r\n",
+        &limits,
+    )
+    .expect("valid response head")
+    .complete()
+    .expect("complete response head");
+    assert!(response.status().is_some_and(HttpStatus::is_client_error));
+    assert_eq!(
+        response.header("retry-after").and_then(Header::value_str),
+        Some("30")
+    );
+    assert_eq!(
+        RequestHead::parse(b"GET / HTTP/1.1\nHost: a\n\n", &limits),
+        Err(HttpParseError::BareLineFeed)
+    );
+}
+
 #[test]
 fn maybe_token_unwrap_or() {
     let present = MaybeToken::Some("tok".to_owned());
@@ -59,3 +160,86 @@ fn maybe_token_unwrap_or() {
     let absent = MaybeToken::None;
     assert_eq!(absent.unwrap_or("default".to_owned()), "default");
 }
+
+#[test]
+fn json_access_log_feeds_detector() {
+    let text = (0..5)
+        .map(|i| {
+            format!(
+                r#"{{"remote_addr":"192.0.2.40","time_iso8601":"2025-03-14T09:00:0{i}+00:00","request":"POST /login HTTP/1.1","status":"403","body_bytes_sent":"0","http_user_agent":"curl/8.5"}}"#
+            )
+        })
+        .chain(std::iter::once("{broken".to_owned()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let log = AccessLog::parse(&text, LogFormat::Json);
+    assert_eq!(log.entries.len(), 5);
+    assert_eq!(log.rejected.len(), 1);
+    assert_eq!(log.rejected[0].line, 6);
+    assert!(
+        log.entries
+            .iter()
+            .all(|e| e.status() == Some(HttpStatus::Forbidden))
+    );
+
+    let config = DetectorConfig {
+        auth_failure_threshold: 5,
+        ..DetectorConfig::default()
+    };
+    let findings = WebAttackDetector::with_config(config).scan(&log.entries);
+    assert_eq!(findings.len(), 1);
+    assert!(matches!(
+        findings[0],
+        WebFinding::AuthFailureBurst { failures: 5, .. }
+    ));
+    assert!(matches!(
+        findings[0].auth_status(),
+        Some(AuthStatus::Failure { .. })
+    ));
+}
+
+#[test]
+fn auth_event_log_survives_a_restart() {
+    let day = chrono::NaiveDate::from_ymd_opt(2025, 3, 14).unwrap_or_default();
+    let at = |minutes| utc_day(day).start + chrono::TimeDelta::minutes(minutes);
+    let mut store = AuthEventStore::new();
+    for i in 0..6 {
+        let pending = store.apply("mallory", AuthEvent::PasswordAccepted, at(i * 2));
+        assert!(pending.is_ok());
+        let status = AuthStatus::Failure {
+            reason: "invalid MFA code".to_owned(),
+        };
+        assert!(
+            store
+                .record_status("mallory", &status, at(i * 2 + 1))
+                .is_ok()
+        );
+    }
+    let lock = AuthStatus::Locked { until: at(30) };
+    assert!(store.record_status("mallory", &lock, at(12)).is_ok());
+    assert!(matches!(
+        store.apply("mallory", AuthEvent::LoginSucceeded, at(31)),
+        Err(AuthEventError::IllegalTransition {
+            from: AuthState::Locked,
+            ..
+        })
+    ));
+
+    let dir = std::env::temp_dir().join(format!("auth-events-{}", std::process::id()));
+    assert!(std::fs::create_dir_all(&dir).is_ok());
+    let path = dir.join("auth.jsonl");
+    assert!(std::fs::write(&path, store.to_jsonl()).is_ok());
+    let text = std::fs::read_to_string(&path).unwrap_or_default();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let restored = AuthEventStore::from_jsonl(&text);
+    assert!(restored.is_ok());
+    let Ok(restored) = restored else { return };
+    assert_eq!(restored.events().len(), 13);
+    assert_eq!(restored.state("mallory"), AuthState::Locked);
+    assert_eq!(restored.state_at("mallory", at(4)), AuthState::Pending);
+    assert_eq!(
+        restored.users_with_transition(AuthState::Pending, AuthState::Failed, &utc_day(day), 5),
+        [("mallory", 6)]
+    );
+}
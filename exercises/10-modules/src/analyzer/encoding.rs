@@ -0,0 +1,112 @@
+//! Hex and base64 decoding for token bodies — `pub(crate)` like the scorer.
+
+/// Decode an even-length hex string (either case).
+pub(crate) fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    let pairs = text.as_bytes().chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| {
+            let digits = std::str::from_utf8(pair).ok()?;
+            u8::from_str_radix(digits, 16).ok()
+        })
+        .collect()
+}
+
+/// Value of one base64 symbol in either the standard or URL-safe alphabet.
+const fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Whether every byte is a base64 symbol (standard or URL-safe) or padding.
+pub(crate) fn is_base64_alphabet(text: &str) -> bool {
+    text.bytes().all(|b| b == b'=' || base64_value(b).is_some())
+}
+
+/// Whether base64 `text` has a length its padding agrees with: padding, if
+/// any, completes the last 4-symbol group, and the unused low bits of a
+/// partial group are zero, as an encoder leaves them. Random words of the
+/// base64 alphabet usually fail one of these.
+pub(crate) fn is_canonical_base64(text: &str) -> bool {
+    let body = text.trim_end_matches('=');
+    let padding = text.len() - body.len();
+    let (expected_padding, unused_bits) = match body.len() % 4 {
+        0 => (0, 0),
+        2 => (2, 4),
+        3 => (1, 2),
+        _ => return false,
+    };
+    if padding != 0 && padding != expected_padding {
+        return false;
+    }
+    body.bytes()
+        .last()
+        .and_then(base64_value)
+        .is_some_and(|last| last & ((1 << unused_bits) - 1) == 0)
+}
+
+/// Decode base64 in the standard or URL-safe alphabet, padded or not.
+pub(crate) fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let body = text.trim_end_matches('=');
+    if body.len() % 4 == 1 || text.len() - body.len() > 2 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(body.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in body.bytes() {
+        buffer = (buffer << 6) | u32::from(base64_value(byte)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(decode_hex("00ff7A"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+
+    #[test]
+    fn base64_accepts_both_alphabets() {
+        assert_eq!(decode_base64("aGVsbG8="), Some(b"hello".to_vec()));
+        assert_eq!(decode_base64("aGVsbG8"), Some(b"hello".to_vec()));
+        assert_eq!(decode_base64("-_8"), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_base64("+/8="), Some(vec![0xfb, 0xff]));
+        assert_eq!(decode_base64("a"), None);
+        assert_eq!(decode_base64("a!=="), None);
+        assert!(is_base64_alphabet("ab+/_-=="));
+        assert!(!is_base64_alphabet("ab cd"));
+    }
+
+    #[test]
+    fn canonical_base64_needs_consistent_padding_and_tail() {
+        assert!(is_canonical_base64("aGVsbG8="));
+        assert!(is_canonical_base64("aGVsbG8"));
+        assert!(is_canonical_base64("aGVsbA=="));
+        assert!(is_canonical_base64("aGVsbA"));
+        assert!(!is_canonical_base64("aGVsbG9="));
+        assert!(!is_canonical_base64("aGVsbB"));
+        assert!(!is_canonical_base64("aGVsbA="));
+        assert!(!is_canonical_base64("aGVsbG8x="));
+        assert!(!is_canonical_base64("aGVsb"));
+        assert!(!is_canonical_base64(""));
+    }
+}
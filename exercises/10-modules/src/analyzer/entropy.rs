@@ -0,0 +1,118 @@
+//! Randomness measurements for token bodies: symbol entropy and run lengths.
+
+use std::collections::HashMap;
+
+/// Counts and lengths as `f64`; token-sized inputs never exceed `u32`.
+pub(crate) fn count_to_f64(n: usize) -> f64 {
+    f64::from(u32::try_from(n).unwrap_or(u32::MAX))
+}
+
+/// Shannon entropy of `symbols` in bits per symbol.
+#[must_use]
+pub fn shannon_entropy(symbols: &[u8]) -> f64 {
+    if symbols.is_empty() {
+        return 0.0;
+    }
+    let mut counts: HashMap<u8, usize> = HashMap::new();
+    for &symbol in symbols {
+        *counts.entry(symbol).or_insert(0) += 1;
+    }
+    let total = count_to_f64(symbols.len());
+    counts
+        .values()
+        .map(|&count| {
+            let p = count_to_f64(count) / total;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+/// The most entropy `len` symbols from an `alphabet`-sized set can show.
+///
+/// A 32-symbol sample can hold at most 32 distinct symbols, so short inputs
+/// are compared against `log2(min(len, alphabet))`, not `log2(alphabet)`.
+#[must_use]
+pub fn max_entropy(len: usize, alphabet: usize) -> f64 {
+    count_to_f64(len.min(alphabet).max(1)).log2()
+}
+
+/// Observed entropy as a fraction of [`max_entropy`]; 1.0 for inputs too
+/// short to judge.
+#[must_use]
+pub fn entropy_ratio(symbols: &[u8], alphabet: usize) -> f64 {
+    let max = max_entropy(symbols.len(), alphabet);
+    if max == 0.0 {
+        1.0
+    } else {
+        shannon_entropy(symbols) / max
+    }
+}
+
+/// Run lengths in a symbol sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunStats {
+    /// Longest stretch of one repeated symbol (`aaaa` → 4).
+    pub longest_repeat: usize,
+    /// Longest stretch stepping by ±1 (`abcd`, `4321` → 4).
+    pub longest_sequence: usize,
+}
+
+/// Measure repeated and sequential runs in `symbols`.
+#[must_use]
+pub fn run_stats(symbols: &[u8]) -> RunStats {
+    let mut stats = RunStats {
+        longest_repeat: usize::from(!symbols.is_empty()),
+        longest_sequence: usize::from(!symbols.is_empty()),
+    };
+    let (mut repeat, mut sequence, mut step) = (1, 1, 0i16);
+    for pair in symbols.windows(2) {
+        let delta = i16::from(pair[1]) - i16::from(pair[0]);
+        repeat = if delta == 0 { repeat + 1 } else { 1 };
+        sequence = if delta.abs() == 1 && (sequence == 1 || delta == step) {
+            sequence + 1
+        } else if delta.abs() == 1 {
+            2
+        } else {
+            1
+        };
+        step = delta;
+        stats.longest_repeat = stats.longest_repeat.max(repeat);
+        stats.longest_sequence = stats.longest_sequence.max(sequence);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_of_uniform_and_constant_input() {
+        assert!(shannon_entropy(b"").abs() < f64::EPSILON);
+        assert!(shannon_entropy(b"aaaa").abs() < f64::EPSILON);
+        assert!(shannon_entropy(b"aaaa").is_sign_positive());
+        assert!((shannon_entropy(b"abcd") - 2.0).abs() < 1e-9);
+        assert!((shannon_entropy(&(0..=255).collect::<Vec<u8>>()) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ratio_accounts_for_short_samples() {
+        // Four distinct hex digits are as random as four symbols can look.
+        assert!((entropy_ratio(b"a3f9", 16) - 1.0).abs() < 1e-9);
+        assert!(entropy_ratio(b"aaaaaaaaaaaaaaab", 16) < 0.2);
+        assert!((max_entropy(1000, 64) - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn runs_detect_repeats_and_sequences() {
+        let stats = run_stats(b"x1111y");
+        assert_eq!(stats.longest_repeat, 4);
+        let stats = run_stats(b"q0123456z");
+        assert_eq!(stats.longest_sequence, 7);
+        let stats = run_stats(b"fedcba");
+        assert_eq!(stats.longest_sequence, 6);
+        // Zig-zag is not a sequence.
+        assert_eq!(run_stats(b"abab").longest_sequence, 2);
+        assert_eq!(run_stats(b""), RunStats::default());
+    }
+}
@@ -1,15 +1,27 @@
-//! Password analyzer module tree — shared types plus `score`, `risk`, and `validate`.
+//! Password and token analyzer module tree — shared types plus `score`, `risk`,
+//! `validate`, the token-aware `token`, `jwt` and `entropy` modules, and the
+//! `randomness` test battery.
 
+pub mod encoding;
+pub mod entropy;
+pub mod jwt;
+pub mod randomness;
 pub mod risk;
 pub mod score;
+pub mod token;
 pub mod validate;
 
+pub use jwt::{Jwt, JwtError};
+pub use randomness::{InputEncoding, RandomnessReport, Verdict, run_battery};
 pub use risk::RiskLevel;
+pub use token::{
+    TokenAnalysis, TokenFinding, TokenFormat, VendorKey, analyze_token, analyze_token_at,
+};
 
 /// Maximum score allowed when a secret is on the common-password denylist.
 const COMMON_SCORE_CAP: u8 = 10;
 
-/// Result of analyzing a password or API token.
+/// Result of analyzing a password. API tokens go through [`analyze_token`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Analysis {
     /// Numeric strength from 0 (worst) to 100 (best).
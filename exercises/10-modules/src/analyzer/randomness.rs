@@ -0,0 +1,670 @@
+//! Statistical randomness battery based on NIST SP 800-22 rev. 1a.
+//!
+//! Only the tests that make sense for short inputs (keys and tokens of a few
+//! hundred bits) are included:
+//!
+//! | Test | § | Minimum bits |
+//! |------|---|--------------|
+//! | Frequency (monobit) | 2.1 | 100 |
+//! | Frequency within a block | 2.2 | 100 |
+//! | Runs | 2.3 | 100 |
+//! | Longest run of ones in a block | 2.4 | 128 |
+//! | Serial | 2.11 | 100 |
+//! | Approximate entropy | 2.12 | 128 |
+//!
+//! Each test yields a p-value. Under the hypothesis that the input is random,
+//! p is uniform on [0, 1]. `alpha` (0.01 by default) is the significance level
+//! for the battery as a whole: with k tests run, a p-value below `alpha / k`
+//! (Bonferroni) counts as evidence against randomness. So at most `alpha` of
+//! truly random inputs, about 1 in 100 by default, are reported non-random.
+//! Without the correction, seven tests at 0.01 each would flag about 7%.
+//!
+//! Passing does not prove a key is secret or unpredictable. A counter
+//! encrypted with a known key passes every test.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::encoding::{decode_base64, decode_hex, is_base64_alphabet};
+use super::entropy::count_to_f64;
+
+/// NIST's recommended significance level, used for the whole battery.
+pub const DEFAULT_ALPHA: f64 = 0.01;
+/// Shortest input the battery runs on; NIST recommends n ≥ 100.
+pub const MIN_BITS: usize = 100;
+/// Cap on serial and approximate entropy pattern lengths for large inputs.
+const MAX_PATTERN_LEN: usize = 16;
+
+/// How to turn input text into bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputEncoding {
+    /// Hex if it decodes as hex, then base64, otherwise the raw text bytes.
+    #[default]
+    Auto,
+    Hex,
+    Base64,
+    Raw,
+}
+
+impl FromStr for InputEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "hex" => Ok(Self::Hex),
+            "base64" | "b64" => Ok(Self::Base64),
+            "raw" => Ok(Self::Raw),
+            other => Err(format!(
+                "unknown encoding {other:?} (expected auto, hex, base64 or raw)"
+            )),
+        }
+    }
+}
+
+impl InputEncoding {
+    /// Decode `text`, ignoring surrounding whitespace for hex and base64.
+    /// Returns the bytes and the encoding actually used.
+    #[must_use]
+    pub fn decode(self, text: &str) -> Option<(Vec<u8>, Self)> {
+        let trimmed = text.trim();
+        match self {
+            Self::Hex => decode_hex(trimmed).map(|b| (b, Self::Hex)),
+            Self::Base64 => decode_base64(trimmed).map(|b| (b, Self::Base64)),
+            Self::Raw => Some((text.as_bytes().to_vec(), Self::Raw)),
+            Self::Auto => Self::Hex
+                .decode(trimmed)
+                .or_else(|| {
+                    is_base64_alphabet(trimmed)
+                        .then(|| Self::Base64.decode(trimmed))
+                        .flatten()
+                })
+                .or_else(|| Self::Raw.decode(text)),
+        }
+    }
+}
+
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Auto => "auto",
+            Self::Hex => "hex",
+            Self::Base64 => "base64",
+            Self::Raw => "raw",
+        })
+    }
+}
+
+/// Expand bytes into bits, most significant bit first.
+#[must_use]
+pub fn to_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|&byte| (0..8).rev().map(move |i| byte >> i & 1 == 1))
+        .collect()
+}
+
+/// Which test produced a result, with the parameters it ran with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RandomnessTest {
+    Frequency,
+    BlockFrequency {
+        block_len: usize,
+    },
+    Runs,
+    LongestRun {
+        block_len: usize,
+    },
+    /// `delta` 1 and 2 are the ∇ψ² and ∇²ψ² statistics.
+    Serial {
+        pattern_len: usize,
+        delta: u8,
+    },
+    ApproximateEntropy {
+        pattern_len: usize,
+    },
+}
+
+impl fmt::Display for RandomnessTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Frequency => f.write_str("frequency"),
+            Self::BlockFrequency { block_len } => write!(f, "block frequency (M={block_len})"),
+            Self::Runs => f.write_str("runs"),
+            Self::LongestRun { block_len } => write!(f, "longest run (M={block_len})"),
+            Self::Serial { pattern_len, delta } => {
+                let statistic = if *delta == 1 { "∇ψ²" } else { "∇²ψ²" };
+                write!(f, "serial (m={pattern_len}, {statistic})")
+            }
+            Self::ApproximateEntropy { pattern_len } => {
+                write!(f, "approximate entropy (m={pattern_len})")
+            }
+        }
+    }
+}
+
+/// One p-value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub test: RandomnessTest,
+    /// The test statistic (z-score, χ² or ψ², depending on the test).
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    #[must_use]
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.p_value >= alpha
+    }
+}
+
+/// Overall outcome of [`run_battery`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Every p-value is at or above the corrected threshold.
+    Random,
+    /// At least one p-value is below the corrected threshold.
+    NonRandom,
+    /// Too few bits to run any test.
+    Inconclusive,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Random => "no evidence against randomness",
+            Self::NonRandom => "non-random",
+            Self::Inconclusive => "inconclusive (input too short)",
+        })
+    }
+}
+
+/// Results of the whole battery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessReport {
+    pub bits: usize,
+    /// Significance level for the battery as a whole.
+    pub alpha: f64,
+    pub results: Vec<TestResult>,
+    /// Tests that need more bits than the input has, with their minimum.
+    pub skipped: Vec<(&'static str, usize)>,
+}
+
+impl RandomnessReport {
+    /// Per-test significance level: `alpha` divided by the number of tests
+    /// run (Bonferroni), so the battery's false-positive rate stays at or
+    /// below `alpha`.
+    #[must_use]
+    pub fn threshold(&self) -> f64 {
+        self.alpha / count_to_f64(self.results.len().max(1))
+    }
+
+    /// [`Verdict::NonRandom`] if any p-value is below [`Self::threshold`].
+    #[must_use]
+    pub fn verdict(&self) -> Verdict {
+        if self.results.is_empty() {
+            Verdict::Inconclusive
+        } else if self.failures().next().is_none() {
+            Verdict::Random
+        } else {
+            Verdict::NonRandom
+        }
+    }
+
+    /// The results with p-values below [`Self::threshold`].
+    pub fn failures(&self) -> impl Iterator<Item = &TestResult> {
+        let threshold = self.threshold();
+        self.results.iter().filter(move |r| !r.passed(threshold))
+    }
+}
+
+/// Run every applicable test on `bytes` with `alpha` as the battery-wide
+/// significance level, choosing block and pattern lengths from the input
+/// length.
+#[must_use]
+pub fn run_battery(bytes: &[u8], alpha: f64) -> RandomnessReport {
+    let bits = to_bits(bytes);
+    let n = bits.len();
+    let log2_n = usize::try_from(n.max(1).ilog2()).unwrap_or(0);
+    let mut report = RandomnessReport {
+        bits: n,
+        alpha,
+        results: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    if n < MIN_BITS {
+        report.skipped.extend([
+            ("frequency", MIN_BITS),
+            ("block frequency", MIN_BITS),
+            ("runs", MIN_BITS),
+            ("serial", MIN_BITS),
+        ]);
+    } else {
+        report.results.push(frequency(&bits));
+        // §2.2.7: M ≥ 20, M > 0.01·n and fewer than 100 blocks.
+        let block_len = (n / 99 + 1).max(20);
+        report.results.extend(block_frequency(&bits, block_len));
+        report.results.push(runs(&bits));
+        // §2.11.7: m < ⌊log2 n⌋ − 2.
+        let pattern_len = (log2_n - 3).min(MAX_PATTERN_LEN);
+        report
+            .results
+            .extend(serial(&bits, pattern_len).into_iter().flatten());
+    }
+    match longest_run(&bits) {
+        Some(result) => report.results.push(result),
+        None => report.skipped.push(("longest run", 128)),
+    }
+    // §2.12.7: m < ⌊log2 n⌋ − 5.
+    let pattern_len = log2_n.saturating_sub(6).min(MAX_PATTERN_LEN);
+    match approximate_entropy(&bits, pattern_len).filter(|_| n >= 128) {
+        Some(result) => report.results.push(result),
+        None => report.skipped.push(("approximate entropy", 128)),
+    }
+    report
+}
+
+fn ones(bits: &[bool]) -> usize {
+    bits.iter().filter(|&&b| b).count()
+}
+
+/// §2.1: are ones and zeros equally common?
+#[must_use]
+pub fn frequency(bits: &[bool]) -> TestResult {
+    let n = count_to_f64(bits.len());
+    let sum = 2.0f64.mul_add(count_to_f64(ones(bits)), -n);
+    let s_obs = sum.abs() / n.sqrt();
+    TestResult {
+        test: RandomnessTest::Frequency,
+        statistic: s_obs,
+        p_value: erfc(s_obs / std::f64::consts::SQRT_2),
+    }
+}
+
+/// §2.2: is the proportion of ones near ½ in each `block_len`-bit block?
+/// `None` when there is not even one full block.
+#[must_use]
+pub fn block_frequency(bits: &[bool], block_len: usize) -> Option<TestResult> {
+    let blocks = bits.len().checked_div(block_len).filter(|&b| b > 0)?;
+    let m = count_to_f64(block_len);
+    let chi_squared = 4.0
+        * m
+        * bits
+            .chunks_exact(block_len)
+            .map(|block| {
+                let pi = count_to_f64(ones(block)) / m - 0.5;
+                pi * pi
+            })
+            .sum::<f64>();
+    Some(TestResult {
+        test: RandomnessTest::BlockFrequency { block_len },
+        statistic: chi_squared,
+        p_value: igamc(count_to_f64(blocks) / 2.0, chi_squared / 2.0),
+    })
+}
+
+/// §2.3: do runs of identical bits switch as often as they should?
+#[must_use]
+pub fn runs(bits: &[bool]) -> TestResult {
+    let n = count_to_f64(bits.len());
+    let pi = count_to_f64(ones(bits)) / n;
+    let changes = bits.windows(2).filter(|w| w[0] != w[1]).count();
+    let v_obs = count_to_f64(changes + 1);
+    // Frequency prerequisite: the test is meaningless if it already failed.
+    let p_value = if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        0.0
+    } else {
+        let spread = 2.0 * n * pi * (1.0 - pi);
+        erfc((v_obs - spread).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)))
+    };
+    TestResult {
+        test: RandomnessTest::Runs,
+        statistic: v_obs,
+        p_value,
+    }
+}
+
+/// §2.4: is the longest run of ones within each block as long as expected?
+/// `None` below 128 bits.
+#[must_use]
+pub fn longest_run(bits: &[bool]) -> Option<TestResult> {
+    // (block length, lowest class, class probabilities) from §3.4.
+    let (block_len, lowest, probabilities): (usize, usize, &[f64]) = match bits.len() {
+        0..128 => return None,
+        128..6272 => (8, 1, &[0.2148, 0.3672, 0.2305, 0.1875]),
+        6272..750_000 => (128, 4, &[0.1174, 0.2430, 0.2493, 0.1752, 0.1027, 0.1124]),
+        _ => (
+            10_000,
+            10,
+            &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+        ),
+    };
+    let classes = probabilities.len();
+    let mut counts = vec![0usize; classes];
+    for block in bits.chunks_exact(block_len) {
+        let (mut longest, mut current) = (0usize, 0usize);
+        for &bit in block {
+            current = if bit { current + 1 } else { 0 };
+            longest = longest.max(current);
+        }
+        counts[longest.saturating_sub(lowest).min(classes - 1)] += 1;
+    }
+    let blocks = count_to_f64(bits.len() / block_len);
+    let chi_squared: f64 = counts
+        .iter()
+        .zip(probabilities)
+        .map(|(&v, &p)| {
+            let expected = blocks * p;
+            (count_to_f64(v) - expected).powi(2) / expected
+        })
+        .sum();
+    Some(TestResult {
+        test: RandomnessTest::LongestRun { block_len },
+        statistic: chi_squared,
+        p_value: igamc(count_to_f64(classes - 1) / 2.0, chi_squared / 2.0),
+    })
+}
+
+/// Counts of every overlapping `m`-bit pattern, wrapping around the end.
+fn pattern_counts(bits: &[bool], m: usize) -> Vec<usize> {
+    let mut counts = vec![0usize; 1 << m];
+    if m == 0 {
+        counts[0] = bits.len();
+        return counts;
+    }
+    for start in 0..bits.len() {
+        let index = (0..m).fold(0, |acc, i| {
+            (acc << 1) | usize::from(bits[(start + i) % bits.len()])
+        });
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// ψ²ₘ from §2.11.4; zero for m ≤ 0.
+fn psi_squared(bits: &[bool], m: usize) -> f64 {
+    if m == 0 {
+        return 0.0;
+    }
+    let n = count_to_f64(bits.len());
+    let sum: f64 = pattern_counts(bits, m)
+        .iter()
+        .map(|&c| count_to_f64(c).powi(2))
+        .sum();
+    (count_to_f64(1 << m) / n).mul_add(sum, -n)
+}
+
+/// §2.11: are all `pattern_len`-bit patterns equally common? Returns the ∇ψ²
+/// and ∇²ψ² results, or `None` unless 3 ≤ `pattern_len` < 24.
+#[must_use]
+pub fn serial(bits: &[bool], pattern_len: usize) -> Option<[TestResult; 2]> {
+    if !(3..24).contains(&pattern_len) || bits.is_empty() {
+        return None;
+    }
+    let m = pattern_len;
+    let psi = [
+        psi_squared(bits, m),
+        psi_squared(bits, m - 1),
+        psi_squared(bits, m - 2),
+    ];
+    let delta1 = psi[0] - psi[1];
+    let delta2 = 2.0f64.mul_add(-psi[1], psi[0]) + psi[2];
+    let result = |delta, statistic: f64, degrees: usize| TestResult {
+        test: RandomnessTest::Serial {
+            pattern_len: m,
+            delta,
+        },
+        statistic,
+        p_value: igamc(count_to_f64(1 << degrees), statistic / 2.0),
+    };
+    Some([result(1, delta1, m - 2), result(2, delta2, m - 3)])
+}
+
+/// φ⁽ᵐ⁾ from §2.12.4.
+fn phi(bits: &[bool], m: usize) -> f64 {
+    let n = count_to_f64(bits.len());
+    pattern_counts(bits, m)
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = count_to_f64(c) / n;
+            p * p.ln()
+        })
+        .sum()
+}
+
+/// §2.12: compares the frequency of overlapping `pattern_len` and
+/// `pattern_len + 1`-bit patterns. `None` unless 1 ≤ `pattern_len` < 24.
+#[must_use]
+pub fn approximate_entropy(bits: &[bool], pattern_len: usize) -> Option<TestResult> {
+    if !(1..24).contains(&pattern_len) || bits.is_empty() {
+        return None;
+    }
+    let n = count_to_f64(bits.len());
+    let ap_en = phi(bits, pattern_len) - phi(bits, pattern_len + 1);
+    let chi_squared = 2.0 * n * (std::f64::consts::LN_2 - ap_en);
+    Some(TestResult {
+        test: RandomnessTest::ApproximateEntropy { pattern_len },
+        statistic: chi_squared,
+        p_value: igamc(count_to_f64(1 << (pattern_len - 1)), chi_squared / 2.0),
+    })
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, fractional
+/// error below 1.2e-7).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / 0.5f64.mul_add(z, 1.0);
+    let poly = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0_f64, |acc, &c| acc.mul_add(t, c));
+    let r = t * (-z).mul_add(z, poly).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// ln Γ(x) for x > 0 (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5;
+    let tmp = (x + 0.5).mul_add(-tmp.ln(), tmp);
+    let series = COEFFICIENTS
+        .iter()
+        .zip(1..)
+        .fold(1.000_000_000_190_015, |acc, (&c, i)| {
+            acc + c / (x + f64::from(i))
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+/// Upper regularized incomplete gamma function Q(a, x), the χ² tail used by
+/// NIST's `igamc` (Numerical Recipes `gammq`).
+fn igamc(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const MAX_ITERATIONS: u32 = 500;
+    if x <= 0.0 || a <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (a.mul_add(x.ln(), -x) - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series for P(a, x), then Q = 1 − P.
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..MAX_ITERATIONS {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        sum.mul_add(-prefix, 1.0).clamp(0.0, 1.0)
+    } else {
+        // Lentz's continued fraction for Q(a, x).
+        let tiny = f64::MIN_POSITIVE / EPS;
+        let mut b_n = x + 1.0 - a;
+        let mut c_n = 1.0 / tiny;
+        let mut d_n = 1.0 / b_n;
+        let mut fraction = d_n;
+        for i in 1..=MAX_ITERATIONS {
+            let a_n = -f64::from(i) * (f64::from(i) - a);
+            b_n += 2.0;
+            d_n = a_n.mul_add(d_n, b_n);
+            if d_n.abs() < tiny {
+                d_n = tiny;
+            }
+            c_n = b_n + a_n / c_n;
+            if c_n.abs() < tiny {
+                c_n = tiny;
+            }
+            d_n = 1.0 / d_n;
+            let step = d_n * c_n;
+            fraction *= step;
+            if (step - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        (prefix * fraction).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The 100-bit ε used in SP 800-22's worked examples.
+    const EPSILON_100: &str = "1100100100001111110110101010001000100001011010001100001000110100110001001100011001100010100010111000";
+
+    fn bits(text: &str) -> Vec<bool> {
+        text.bytes().map(|b| b == b'1').collect()
+    }
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-4
+    }
+
+    #[test]
+    fn special_functions() {
+        assert!(close(erfc(0.0), 1.0));
+        assert!(close(erfc(1.0), 0.157_299));
+        assert!(close(erfc(-1.0), 1.842_701));
+        assert!(close(ln_gamma(5.0), 24f64.ln()));
+        // Q(1, x) = e^-x
+        assert!(close(igamc(1.0, 2.0), (-2f64).exp()));
+        assert!(close(igamc(3.0, 0.5), 0.985_612));
+        assert!(close(igamc(3.0, 10.0), 0.002_769));
+    }
+
+    #[test]
+    fn nist_worked_examples() {
+        let e = bits(EPSILON_100);
+        assert!(close(frequency(&e).p_value, 0.109_599));
+        assert!(block_frequency(&e, 10).is_some_and(|r| close(r.p_value, 0.706_438)));
+        assert!(close(runs(&e).p_value, 0.500_798));
+        assert!(approximate_entropy(&e, 2).is_some_and(|r| close(r.p_value, 0.235_301)));
+
+        let e128 = bits(
+            "11001100000101010110110001001100111000000000001001001101010100010001001111010110100000001101011111001100111001101101100010110010",
+        );
+        assert!(longest_run(&e128).is_some_and(|r| close(r.p_value, 0.180_609)));
+
+        let serial_results = serial(&bits("0011011101"), 3);
+        assert!(serial_results.is_some_and(|[d1, d2]| {
+            close(d1.p_value, 0.808_792) && close(d2.p_value, 0.670_320)
+        }));
+        let ap_en = approximate_entropy(&bits("0100110101"), 3);
+        assert!(ap_en.is_some_and(|r| close(r.p_value, 0.261_961)));
+    }
+
+    #[test]
+    fn parameters_out_of_range() {
+        let e = bits(EPSILON_100);
+        assert!(block_frequency(&e, 0).is_none());
+        assert!(block_frequency(&e, 101).is_none());
+        assert!(longest_run(&e).is_none());
+        assert!(serial(&e, 2).is_none());
+        assert!(approximate_entropy(&e, 0).is_none());
+    }
+
+    #[test]
+    fn battery_flags_patterns() {
+        let zeros = run_battery(&[0u8; 32], DEFAULT_ALPHA);
+        assert_eq!(zeros.bits, 256);
+        assert_eq!(zeros.verdict(), Verdict::NonRandom);
+        assert!(
+            zeros
+                .failures()
+                .any(|r| r.test == RandomnessTest::Frequency)
+        );
+
+        // Balanced but periodic: passes frequency, fails runs.
+        let alternating = run_battery(&[0b0101_0101; 32], DEFAULT_ALPHA);
+        assert!(alternating.results[0].passed(DEFAULT_ALPHA));
+        assert!(
+            alternating
+                .failures()
+                .any(|r| r.test == RandomnessTest::Runs)
+        );
+        assert_eq!(alternating.verdict(), Verdict::NonRandom);
+
+        assert_eq!(alternating.results[3].test.to_string(), "serial (m=5, ∇ψ²)");
+
+        // Seven tests at alpha = 0.01: p = 0.005 is marginal, not a failure.
+        let mut marginal = run_battery(&[0x5a, 0xc3, 0x1e, 0x96].repeat(8), DEFAULT_ALPHA);
+        assert_eq!(marginal.results.len(), 7);
+        assert!((marginal.threshold() - DEFAULT_ALPHA / 7.0).abs() < 1e-12);
+        for result in &mut marginal.results {
+            result.p_value = 0.5;
+        }
+        marginal.results[0].p_value = 0.005;
+        assert_eq!(marginal.verdict(), Verdict::Random);
+        marginal.results[0].p_value = 0.001;
+        assert_eq!(marginal.verdict(), Verdict::NonRandom);
+
+        let short = run_battery(b"short", DEFAULT_ALPHA);
+        assert_eq!(short.verdict(), Verdict::Inconclusive);
+        assert_eq!(short.skipped.len(), 6);
+    }
+
+    #[test]
+    fn input_encodings() {
+        assert_eq!(
+            InputEncoding::Auto.decode(" 00ff\n"),
+            Some((vec![0x00, 0xff], InputEncoding::Hex))
+        );
+        assert_eq!(
+            InputEncoding::Auto.decode("aGVsbG8="),
+            Some((b"hello".to_vec(), InputEncoding::Base64))
+        );
+        assert_eq!(
+            InputEncoding::Auto.decode("not base64!"),
+            Some((b"not base64!".to_vec(), InputEncoding::Raw))
+        );
+        assert_eq!(InputEncoding::Hex.decode("xyz"), None);
+        assert_eq!("B64".parse(), Ok(InputEncoding::Base64));
+        assert!("rot13".parse::<InputEncoding>().is_err());
+        assert_eq!(
+            to_bits(&[0b1000_0001]),
+            [true, false, false, false, false, false, false, true]
+        );
+    }
+}
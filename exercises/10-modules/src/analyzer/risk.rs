@@ -1,6 +1,6 @@
 //! Risk classification for password strength scores.
 
-/// Coarse risk bucket derived from a numeric score.
+/// Coarse risk bucket derived from a numeric score or token findings.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RiskLevel {
     Critical,
@@ -31,6 +31,23 @@ impl ClassFlags {
     }
 }
 
+/// Size of the ASCII alphabet drawn from: 26 per letter case, 10 digits, 33 symbols.
+pub(crate) fn alphabet_size(secret: &str) -> usize {
+    let flags = secret
+        .chars()
+        .fold(ClassFlags::default(), ClassFlags::observe);
+    [
+        (ClassFlags::LOWER, 26),
+        (ClassFlags::UPPER, 26),
+        (ClassFlags::DIGIT, 10),
+        (ClassFlags::SYMBOL, 33),
+    ]
+    .iter()
+    .filter(|(bit, _)| flags.0 & bit != 0)
+    .map(|(_, size)| size)
+    .sum()
+}
+
 /// Scan `secret` and return whether at least two character classes appear.
 pub(crate) fn has_mixed_classes(secret: &str) -> bool {
     let flags = secret
@@ -74,6 +91,14 @@ mod tests {
         assert!(!has_mixed_classes("aaaa"));
     }
 
+    #[test]
+    fn alphabet_size_sums_classes() {
+        assert_eq!(alphabet_size(""), 0);
+        assert_eq!(alphabet_size("abc"), 26);
+        assert_eq!(alphabet_size("aB3"), 62);
+        assert_eq!(alphabet_size("aB3!"), 95);
+    }
+
     #[test]
     fn longer_mixed_secret_scores_higher() {
         let weak = compute_score("aaaa");
@@ -1,4 +1,4 @@
-//! Modules exercises — multi-file password analysis with visibility control.
+//! Modules exercises — multi-file password and token analysis with visibility control.
 //!
 //! # Module tree
 //!
@@ -8,12 +8,21 @@
 //!     ├── mod.rs       — Analysis, analyze
 //!     ├── score.rs     — pub(crate) compute_score
 //!     ├── risk.rs      — RiskLevel
-//!     └── validate.rs  — pub(crate) is_too_common
+//!     ├── validate.rs  — pub(crate) is_too_common
+//!     ├── token.rs     — TokenFormat, TokenFinding, analyze_token
+//!     ├── jwt.rs       — Jwt (decode without verifying)
+//!     ├── entropy.rs   — shannon_entropy, run_stats
+//!     ├── randomness.rs — NIST SP 800-22 subset, run_battery
+//!     └── encoding.rs  — pub(crate) hex/base64 decoding
 //! ```
 
 pub mod analyzer;
 
-pub use analyzer::{Analysis, RiskLevel, analyze};
+pub use analyzer::{
+    Analysis, InputEncoding, Jwt, JwtError, RandomnessReport, RiskLevel, TokenAnalysis,
+    TokenFinding, TokenFormat, VendorKey, Verdict, analyze, analyze_token, analyze_token_at,
+    run_battery,
+};
 
 pub type Result<T> = anyhow::Result<T>;
 
@@ -42,6 +51,9 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "pub vs pub(crate) visibility",
                 "Re-exported analyze from crate root",
                 "Score capping for common passwords",
+                "Token formats (JWT, PEM, UUID, vendor prefixes) via analyze_token",
+                "Entropy and run checks in analyzer::entropy",
+                "NIST SP 800-22 p-values via analyzer::randomness",
             ],
         },
         ExerciseInfo {
@@ -4,11 +4,20 @@
 //! cargo run -p exercise_modules
 //! cargo run -p exercise_modules -- list
 //! cargo run -p exercise_modules -- score --verbose
+//! echo "$GITHUB_TOKEN" | cargo run -p exercise_modules -- token
+//! openssl rand -hex 64 | cargo run -p exercise_modules -- randomness
 //! ```
 
-use anyhow::Result;
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
-use modules_exercises::{get_exercise_list, run_all, run_exercise};
+use modules_exercises::analyzer::randomness::DEFAULT_ALPHA;
+use modules_exercises::{
+    InputEncoding, RandomnessReport, TokenAnalysis, Verdict, analyze_token, get_exercise_list,
+    run_all, run_battery, run_exercise,
+};
 
 /// Modules exercises — multi-file password analysis
 #[derive(Parser, Debug)]
@@ -35,6 +44,25 @@ enum Commands {
     Score,
     /// Exercise 2: validate against denylist
     Validate,
+    /// Analyze an API token or key (read from stdin when omitted)
+    Token {
+        /// Token to analyze; prefer stdin so it stays out of shell history
+        token: Option<String>,
+    },
+    /// Run the NIST SP 800-22 randomness battery on a key (stdin when omitted)
+    Randomness {
+        /// Key material to test
+        input: Option<String>,
+        /// Input encoding: auto, hex, base64 or raw
+        #[arg(short, long, default_value = "auto")]
+        encoding: InputEncoding,
+        /// Read raw bytes from a file instead
+        #[arg(short, long, conflicts_with_all = ["input", "encoding"])]
+        file: Option<PathBuf>,
+        /// Significance level for the whole battery, in (0, 1)
+        #[arg(short, long, default_value_t = DEFAULT_ALPHA, value_parser = parse_alpha)]
+        alpha: f64,
+    },
 }
 
 fn main() -> Result<()> {
@@ -50,11 +78,120 @@ fn main() -> Result<()> {
         Some(Commands::List) => list_exercises(),
         Some(Commands::Score) => run_exercise("score", verbose)?,
         Some(Commands::Validate) => run_exercise("validate", verbose)?,
+        Some(Commands::Token { token }) => {
+            let token = arg_or_stdin(token)?;
+            if token.trim().is_empty() {
+                bail!("no token given");
+            }
+            print_token_report(&analyze_token(&token), verbose);
+        }
+        Some(Commands::Randomness {
+            input,
+            encoding,
+            file,
+            alpha,
+        }) => {
+            let (bytes, used) = if let Some(path) = file {
+                let bytes =
+                    std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+                (bytes, InputEncoding::Raw)
+            } else {
+                let text = arg_or_stdin(input)?;
+                encoding
+                    .decode(&text)
+                    .with_context(|| format!("input is not valid {encoding}"))?
+            };
+            let report = run_battery(&bytes, alpha);
+            print_randomness_report(&report, used, verbose);
+            if report.verdict() == Verdict::NonRandom {
+                bail!(
+                    "{} of {} tests below alpha/{}={:.6}",
+                    report.failures().count(),
+                    report.results.len(),
+                    report.results.len(),
+                    report.threshold()
+                );
+            }
+        }
     }
 
     Ok(())
 }
 
+/// A significance level strictly between 0 and 1.
+fn parse_alpha(text: &str) -> Result<f64, String> {
+    let alpha: f64 = text.parse().map_err(|e| format!("{e}"))?;
+    if alpha > 0.0 && alpha < 1.0 {
+        Ok(alpha)
+    } else {
+        Err(format!("{alpha} is not between 0 and 1 (exclusive)"))
+    }
+}
+
+/// The positional argument, or all of stdin when it was omitted. One
+/// trailing line ending is dropped from stdin, since `echo` and editors add
+/// it and it is not part of the key.
+fn arg_or_stdin(arg: Option<String>) -> Result<String> {
+    if let Some(arg) = arg {
+        return Ok(arg);
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(strip_line_ending(input))
+}
+
+fn strip_line_ending(mut text: String) -> String {
+    if text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    text
+}
+
+fn print_randomness_report(report: &RandomnessReport, encoding: InputEncoding, verbose: bool) {
+    println!(
+        "encoding={encoding} bits={} alpha={} per-test={:.6}",
+        report.bits,
+        report.alpha,
+        report.threshold()
+    );
+    for result in &report.results {
+        let mark = if result.passed(report.threshold()) {
+            "pass"
+        } else {
+            "FAIL"
+        };
+        print!(
+            "  [{mark}] {:<32} p={:.6}",
+            result.test.to_string(),
+            result.p_value
+        );
+        if verbose {
+            print!("  statistic={:.4}", result.statistic);
+        }
+        println!();
+    }
+    for (name, min_bits) in &report.skipped {
+        println!("  [skip] {name} (needs {min_bits} bits)");
+    }
+    println!("verdict: {}", report.verdict());
+}
+
+fn print_token_report(analysis: &TokenAnalysis, verbose: bool) {
+    println!("{}", analysis.summary());
+    for finding in &analysis.findings {
+        println!("  - [{}] {finding}", finding.severity().as_str());
+    }
+    if verbose && let Some(runs) = analysis.runs {
+        println!(
+            "  longest repeat={} sequence={}",
+            runs.longest_repeat, runs.longest_sequence
+        );
+    }
+}
+
 fn list_exercises() {
     println!("Available exercises:\n");
     for info in get_exercise_list() {
@@ -69,6 +206,10 @@ fn list_exercises() {
     println!("  cargo run -p exercise_modules -- list");
     println!("  cargo run -p exercise_modules -- score [--verbose]");
     println!("  cargo run -p exercise_modules -- validate [--verbose]");
+    println!("  cargo run -p exercise_modules -- token [TOKEN] [--verbose]");
+    println!(
+        "  cargo run -p exercise_modules -- randomness [INPUT | --file PATH] [--encoding auto|hex|base64|raw] [--alpha 0.01]"
+    );
     println!("  cargo run -p exercise_modules -- all [--verbose]");
 }
 
@@ -82,6 +223,70 @@ mod tests {
         Cli::command().debug_assert();
     }
 
+    #[test]
+    fn token_command_parses_and_reports() {
+        let cli = Cli::try_parse_from(["exercise_modules", "token", "ghp_abc", "-v"]);
+        assert!(matches!(
+            cli,
+            Ok(Cli {
+                verbose: true,
+                command: Some(Commands::Token { token: Some(_) }),
+            })
+        ));
+        print_token_report(&analyze_token("sk_live_aaaaaaaaaaaaaaaa"), true);
+    }
+
+    #[test]
+    fn randomness_command_parses_and_reports() {
+        let cli = Cli::try_parse_from(["exercise_modules", "randomness", "-e", "hex", "00ff"]);
+        assert!(matches!(
+            cli,
+            Ok(Cli {
+                command: Some(Commands::Randomness {
+                    encoding: InputEncoding::Hex,
+                    file: None,
+                    ..
+                }),
+                ..
+            })
+        ));
+        let both = [
+            "exercise_modules",
+            "randomness",
+            "00ff",
+            "--file",
+            "key.bin",
+        ];
+        assert!(Cli::try_parse_from(both).is_err());
+        let both = [
+            "exercise_modules",
+            "randomness",
+            "-e",
+            "hex",
+            "--file",
+            "key.bin",
+        ];
+        assert!(Cli::try_parse_from(both).is_err());
+        assert!(
+            Cli::try_parse_from(["exercise_modules", "randomness", "--file", "key.bin"]).is_ok()
+        );
+        let bad = ["exercise_modules", "randomness", "-e", "rot13", "00"];
+        assert!(Cli::try_parse_from(bad).is_err());
+        print_randomness_report(&run_battery(&[0u8; 32], 0.01), InputEncoding::Hex, true);
+    }
+
+    #[test]
+    fn stdin_line_ending_is_not_key_material() {
+        assert_eq!(strip_line_ending("s3cret\r\n".into()), "s3cret");
+        assert_eq!(strip_line_ending("s3cret\n".into()), "s3cret");
+        assert_eq!(strip_line_ending("s3cret\n\n".into()), "s3cret\n");
+        assert_eq!(strip_line_ending("s3cret ".into()), "s3cret ");
+        assert_eq!(
+            InputEncoding::Auto.decode(&strip_line_ending("not a key!\n".into())),
+            Some((b"not a key!".to_vec(), InputEncoding::Raw))
+        );
+    }
+
     #[test]
     fn list_runs() {
         list_exercises();
@@ -2,7 +2,13 @@
 //!
 //! Uses the **public API only** — no direct access to `pub(crate)` helpers.
 
-use modules_exercises::{RiskLevel, analyze, get_exercise_list, risk_label, run_all, run_exercise};
+#![allow(clippy::expect_used)]
+
+use modules_exercises::analyzer::randomness::{self, DEFAULT_ALPHA, RandomnessTest};
+use modules_exercises::{
+    InputEncoding, Jwt, RiskLevel, TokenFinding, TokenFormat, Verdict, analyze, analyze_token,
+    analyze_token_at, get_exercise_list, risk_label, run_all, run_battery, run_exercise,
+};
 
 #[test]
 fn exercise_list_has_two_entries() {
@@ -55,3 +61,80 @@ fn risk_label_tracks_risk_level() {
     let low = analyze("Z9x!Correct-Horse-Battery");
     assert_eq!(risk_label(&low), low.risk.as_str());
 }
+
+#[test]
+fn token_path_differs_from_password_path() {
+    // Four character classes and 24 characters: a perfect password score, but
+    // as a key it is mostly one repeated symbol.
+    let repetitive = "Aa1!aaaaaaaaaaaaaaaaaaaa";
+    assert_eq!(analyze(repetitive).risk, RiskLevel::Low);
+    let token = analyze_token(repetitive);
+    assert_eq!(token.format, TokenFormat::Unknown);
+    assert!(
+        token
+            .findings
+            .contains(&TokenFinding::RepeatedRun { len: 20 })
+    );
+    assert_eq!(token.risk, RiskLevel::High);
+
+    let key = "9c4e1f7a2b8d03e6a5f19c7b4d2e80f3a6c1b5d8";
+    let token = analyze_token(key);
+    assert_eq!(token.format, TokenFormat::Hex { bytes: 20 });
+    assert_eq!(token.estimated_bits, Some(160));
+    assert_eq!(token.risk, RiskLevel::Low);
+}
+
+#[test]
+fn jwt_claims_are_inspected_without_verification() {
+    // {"alg":"none","typ":"JWT"} . {"sub":"admin","exp":1700000000} . (empty)
+    let token = "eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.eyJzdWIiOiJhZG1pbiIsImV4cCI6MTcwMDAwMDAwMH0.";
+    let jwt = Jwt::decode(token);
+    assert!(jwt.as_ref().is_ok_and(|j| j.subject() == Some("admin")));
+    assert!(jwt.is_ok_and(|j| j.expires_at() == Some(1_700_000_000)));
+
+    let before = analyze_token_at(token, 1_600_000_000);
+    assert_eq!(before.findings, [TokenFinding::UnsignedJwt]);
+    let after = analyze_token_at(token, 1_800_000_000);
+    assert_eq!(
+        after.findings,
+        [
+            TokenFinding::UnsignedJwt,
+            TokenFinding::ExpiredJwt {
+                expired_at: 1_700_000_000
+            }
+        ]
+    );
+    assert_eq!(after.risk, RiskLevel::Critical);
+    assert_eq!(risk_label(&analyze("x")), "critical");
+}
+
+#[test]
+fn randomness_battery_over_encoded_keys() {
+    let hex = "3f1c5e0a9b7d24e68c13f0a5d9e2b7c4618a0f3e5d27c9b1a4e6f08d3c5b72a9";
+    let decoded = InputEncoding::Auto.decode(hex);
+    assert!(matches!(decoded, Some((_, InputEncoding::Hex))));
+    let Some((bytes, _)) = decoded else { return };
+    let report = run_battery(&bytes, DEFAULT_ALPHA);
+    assert_eq!(report.bits, 256);
+    assert_eq!(report.results.len(), 7);
+    assert!(report.skipped.is_empty());
+    assert_eq!(report.verdict(), Verdict::Random);
+
+    // The same key in base64 gives the same bits.
+    let b64 = InputEncoding::Base64.decode("PxxeCpt9JOaME/Cl2eK3xGGKDz5dJ8mxpObwjTxbcqk=");
+    assert_eq!(b64.map(|(b, _)| b), Some(bytes));
+
+    // A repeating pattern shows up in the serial test.
+    let repeated = "c0ffeec0ffeec0ffeec0ffeec0ffeec0ffeec0ffeec0ffeec0ffeec0ffeec0ff";
+    let (bytes, _) = InputEncoding::Hex.decode(repeated).expect("valid hex");
+    let report = run_battery(&bytes, DEFAULT_ALPHA);
+    assert_eq!(report.verdict(), Verdict::NonRandom);
+    assert!(
+        report
+            .failures()
+            .any(|r| matches!(r.test, RandomnessTest::Serial { .. }))
+    );
+
+    let bits = randomness::to_bits(&[0xff; 16]);
+    assert!(randomness::frequency(&bits).p_value < DEFAULT_ALPHA);
+}
@@ -2,18 +2,16 @@
 
 use anyhow::Result;
 
-use crate::intrusion::sample_log;
+use crate::intrusion::{SAMPLE_START, sample_log};
 
-/// Run the analysis exercise: IP counts, unique users, actions, hot IPs.
+/// Run the analysis exercise: IP counts, unique users, actions, hot IPs, windows.
 pub fn run(verbose: bool) -> Result<()> {
     println!("📊 Analysis — HashMap & HashSet aggregation");
     println!();
 
     let log = sample_log();
     let ip_counts = log.ip_counts();
-    let hot = log.hot_ips();
 
-    println!("  ip_counts == hot_ips: {}", ip_counts == hot);
     println!("  unique users: {}", log.unique_users().len());
 
     let mut sorted_ips: Vec<_> = ip_counts.into_iter().collect();
@@ -28,10 +26,20 @@ pub fn run(verbose: bool) -> Result<()> {
     actions.sort();
     println!("  distinct actions: {}", actions.join(", "));
 
+    let mut hot: Vec<_> = log.hot_ips(60, 3).into_iter().collect();
+    hot.sort();
+    println!("  hot IPs (≥3 events in 60s): {hot:?}");
+
+    println!("  tumbling 60s windows:");
+    for (start, events) in log.tumbling_windows(60) {
+        let offset = i128::from(start) - i128::from(SAMPLE_START);
+        println!("    t{offset:+}s  {} event(s)", events.len());
+    }
+
     if verbose {
         println!();
-        println!("  hot_ips is an alias for ip_counts — same IP→count map,");
-        println!("  named for SOC dashboards tracking repeat offenders.");
+        println!("  ip_counts counts over all time; hot_ips only counts bursts,");
+        println!("  so a slow trickle from one IP doesn't light up the dashboard.");
     }
 
     Ok(())
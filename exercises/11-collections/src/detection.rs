@@ -0,0 +1,608 @@
+//! Windowed attack detection over an [`IntrusionLog`].
+//!
+//! Every detector runs the same scan: failed events are grouped by a key
+//! (source IP or user). Each group keeps a `VecDeque` window that is trimmed
+//! as time moves on, plus per-value counts of a second field that are updated
+//! as events enter and leave, so checking a window costs O(1) per event. A
+//! predicate decides when the window trips. A tripped window becomes an
+//! [`Alert`] carrying copies of its events. The key then stays quiet until
+//! its window empties, so one burst raises one alert however long it lasts.
+//! Keys whose windows empty are dropped, so memory follows the active keys.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::intrusion::{IntrusionLog, LogEvent};
+
+/// How events are grouped in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// Any run of events less than `width` seconds apart, end to end.
+    Sliding { width: u64 },
+    /// Fixed, non-overlapping buckets of `width` seconds aligned to the epoch.
+    Tumbling { width: u64 },
+}
+
+impl Window {
+    /// Window length in seconds (at least 1).
+    #[must_use]
+    pub fn width(self) -> u64 {
+        match self {
+            Self::Sliding { width } | Self::Tumbling { width } => width.max(1),
+        }
+    }
+
+    /// Whether `next` no longer shares a window with the earlier `oldest`.
+    ///
+    /// Events arrive in timestamp order, so for a tumbling window every event
+    /// in the old bucket expires together.
+    fn expired(self, oldest: &LogEvent, next: &LogEvent) -> bool {
+        let width = self.width();
+        match self {
+            Self::Sliding { .. } => oldest.timestamp.saturating_add(width) <= next.timestamp,
+            Self::Tumbling { .. } => oldest.timestamp / width != next.timestamp / width,
+        }
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sliding { .. } => write!(f, "sliding {}s", self.width()),
+            Self::Tumbling { .. } => write!(f, "tumbling {}s", self.width()),
+        }
+    }
+}
+
+/// Thresholds for [`detect`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectorConfig {
+    /// Window shared by all detectors.
+    pub window: Window,
+    /// Actions that count as a failed attempt.
+    pub failure_actions: HashSet<String>,
+    /// Failures from one IP that make a brute-force alert.
+    pub ip_failures: usize,
+    /// Failures against one user that make a brute-force alert.
+    pub user_failures: usize,
+    /// Distinct users one IP must try to count as spraying.
+    pub spray_min_users: usize,
+    /// Most attempts per user that still look like spraying, not brute force.
+    pub spray_max_per_user: usize,
+    /// Distinct IPs failing one user that make a stuffing alert.
+    pub stuffing_min_ips: usize,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            window: Window::Sliding { width: 60 },
+            failure_actions: HashSet::from(["login_fail".to_string()]),
+            ip_failures: 5,
+            user_failures: 5,
+            spray_min_users: 5,
+            spray_max_per_user: 2,
+            stuffing_min_ips: 5,
+        }
+    }
+}
+
+/// A detection, with the events that triggered it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Alert {
+    /// Many failures from one source IP.
+    IpBruteForce { ip: String, events: Vec<LogEvent> },
+    /// Many failures against one user.
+    UserBruteForce { user: String, events: Vec<LogEvent> },
+    /// One IP trying many users a few times each.
+    PasswordSpraying {
+        ip: String,
+        users: BTreeSet<String>,
+        events: Vec<LogEvent>,
+    },
+    /// Many IPs failing the same user.
+    CredentialStuffing {
+        user: String,
+        ips: BTreeSet<String>,
+        events: Vec<LogEvent>,
+    },
+}
+
+impl Alert {
+    /// Short machine-friendly name.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::IpBruteForce { .. } => "ip_brute_force",
+            Self::UserBruteForce { .. } => "user_brute_force",
+            Self::PasswordSpraying { .. } => "password_spraying",
+            Self::CredentialStuffing { .. } => "credential_stuffing",
+        }
+    }
+
+    /// The events that contributed to this alert, in timestamp order.
+    #[must_use]
+    pub fn events(&self) -> &[LogEvent] {
+        match self {
+            Self::IpBruteForce { events, .. }
+            | Self::UserBruteForce { events, .. }
+            | Self::PasswordSpraying { events, .. }
+            | Self::CredentialStuffing { events, .. } => events,
+        }
+    }
+
+    /// Timestamp of the first contributing event.
+    #[must_use]
+    pub fn first_seen(&self) -> u64 {
+        self.events().first().map_or(0, |e| e.timestamp)
+    }
+
+    /// Timestamp of the event that tripped the detector.
+    #[must_use]
+    pub fn last_seen(&self) -> u64 {
+        self.events().last().map_or(0, |e| e.timestamp)
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.events().len();
+        let span = self.last_seen() - self.first_seen();
+        match self {
+            Self::IpBruteForce { ip, .. } => {
+                write!(f, "{}: {count} failures from {ip} in {span}s", self.kind())
+            }
+            Self::UserBruteForce { user, .. } => {
+                write!(f, "{}: {count} failures for {user} in {span}s", self.kind())
+            }
+            Self::PasswordSpraying { ip, users, .. } => write!(
+                f,
+                "{}: {ip} tried {} users in {span}s",
+                self.kind(),
+                users.len()
+            ),
+            Self::CredentialStuffing { user, ips, .. } => write!(
+                f,
+                "{}: {user} failed from {} IPs in {span}s",
+                self.kind(),
+                ips.len()
+            ),
+        }
+    }
+}
+
+/// One key's window, with a running count per value of its tallied field.
+struct Group<'a> {
+    events: VecDeque<&'a LogEvent>,
+    field: fn(&LogEvent) -> &str,
+    counts: HashMap<&'a str, usize>,
+    /// Values seen at least once but no more than `light_limit` times.
+    light: usize,
+    light_limit: usize,
+    /// Already alerted for the burst now in the window.
+    alerted: bool,
+}
+
+impl<'a> Group<'a> {
+    fn new(field: fn(&LogEvent) -> &str, light_limit: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            field,
+            counts: HashMap::new(),
+            light: 0,
+            light_limit,
+            alerted: false,
+        }
+    }
+
+    fn push(&mut self, event: &'a LogEvent) {
+        let count = self.counts.entry((self.field)(event)).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            self.light += 1;
+        } else if *count == self.light_limit.saturating_add(1) {
+            self.light -= 1;
+        }
+        self.events.push_back(event);
+    }
+
+    fn pop_front(&mut self) {
+        let Some(event) = self.events.pop_front() else {
+            return;
+        };
+        let value = (self.field)(event);
+        let Some(count) = self.counts.get_mut(value) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.light -= 1;
+            self.counts.remove(value);
+        } else if *count == self.light_limit {
+            self.light += 1;
+        }
+    }
+
+    /// Drop events that no longer share a window with `next`.
+    fn trim(&mut self, window: Window, next: &LogEvent) {
+        while self
+            .events
+            .front()
+            .is_some_and(|oldest| window.expired(oldest, next))
+        {
+            self.pop_front();
+        }
+        if self.events.is_empty() {
+            self.alerted = false;
+        }
+    }
+
+    /// Whether every event would be trimmed by `next`.
+    fn is_stale(&self, window: Window, next: &LogEvent) -> bool {
+        self.events
+            .back()
+            .is_none_or(|newest| window.expired(newest, next))
+    }
+
+    /// Distinct values of the tallied field in the window.
+    fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+/// Every key's window, plus when stale keys are next swept.
+struct Windows<'a, K> {
+    window: Window,
+    tally: (fn(&LogEvent) -> &str, usize),
+    groups: HashMap<K, Group<'a>>,
+    next_sweep: u64,
+}
+
+impl<'a, K: Eq + Hash + Clone> Windows<'a, K> {
+    fn new(window: Window, tally: (fn(&LogEvent) -> &str, usize)) -> Self {
+        Self {
+            window,
+            tally,
+            groups: HashMap::new(),
+            next_sweep: 0,
+        }
+    }
+
+    /// Add `event` to `key`'s window and return a copy of the window if it
+    /// trips for the first time since it was last empty.
+    ///
+    /// Once per window width, keys with nothing left in their windows are
+    /// removed, so the map only holds keys seen recently.
+    fn observe(
+        &mut self,
+        key: K,
+        event: &'a LogEvent,
+        trips: impl Fn(&Group<'a>) -> bool,
+    ) -> Option<Vec<LogEvent>> {
+        let window = self.window;
+        if event.timestamp >= self.next_sweep {
+            self.groups
+                .retain(|_, group| !group.is_stale(window, event));
+            self.next_sweep = event.timestamp.saturating_add(window.width());
+        }
+        let (field, light_limit) = self.tally;
+        let group = self
+            .groups
+            .entry(key)
+            .or_insert_with(|| Group::new(field, light_limit));
+        group.trim(window, event);
+        group.push(event);
+        if group.alerted || !trips(group) {
+            return None;
+        }
+        group.alerted = true;
+        Some(group.events.iter().map(|&e| e.clone()).collect())
+    }
+}
+
+/// Group `events` by `key`, tallying `field` within each window, and return
+/// every window that trips `trips`.
+///
+/// A tripped window is emitted once. Its key trips again only after its
+/// window has emptied.
+fn scan<'a, K, F, P>(
+    events: &[&'a LogEvent],
+    window: Window,
+    key: F,
+    tally: (fn(&LogEvent) -> &str, usize),
+    trips: P,
+) -> Vec<(K, Vec<LogEvent>)>
+where
+    K: Eq + Hash + Clone,
+    F: Fn(&'a LogEvent) -> K,
+    P: Fn(&Group<'a>) -> bool,
+{
+    let mut windows = Windows::new(window, tally);
+    events
+        .iter()
+        .filter_map(|&event| {
+            let k = key(event);
+            windows
+                .observe(k.clone(), event, &trips)
+                .map(|events| (k, events))
+        })
+        .collect()
+}
+
+/// Tally for detectors that only look at the window length.
+const NO_TALLY: (fn(&LogEvent) -> &str, usize) = (|e| &e.user, usize::MAX);
+
+/// Distinct values of `field` across `events`.
+fn distinct<'a>(
+    events: impl IntoIterator<Item = &'a LogEvent>,
+    field: fn(&LogEvent) -> &str,
+) -> BTreeSet<String> {
+    events.into_iter().map(|e| field(e).to_string()).collect()
+}
+
+/// Failures from one IP reaching `config.ip_failures` in a window.
+#[must_use]
+pub fn detect_ip_brute_force(log: &IntrusionLog, config: &DetectorConfig) -> Vec<Alert> {
+    let failures = failures(log, config);
+    scan(
+        &failures,
+        config.window,
+        |e| e.ip.as_str(),
+        NO_TALLY,
+        |w| w.events.len() >= config.ip_failures,
+    )
+    .into_iter()
+    .map(|(ip, events)| Alert::IpBruteForce {
+        ip: ip.to_string(),
+        events,
+    })
+    .collect()
+}
+
+/// Failures against one user reaching `config.user_failures` in a window.
+#[must_use]
+pub fn detect_user_brute_force(log: &IntrusionLog, config: &DetectorConfig) -> Vec<Alert> {
+    let failures = failures(log, config);
+    scan(
+        &failures,
+        config.window,
+        |e| e.user.as_str(),
+        NO_TALLY,
+        |w| w.events.len() >= config.user_failures,
+    )
+    .into_iter()
+    .map(|(user, events)| Alert::UserBruteForce {
+        user: user.to_string(),
+        events,
+    })
+    .collect()
+}
+
+/// One IP failing at least `spray_min_users` users in a window, each tried
+/// no more than `spray_max_per_user` times.
+///
+/// Users tried more often don't count toward the total, but they don't hide
+/// the others either: hammering a decoy account does not mask a spray.
+#[must_use]
+pub fn detect_password_spraying(log: &IntrusionLog, config: &DetectorConfig) -> Vec<Alert> {
+    let failures = failures(log, config);
+    scan(
+        &failures,
+        config.window,
+        |e| e.ip.as_str(),
+        (|e| &e.user, config.spray_max_per_user),
+        |w| w.light >= config.spray_min_users,
+    )
+    .into_iter()
+    .map(|(ip, events)| Alert::PasswordSpraying {
+        ip: ip.to_string(),
+        users: distinct(&events, |e| &e.user),
+        events,
+    })
+    .collect()
+}
+
+/// One user failed from at least `stuffing_min_ips` distinct IPs in a window.
+#[must_use]
+pub fn detect_credential_stuffing(log: &IntrusionLog, config: &DetectorConfig) -> Vec<Alert> {
+    let failures = failures(log, config);
+    scan(
+        &failures,
+        config.window,
+        |e| e.user.as_str(),
+        (|e| &e.ip, usize::MAX),
+        |w| w.distinct() >= config.stuffing_min_ips,
+    )
+    .into_iter()
+    .map(|(user, events)| Alert::CredentialStuffing {
+        user: user.to_string(),
+        ips: distinct(&events, |e| &e.ip),
+        events,
+    })
+    .collect()
+}
+
+/// Run every detector and return alerts ordered by when they fired.
+///
+/// Detectors overlap on purpose: a spraying IP may also trip the per-IP
+/// brute-force threshold, and both alerts are reported.
+#[must_use]
+pub fn detect(log: &IntrusionLog, config: &DetectorConfig) -> Vec<Alert> {
+    let mut alerts = detect_ip_brute_force(log, config);
+    alerts.extend(detect_user_brute_force(log, config));
+    alerts.extend(detect_password_spraying(log, config));
+    alerts.extend(detect_credential_stuffing(log, config));
+    alerts.sort_by_key(|a| (a.last_seen(), a.kind()));
+    alerts
+}
+
+/// Events whose action is listed in `config.failure_actions`.
+fn failures<'a>(log: &'a IntrusionLog, config: &DetectorConfig) -> Vec<&'a LogEvent> {
+    log.events()
+        .iter()
+        .filter(|e| config.failure_actions.contains(&e.action))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intrusion::{SAMPLE_START, sample_attack_log, sample_log};
+
+    fn kinds(alerts: &[Alert]) -> Vec<&'static str> {
+        alerts.iter().map(Alert::kind).collect()
+    }
+
+    #[test]
+    fn attack_log_raises_one_alert_per_attack() {
+        let alerts = detect(&sample_attack_log(), &DetectorConfig::default());
+        assert_eq!(
+            kinds(&alerts),
+            [
+                "ip_brute_force",
+                "user_brute_force",
+                "ip_brute_force",
+                "password_spraying",
+                "credential_stuffing",
+                "user_brute_force",
+            ]
+        );
+    }
+
+    #[test]
+    fn brute_force_carries_its_events() {
+        let alerts = detect_ip_brute_force(&sample_attack_log(), &DetectorConfig::default());
+        assert!(matches!(alerts.first(), Some(Alert::IpBruteForce { .. })));
+        let Some(Alert::IpBruteForce { ip, events }) = alerts.first() else {
+            return;
+        };
+        assert_eq!(ip, "203.0.113.66");
+        // Cleared after tripping at the fifth failure, so the sixth starts over.
+        assert_eq!(events.len(), 5);
+        assert!(events.iter().all(|e| e.user == "root"));
+        assert_eq!(alerts[0].first_seen(), SAMPLE_START);
+        assert_eq!(alerts[0].last_seen(), SAMPLE_START + 20);
+    }
+
+    #[test]
+    fn spraying_and_stuffing_name_their_targets() {
+        let config = DetectorConfig::default();
+        let log = sample_attack_log();
+        let spray = detect_password_spraying(&log, &config);
+        assert!(matches!(
+            spray.as_slice(),
+            [Alert::PasswordSpraying { ip, users, .. }] if ip == "198.51.100.23" && users.len() == 5
+        ));
+        let stuffing = detect_credential_stuffing(&log, &config);
+        assert!(matches!(
+            stuffing.as_slice(),
+            [Alert::CredentialStuffing { user, ips, .. }] if user == "alice" && ips.len() == 5
+        ));
+    }
+
+    #[test]
+    fn hammered_users_do_not_count_toward_spraying() {
+        let mut log = IntrusionLog::new();
+        for (t, user) in (0..).zip(["a", "b", "c", "d", "d", "d", "e"]) {
+            log.push(LogEvent::at(t, "10.1.1.1", user, "login_fail"));
+        }
+        let config = DetectorConfig::default();
+        assert!(detect_password_spraying(&log, &config).is_empty());
+        let lenient = DetectorConfig {
+            spray_max_per_user: 3,
+            ..config.clone()
+        };
+        assert_eq!(detect_password_spraying(&log, &lenient).len(), 1);
+
+        // A decoy hit three times doesn't hide five users tried once each.
+        let mut log = IntrusionLog::new();
+        for (t, user) in (0..).zip(["decoy", "decoy", "decoy", "a", "b", "c", "d", "e"]) {
+            log.push(LogEvent::at(t, "10.1.1.1", user, "login_fail"));
+        }
+        let spray = detect_password_spraying(&log, &config);
+        assert!(matches!(
+            spray.as_slice(),
+            [Alert::PasswordSpraying { users, .. }] if users.len() == 6
+        ));
+    }
+
+    #[test]
+    fn counts_follow_events_out_of_the_window() {
+        let mut log = IntrusionLog::new();
+        // Five users, but never more than four inside one 60 s window.
+        for (t, user) in [(0, "a"), (20, "b"), (40, "c"), (55, "d"), (61, "e")] {
+            log.push(LogEvent::at(t, "10.1.1.1", user, "login_fail"));
+            log.push(LogEvent::at(
+                t,
+                format!("10.2.2.{t}"),
+                "alice",
+                "login_fail",
+            ));
+        }
+        let config = DetectorConfig::default();
+        assert!(detect_password_spraying(&log, &config).is_empty());
+        assert!(detect_credential_stuffing(&log, &config).is_empty());
+    }
+
+    #[test]
+    fn sliding_and_tumbling_windows_differ_at_boundaries() {
+        let mut log = IntrusionLog::new();
+        // Three failures straddling a minute boundary, 10 s apart.
+        for t in [50, 60, 70] {
+            log.push(LogEvent::at(t, "10.9.9.9", "root", "login_fail"));
+        }
+        let sliding = DetectorConfig {
+            ip_failures: 3,
+            ..DetectorConfig::default()
+        };
+        let tumbling = DetectorConfig {
+            window: Window::Tumbling { width: 60 },
+            ..sliding.clone()
+        };
+        assert_eq!(detect_ip_brute_force(&log, &sliding).len(), 1);
+        assert!(detect_ip_brute_force(&log, &tumbling).is_empty());
+        assert_eq!(tumbling.window.to_string(), "tumbling 60s");
+    }
+
+    #[test]
+    fn quiet_log_raises_nothing() {
+        assert!(detect(&sample_log(), &DetectorConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn sustained_burst_raises_one_alert_until_it_goes_quiet() {
+        let mut log = IntrusionLog::new();
+        // 100 failures a second apart, then a 60 s pause, then another burst.
+        for t in (0..100).chain(160..165) {
+            log.push(LogEvent::at(t, "10.7.7.7", "root", "login_fail"));
+        }
+        let alerts = detect_ip_brute_force(&log, &DetectorConfig::default());
+        let starts: Vec<u64> = alerts.iter().map(Alert::first_seen).collect();
+        assert_eq!(starts, [0, 160]);
+        assert_eq!(alerts[0].events().len(), 5);
+    }
+
+    #[test]
+    fn idle_keys_are_dropped() {
+        let events: Vec<LogEvent> = (0..1_000u64)
+            .map(|i| {
+                LogEvent::at(
+                    i,
+                    format!("10.0.{}.{}", i / 256, i % 256),
+                    "u",
+                    "login_fail",
+                )
+            })
+            .collect();
+        let mut windows = Windows::new(Window::Sliding { width: 60 }, NO_TALLY);
+        for event in &events {
+            assert!(
+                windows
+                    .observe(event.ip.as_str(), event, |_| false)
+                    .is_none()
+            );
+            assert!(windows.groups.len() <= 2 * 60);
+        }
+    }
+}
@@ -1,19 +1,37 @@
 //! Core intrusion log types and collection-based aggregation.
+//!
+//! The log keeps events in timestamp order, so a time range is a contiguous
+//! slice found by binary search. Windowed views build on that:
+//! [`IntrusionLog::tumbling_windows`] and [`IntrusionLog::hot_ips`] here, and
+//! the detectors in [`crate::detection`].
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 /// A single intrusion / auth-related log event.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LogEvent {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
     pub ip: String,
     pub user: String,
     pub action: String,
 }
 
 impl LogEvent {
-    /// Build an event from owned or borrowed string-like values.
+    /// Build an untimed event (timestamp 0) from owned or borrowed string-like values.
     pub fn new(ip: impl Into<String>, user: impl Into<String>, action: impl Into<String>) -> Self {
+        Self::at(0, ip, user, action)
+    }
+
+    /// Build an event observed at `timestamp` (Unix seconds).
+    pub fn at(
+        timestamp: u64,
+        ip: impl Into<String>,
+        user: impl Into<String>,
+        action: impl Into<String>,
+    ) -> Self {
         Self {
+            timestamp,
             ip: ip.into(),
             user: user.into(),
             action: action.into(),
@@ -34,9 +52,15 @@ impl IntrusionLog {
         Self { events: Vec::new() }
     }
 
-    /// Append an event (takes ownership of `event`).
+    /// Insert an event in timestamp order (takes ownership of `event`).
+    ///
+    /// Events with equal timestamps keep their arrival order. In-order
+    /// appends, the common case, go straight to the end.
     pub fn push(&mut self, event: LogEvent) {
-        self.events.push(event);
+        let index = self
+            .events
+            .partition_point(|e| e.timestamp <= event.timestamp);
+        self.events.insert(index, event);
     }
 
     /// Number of stored events.
@@ -51,21 +75,46 @@ impl IntrusionLog {
         self.events.is_empty()
     }
 
-    /// Borrow the underlying event slice.
+    /// Borrow the underlying event slice, in timestamp order.
     #[must_use]
     pub fn events(&self) -> &[LogEvent] {
         &self.events
     }
 
-    /// Count how many times each source IP appears.
+    /// Events with `start <= timestamp < end`.
+    #[must_use]
+    pub fn events_between(&self, start: u64, end: u64) -> &[LogEvent] {
+        let from = self.events.partition_point(|e| e.timestamp < start);
+        let to = self.events.partition_point(|e| e.timestamp < end);
+        &self.events[from..to.max(from)]
+    }
+
+    /// Count how many times each source IP appears, over all time.
     #[must_use]
     pub fn ip_counts(&self) -> HashMap<String, u32> {
-        let mut counts: HashMap<String, u32> = HashMap::new();
-        for event in &self.events {
-            let counter = counts.entry(event.ip.clone()).or_insert(0);
-            *counter = counter.saturating_add(1);
+        count_ips(&self.events)
+    }
+
+    /// [`Self::ip_counts`] restricted to `start <= timestamp < end`.
+    #[must_use]
+    pub fn ip_counts_between(&self, start: u64, end: u64) -> HashMap<String, u32> {
+        count_ips(self.events_between(start, end))
+    }
+
+    /// Split the log into fixed, non-overlapping windows of `width` seconds,
+    /// keyed by window start. Empty windows are omitted.
+    #[must_use]
+    pub fn tumbling_windows(&self, width: u64) -> BTreeMap<u64, &[LogEvent]> {
+        let width = width.max(1);
+        let mut windows = BTreeMap::new();
+        let mut rest = self.events.as_slice();
+        while let Some(first) = rest.first() {
+            let start = first.timestamp - first.timestamp % width;
+            let len = rest.partition_point(|e| e.timestamp < start.saturating_add(width));
+            windows.insert(start, &rest[..len]);
+            rest = &rest[len..];
         }
-        counts
+        windows
     }
 
     /// Unique usernames observed in the log (`HashSet` deduplicates).
@@ -88,13 +137,32 @@ impl IntrusionLog {
         actions
     }
 
-    /// IP → hit count map.
+    /// IPs that reached `threshold` events inside some sliding window of
+    /// `width` seconds, mapped to their peak count in any such window.
     ///
-    /// Semantically identical to [`Self::ip_counts`]; provided as a SOC analyst
-    /// alias for “hot” repeat-offender tracking dashboards.
+    /// Unlike [`Self::ip_counts`], a slow trickle spread over a day doesn't
+    /// make an IP "hot". A burst does.
     #[must_use]
-    pub fn hot_ips(&self) -> HashMap<String, u32> {
-        self.ip_counts()
+    pub fn hot_ips(&self, width: u64, threshold: u32) -> HashMap<String, u32> {
+        let width = width.max(1);
+        let mut windows: HashMap<&str, VecDeque<u64>> = HashMap::new();
+        let mut peaks: HashMap<String, u32> = HashMap::new();
+        for event in &self.events {
+            let window = windows.entry(event.ip.as_str()).or_default();
+            window.push_back(event.timestamp);
+            while window
+                .front()
+                .is_some_and(|&t| t.saturating_add(width) <= event.timestamp)
+            {
+                window.pop_front();
+            }
+            let count = u32::try_from(window.len()).unwrap_or(u32::MAX);
+            if count >= threshold {
+                let peak = peaks.entry(event.ip.clone()).or_insert(0);
+                *peak = (*peak).max(count);
+            }
+        }
+        peaks
     }
 
     /// Events whose action equals `action` (case-sensitive).
@@ -140,15 +208,89 @@ impl IntrusionLog {
     }
 }
 
+/// Count events per source IP.
+fn count_ips(events: &[LogEvent]) -> HashMap<String, u32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    for event in events {
+        let counter = counts.entry(event.ip.clone()).or_insert(0);
+        *counter = counter.saturating_add(1);
+    }
+    counts
+}
+
+/// Start of the sample logs: 2023-11-14 22:13:20 UTC.
+pub const SAMPLE_START: u64 = 1_700_000_000;
+
 /// Build a sample log for demos and tests.
 #[must_use]
 pub fn sample_log() -> IntrusionLog {
     let mut log = IntrusionLog::new();
-    log.push(LogEvent::new("203.0.113.10", "alice", "login_fail"));
-    log.push(LogEvent::new("203.0.113.10", "alice", "login_fail"));
-    log.push(LogEvent::new("198.51.100.7", "bob", "login_ok"));
-    log.push(LogEvent::new("203.0.113.10", "carol", "login_fail"));
-    log.push(LogEvent::new("198.51.100.7", "bob", "file_access"));
+    log.push(LogEvent::at(
+        SAMPLE_START,
+        "203.0.113.10",
+        "alice",
+        "login_fail",
+    ));
+    log.push(LogEvent::at(
+        SAMPLE_START + 5,
+        "203.0.113.10",
+        "alice",
+        "login_fail",
+    ));
+    log.push(LogEvent::at(
+        SAMPLE_START + 30,
+        "198.51.100.7",
+        "bob",
+        "login_ok",
+    ));
+    log.push(LogEvent::at(
+        SAMPLE_START + 40,
+        "203.0.113.10",
+        "carol",
+        "login_fail",
+    ));
+    log.push(LogEvent::at(
+        SAMPLE_START + 90,
+        "198.51.100.7",
+        "bob",
+        "file_access",
+    ));
+    log
+}
+
+/// A ten-minute log with one of each attack the detectors look for:
+///
+/// - brute force: 203.0.113.66 fails `root` six times in 30 s;
+/// - password spraying: 198.51.100.23 tries six users once each;
+/// - credential stuffing: six IPs each fail `alice` once;
+/// - background noise: successful logins and a slow, legitimate typo.
+#[must_use]
+pub fn sample_attack_log() -> IntrusionLog {
+    let mut log = IntrusionLog::new();
+    let t = SAMPLE_START;
+    for i in 0..6 {
+        log.push(LogEvent::at(
+            t + i * 5,
+            "203.0.113.66",
+            "root",
+            "login_fail",
+        ));
+    }
+    for (i, user) in (0..).zip(["admin", "bob", "carol", "dave", "erin", "frank"]) {
+        log.push(LogEvent::at(
+            t + 120 + i * 4,
+            "198.51.100.23",
+            user,
+            "login_fail",
+        ));
+    }
+    for i in 0..6 {
+        let ip = format!("192.0.2.{}", 10 + i);
+        log.push(LogEvent::at(t + 300 + i * 6, ip, "alice", "login_fail"));
+    }
+    log.push(LogEvent::at(t + 60, "10.0.0.5", "grace", "login_ok"));
+    log.push(LogEvent::at(t + 200, "10.0.0.8", "heidi", "login_fail"));
+    log.push(LogEvent::at(t + 500, "10.0.0.8", "heidi", "login_ok"));
     log
 }
 
@@ -164,10 +306,46 @@ mod tests {
     }
 
     #[test]
-    fn ip_counts_and_hot_ips_match() {
+    fn ip_counts_over_all_time_and_ranges() {
         let log = sample_log();
-        assert_eq!(log.ip_counts(), log.hot_ips());
         assert_eq!(log.ip_counts().get("203.0.113.10"), Some(&3));
+        let early = log.ip_counts_between(SAMPLE_START, SAMPLE_START + 30);
+        assert_eq!(early.get("203.0.113.10"), Some(&2));
+        assert_eq!(early.get("198.51.100.7"), None);
+        assert!(
+            log.events_between(SAMPLE_START + 91, SAMPLE_START)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn hot_ips_counts_bursts_not_totals() {
+        let log = sample_log();
+        // Three hits from 203.0.113.10 span 40 s: hot at 60 s, not at 10 s.
+        assert_eq!(log.hot_ips(60, 3).get("203.0.113.10"), Some(&3));
+        assert!(log.hot_ips(10, 3).is_empty());
+        assert_eq!(log.hot_ips(10, 2).get("203.0.113.10"), Some(&2));
+        // Window boundary: events exactly `width` apart don't share a window.
+        assert_eq!(log.hot_ips(5, 2).get("203.0.113.10"), None);
+    }
+
+    #[test]
+    fn push_keeps_timestamp_order() {
+        let mut log = IntrusionLog::new();
+        log.push(LogEvent::at(20, "10.0.0.1", "a", "x"));
+        log.push(LogEvent::at(10, "10.0.0.2", "b", "x"));
+        log.push(LogEvent::at(20, "10.0.0.3", "c", "x"));
+        let ips: Vec<&str> = log.events().iter().map(|e| e.ip.as_str()).collect();
+        assert_eq!(ips, ["10.0.0.2", "10.0.0.1", "10.0.0.3"]);
+    }
+
+    #[test]
+    fn tumbling_windows_align_to_width() {
+        let log = sample_log();
+        let windows = log.tumbling_windows(60);
+        let sizes: Vec<(u64, usize)> = windows.iter().map(|(&s, e)| (s, e.len())).collect();
+        // SAMPLE_START is 20 s past a minute boundary.
+        assert_eq!(sizes, [(SAMPLE_START - 20, 3), (SAMPLE_START + 40, 2)]);
     }
 
     #[test]
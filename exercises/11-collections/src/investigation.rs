@@ -2,9 +2,10 @@
 
 use anyhow::Result;
 
-use crate::intrusion::sample_log;
+use crate::detection::{DetectorConfig, detect};
+use crate::intrusion::{SAMPLE_START, sample_attack_log, sample_log};
 
-/// Run the investigation exercise: filter by action and find top user.
+/// Run the investigation exercise: filter by action, find top user, raise alerts.
 pub fn run(verbose: bool) -> Result<()> {
     println!("🔍 Investigation — filter & rank suspects");
     println!();
@@ -27,5 +28,22 @@ pub fn run(verbose: bool) -> Result<()> {
         println!("  lexicographically smallest username is returned.");
     }
 
+    let config = DetectorConfig::default();
+    println!();
+    println!("  alerts on a ten-minute attack log ({}):", config.window);
+    for alert in detect(&sample_attack_log(), &config) {
+        println!("    t+{:>3}s  {alert}", alert.last_seen() - SAMPLE_START);
+        if verbose {
+            for event in alert.events() {
+                println!(
+                    "             t+{:>3}s {} @ {}",
+                    event.timestamp - SAMPLE_START,
+                    event.user,
+                    event.ip
+                );
+            }
+        }
+    }
+
     Ok(())
 }
@@ -3,16 +3,19 @@
 //! # Modules
 //!
 //! - [`intrusion`] — `LogEvent`, `IntrusionLog`, and aggregation helpers
+//! - [`detection`] — sliding/tumbling windows and brute-force, spraying, stuffing alerts
 //! - [`aggregator`] — Exercise 1: building a log with `Vec`
 //! - [`analysis`] — Exercise 2: counts and sets
 //! - [`investigation`] — Exercise 3: filtering and ranking
 
 pub mod aggregator;
 pub mod analysis;
+pub mod detection;
 pub mod intrusion;
 pub mod investigation;
 
-pub use intrusion::{IntrusionLog, LogEvent, sample_log};
+pub use detection::{Alert, DetectorConfig, Window, detect};
+pub use intrusion::{IntrusionLog, LogEvent, sample_attack_log, sample_log};
 
 pub type Result<T> = anyhow::Result<T>;
 
@@ -43,7 +46,8 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
             concepts: vec![
                 "HashMap entry API for ip_counts",
                 "HashSet for unique_users and actions",
-                "hot_ips as an alias of ip_counts",
+                "hot_ips: per-IP peaks over a VecDeque sliding window",
+                "BTreeMap of tumbling windows over a time-sorted slice",
             ],
         },
         ExerciseInfo {
@@ -53,6 +57,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Iterator filter + collect into Vec",
                 "HashMap for per-user counts",
                 "Deterministic tie-breaking in top_user",
+                "Windowed detectors emitting typed Alert values",
             ],
         },
     ]
@@ -1,6 +1,7 @@
 //! Integration tests for `exercise_collections`.
 
-use collections_exercises::intrusion::{IntrusionLog, LogEvent, sample_log};
+use collections_exercises::detection::{Alert, DetectorConfig, Window, detect};
+use collections_exercises::intrusion::{IntrusionLog, LogEvent, sample_attack_log, sample_log};
 use collections_exercises::{get_exercise_list, run_all, run_exercise};
 
 #[test]
@@ -39,7 +40,8 @@ fn intrusion_log_end_to_end() {
     let log = sample_log();
     assert_eq!(log.len(), 5);
     assert_eq!(log.ip_counts().get("203.0.113.10"), Some(&3));
-    assert_eq!(log.hot_ips(), log.ip_counts());
+    assert_eq!(log.hot_ips(3600, 1).len(), log.ip_counts().len());
+    assert_eq!(log.hot_ips(60, 3).len(), 1);
     assert_eq!(log.unique_users().len(), 3);
     assert_eq!(log.filter_by_action("login_fail").len(), 3);
     assert_eq!(log.top_user().as_deref(), Some("alice"));
@@ -60,3 +62,36 @@ fn custom_log_push() {
     assert_eq!(log.len(), 1);
     assert!(log.actions().contains("sudo_fail"));
 }
+
+#[test]
+fn detectors_flag_each_attack_with_its_events() {
+    let log = sample_attack_log();
+    let alerts = detect(&log, &DetectorConfig::default());
+    let spraying: Vec<&Alert> = alerts
+        .iter()
+        .filter(|a| matches!(a, Alert::PasswordSpraying { .. }))
+        .collect();
+    assert_eq!(spraying.len(), 1);
+    assert!(
+        spraying[0]
+            .events()
+            .iter()
+            .all(|e| e.ip == "198.51.100.23" && e.action == "login_fail")
+    );
+    assert!(alerts.iter().any(|a| matches!(
+        a,
+        Alert::CredentialStuffing { user, .. } if user == "alice"
+    )));
+    assert!(
+        alerts
+            .windows(2)
+            .all(|w| w[0].last_seen() <= w[1].last_seen())
+    );
+
+    // A tumbling window too short to hold any burst raises nothing.
+    let strict = DetectorConfig {
+        window: Window::Tumbling { width: 4 },
+        ..DetectorConfig::default()
+    };
+    assert!(detect(&log, &strict).is_empty());
+}
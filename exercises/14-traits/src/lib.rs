@@ -6,12 +6,18 @@
 //! - [`auth`] — Exercise 1: authentication failures
 //! - [`network`] — Exercise 2: network scans
 //! - [`malware`] — Exercise 3: malware and file integrity
+//! - [`scan`] — port-scan and sweep detection feeding `NetworkScan`
 
 pub mod auth;
 pub mod malware;
 pub mod network;
+pub mod scan;
 pub mod scoring;
 
+pub use scan::{
+    ScanConfig, ScanDetector, ScanEvent, ScanEvidence, ScanPace, ScanProbe, ScanShape,
+    ScanTechnique,
+};
 pub use scoring::{
     AuthFailure, FileIntegrityEvent, MalwareAlert, NetworkScan, RiskLevel, ThreatScorer,
     analyze_event, is_actionable, max_score,
@@ -47,6 +53,7 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
                 "Overriding default trait methods",
                 "max_score over homogeneous slices",
                 "is_actionable helper",
+                "ScanDetector emitting scored events",
             ],
         },
         ExerciseInfo {
@@ -1,7 +1,10 @@
 //! Exercise 2 — `NetworkScan` with overridden summary.
 
+use std::net::Ipv4Addr;
+
 use anyhow::Result;
 
+use crate::scan::{ScanDetector, ScanProbe, TCP_FIN, TCP_PSH, TCP_SYN, TCP_URG};
 use crate::scoring::{NetworkScan, analyze_event, is_actionable, max_score};
 
 /// Run the network exercise.
@@ -30,9 +33,33 @@ pub fn run(verbose: bool) -> Result<()> {
     println!();
     println!("  max_score in batch: {}", max_score(&batch));
 
+    println!();
+    println!("  Detected from raw probes:");
+    let mut detector = ScanDetector::default();
+    let scanner = Ipv4Addr::new(198, 51, 100, 20);
+    let target = Ipv4Addr::new(10, 0, 0, 5);
+    let mut probes: Vec<ScanProbe> = (0..20u16)
+        .map(|i| ScanProbe::tcp(u64::from(i) * 200, scanner, target, 20 + i, TCP_SYN))
+        .collect();
+    probes.extend((1..=8u8).map(|host| {
+        ScanProbe::tcp(
+            10_000 + u64::from(host) * 100,
+            Ipv4Addr::new(203, 0, 113, 9),
+            Ipv4Addr::new(10, 0, 1, host),
+            3389,
+            TCP_FIN | TCP_PSH | TCP_URG,
+        )
+    }));
+    let events = detector.observe_all(&probes);
+    for event in &events {
+        println!("  {}", analyze_event(event));
+    }
+    println!("  max_score of detections: {}", max_score(&events));
+
     if verbose {
         println!();
         println!("  NetworkScan overrides summary() for richer SOC output.");
+        println!("  ScanEvent wraps it with evidence and adds technique/pace bonuses.");
     }
 
     Ok(())
@@ -0,0 +1,968 @@
+//! Port-scan and sweep detection over decoded probes.
+//!
+//! [`ScanDetector`] keeps a short per-source history of connection attempts
+//! and recognizes the shapes scans actually take:
+//!
+//! - **vertical** — many ports on one host,
+//! - **horizontal** — one port across many hosts,
+//! - **stealth techniques** — SYN, FIN, NULL, and Xmas flag patterns,
+//! - **slow scans** — the same shapes spread over a long window to stay
+//!   under per-minute thresholds.
+//!
+//! Detections come out as [`ScanEvent`]s: a [`NetworkScan`] summary plus the
+//! [`ScanEvidence`] behind it, scored through [`ThreatScorer`].
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::net::Ipv4Addr;
+
+use crate::scoring::{NetworkScan, ThreatScorer};
+
+/// TCP FIN flag bit.
+pub const TCP_FIN: u8 = 0x01;
+/// TCP SYN flag bit.
+pub const TCP_SYN: u8 = 0x02;
+/// TCP RST flag bit.
+pub const TCP_RST: u8 = 0x04;
+/// TCP PSH flag bit.
+pub const TCP_PSH: u8 = 0x08;
+/// TCP ACK flag bit.
+pub const TCP_ACK: u8 = 0x10;
+/// TCP URG flag bit.
+pub const TCP_URG: u8 = 0x20;
+
+const TCP_FLAG_MASK: u8 = TCP_FIN | TCP_SYN | TCP_RST | TCP_PSH | TCP_ACK | TCP_URG;
+
+/// One decoded packet (or the first packet of a flow) as seen by the detector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProbe {
+    /// Capture time in milliseconds.
+    pub timestamp_ms: u64,
+    /// Sender address.
+    pub src: Ipv4Addr,
+    /// Receiver address.
+    pub dst: Ipv4Addr,
+    /// Sender port; 0 when the capture didn't record it.
+    pub src_port: u16,
+    /// Receiver port.
+    pub dst_port: u16,
+    /// TCP flag byte, or `None` for UDP.
+    pub tcp_flags: Option<u8>,
+    /// Payload bytes carried.
+    pub payload_len: u32,
+}
+
+impl ScanProbe {
+    /// A TCP probe with the given flag byte.
+    #[must_use]
+    pub const fn tcp(
+        timestamp_ms: u64,
+        src: Ipv4Addr,
+        dst: Ipv4Addr,
+        dst_port: u16,
+        flags: u8,
+    ) -> Self {
+        Self {
+            timestamp_ms,
+            src,
+            dst,
+            src_port: 0,
+            dst_port,
+            tcp_flags: Some(flags),
+            payload_len: 0,
+        }
+    }
+
+    /// A UDP datagram.
+    #[must_use]
+    pub const fn udp(
+        timestamp_ms: u64,
+        src: Ipv4Addr,
+        dst: Ipv4Addr,
+        dst_port: u16,
+        payload_len: u32,
+    ) -> Self {
+        Self {
+            timestamp_ms,
+            src,
+            dst,
+            src_port: 0,
+            dst_port,
+            tcp_flags: None,
+            payload_len,
+        }
+    }
+
+    /// The same probe sent from `src_port`.
+    #[must_use]
+    pub const fn from_port(mut self, src_port: u16) -> Self {
+        self.src_port = src_port;
+        self
+    }
+
+    /// Whether this is a SYN-ACK: the sender's port answered a SYN.
+    #[must_use]
+    pub const fn is_syn_ack(&self) -> bool {
+        matches!(self.tcp_flags, Some(f) if f & (TCP_SYN | TCP_ACK | TCP_RST) == TCP_SYN | TCP_ACK)
+    }
+
+    /// The scan technique this probe looks like, or `None` for packets that
+    /// belong to ordinary established traffic (ACKs, SYN-ACKs, RSTs, ...).
+    #[must_use]
+    pub const fn technique(&self) -> Option<ScanTechnique> {
+        let Some(flags) = self.tcp_flags else {
+            return Some(ScanTechnique::Udp);
+        };
+        match flags & TCP_FLAG_MASK {
+            0 => Some(ScanTechnique::Null),
+            TCP_FIN => Some(ScanTechnique::Fin),
+            TCP_SYN => Some(ScanTechnique::Syn),
+            f if f == TCP_FIN | TCP_PSH | TCP_URG => Some(ScanTechnique::Xmas),
+            _ => None,
+        }
+    }
+}
+
+/// How the scanner is probing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScanTechnique {
+    /// Bare SYNs (connect or half-open scan).
+    Syn,
+    /// FIN only — no legitimate connection opens this way.
+    Fin,
+    /// No flags at all.
+    Null,
+    /// FIN + PSH + URG ("lit up like a Christmas tree").
+    Xmas,
+    /// UDP datagrams.
+    Udp,
+    /// More than one of the above from the same source.
+    Mixed,
+}
+
+impl ScanTechnique {
+    /// Techniques that never occur in normal traffic and exist only to
+    /// slip past stateless filters.
+    #[must_use]
+    pub const fn is_stealth(self) -> bool {
+        matches!(self, Self::Fin | Self::Null | Self::Xmas)
+    }
+}
+
+impl Display for ScanTechnique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syn => write!(f, "SYN"),
+            Self::Fin => write!(f, "FIN"),
+            Self::Null => write!(f, "NULL"),
+            Self::Xmas => write!(f, "Xmas"),
+            Self::Udp => write!(f, "UDP"),
+            Self::Mixed => write!(f, "mixed"),
+        }
+    }
+}
+
+/// Which dimension the scan fans out across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanShape {
+    /// Many ports on a single host.
+    Vertical { target: Ipv4Addr },
+    /// A single port across many hosts.
+    Horizontal { port: u16 },
+}
+
+impl Display for ScanShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vertical { target } => write!(f, "vertical scan of {target}"),
+            Self::Horizontal { port } => write!(f, "horizontal sweep of port {port}"),
+        }
+    }
+}
+
+/// Whether the scan tripped the short window or only the long one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanPace {
+    /// Crossed a threshold within [`ScanConfig::window_ms`].
+    Fast,
+    /// Crossed [`ScanConfig::slow_threshold`] only over the long window.
+    Slow,
+}
+
+/// Everything the detector saw that led to an alert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanEvidence {
+    /// Address the probes came from.
+    pub source: Ipv4Addr,
+    /// Which dimension the scan fanned out across.
+    pub shape: ScanShape,
+    /// Probe technique, or [`ScanTechnique::Mixed`].
+    pub technique: ScanTechnique,
+    /// Which window the scan tripped.
+    pub pace: ScanPace,
+    /// Timestamp of the oldest probe in the evidence.
+    pub first_seen_ms: u64,
+    /// Timestamp of the newest probe in the evidence.
+    pub last_seen_ms: u64,
+    /// Hosts probed: one for a vertical scan.
+    pub targets: BTreeSet<Ipv4Addr>,
+    /// Ports probed: one for a horizontal sweep.
+    pub ports: BTreeSet<u16>,
+    /// Unanswered probes in the window.
+    pub probes: u32,
+}
+
+impl ScanEvidence {
+    /// Time between the first and last probe that make up the evidence.
+    #[must_use]
+    pub const fn duration_ms(&self) -> u64 {
+        self.last_seen_ms.saturating_sub(self.first_seen_ms)
+    }
+}
+
+/// A detected scan: the scoring summary plus the evidence behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanEvent {
+    /// Summary scored by [`NetworkScan`].
+    pub scan: NetworkScan,
+    /// What the detector saw.
+    pub evidence: ScanEvidence,
+}
+
+impl ThreatScorer for ScanEvent {
+    /// The base [`NetworkScan`] score, raised for stealth techniques, for
+    /// each host a sweep hit, and for deliberately slow scans.
+    fn score(&self) -> u32 {
+        let technique = match self.evidence.technique {
+            t if t.is_stealth() => 20,
+            ScanTechnique::Mixed => 10,
+            _ => 0,
+        };
+        // The base score counts ports; a sweep's fan-out is in hosts, scored
+        // at the same 5 per host, capped at 60, plus the sweep bonus.
+        let shape = match self.evidence.shape {
+            ScanShape::Horizontal { .. } => {
+                let hosts = u32::try_from(self.evidence.targets.len()).unwrap_or(u32::MAX);
+                10 + hosts.saturating_mul(5).min(60)
+            }
+            ScanShape::Vertical { .. } => 0,
+        };
+        let pace = match self.evidence.pace {
+            ScanPace::Slow => 10,
+            ScanPace::Fast => 0,
+        };
+        self.scan
+            .score()
+            .saturating_add(technique + shape + pace)
+            .min(100)
+    }
+
+    fn category(&self) -> &'static str {
+        self.scan.category()
+    }
+
+    fn summary(&self) -> String {
+        let pace = match self.evidence.pace {
+            ScanPace::Fast => "",
+            ScanPace::Slow => "slow ",
+        };
+        format!(
+            "[network] {} {pace}{} {} ({} probes, {} ports, {} hosts over {} s) → {} ({})",
+            self.evidence.source,
+            self.evidence.technique,
+            self.evidence.shape,
+            self.evidence.probes,
+            self.evidence.ports.len(),
+            self.evidence.targets.len(),
+            self.evidence.duration_ms() / 1000,
+            self.score(),
+            self.risk_level()
+        )
+    }
+}
+
+/// Thresholds and windows for [`ScanDetector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanConfig {
+    /// Short window for fast scans.
+    pub window_ms: u64,
+    /// Distinct ports on one host within `window_ms` for a vertical scan.
+    pub vertical_threshold: usize,
+    /// Distinct hosts on one port within `window_ms` for a horizontal sweep.
+    pub horizontal_threshold: usize,
+    /// Lower threshold for FIN/NULL/Xmas probes, which are never benign.
+    pub stealth_threshold: usize,
+    /// Long window for slow scans; also how long history is kept.
+    pub slow_window_ms: u64,
+    /// Distinct ports or hosts within `slow_window_ms` for a slow scan.
+    pub slow_threshold: usize,
+    /// History cap per source; the oldest probes are dropped first.
+    pub max_probes_per_source: usize,
+    /// Sources tracked at once; the least recently seen is evicted beyond it.
+    pub max_sources: usize,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 60_000,
+            vertical_threshold: 15,
+            horizontal_threshold: 10,
+            stealth_threshold: 5,
+            slow_window_ms: 3_600_000,
+            slow_threshold: 25,
+            max_probes_per_source: 4096,
+            max_sources: 65_536,
+        }
+    }
+}
+
+/// Probes sharing a host (vertical) or a port (horizontal) inside one window,
+/// summarized incrementally as probes enter and leave it.
+#[derive(Debug)]
+struct Group<T> {
+    /// The other dimension: ports on this host, or hosts on this port.
+    spread: HashMap<T, u32>,
+    techniques: HashMap<ScanTechnique, u32>,
+    /// Timestamps keyed by arrival sequence number.
+    times: BTreeMap<u64, u64>,
+    payload_bytes: u64,
+}
+
+impl<T> Default for Group<T> {
+    fn default() -> Self {
+        Self {
+            spread: HashMap::new(),
+            techniques: HashMap::new(),
+            times: BTreeMap::new(),
+            payload_bytes: 0,
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> Group<T> {
+    fn add(&mut self, other: T, entry: &Entry) {
+        *self.spread.entry(other).or_default() += 1;
+        *self.techniques.entry(entry.technique).or_default() += 1;
+        self.times.insert(entry.seq, entry.probe.timestamp_ms);
+        self.payload_bytes += u64::from(entry.probe.payload_len);
+    }
+
+    fn remove(&mut self, other: T, entry: &Entry) {
+        decrement(&mut self.spread, &other);
+        decrement(&mut self.techniques, &entry.technique);
+        self.times.remove(&entry.seq);
+        self.payload_bytes = self
+            .payload_bytes
+            .saturating_sub(u64::from(entry.probe.payload_len));
+    }
+
+    fn technique(&self) -> ScanTechnique {
+        combine(self.techniques.keys().copied())
+    }
+}
+
+fn decrement<K: Eq + Hash>(counts: &mut HashMap<K, u32>, key: &K) {
+    if let Some(count) = counts.get_mut(key) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(key);
+        }
+    }
+}
+
+/// A probe in a window. `live` is cleared when a SYN-ACK shows it was an
+/// ordinary connection, which takes it out of the groups.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    seq: u64,
+    probe: ScanProbe,
+    technique: ScanTechnique,
+    live: bool,
+}
+
+/// One sliding window over a source's probes, grouped by host and by port.
+#[derive(Debug, Default)]
+struct ProbeWindow {
+    /// Probes with consecutive sequence numbers, oldest first.
+    probes: VecDeque<Entry>,
+    by_host: HashMap<Ipv4Addr, Group<u16>>,
+    by_port: HashMap<u16, Group<Ipv4Addr>>,
+}
+
+impl ProbeWindow {
+    fn push(&mut self, entry: Entry) {
+        self.by_host
+            .entry(entry.probe.dst)
+            .or_default()
+            .add(entry.probe.dst_port, &entry);
+        self.by_port
+            .entry(entry.probe.dst_port)
+            .or_default()
+            .add(entry.probe.dst, &entry);
+        self.probes.push_back(entry);
+    }
+
+    fn pop_front(&mut self) -> Option<Entry> {
+        let entry = self.probes.pop_front()?;
+        if entry.live {
+            self.unlink(&entry);
+        }
+        Some(entry)
+    }
+
+    /// Take probe `seq` out of the groups, if it is still in the window.
+    fn kill(&mut self, seq: u64) {
+        let Some(front) = self.probes.front().map(|e| e.seq) else {
+            return;
+        };
+        let index = usize::try_from(seq.saturating_sub(front)).unwrap_or(usize::MAX);
+        let Some(entry) = self.probes.get_mut(index) else {
+            return;
+        };
+        if entry.seq != seq || !entry.live {
+            return;
+        }
+        entry.live = false;
+        let entry = *entry;
+        self.unlink(&entry);
+    }
+
+    fn unlink(&mut self, entry: &Entry) {
+        let probe = &entry.probe;
+        if let Some(group) = self.by_host.get_mut(&probe.dst) {
+            group.remove(probe.dst_port, entry);
+            if group.times.is_empty() {
+                self.by_host.remove(&probe.dst);
+            }
+        }
+        if let Some(group) = self.by_port.get_mut(&probe.dst_port) {
+            group.remove(probe.dst, entry);
+            if group.times.is_empty() {
+                self.by_port.remove(&probe.dst_port);
+            }
+        }
+    }
+
+    /// Drop probes older than `horizon` or numbered below `min_seq`.
+    fn trim(&mut self, horizon: u64, min_seq: u64) {
+        while self
+            .probes
+            .front()
+            .is_some_and(|e| e.probe.timestamp_ms < horizon || e.seq < min_seq)
+        {
+            self.pop_front();
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SourceHistory {
+    fast: ProbeWindow,
+    /// Also the full history kept for the source.
+    slow: ProbeWindow,
+    next_seq: u64,
+    alerted: HashMap<(ScanShape, ScanPace), u64>,
+    /// Live SYNs still waiting for an answer, by `(host, port)`, oldest first.
+    open_syns: HashMap<(Ipv4Addr, u16), VecDeque<u64>>,
+    /// This source's entry in [`ScanDetector`]'s recency index.
+    lru_slot: Option<(u64, u64)>,
+}
+
+impl SourceHistory {
+    fn push(&mut self, probe: &ScanProbe, technique: ScanTechnique) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let entry = Entry {
+            seq,
+            probe: *probe,
+            technique,
+            live: true,
+        };
+        self.slow.push(entry);
+        self.fast.push(entry);
+        if technique == ScanTechnique::Syn {
+            self.open_syns
+                .entry((probe.dst, probe.dst_port))
+                .or_default()
+                .push_back(seq);
+        }
+        seq
+    }
+
+    /// Drop the oldest probe from the history.
+    fn pop_oldest(&mut self) {
+        let Some(entry) = self.slow.pop_front() else {
+            return;
+        };
+        let key = (entry.probe.dst, entry.probe.dst_port);
+        if entry.live
+            && entry.technique == ScanTechnique::Syn
+            && let Some(seqs) = self.open_syns.get_mut(&key)
+        {
+            if seqs.front() == Some(&entry.seq) {
+                seqs.pop_front();
+            }
+            if seqs.is_empty() {
+                self.open_syns.remove(&key);
+            }
+        }
+    }
+
+    /// `host:port` answered the newest open SYN to it with a SYN-ACK, so
+    /// that SYN was a connection, not a probe.
+    fn answered(&mut self, host: Ipv4Addr, port: u16) {
+        let Some(seqs) = self.open_syns.get_mut(&(host, port)) else {
+            return;
+        };
+        let seq = seqs.pop_back();
+        if seqs.is_empty() {
+            self.open_syns.remove(&(host, port));
+        }
+        if let Some(seq) = seq {
+            self.slow.kill(seq);
+            self.fast.kill(seq);
+        }
+    }
+}
+
+/// Sliding-window scan detector keyed by source address.
+///
+/// Each window keeps its probes grouped by host and by port, updated as
+/// probes enter and leave, so a probe costs O(1) amortized even while a scan
+/// is filling the window. SYNs answered by a SYN-ACK leave the groups again:
+/// ordinary clients connect to open ports, scanners mostly hit closed ones.
+/// At most [`ScanConfig::max_sources`] sources are tracked, so spoofed
+/// sources cannot grow the table without bound.
+#[derive(Debug, Default)]
+pub struct ScanDetector {
+    config: ScanConfig,
+    sources: HashMap<Ipv4Addr, SourceHistory>,
+    /// Sources ordered by `(last probe time, insertion)`.
+    lru: BTreeMap<(u64, u64), Ipv4Addr>,
+    next_slot: u64,
+    evicted: u64,
+}
+
+impl ScanDetector {
+    #[must_use]
+    pub fn new(config: ScanConfig) -> Self {
+        Self {
+            config,
+            sources: HashMap::new(),
+            lru: BTreeMap::new(),
+            next_slot: 0,
+            evicted: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &ScanConfig {
+        &self.config
+    }
+
+    /// Number of sources with probes still in history.
+    #[must_use]
+    pub fn tracked_sources(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Sources dropped to respect [`ScanConfig::max_sources`].
+    #[must_use]
+    pub const fn evicted_sources(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Feed one probe; returns any scans it completes.
+    ///
+    /// Each (shape, pace) pair alerts at most once per window per source, so
+    /// a scan in progress does not produce an event for every extra port.
+    pub fn observe(&mut self, probe: &ScanProbe) -> Vec<ScanEvent> {
+        if probe.is_syn_ack() {
+            if let Some(client) = self.sources.get_mut(&probe.dst) {
+                client.answered(probe.src, probe.src_port);
+            }
+            return Vec::new();
+        }
+        let Some(technique) = probe.technique() else {
+            return Vec::new();
+        };
+        let config = self.config;
+        let now = probe.timestamp_ms;
+        if !self.sources.contains_key(&probe.src)
+            && self.sources.len() >= config.max_sources.max(1)
+            && let Some((_, oldest)) = self.lru.pop_first()
+        {
+            self.sources.remove(&oldest);
+            self.evicted += 1;
+        }
+        let history = self.sources.entry(probe.src).or_default();
+        let last_seen = history.lru_slot.map_or(now, |(seen, _)| seen.max(now));
+        if history.lru_slot.map(|(seen, _)| seen) != Some(last_seen) {
+            if let Some(slot) = history.lru_slot {
+                self.lru.remove(&slot);
+            }
+            let slot = (last_seen, self.next_slot);
+            self.next_slot += 1;
+            self.lru.insert(slot, probe.src);
+            history.lru_slot = Some(slot);
+        }
+
+        let seq = history.push(probe, technique);
+        let horizon = now.saturating_sub(config.slow_window_ms);
+        while history.slow.probes.len() > config.max_probes_per_source
+            || history
+                .slow
+                .probes
+                .front()
+                .is_some_and(|e| e.probe.timestamp_ms < horizon)
+        {
+            history.pop_oldest();
+        }
+        // The fast window never holds a probe the capped history dropped.
+        let oldest = history.slow.probes.front().map_or(seq, |e| e.seq);
+        history
+            .fast
+            .trim(now.saturating_sub(config.window_ms), oldest);
+
+        let mut events = Vec::new();
+        for (pace, span) in [
+            (ScanPace::Fast, config.window_ms),
+            (ScanPace::Slow, config.slow_window_ms),
+        ] {
+            let window = match pace {
+                ScanPace::Fast => &history.fast,
+                ScanPace::Slow => &history.slow,
+            };
+            for shape in tripped(probe, pace, window, &config) {
+                let key = (shape, pace);
+                let suppressed = history
+                    .alerted
+                    .get(&key)
+                    .is_some_and(|&at| now.saturating_sub(at) < span);
+                // A fast alert already covers the same shape in the long window.
+                let covered = pace == ScanPace::Slow
+                    && history
+                        .alerted
+                        .get(&(shape, ScanPace::Fast))
+                        .is_some_and(|&at| now.saturating_sub(at) < span);
+                if suppressed || covered {
+                    continue;
+                }
+                history.alerted.insert(key, now);
+                events.extend(to_event(probe.src, shape, pace, window));
+            }
+        }
+        events
+    }
+
+    /// Feed many probes in order and collect every event.
+    pub fn observe_all<'a>(
+        &mut self,
+        probes: impl IntoIterator<Item = &'a ScanProbe>,
+    ) -> Vec<ScanEvent> {
+        probes.into_iter().flat_map(|p| self.observe(p)).collect()
+    }
+
+    /// Drop sources whose newest probe is older than the slow window.
+    pub fn expire(&mut self, now_ms: u64) {
+        let horizon = now_ms.saturating_sub(self.config.slow_window_ms);
+        while let Some(entry) = self.lru.first_entry()
+            && entry.key().0 < horizon
+        {
+            let source = entry.remove();
+            self.sources.remove(&source);
+        }
+    }
+}
+
+fn combine(techniques: impl IntoIterator<Item = ScanTechnique>) -> ScanTechnique {
+    let mut seen = techniques.into_iter();
+    let Some(first) = seen.next() else {
+        return ScanTechnique::Mixed;
+    };
+    if seen.all(|t| t == first) {
+        first
+    } else {
+        ScanTechnique::Mixed
+    }
+}
+
+/// Fan-out threshold for a group seen through `technique` at `pace`.
+const fn threshold(
+    shape: ScanShape,
+    technique: ScanTechnique,
+    pace: ScanPace,
+    config: &ScanConfig,
+) -> usize {
+    match (pace, shape) {
+        (ScanPace::Slow, _) => config.slow_threshold,
+        (ScanPace::Fast, _) if technique.is_stealth() => config.stealth_threshold,
+        (ScanPace::Fast, ScanShape::Vertical { .. }) => config.vertical_threshold,
+        (ScanPace::Fast, ScanShape::Horizontal { .. }) => config.horizontal_threshold,
+    }
+}
+
+/// Shapes through `probe` whose fan-out in `window` crosses the threshold for
+/// `pace`. Only the host and port groups the probe just joined can have grown.
+fn tripped(
+    probe: &ScanProbe,
+    pace: ScanPace,
+    window: &ProbeWindow,
+    config: &ScanConfig,
+) -> Vec<ScanShape> {
+    let vertical = window.by_host.get(&probe.dst).map(|group| {
+        let shape = ScanShape::Vertical { target: probe.dst };
+        (shape, group.spread.len(), group.technique())
+    });
+    let horizontal = window.by_port.get(&probe.dst_port).map(|group| {
+        let shape = ScanShape::Horizontal {
+            port: probe.dst_port,
+        };
+        (shape, group.spread.len(), group.technique())
+    });
+    [vertical, horizontal]
+        .into_iter()
+        .flatten()
+        .filter(|&(shape, fan_out, technique)| fan_out >= threshold(shape, technique, pace, config))
+        .map(|(shape, _, _)| shape)
+        .collect()
+}
+
+/// Build the event for a tripped `shape` from its group in `window`.
+fn to_event(
+    source: Ipv4Addr,
+    shape: ScanShape,
+    pace: ScanPace,
+    window: &ProbeWindow,
+) -> Option<ScanEvent> {
+    let (targets, ports, technique, times, payload_bytes) = match shape {
+        ScanShape::Vertical { target } => {
+            let group = window.by_host.get(&target)?;
+            (
+                BTreeSet::from([target]),
+                group.spread.keys().copied().collect::<BTreeSet<_>>(),
+                group.technique(),
+                &group.times,
+                group.payload_bytes,
+            )
+        }
+        ScanShape::Horizontal { port } => {
+            let group = window.by_port.get(&port)?;
+            (
+                group.spread.keys().copied().collect(),
+                BTreeSet::from([port]),
+                group.technique(),
+                &group.times,
+                group.payload_bytes,
+            )
+        }
+    };
+    let evidence = ScanEvidence {
+        source,
+        shape,
+        technique,
+        pace,
+        first_seen_ms: times.first_key_value().map_or(0, |(_, &t)| t),
+        last_seen_ms: times.last_key_value().map_or(0, |(_, &t)| t),
+        probes: u32::try_from(times.len()).unwrap_or(u32::MAX),
+        targets,
+        ports,
+    };
+    Some(ScanEvent {
+        scan: NetworkScan {
+            source_ip: source.to_string(),
+            ports_hit: u32::try_from(evidence.ports.len()).unwrap_or(u32::MAX),
+            payload_bytes: u32::try_from(payload_bytes).unwrap_or(u32::MAX),
+        },
+        evidence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCANNER: Ipv4Addr = Ipv4Addr::new(198, 51, 100, 7);
+    const TARGET: Ipv4Addr = Ipv4Addr::new(10, 0, 0, 5);
+
+    fn vertical(start_ms: u64, step_ms: u64, ports: u16, flags: u8) -> Vec<ScanProbe> {
+        (0..ports)
+            .map(|i| {
+                ScanProbe::tcp(
+                    start_ms + u64::from(i) * step_ms,
+                    SCANNER,
+                    TARGET,
+                    20 + i,
+                    flags,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn technique_from_flags() {
+        let probe = |flags| ScanProbe::tcp(0, SCANNER, TARGET, 80, flags).technique();
+        assert_eq!(probe(TCP_SYN), Some(ScanTechnique::Syn));
+        assert_eq!(probe(0), Some(ScanTechnique::Null));
+        assert_eq!(probe(TCP_FIN), Some(ScanTechnique::Fin));
+        assert_eq!(
+            probe(TCP_FIN | TCP_PSH | TCP_URG),
+            Some(ScanTechnique::Xmas)
+        );
+        assert_eq!(probe(TCP_ACK), None);
+        assert_eq!(probe(TCP_SYN | TCP_ACK), None);
+        assert_eq!(
+            ScanProbe::udp(0, SCANNER, TARGET, 53, 0).technique(),
+            Some(ScanTechnique::Udp)
+        );
+    }
+
+    #[test]
+    fn vertical_scan_alerts_once() {
+        let mut detector = ScanDetector::default();
+        let events = detector.observe_all(&vertical(0, 100, 40, TCP_SYN));
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.evidence.shape, ScanShape::Vertical { target: TARGET });
+        assert_eq!(event.evidence.technique, ScanTechnique::Syn);
+        assert_eq!(event.evidence.pace, ScanPace::Fast);
+        assert_eq!(event.evidence.ports.len(), 15);
+        assert_eq!(event.scan.ports_hit, 15);
+    }
+
+    #[test]
+    fn horizontal_sweep() {
+        let mut detector = ScanDetector::default();
+        let probes: Vec<ScanProbe> = (1..=12)
+            .map(|host| {
+                ScanProbe::tcp(
+                    u64::from(host) * 50,
+                    SCANNER,
+                    Ipv4Addr::new(10, 0, 1, host),
+                    445,
+                    TCP_SYN,
+                )
+            })
+            .collect();
+        let events = detector.observe_all(&probes);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].evidence.shape,
+            ScanShape::Horizontal { port: 445 }
+        );
+        assert_eq!(events[0].evidence.targets.len(), 10);
+        // One port swept: the host count lives in the evidence, not ports_hit.
+        assert_eq!(events[0].scan.ports_hit, 1);
+        assert_eq!(events[0].score(), events[0].scan.score() + 10 + 50);
+    }
+
+    #[test]
+    fn history_cap_applies_to_both_windows() {
+        let mut detector = ScanDetector::new(ScanConfig {
+            max_probes_per_source: 10,
+            ..ScanConfig::default()
+        });
+        assert!(
+            detector
+                .observe_all(&vertical(0, 10, 100, TCP_SYN))
+                .is_empty()
+        );
+        let history = &detector.sources[&SCANNER];
+        assert_eq!(history.slow.probes.len(), 10);
+        assert_eq!(history.fast.probes.len(), 10);
+        assert_eq!(history.fast.by_host[&TARGET].spread.len(), 10);
+        assert_eq!(history.slow.by_port.len(), 10);
+    }
+
+    #[test]
+    fn answered_syns_do_not_count_toward_fan_out() {
+        let mut detector = ScanDetector::default();
+        let mut traffic = Vec::new();
+        for (i, syn) in vertical(0, 100, 40, TCP_SYN).into_iter().enumerate() {
+            let syn = syn.from_port(40_000 + u16::try_from(i).unwrap_or(0));
+            let reply = ScanProbe::tcp(
+                syn.timestamp_ms + 1,
+                TARGET,
+                SCANNER,
+                syn.src_port,
+                TCP_SYN | TCP_ACK,
+            );
+            traffic.push(syn);
+            traffic.push(reply.from_port(syn.dst_port));
+        }
+        assert!(detector.observe_all(&traffic).is_empty());
+        let history = &detector.sources[&SCANNER];
+        assert!(history.fast.by_host.is_empty());
+        assert!(history.open_syns.is_empty());
+
+        // Without the answers the same SYNs are a scan.
+        let syns: Vec<ScanProbe> = traffic.iter().step_by(2).copied().collect();
+        assert_eq!(ScanDetector::default().observe_all(&syns).len(), 1);
+    }
+
+    #[test]
+    fn source_cap_evicts_the_least_recently_seen() {
+        let mut detector = ScanDetector::new(ScanConfig {
+            max_sources: 2,
+            ..ScanConfig::default()
+        });
+        let source = |last| Ipv4Addr::new(203, 0, 113, last);
+        detector.observe(&ScanProbe::tcp(0, source(1), TARGET, 22, TCP_SYN));
+        detector.observe(&ScanProbe::tcp(10, source(2), TARGET, 22, TCP_SYN));
+        detector.observe(&ScanProbe::tcp(20, source(1), TARGET, 23, TCP_SYN));
+        detector.observe(&ScanProbe::tcp(30, source(3), TARGET, 22, TCP_SYN));
+        assert_eq!(detector.tracked_sources(), 2);
+        assert_eq!(detector.evicted_sources(), 1);
+        assert!(detector.sources.contains_key(&source(1)));
+        assert!(!detector.sources.contains_key(&source(2)));
+        assert_eq!(detector.lru.len(), 2);
+    }
+
+    #[test]
+    fn stealth_scan_uses_lower_threshold_and_scores_higher() {
+        let mut syn = ScanDetector::default();
+        assert!(syn.observe_all(&vertical(0, 100, 6, TCP_SYN)).is_empty());
+
+        let mut xmas = ScanDetector::default();
+        let events = xmas.observe_all(&vertical(0, 100, 6, TCP_FIN | TCP_PSH | TCP_URG));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].evidence.technique, ScanTechnique::Xmas);
+        assert!(events[0].score() >= events[0].scan.score() + 20);
+    }
+
+    #[test]
+    fn slow_scan_over_long_window() {
+        let mut detector = ScanDetector::default();
+        // One port every two minutes never trips the one-minute window.
+        let events = detector.observe_all(&vertical(0, 120_000, 30, TCP_SYN));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].evidence.pace, ScanPace::Slow);
+        assert_eq!(events[0].evidence.ports.len(), 25);
+        assert!(events[0].summary().contains("slow"));
+    }
+
+    #[test]
+    fn established_traffic_is_ignored_and_expires() {
+        let mut detector = ScanDetector::default();
+        let acks: Vec<ScanProbe> = (0..100)
+            .map(|i| {
+                ScanProbe::tcp(
+                    i,
+                    SCANNER,
+                    TARGET,
+                    1000 + u16::try_from(i).unwrap_or(0),
+                    TCP_ACK,
+                )
+            })
+            .collect();
+        assert!(detector.observe_all(&acks).is_empty());
+        assert_eq!(detector.tracked_sources(), 0);
+
+        detector.observe(&ScanProbe::tcp(0, SCANNER, TARGET, 22, TCP_SYN));
+        assert_eq!(detector.tracked_sources(), 1);
+        detector.expire(detector.config().slow_window_ms + 1);
+        assert_eq!(detector.tracked_sources(), 0);
+    }
+}
@@ -1,8 +1,12 @@
 //! Integration tests for `exercise_traits`.
 
+use std::net::Ipv4Addr;
+
+use traits_exercises::scan::{TCP_ACK, TCP_SYN};
 use traits_exercises::{
-    AuthFailure, FileIntegrityEvent, MalwareAlert, NetworkScan, RiskLevel, ThreatScorer,
-    analyze_event, get_exercise_list, is_actionable, max_score, run_all, run_exercise,
+    AuthFailure, FileIntegrityEvent, MalwareAlert, NetworkScan, RiskLevel, ScanDetector, ScanPace,
+    ScanProbe, ScanShape, ThreatScorer, analyze_event, get_exercise_list, is_actionable, max_score,
+    run_all, run_exercise,
 };
 
 #[test]
@@ -76,3 +80,40 @@ fn file_integrity_and_max_score() {
     assert_eq!(max_score(&events), 90);
     assert_eq!(events[1].category(), "integrity");
 }
+
+#[test]
+fn scan_detector_emits_scored_network_scans() {
+    let scanner = Ipv4Addr::new(192, 0, 2, 66);
+    let mut detector = ScanDetector::default();
+
+    let mut probes: Vec<ScanProbe> = (1..=30u8)
+        .map(|host| {
+            ScanProbe::tcp(
+                u64::from(host) * 10,
+                scanner,
+                Ipv4Addr::new(10, 1, 0, host),
+                22,
+                TCP_SYN,
+            )
+        })
+        .collect();
+    // Replies and established traffic never count as probes.
+    probes.push(ScanProbe::tcp(
+        400,
+        scanner,
+        Ipv4Addr::new(10, 1, 0, 1),
+        22,
+        TCP_ACK,
+    ));
+
+    let events = detector.observe_all(&probes);
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.evidence.shape, ScanShape::Horizontal { port: 22 });
+    assert_eq!(event.evidence.pace, ScanPace::Fast);
+    assert_eq!(event.scan.source_ip, "192.0.2.66");
+    assert_eq!(event.category(), "network");
+    assert!(event.score() > event.scan.score());
+    assert!(analyze_event(event).contains("port 22"));
+    assert!(is_actionable(event));
+}
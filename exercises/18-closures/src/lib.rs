@@ -5,7 +5,7 @@
 //! - [`threat`] — `ThreatEvent` and closure helpers
 //! - [`sorting`] — Exercise 1: `sort_by_key`
 //! - [`partition`] — Exercise 2: `into_iter().partition`
-//! - [`watchlist`] — Exercise 3: capturing `HashSet` in closures
+//! - [`watchlist`] — Exercise 3: capturing an `IpSet` in closures
 
 pub mod partition;
 pub mod sorting;
@@ -13,8 +13,8 @@ pub mod threat;
 pub mod watchlist;
 
 pub use threat::{
-    ThreatEvent, count_matching, count_watchlisted, partition_critical, sample_events,
-    sort_by_source,
+    ThreatEvent, count_matching, count_watchlisted, is_watchlisted, partition_critical,
+    sample_events, sort_by_source,
 };
 
 pub type Result<T> = anyhow::Result<T>;
@@ -51,11 +51,11 @@ pub fn get_exercise_list() -> Vec<ExerciseInfo> {
         },
         ExerciseInfo {
             name: "watchlist",
-            description: "Filter events using a captured HashSet",
+            description: "Filter events using a captured IpSet",
             concepts: vec![
                 "Closures capture environment by reference",
                 "count_matching accepts impl Fn predicate",
-                "HashSet membership checks in O(1) average time",
+                "IpSet matches single hosts and whole CIDR blocks",
             ],
         },
     ]
@@ -1,6 +1,6 @@
 //! Threat events and closure-based triage helpers.
 
-use std::collections::HashSet;
+use example_datatypes::IpSet;
 
 /// A scored security event from a monitored source.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -41,13 +41,21 @@ pub fn count_matching(events: &[ThreatEvent], pred: impl Fn(&ThreatEvent) -> boo
     events.iter().filter(|event| pred(event)).count()
 }
 
-/// Count events whose source appears in `watchlist` (closure captures `HashSet`).
+/// Whether the event's source is an address inside `watchlist`.
+///
+/// Hostnames never match: the watchlist holds networks, not names.
 #[must_use]
-pub fn count_watchlisted<S: std::hash::BuildHasher>(
-    events: &[ThreatEvent],
-    watchlist: &HashSet<String, S>,
-) -> usize {
-    let is_listed = |event: &ThreatEvent| watchlist.contains(&event.source);
+pub fn is_watchlisted(event: &ThreatEvent, watchlist: &IpSet) -> bool {
+    event
+        .source
+        .parse()
+        .is_ok_and(|addr| watchlist.contains(addr))
+}
+
+/// Count events whose source falls inside `watchlist` (closure captures `IpSet`).
+#[must_use]
+pub fn count_watchlisted(events: &[ThreatEvent], watchlist: &IpSet) -> usize {
+    let is_listed = |event: &ThreatEvent| is_watchlisted(event, watchlist);
     count_matching(events, is_listed)
 }
 
@@ -66,6 +74,7 @@ pub fn sample_events() -> Vec<ThreatEvent> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use example_datatypes::Cidr;
 
     #[test]
     fn sort_by_source_orders_lexicographically() {
@@ -96,11 +105,20 @@ mod tests {
     }
 
     #[test]
-    fn count_watchlisted_captures_hashset() {
+    fn count_watchlisted_captures_ipset() {
         let events = sample_events();
-        let mut watchlist = HashSet::new();
-        watchlist.insert("203.0.113.10".to_owned());
-        watchlist.insert("10.0.0.5".to_owned());
+        let watchlist: IpSet = ["203.0.113.10", "10.0.0.5"]
+            .iter()
+            .filter_map(|ip| ip.parse::<Cidr>().ok())
+            .collect();
         assert_eq!(count_watchlisted(&events, &watchlist), 3);
+
+        // A whole block matches every address inside it; names never match.
+        let block: IpSet = "10.0.0.0/24".parse::<Cidr>().into_iter().collect();
+        assert_eq!(count_watchlisted(&events, &block), 2);
+        assert!(!is_watchlisted(
+            &ThreatEvent::new("evil.example", 99, true),
+            &block
+        ));
     }
 }
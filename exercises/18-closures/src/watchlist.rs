@@ -1,37 +1,37 @@
-//! Exercise 3 — closures capturing a `HashSet` watchlist.
-
-use std::collections::HashSet;
+//! Exercise 3 — closures capturing an `IpSet` watchlist.
 
 use anyhow::Result;
+use example_datatypes::{Cidr, IpSet};
 
-use crate::threat::{count_matching, count_watchlisted, sample_events};
+use crate::threat::{count_matching, count_watchlisted, is_watchlisted, sample_events};
 
 /// Demonstrate a closure that captures a watchlist of suspicious IPs.
 pub fn run(verbose: bool) -> Result<()> {
-    println!("👁️  Watchlist — closure over HashSet");
+    println!("👁️  Watchlist — closure over IpSet");
     println!();
 
     let events = sample_events();
-    let mut watchlist = HashSet::new();
-    watchlist.insert("203.0.113.10".to_owned());
-    watchlist.insert("198.51.100.1".to_owned());
+    let watchlist: IpSet = ["203.0.113.10", "198.51.100.0/24"]
+        .iter()
+        .filter_map(|entry| entry.parse::<Cidr>().ok())
+        .collect();
 
     let listed = count_watchlisted(&events, &watchlist);
     let high_score = count_matching(&events, |e| e.score >= 50);
 
-    println!("  watchlist size: {}", watchlist.len());
+    println!("  watchlist blocks: {}", watchlist.to_cidrs().len());
     println!("  events from watchlisted IPs: {listed}");
     println!("  events with score >= 50: {high_score}");
 
     if verbose {
         println!();
         println!("  Watchlist entries:");
-        for ip in &watchlist {
-            println!("    {ip}");
+        for cidr in watchlist.to_cidrs() {
+            println!("    {cidr}");
         }
         let flagged = events
             .iter()
-            .filter(|e| watchlist.contains(&e.source))
+            .filter(|e| is_watchlisted(e, &watchlist))
             .collect::<Vec<_>>();
         for event in flagged {
             println!(
@@ -1,11 +1,10 @@
 //! Integration tests for `exercise_closures`.
 
-use std::collections::HashSet;
-
 use closures_exercises::{
     ThreatEvent, count_matching, count_watchlisted, get_exercise_list, partition_critical, run_all,
     run_exercise, sample_events, sort_by_source,
 };
+use example_datatypes::{Cidr, IpSet};
 
 #[test]
 fn exercise_list_has_three_entries() {
@@ -51,8 +50,7 @@ fn threat_event_end_to_end() {
     let events = sample_events();
     assert_eq!(count_matching(&events, |e| e.critical), 2);
 
-    let mut watchlist = HashSet::new();
-    watchlist.insert("203.0.113.10".to_owned());
+    let watchlist: IpSet = "203.0.113.10".parse::<Cidr>().into_iter().collect();
     assert_eq!(count_watchlisted(&events, &watchlist), 2);
 }
 
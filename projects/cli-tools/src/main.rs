@@ -10,6 +10,7 @@ use std::io::{self, BufRead, Write};
 
 use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand};
+use ownership_exercises::Secret;
 use project_cli_tools::{
     AnalysisReport, CharClass, EntropyEstimate, analyze_password, estimate_entropy,
 };
@@ -32,12 +33,12 @@ enum Commands {
     /// Print Shannon-style entropy in bits for a password
     Entropy {
         /// Password to measure (prefer quoting in the shell)
-        password: String,
+        password: Secret<String>,
     },
     /// Full strength analysis: entropy, classes, findings, rating
     Analyze {
         /// Password to analyze
-        password: String,
+        password: Secret<String>,
     },
     /// Read one password per line from stdin and analyze each
     Batch {
@@ -51,12 +52,12 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
         Commands::Entropy { password } => {
-            require_nonempty(&password)?;
-            print_entropy(&estimate_entropy(&password))?;
+            require_nonempty(password.expose())?;
+            print_entropy(&estimate_entropy(password.expose()))?;
         }
         Commands::Analyze { password } => {
-            require_nonempty(&password)?;
-            print_analysis(&analyze_password(&password))?;
+            require_nonempty(password.expose())?;
+            print_analysis(&analyze_password(password.expose()))?;
         }
         Commands::Batch { skip_empty } => {
             run_batch(skip_empty)?;
@@ -147,9 +148,11 @@ fn run_batch(skip_empty: bool) -> Result<()> {
 
     for (idx, line) in stdin.lock().lines().enumerate() {
         let line_no = idx + 1;
-        let password = line.with_context(|| format!("failed to read stdin line {line_no}"))?;
+        let password: Secret<String> = line
+            .with_context(|| format!("failed to read stdin line {line_no}"))?
+            .into();
 
-        if password.is_empty() {
+        if password.expose().is_empty() {
             if skip_empty {
                 continue;
             }
@@ -159,12 +162,12 @@ fn run_batch(skip_empty: bool) -> Result<()> {
             continue;
         }
 
-        let report = analyze_password(&password);
+        let report = analyze_password(password.expose());
         if report.strength <= project_cli_tools::Strength::Weak {
             weak_or_worse += 1;
         }
 
-        let preview = redact_preview(&password);
+        let preview = redact_preview(password.expose());
         writeln!(
             out,
             "[{line_no}] {preview} → {} ({:.1} bits)",
@@ -206,6 +209,17 @@ mod tests {
         assert_eq!(redact_preview("abcdef"), "a****f");
     }
 
+    #[test]
+    fn parsed_password_is_redacted_in_debug() {
+        let cli = Cli::try_parse_from(["seccheck", "analyze", "hunter2"]);
+        assert!(cli.is_ok());
+        if let Ok(cli) = cli {
+            let debug = format!("{cli:?}");
+            assert!(debug.contains("[REDACTED]"));
+            assert!(!debug.contains("hunter2"));
+        }
+    }
+
     #[test]
     fn require_nonempty_rejects_blank() {
         assert!(require_nonempty("").is_err());